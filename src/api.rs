@@ -7,9 +7,12 @@ use std::time::Duration;
 use anyhow::{anyhow, bail, Context, Result};
 use reqwest::{Client, StatusCode};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Value};
 
 use crate::config::RuntimeContext;
+use crate::credentials::{self, Store, StoredToken};
+use crate::dump;
+use crate::error::{self, Error};
 
 /// Validate and encode an entity_id for use in URL paths.
 ///
@@ -93,6 +96,36 @@ fn validate_event_type(event_type: &str) -> Result<&str> {
     Ok(event_type)
 }
 
+/// Validate a Supervisor add-on slug (e.g., "core_mosquitto", "a0d7b954_vscode")
+fn validate_addon_slug(slug: &str) -> Result<&str> {
+    let is_valid = !slug.is_empty()
+        && slug
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+
+    if !is_valid {
+        bail!(
+            "Invalid add-on slug: '{slug}'. Must contain only lowercase letters, numbers, underscores, and hyphens"
+        );
+    }
+
+    Ok(slug)
+}
+
+/// Explain a 404 from the Supervisor proxy, which only exists on Home
+/// Assistant OS and Supervised installs
+fn supervisor_error(err: anyhow::Error) -> anyhow::Error {
+    match error::find(&err) {
+        Some(Error::NotFound(_)) => Error::NotFound(
+            "The Supervisor is not available. Add-ons need Home Assistant OS or a \
+             Supervised install; Container and Core installs don't have them"
+                .to_string(),
+        )
+        .into(),
+        _ => err,
+    }
+}
+
 /// Home Assistant REST API client.
 ///
 /// Each instance wraps a reqwest::Client which handles connection pooling internally.
@@ -101,44 +134,127 @@ fn validate_event_type(event_type: &str) -> Result<&str> {
 pub struct HassClient {
     client: Client,
     base_url: String,
+    auth: tokio::sync::Mutex<Auth>,
+}
+
+/// The bearer token, and the login it came from if it can be refreshed
+struct Auth {
     token: String,
+    login: Option<(Store, StoredToken)>,
+}
+
+/// Build the HTTP client with the configured timeout and TLS settings
+pub fn http_client(ctx: &RuntimeContext) -> Result<Client> {
+    let mut builder = Client::builder()
+        .timeout(Duration::from_secs(ctx.timeout()))
+        .user_agent(format!("hmr/{}", env!("CARGO_PKG_VERSION")));
+
+    if ctx.insecure() {
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+
+    builder.build().context("building HTTP client")
 }
 
 impl HassClient {
     /// Create a new Home Assistant client from runtime context
+    ///
+    /// Without a configured token, the credentials stored by `hmr auth login`
+    /// for the server are used.
     pub fn new(ctx: &RuntimeContext) -> Result<Self> {
         let base_url = ctx.server_url()?.trim_end_matches('/').to_string();
-        let token = ctx.token()?.to_string();
+        let auth = match ctx.token() {
+            Ok(token) => Auth {
+                token: token.to_string(),
+                login: None,
+            },
+            // A misspelled profile must not fall back to another identity
+            Err(missing) if ctx.token_profile().is_some() => return Err(missing),
+            Err(missing) => {
+                let store = Store::new(ctx);
+                let stored = store.load(&base_url)?.ok_or(missing)?;
+                Auth {
+                    token: stored.access_token.clone(),
+                    login: Some((store, stored)),
+                }
+            }
+        };
+
+        Ok(Self {
+            client: http_client(ctx)?,
+            base_url,
+            auth: tokio::sync::Mutex::new(auth),
+        })
+    }
 
-        let mut builder = Client::builder()
-            .timeout(Duration::from_secs(ctx.timeout()))
-            .user_agent(format!("hmr/{}", env!("CARGO_PKG_VERSION")));
+    /// The current access token, refreshed first if a stored login expired
+    pub async fn access_token(&self) -> Result<String> {
+        let mut auth = self.auth.lock().await;
+        if auth
+            .login
+            .as_ref()
+            .is_some_and(|(_, stored)| stored.needs_refresh())
+        {
+            self.refresh(&mut auth).await?;
+        }
+        Ok(auth.token.clone())
+    }
 
-        if ctx.insecure() {
-            builder = builder.danger_accept_invalid_certs(true);
+    /// Refresh a stored login's access token; false if there is nothing to refresh
+    async fn refresh(&self, auth: &mut Auth) -> Result<bool> {
+        let Auth { token, login } = auth;
+        let Some((store, stored)) = login.as_mut() else {
+            return Ok(false);
+        };
+        if stored.refresh_token.is_none() {
+            return Ok(false);
         }
+        credentials::refresh(&self.client, &self.base_url, stored).await?;
+        store.save(&self.base_url, stored)?;
+        *token = stored.access_token.clone();
+        Ok(true)
+    }
 
-        let client = builder.build().context("building HTTP client")?;
+    /// Send a request with the bearer token
+    ///
+    /// If the server rejects the token of a stored login, it is refreshed and
+    /// the request sent once more.
+    async fn send(
+        &self,
+        url: &str,
+        request: impl Fn() -> reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response> {
+        let send = |token: &str| {
+            request()
+                .header("Authorization", format!("Bearer {token}"))
+                .send()
+        };
+        let token = self.access_token().await?;
+        let response = send(&token)
+            .await
+            .map_err(|e| Error::network(format!("request to {url}"), e))?;
+        if response.status() != StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
 
-        Ok(Self {
-            client,
-            base_url,
-            token,
-        })
+        let mut auth = self.auth.lock().await;
+        if !self.refresh(&mut auth).await? {
+            return Ok(response);
+        }
+        let token = auth.token.clone();
+        drop(auth);
+        send(&token)
+            .await
+            .map_err(|e| Error::network(format!("request to {url}"), e).into())
     }
 
     /// Make a GET request to the API
     async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
         let url = format!("{}/api{}", self.base_url, path);
         log::debug!("GET {url}");
+        dump::http_request("GET", &url, None);
 
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.token))
-            .send()
-            .await
-            .with_context(|| format!("request to {url}"))?;
+        let response = self.send(&url, || self.client.get(&url)).await?;
 
         self.handle_response(response).await
     }
@@ -148,16 +264,16 @@ impl HassClient {
         let url = format!("{}/api{}", self.base_url, path);
         log::debug!("POST {url}");
         log::trace!("POST body: {body:?}");
+        dump::http_request("POST", &url, Some(body));
 
         let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.token))
-            .header("Content-Type", "application/json")
-            .json(body)
-            .send()
-            .await
-            .with_context(|| format!("request to {url}"))?;
+            .send(&url, || {
+                self.client
+                    .post(&url)
+                    .header("Content-Type", "application/json")
+                    .json(body)
+            })
+            .await?;
 
         self.handle_response(response).await
     }
@@ -167,18 +283,65 @@ impl HassClient {
     async fn delete<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
         let url = format!("{}/api{}", self.base_url, path);
         log::debug!("DELETE {url}");
+        dump::http_request("DELETE", &url, None);
 
-        let response = self
-            .client
-            .delete(&url)
-            .header("Authorization", format!("Bearer {}", self.token))
-            .send()
-            .await
-            .with_context(|| format!("request to {url}"))?;
+        let response = self.send(&url, || self.client.delete(&url)).await?;
 
         self.handle_response(response).await
     }
 
+    /// Make a GET request for a binary body, reporting (received, total) bytes
+    /// to `on_progress` after each chunk
+    async fn get_bytes(
+        &self,
+        path: &str,
+        mut on_progress: impl FnMut(u64, Option<u64>),
+    ) -> Result<Download> {
+        let url = format!("{}/api{}", self.base_url, path);
+        log::debug!("GET {url}");
+        dump::http_request("GET", &url, None);
+
+        let mut response = self.send(&url, || self.client.get(&url)).await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            dump::http_response(status.as_u16(), &url, &error_text);
+            return Err(self.status_to_error(status, &url, &error_text));
+        }
+
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.split(';').next().unwrap_or(v).trim().to_string());
+        let total = response.content_length();
+
+        let mut bytes = Vec::with_capacity(total.unwrap_or(0) as usize);
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .with_context(|| format!("reading response from {url}"))?
+        {
+            bytes.extend_from_slice(&chunk);
+            on_progress(bytes.len() as u64, total);
+        }
+
+        dump::http_response(
+            status.as_u16(),
+            &url,
+            &format!(
+                "<{} bytes of {}>",
+                bytes.len(),
+                content_type.as_deref().unwrap_or("unknown type")
+            ),
+        );
+        Ok(Download {
+            content_type,
+            bytes,
+        })
+    }
+
     async fn handle_response<T: DeserializeOwned>(&self, response: reqwest::Response) -> Result<T> {
         let status = response.status();
         let url = response.url().to_string();
@@ -192,13 +355,23 @@ impl HassClient {
                     String::new()
                 }
             };
+            dump::http_response(status.as_u16(), &url, &error_text);
             return Err(self.status_to_error(status, &url, &error_text));
         }
 
-        response
-            .json()
+        if !dump::enabled() {
+            return response
+                .json()
+                .await
+                .with_context(|| format!("parsing response from {url}"));
+        }
+
+        let text = response
+            .text()
             .await
-            .with_context(|| format!("parsing response from {url}"))
+            .with_context(|| format!("reading response from {url}"))?;
+        dump::http_response(status.as_u16(), &url, &text);
+        serde_json::from_str(&text).with_context(|| format!("parsing response from {url}"))
     }
 
     fn status_to_error(&self, status: StatusCode, url: &str, body: &str) -> anyhow::Error {
@@ -217,11 +390,20 @@ impl HassClient {
             format!("HTTP {status} from {url}: {body}")
         };
 
-        if hint.is_empty() {
-            anyhow!(msg)
+        let message = if hint.is_empty() {
+            msg
         } else {
-            anyhow!("{msg}\nHint: {hint}")
+            format!("{msg}\nHint: {hint}")
+        };
+        match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Error::Auth(message),
+            StatusCode::NOT_FOUND => Error::NotFound(message),
+            _ => Error::Http {
+                status: status.as_u16(),
+                message,
+            },
         }
+        .into()
     }
 
     // --- API Methods ---
@@ -253,6 +435,11 @@ impl HassClient {
         })
     }
 
+    /// Get the core configuration (location, units, currency)
+    pub async fn get_config(&self) -> Result<HassConfig> {
+        self.get("/config").await
+    }
+
     /// Get all entity states
     pub async fn get_states(&self) -> Result<Vec<EntityState>> {
         self.get("/states").await
@@ -286,6 +473,96 @@ impl HassClient {
         .await
     }
 
+    /// Get compact history (state and time only) for several entities between
+    /// two times
+    ///
+    /// Returns one list per entity that had history; the first point of each
+    /// list carries the entity ID.
+    pub async fn get_history_compact(
+        &self,
+        entity_ids: &[String],
+        start_time: &str,
+        end_time: &str,
+    ) -> Result<Vec<Vec<HistoryPoint>>> {
+        let ids = entity_ids
+            .iter()
+            .map(|id| validate_entity_id(id))
+            .collect::<Result<Vec<_>>>()?;
+        let filter = urlencoding::encode(&ids.join(",")).into_owned();
+        self.get(&format!(
+            "/history/period/{start_time}?filter_entity_id={filter}&end_time={}&minimal_response&no_attributes",
+            urlencoding::encode(end_time)
+        ))
+        .await
+    }
+
+    /// Get full state history for several entities between two times
+    ///
+    /// Returns one list per entity that had history, oldest state first.
+    pub async fn get_history_window(
+        &self,
+        entity_ids: &[String],
+        start_time: &str,
+        end_time: &str,
+    ) -> Result<Vec<Vec<EntityState>>> {
+        let ids = entity_ids
+            .iter()
+            .map(|id| validate_entity_id(id))
+            .collect::<Result<Vec<_>>>()?;
+        let filter = urlencoding::encode(&ids.join(",")).into_owned();
+        self.get(&format!(
+            "/history/period/{start_time}?filter_entity_id={filter}&end_time={}",
+            urlencoding::encode(end_time)
+        ))
+        .await
+    }
+
+    /// Get logbook entries between `start_time` and `end_time` (default: one
+    /// day after the start), optionally for one entity
+    pub async fn get_logbook(
+        &self,
+        start_time: impl AsRef<str>,
+        end_time: Option<&str>,
+        entity_id: Option<&str>,
+    ) -> Result<Vec<LogbookEntry>> {
+        let mut query = Vec::new();
+        if let Some(end_time) = end_time {
+            query.push(format!("end_time={}", urlencoding::encode(end_time)));
+        }
+        if let Some(entity_id) = entity_id {
+            let entity_id = validate_entity_id(entity_id)?;
+            query.push(format!("entity={}", urlencoding::encode(entity_id)));
+        }
+
+        let mut path = format!("/logbook/{}", urlencoding::encode(start_time.as_ref()));
+        if !query.is_empty() {
+            path.push('?');
+            path.push_str(&query.join("&"));
+        }
+        self.get(&path).await
+    }
+
+    /// List calendar entities
+    pub async fn get_calendars(&self) -> Result<Vec<Calendar>> {
+        self.get("/calendars").await
+    }
+
+    /// Get a calendar's events between two RFC 3339 timestamps
+    pub async fn get_calendar_events(
+        &self,
+        entity_id: impl AsRef<str>,
+        start: &str,
+        end: &str,
+    ) -> Result<Vec<CalendarEvent>> {
+        let entity_id = validate_entity_id(entity_id.as_ref())?;
+        self.get(&format!(
+            "/calendars/{entity_id}?start={}&end={}",
+            urlencoding::encode(start),
+            urlencoding::encode(end)
+        ))
+        .await
+    }
+
     /// Get all services
     pub async fn get_services(&self) -> Result<Vec<ServiceDomain>> {
         self.get("/services").await
@@ -304,6 +581,110 @@ impl HassClient {
             .await
     }
 
+    /// Download the current image of a camera
+    pub async fn get_camera_image(
+        &self,
+        entity_id: impl AsRef<str>,
+        on_progress: impl FnMut(u64, Option<u64>),
+    ) -> Result<Download> {
+        let entity_id = validate_entity_id(entity_id.as_ref())?;
+        self.get_bytes(&format!("/camera_proxy/{entity_id}"), on_progress)
+            .await
+    }
+
+    /// Download speech synthesized by an Assist pipeline
+    ///
+    /// `url` is the `tts_output.url` of a `tts-end` event, which Home
+    /// Assistant sends as a path or as an absolute URL on its own base URL.
+    pub async fn get_tts_audio(
+        &self,
+        url: &str,
+        on_progress: impl FnMut(u64, Option<u64>),
+    ) -> Result<Download> {
+        let path = match reqwest::Url::parse(url) {
+            Ok(absolute) => absolute.path().to_string(),
+            Err(_) => url.to_string(),
+        };
+        let path = path
+            .strip_prefix("/api")
+            .ok_or_else(|| anyhow!("Unexpected TTS audio URL: {url}"))?;
+        self.get_bytes(path, on_progress).await
+    }
+
+    /// Validate the configuration files without applying them
+    pub async fn check_config(&self) -> Result<ConfigCheck> {
+        self.post("/config/core/check_config", &json!({})).await
+    }
+
+    /// List the config entries (one per configured integration instance)
+    pub async fn get_config_entries(&self) -> Result<Vec<ConfigEntry>> {
+        self.get("/config/config_entries/entry").await
+    }
+
+    /// Reload a config entry, setting it up again if it failed
+    pub async fn reload_config_entry(&self, entry_id: &str) -> Result<Value> {
+        if entry_id.is_empty() || !entry_id.chars().all(|c| c.is_ascii_alphanumeric()) {
+            bail!("Invalid config entry ID: '{entry_id}'");
+        }
+        self.post(
+            &format!("/config/config_entries/entry/{entry_id}/reload"),
+            &json!({}),
+        )
+        .await
+    }
+
+    /// List the Supervisor add-ons installed on this system
+    pub async fn get_addons(&self) -> Result<Vec<Addon>> {
+        #[derive(Deserialize)]
+        struct AddonList {
+            addons: Vec<Addon>,
+        }
+        let list: AddonList = self.supervisor_get("/addons").await?;
+        Ok(list.addons)
+    }
+
+    /// Get the full Supervisor info for one add-on
+    pub async fn get_addon_info(&self, slug: &str) -> Result<Value> {
+        let slug = validate_addon_slug(slug)?;
+        self.supervisor_get(&format!("/addons/{slug}/info")).await
+    }
+
+    /// Start, stop, or restart an add-on
+    pub async fn addon_action(&self, slug: &str, action: &str) -> Result<()> {
+        let slug = validate_addon_slug(slug)?;
+        if !matches!(action, "start" | "stop" | "restart") {
+            bail!("Unknown add-on action: '{action}'");
+        }
+        self.post::<Value>(&format!("/hassio/addons/{slug}/{action}"), &json!({}))
+            .await
+            .map_err(supervisor_error)?;
+        Ok(())
+    }
+
+    /// Get an add-on's container log as text
+    pub async fn get_addon_logs(&self, slug: &str) -> Result<String> {
+        let slug = validate_addon_slug(slug)?;
+        let download = self
+            .get_bytes(&format!("/hassio/addons/{slug}/logs"), |_, _| {})
+            .await
+            .map_err(supervisor_error)?;
+        Ok(String::from_utf8_lossy(&download.bytes).into_owned())
+    }
+
+    /// GET a Supervisor endpoint through Home Assistant's proxy, unwrapping
+    /// the `{"result": "ok", "data": ...}` envelope
+    async fn supervisor_get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        #[derive(Deserialize)]
+        struct Envelope<T> {
+            data: T,
+        }
+        let envelope: Envelope<T> = self
+            .get(&format!("/hassio{path}"))
+            .await
+            .map_err(supervisor_error)?;
+        Ok(envelope.data)
+    }
+
     /// Fire an event
     pub async fn fire_event(&self, event_type: impl AsRef<str>, data: &Value) -> Result<Value> {
         let event_type = validate_event_type(event_type.as_ref())?;
@@ -314,25 +695,28 @@ impl HassClient {
     pub async fn render_template(&self, template: impl AsRef<str>) -> Result<String> {
         let template = template.as_ref();
         let body = serde_json::json!({ "template": template });
-        let url = format!("{}/api/template", self.base_url);
+        let url = format!("{}/api/template?report_errors=true", self.base_url);
+        dump::http_request("POST", &url, Some(&body));
 
         let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.token))
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await
-            .with_context(|| format!("request to {url}"))?;
+            .send(&url, || {
+                self.client
+                    .post(&url)
+                    .header("Content-Type", "application/json")
+                    .json(&body)
+            })
+            .await?;
 
         let status = response.status();
         if !status.is_success() {
             let error_text = response.text().await.unwrap_or_default();
+            dump::http_response(status.as_u16(), &url, &error_text);
             return Err(self.status_to_error(status, &url, &error_text));
         }
 
-        response.text().await.context("reading template response")
+        let text = response.text().await.context("reading template response")?;
+        dump::http_response(status.as_u16(), &url, &text);
+        Ok(text)
     }
 
     /// Process a conversation through Home Assistant's conversation agent
@@ -361,6 +745,20 @@ impl HassClient {
 
         self.post("/conversation/process", &body).await
     }
+
+    /// Fire an intent (e.g., HassTurnOn) with its slots, bypassing the
+    /// conversation agent; needs `intent:` in configuration.yaml
+    pub async fn handle_intent(
+        &self,
+        name: impl AsRef<str>,
+        slots: &Value,
+    ) -> Result<ConversationResponseData> {
+        let body = serde_json::json!({
+            "name": name.as_ref(),
+            "data": slots,
+        });
+        self.post("/intent/handle", &body).await
+    }
 }
 
 // --- Request Types ---
@@ -477,6 +875,9 @@ pub struct HassConfig {
     #[serde(default)]
     pub elevation: i32,
     pub unit_system: UnitSystem,
+    /// ISO 4217 code, e.g. EUR
+    #[serde(default)]
+    pub currency: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -493,6 +894,121 @@ pub struct UnitSystem {
     pub accumulated_precipitation: String,
 }
 
+/// One state change from a compact history query
+#[derive(Debug, Clone, Deserialize)]
+pub struct HistoryPoint {
+    /// Only present on the first point of each entity's list
+    #[serde(default)]
+    pub entity_id: Option<String>,
+    pub state: String,
+    pub last_changed: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Calendar {
+    pub entity_id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarEvent {
+    pub summary: String,
+    pub start: CalendarTime,
+    pub end: CalendarTime,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub uid: Option<String>,
+}
+
+/// An event boundary: a date for all-day events, a date and time otherwise
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarTime {
+    #[serde(rename = "dateTime", default, skip_serializing_if = "Option::is_none")]
+    pub date_time: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+}
+
+/// One human-readable logbook entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogbookEntry {
+    pub when: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Set for events; state changes carry `state` instead
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub entity_id: Option<String>,
+    #[serde(default)]
+    pub state: Option<String>,
+    #[serde(default)]
+    pub domain: Option<String>,
+    /// Context fields (context_user_id, context_service, ...) kept as sent
+    #[serde(flatten)]
+    pub context: serde_json::Map<String, Value>,
+}
+
+/// Result of `/api/config/core/check_config`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigCheck {
+    /// "valid" or "invalid"
+    pub result: String,
+    /// Newline-separated error messages
+    #[serde(default)]
+    pub errors: Option<String>,
+    /// Newline-separated warning messages
+    #[serde(default)]
+    pub warnings: Option<String>,
+}
+
+/// A config entry from `/api/config/config_entries/entry`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigEntry {
+    pub entry_id: String,
+    pub domain: String,
+    pub title: String,
+    /// How it was created; "ignore" marks discoveries the user dismissed
+    #[serde(default)]
+    pub source: Option<String>,
+    /// loaded, setup_error, setup_retry, not_loaded, ...
+    pub state: String,
+    #[serde(default)]
+    pub disabled_by: Option<String>,
+    /// Why setup failed, when it did
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// An installed add-on from the Supervisor's `/addons` list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Addon {
+    pub slug: String,
+    pub name: String,
+    /// started, stopped, unknown, or error
+    #[serde(default)]
+    pub state: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub version_latest: Option<String>,
+    #[serde(default)]
+    pub update_available: bool,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// A binary response body, such as a camera image
+#[derive(Debug, Clone)]
+pub struct Download {
+    /// MIME type without parameters (e.g. "image/jpeg")
+    pub content_type: Option<String>,
+    pub bytes: Vec<u8>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EntityState {
     pub entity_id: String,
@@ -574,6 +1090,20 @@ mod tests {
         assert_eq!(state.state, "on");
     }
 
+    #[test]
+    fn test_history_point_deserialize() {
+        let json = r#"[[
+            {"entity_id": "sensor.temp", "state": "21.5", "attributes": {},
+             "last_changed": "2025-01-15T10:00:00Z", "last_updated": "2025-01-15T10:00:00Z"},
+            {"state": "21.7", "last_changed": "2025-01-15T11:00:00Z"}
+        ]]"#;
+
+        let history: Vec<Vec<HistoryPoint>> = serde_json::from_str(json).unwrap();
+        assert_eq!(history[0][0].entity_id.as_deref(), Some("sensor.temp"));
+        assert_eq!(history[0][1].entity_id, None);
+        assert_eq!(history[0][1].state, "21.7");
+    }
+
     #[test]
     fn test_service_domain_deserialize() {
         let json = r#"{
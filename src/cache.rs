@@ -7,7 +7,7 @@
 //!
 //! Cache is stored at XDG_CACHE_HOME/hmr/ with configurable TTL.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -15,10 +15,12 @@ use std::time::{Duration, SystemTime};
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use crate::api::{EntityState, HassClient};
-use crate::config::RuntimeContext;
-use crate::websocket::{Area, Device, WsClient};
+use crate::config::{CacheConfig, RuntimeContext};
+use crate::progress;
+use crate::websocket::{Area, Device, EntityRegistryEntry, Label, WsClient, WsEvent};
 
 const APP_NAME: &str = env!("CARGO_PKG_NAME");
 
@@ -32,6 +34,8 @@ pub mod ttl {
     pub const SERVICES: u64 = 3600;
     /// Devices - relatively static (1 hour)
     pub const DEVICES: u64 = 3600;
+    /// Labels - relatively static (1 hour)
+    pub const LABELS: u64 = 3600;
 }
 
 /// Cached entity data with metadata for fuzzy matching
@@ -43,10 +47,88 @@ pub struct CachedEntity {
     pub state: String,
     pub friendly_name: Option<String>,
     pub area_id: Option<String>,
+    /// Device this entity belongs to (from the entity registry)
+    #[serde(default)]
+    pub device_id: Option<String>,
+    /// Integration providing this entity (from the entity registry)
+    #[serde(default)]
+    pub platform: Option<String>,
+    /// Label IDs assigned to this entity (from the entity registry)
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// What kind of device this is within its domain (door, window, outlet, ...)
+    #[serde(default)]
+    pub device_class: Option<String>,
+    /// State attributes picked by `[cache] attributes`, for use without an API call
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, Value>,
     /// All searchable names for this entity
     pub search_names: Vec<String>,
 }
 
+impl CachedEntity {
+    /// Merge registry metadata into this entity.
+    ///
+    /// Registry area assignments take precedence over the state attribute.
+    pub fn apply_registry(&mut self, entry: &EntityRegistryEntry) {
+        self.device_id = entry.device_id.clone();
+        self.platform = entry.platform.clone();
+        self.labels = entry.labels.clone();
+        if entry.area_id.is_some() {
+            self.area_id = entry.area_id.clone();
+        }
+    }
+
+    /// Keep the attributes `config` asks for, skipping values over its size limit
+    pub fn with_attributes(mut self, state: &EntityState, config: &CacheConfig) -> Self {
+        for name in &config.attributes {
+            let Some(value) = state.attributes.get(name).filter(|v| !v.is_null()) else {
+                continue;
+            };
+            let size = value.to_string().len();
+            if size > config.max_attribute_bytes {
+                log::debug!("Not caching {name} of {}: {size} bytes", self.entity_id);
+                continue;
+            }
+            self.attributes.insert(name.clone(), value.clone());
+        }
+        self
+    }
+
+    /// Options of a select or input_select, if cached
+    pub fn options(&self) -> Option<Vec<String>> {
+        let options = self.attributes.get("options")?.as_array()?;
+        Some(
+            options
+                .iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+impl CachedEntity {
+    /// Bare entity for tests; override other fields with struct update syntax
+    pub fn for_test(entity_id: &str, state: &str) -> Self {
+        let (domain, object_id) = entity_id.split_once('.').unwrap();
+        Self {
+            entity_id: entity_id.to_string(),
+            domain: domain.to_string(),
+            object_id: object_id.to_string(),
+            state: state.to_string(),
+            friendly_name: None,
+            area_id: None,
+            device_id: None,
+            platform: None,
+            labels: vec![],
+            device_class: None,
+            attributes: Default::default(),
+            search_names: vec![],
+        }
+    }
+}
+
 impl From<&EntityState> for CachedEntity {
     fn from(state: &EntityState) -> Self {
         let parts: Vec<&str> = state.entity_id.split('.').collect();
@@ -68,6 +150,12 @@ impl From<&EntityState> for CachedEntity {
             .and_then(|v| v.as_str())
             .map(|s| s.to_string());
 
+        let device_class = state
+            .attributes
+            .get("device_class")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+
         // Build search names for fuzzy matching
         let mut search_names = vec![state.entity_id.clone(), object_id.clone()];
         if let Some(ref name) = friendly_name {
@@ -84,6 +172,11 @@ impl From<&EntityState> for CachedEntity {
             state: state.state.clone(),
             friendly_name,
             area_id,
+            device_id: None,
+            platform: None,
+            labels: Vec::new(),
+            device_class,
+            attributes: BTreeMap::new(),
             search_names,
         }
     }
@@ -97,6 +190,10 @@ pub struct CachedArea {
     pub aliases: Vec<String>,
     /// All searchable names for this area
     pub search_names: Vec<String>,
+    #[serde(default)]
+    pub floor_id: Option<String>,
+    #[serde(default)]
+    pub floor_name: Option<String>,
 }
 
 impl From<&Area> for CachedArea {
@@ -117,6 +214,40 @@ impl From<&Area> for CachedArea {
             name: area.name.clone(),
             aliases: area.aliases.clone(),
             search_names,
+            floor_id: area.floor_id.clone(),
+            floor_name: None,
+        }
+    }
+}
+
+/// Cached label with search names
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedLabel {
+    pub label_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// All searchable names for this label
+    pub search_names: Vec<String>,
+}
+
+impl From<&Label> for CachedLabel {
+    fn from(label: &Label) -> Self {
+        Self {
+            label_id: label.label_id.clone(),
+            name: label.name.clone(),
+            color: label.color.clone(),
+            icon: label.icon.clone(),
+            description: label.description.clone(),
+            search_names: vec![
+                label.label_id.clone(),
+                label.name.clone(),
+                label.name.to_lowercase(),
+            ],
         }
     }
 }
@@ -139,6 +270,10 @@ pub struct CachedDevice {
     pub manufacturer: Option<String>,
     pub model: Option<String>,
     pub area_id: Option<String>,
+    #[serde(default)]
+    pub sw_version: Option<String>,
+    #[serde(default)]
+    pub hw_version: Option<String>,
     /// All searchable names for this device
     pub search_names: Vec<String>,
 }
@@ -162,11 +297,22 @@ impl From<&Device> for CachedDevice {
             manufacturer: device.manufacturer.clone(),
             model: device.model.clone(),
             area_id: device.area_id.clone(),
+            sw_version: device.sw_version.clone(),
+            hw_version: device.hw_version.clone(),
             search_names,
         }
     }
 }
 
+/// An entity joined with its related cached objects
+#[derive(Debug, Clone, Serialize)]
+pub struct EntityContext {
+    pub entity: CachedEntity,
+    pub integration: Option<String>,
+    pub device: Option<CachedDevice>,
+    pub area: Option<CachedArea>,
+}
+
 /// A cache file with metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CacheFile<T> {
@@ -239,6 +385,7 @@ pub struct Cache {
     pub areas: Option<CacheFile<Vec<CachedArea>>>,
     pub services: Option<CacheFile<Vec<CachedService>>>,
     pub devices: Option<CacheFile<Vec<CachedDevice>>>,
+    pub labels: Option<CacheFile<Vec<CachedLabel>>>,
     /// Lookup maps for fast access
     entity_map: HashMap<String, CachedEntity>,
     area_map: HashMap<String, CachedArea>,
@@ -306,6 +453,18 @@ impl Cache {
             }
         }
 
+        // Load labels
+        let labels_path = cache_dir.join("labels.json");
+        if labels_path.exists() {
+            if let Ok(content) = fs::read_to_string(&labels_path) {
+                if let Ok(file) = serde_json::from_str::<CacheFile<Vec<CachedLabel>>>(&content) {
+                    if file.is_valid(server_url) {
+                        cache.set_labels(file);
+                    }
+                }
+            }
+        }
+
         Ok(cache)
     }
 
@@ -343,6 +502,13 @@ impl Cache {
                 .with_context(|| format!("writing devices cache to {}", path.display()))?;
         }
 
+        if let Some(ref labels) = self.labels {
+            let path = cache_dir.join("labels.json");
+            let content = serde_json::to_string_pretty(labels)?;
+            fs::write(&path, content)
+                .with_context(|| format!("writing labels cache to {}", path.display()))?;
+        }
+
         Ok(())
     }
 
@@ -382,6 +548,73 @@ impl Cache {
         self.devices = Some(file);
     }
 
+    /// Set labels
+    pub fn set_labels(&mut self, file: CacheFile<Vec<CachedLabel>>) {
+        self.labels = Some(file);
+    }
+
+    /// Insert or replace one cached entity, keeping the entities file's timestamp
+    pub fn upsert_entity(&mut self, entity: CachedEntity) {
+        if let Some(ref mut file) = self.entities {
+            match file
+                .data
+                .iter_mut()
+                .find(|e| e.entity_id == entity.entity_id)
+            {
+                Some(existing) => *existing = entity.clone(),
+                None => file.data.push(entity.clone()),
+            }
+            self.entity_map.insert(entity.entity_id.clone(), entity);
+        }
+    }
+
+    /// Drop one cached entity; returns whether it was cached
+    pub fn remove_entity(&mut self, entity_id: &str) -> bool {
+        if let Some(ref mut file) = self.entities {
+            file.data.retain(|e| e.entity_id != entity_id);
+        }
+        self.entity_map.remove(entity_id).is_some()
+    }
+
+    /// Copy of this cache whose entities are limited to `domains`
+    ///
+    /// Used for strict per-domain lookups, where a name must never resolve to an
+    /// entity of another domain.
+    pub fn restricted_to_domains(&self, domains: &[&str]) -> Cache {
+        self.restricted_to(|e| domains.contains(&e.domain.as_str()))
+    }
+
+    /// Copy of this cache whose entities are limited to one area
+    ///
+    /// Entities count as in the area directly or through their device, as in
+    /// [`Cache::entity_context`]. Used for `--room`.
+    pub fn restricted_to_area(&self, area_id: &str) -> Cache {
+        self.restricted_to(|e| self.entity_area_id(e) == Some(area_id))
+    }
+
+    fn restricted_to(&self, keep: impl Fn(&CachedEntity) -> bool) -> Cache {
+        let mut cache = Cache::new();
+        if let Some(ref file) = self.entities {
+            let data = file.data.iter().filter(|e| keep(e)).cloned().collect();
+            cache.set_entities(CacheFile {
+                updated_at: file.updated_at,
+                ttl: file.ttl,
+                server_url: file.server_url.clone(),
+                data,
+            });
+        }
+        if let Some(ref file) = self.areas {
+            cache.set_areas(file.clone());
+        }
+        if let Some(ref file) = self.devices {
+            cache.set_devices(file.clone());
+        }
+        if let Some(ref file) = self.labels {
+            cache.set_labels(file.clone());
+        }
+        cache
+    }
+
     /// Get an entity by ID
     pub fn get_entity(&self, entity_id: &str) -> Option<&CachedEntity> {
         self.entity_map.get(entity_id)
@@ -392,6 +625,83 @@ impl Cache {
         self.area_map.get(area_id)
     }
 
+    /// Get a device by ID
+    pub fn get_device(&self, device_id: &str) -> Option<&CachedDevice> {
+        self.devices().iter().find(|d| d.id == device_id)
+    }
+
+    /// Join an entity with its device, area, and integration
+    ///
+    /// The area comes from the entity itself, falling back to its device's area.
+    pub fn entity_context(&self, entity: &CachedEntity) -> EntityContext {
+        let device = entity
+            .device_id
+            .as_deref()
+            .and_then(|id| self.get_device(id))
+            .cloned();
+        let area = self
+            .entity_area_id(entity)
+            .and_then(|id| self.get_area(id))
+            .cloned();
+
+        EntityContext {
+            entity: entity.clone(),
+            integration: entity.platform.clone(),
+            device,
+            area,
+        }
+    }
+
+    /// Area of an entity, falling back to its device's area
+    pub fn entity_area_id<'a>(&'a self, entity: &'a CachedEntity) -> Option<&'a str> {
+        entity.area_id.as_deref().or_else(|| {
+            entity
+                .device_id
+                .as_deref()
+                .and_then(|id| self.get_device(id))
+                .and_then(|d| d.area_id.as_deref())
+        })
+    }
+
+    /// Fingerprint of the cached data the matcher works on.
+    ///
+    /// Derived from entity IDs, names, areas and device classes, so refreshes
+    /// that don't change anything relevant to matching keep the same
+    /// fingerprint. None when no entities are cached.
+    pub fn fingerprint(&self) -> Option<String> {
+        if !self.has_entities() {
+            return None;
+        }
+
+        let mut lines: Vec<String> = self
+            .entities()
+            .iter()
+            .map(|e| {
+                format!(
+                    "e|{}|{}|{}|{}",
+                    e.entity_id,
+                    e.friendly_name.as_deref().unwrap_or(""),
+                    e.area_id.as_deref().unwrap_or(""),
+                    e.device_class.as_deref().unwrap_or("")
+                )
+            })
+            .chain(
+                self.areas()
+                    .iter()
+                    .map(|a| format!("a|{}|{}|{}", a.area_id, a.name, a.aliases.join(","))),
+            )
+            .collect();
+        lines.sort();
+
+        // FNV-1a: stable across runs and platforms, unlike the std hasher
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for byte in lines.join("\n").bytes() {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        Some(format!("{hash:016x}"))
+    }
+
     /// Get all entities
     pub fn entities(&self) -> &[CachedEntity] {
         self.entities
@@ -424,6 +734,14 @@ impl Cache {
             .unwrap_or(&[])
     }
 
+    /// Get all labels
+    pub fn labels(&self) -> &[CachedLabel] {
+        self.labels
+            .as_ref()
+            .map(|f| f.data.as_slice())
+            .unwrap_or(&[])
+    }
+
     /// Get services for a domain
     pub fn services_for_domain(&self, domain: &str) -> &[String] {
         self.domain_services
@@ -448,6 +766,14 @@ impl Cache {
             .collect()
     }
 
+    /// Get entities carrying a label
+    pub fn entities_with_label(&self, label_id: &str) -> Vec<&CachedEntity> {
+        self.entities()
+            .iter()
+            .filter(|e| e.labels.iter().any(|l| l == label_id))
+            .collect()
+    }
+
     /// Get all known domains
     pub fn domains(&self) -> Vec<&str> {
         let mut domains: Vec<&str> = self.entities().iter().map(|e| e.domain.as_str()).collect();
@@ -475,6 +801,11 @@ impl Cache {
     pub fn has_devices(&self) -> bool {
         self.devices.is_some()
     }
+
+    /// Check if cache has valid labels
+    pub fn has_labels(&self) -> bool {
+        self.labels.is_some()
+    }
 }
 
 /// Cache manager for refreshing and managing cache
@@ -512,10 +843,29 @@ impl<'a> CacheManager<'a> {
 
     /// Refresh entities from Home Assistant
     pub async fn refresh_entities(&mut self) -> Result<()> {
+        let _spinner = progress::spinner(self.ctx, "Refreshing entities");
         let client = HassClient::new(self.ctx)?;
         let states = client.get_states().await?;
 
-        let cached: Vec<CachedEntity> = states.iter().map(CachedEntity::from).collect();
+        let attributes = &self.ctx.config.cache;
+        let mut cached: Vec<CachedEntity> = states
+            .iter()
+            .map(|state| CachedEntity::from(state).with_attributes(state, attributes))
+            .collect();
+
+        // Join registry metadata (device, integration, area). This needs the
+        // WebSocket API and admin rights, so carry on with plain states if it fails.
+        match self.fetch_entity_registry().await {
+            Ok(registry) => {
+                for entity in &mut cached {
+                    if let Some(entry) = registry.get(&entity.entity_id) {
+                        entity.apply_registry(entry);
+                    }
+                }
+            }
+            Err(e) => log::debug!("Skipping entity registry join: {e:#}"),
+        }
+
         let server_url = self.ctx.server_url()?.to_string();
 
         // Use cache_mut for direct manipulation
@@ -527,12 +877,23 @@ impl<'a> CacheManager<'a> {
         Ok(())
     }
 
+    async fn fetch_entity_registry(&self) -> Result<HashMap<String, EntityRegistryEntry>> {
+        let ws = WsClient::connect(self.ctx).await?;
+        let entries = ws.list_entity_registry().await?;
+        Ok(entries
+            .into_iter()
+            .map(|e| (e.entity_id.clone(), e))
+            .collect())
+    }
+
     /// Refresh areas from Home Assistant (requires WebSocket)
     pub async fn refresh_areas(&mut self) -> Result<()> {
-        let mut ws = WsClient::connect(self.ctx).await?;
-        let areas = ws.list_areas().await?;
+        let _spinner = progress::spinner(self.ctx, "Refreshing areas");
+        let ws = WsClient::connect(self.ctx).await?;
+        self.store_areas(fetch_areas(&ws).await?)
+    }
 
-        let cached: Vec<CachedArea> = areas.iter().map(CachedArea::from).collect();
+    fn store_areas(&mut self, cached: Vec<CachedArea>) -> Result<()> {
         let server_url = self.ctx.server_url()?.to_string();
 
         // Use cache_mut for direct manipulation
@@ -546,6 +907,7 @@ impl<'a> CacheManager<'a> {
 
     /// Refresh services from Home Assistant
     pub async fn refresh_services(&mut self) -> Result<()> {
+        let _spinner = progress::spinner(self.ctx, "Refreshing services");
         let client = HassClient::new(self.ctx)?;
         let domains = client.get_services().await?;
 
@@ -574,9 +936,12 @@ impl<'a> CacheManager<'a> {
 
     /// Refresh devices from Home Assistant (requires WebSocket)
     pub async fn refresh_devices(&mut self) -> Result<()> {
-        let mut ws = WsClient::connect(self.ctx).await?;
-        let devices = ws.list_devices().await?;
+        let _spinner = progress::spinner(self.ctx, "Refreshing devices");
+        let ws = WsClient::connect(self.ctx).await?;
+        self.store_devices(&ws.list_devices().await?)
+    }
 
+    fn store_devices(&mut self, devices: &[Device]) -> Result<()> {
         let cached: Vec<CachedDevice> = devices.iter().map(CachedDevice::from).collect();
         let server_url = self.ctx.server_url()?.to_string();
 
@@ -589,19 +954,134 @@ impl<'a> CacheManager<'a> {
         Ok(())
     }
 
+    /// Refresh labels from Home Assistant (requires WebSocket)
+    pub async fn refresh_labels(&mut self) -> Result<()> {
+        let _spinner = progress::spinner(self.ctx, "Refreshing labels");
+        let ws = WsClient::connect(self.ctx).await?;
+        self.store_labels(&ws.list_labels().await?)
+    }
+
+    fn store_labels(&mut self, labels: &[Label]) -> Result<()> {
+        let cached: Vec<CachedLabel> = labels.iter().map(CachedLabel::from).collect();
+        let server_url = self.ctx.server_url()?.to_string();
+
+        let cache = self.cache_mut();
+        cache.set_labels(CacheFile::new(cached, ttl::LABELS, server_url));
+        cache.save()?;
+
+        log::info!("Refreshed {} labels", self.cache.labels().len());
+        Ok(())
+    }
+
     /// Refresh all caches
     pub async fn refresh_all(&mut self) -> Result<()> {
         // Refresh REST API caches first (entities, services)
         self.refresh_entities().await?;
         self.refresh_services().await?;
 
-        // Then WebSocket-based caches (areas, devices)
+        // Then WebSocket-based caches (areas, devices, labels)
         self.refresh_areas().await?;
         self.refresh_devices().await?;
+        self.refresh_labels().await?;
 
         Ok(())
     }
 
+    /// Apply an `*_registry_updated` event to the cache and save it
+    ///
+    /// Entity changes are applied to the one entity involved. Home Assistant
+    /// has no call to fetch a single area, device or label, so those events
+    /// re-list the (small) registry involved. Returns a short description of
+    /// what changed, or None for events that don't affect the cache.
+    ///
+    /// `ws` must not be the connection the events arrive on: its receive task
+    /// stalls once a subscription's buffer is full, so a reply queued behind
+    /// undrained events would never arrive.
+    pub async fn apply_registry_event(
+        &mut self,
+        ws: &WsClient,
+        event: &WsEvent,
+    ) -> Result<Option<String>> {
+        let action = event.data["action"].as_str().unwrap_or("update");
+        match event.event_type.as_str() {
+            "entity_registry_updated" => {
+                let Some(entity_id) = event.data["entity_id"].as_str() else {
+                    return Ok(None);
+                };
+                if let Some(old_id) = event.data["old_entity_id"].as_str() {
+                    self.cache.remove_entity(old_id);
+                }
+                if action == "remove" {
+                    if !self.cache.remove_entity(entity_id) {
+                        return Ok(None);
+                    }
+                } else {
+                    let entry = ws.get_entity_registry_entry(entity_id).await?;
+                    let cached = self.cache.get_entity(entity_id).cloned();
+                    if !self.upsert_registry_entry(cached, &entry).await? {
+                        return Ok(None);
+                    }
+                }
+                self.cache.save()?;
+                Ok(Some(format!("entity {entity_id} {action}d")))
+            }
+            "area_registry_updated" => {
+                self.store_areas(fetch_areas(ws).await?)?;
+                let area_id = event.data["area_id"].as_str().unwrap_or("?");
+                Ok(Some(format!("area {area_id} {action}d")))
+            }
+            // Areas carry their floor's name
+            "floor_registry_updated" => {
+                self.store_areas(fetch_areas(ws).await?)?;
+                let floor_id = event.data["floor_id"].as_str().unwrap_or("?");
+                Ok(Some(format!("floor {floor_id} {action}d")))
+            }
+            "device_registry_updated" => {
+                self.store_devices(&ws.list_devices().await?)?;
+                let device_id = event.data["device_id"].as_str().unwrap_or("?");
+                Ok(Some(format!("device {device_id} {action}d")))
+            }
+            "label_registry_updated" => {
+                self.store_labels(&ws.list_labels().await?)?;
+                let label_id = event.data["label_id"].as_str().unwrap_or("?");
+                Ok(Some(format!("label {label_id} {action}d")))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Re-cache one entity after its registry entry changed
+    ///
+    /// Fetches the live state, since a rename changes the friendly name too.
+    /// Returns false for entities without a state (e.g. disabled), which
+    /// aren't cached.
+    pub async fn refresh_entity(&mut self, entry: &EntityRegistryEntry) -> Result<bool> {
+        let cached = self.upsert_registry_entry(None, entry).await?;
+        if cached {
+            self.cache.save()?;
+        }
+        Ok(cached)
+    }
+
+    async fn upsert_registry_entry(
+        &mut self,
+        cached: Option<CachedEntity>,
+        entry: &EntityRegistryEntry,
+    ) -> Result<bool> {
+        let mut entity = match cached {
+            Some(entity) => entity,
+            None => match HassClient::new(self.ctx)?.get_state(&entry.entity_id).await {
+                Ok(state) => {
+                    CachedEntity::from(&state).with_attributes(&state, &self.ctx.config.cache)
+                }
+                Err(_) => return Ok(false),
+            },
+        };
+        entity.apply_registry(entry);
+        self.cache.upsert_entity(entity);
+        Ok(true)
+    }
+
     /// Ensure entities are cached, refreshing if needed
     pub async fn ensure_entities(&mut self) -> Result<&[CachedEntity]> {
         if !self.cache.has_entities() {
@@ -633,6 +1113,38 @@ impl<'a> CacheManager<'a> {
         }
         Ok(self.cache.devices())
     }
+
+    /// Ensure labels are cached, refreshing if needed
+    pub async fn ensure_labels(&mut self) -> Result<&[CachedLabel]> {
+        if !self.cache.has_labels() {
+            self.refresh_labels().await?;
+        }
+        Ok(self.cache.labels())
+    }
+}
+
+/// List the areas, with the names of their floors
+async fn fetch_areas(ws: &WsClient) -> Result<Vec<CachedArea>> {
+    let areas = ws.list_areas().await?;
+    // Servers from before floors existed (2024.4) reject the call
+    let floors: HashMap<String, String> = match ws.list_floors().await {
+        Ok(floors) => floors.into_iter().map(|f| (f.floor_id, f.name)).collect(),
+        Err(e) => {
+            log::debug!("Could not list floors: {e:#}");
+            HashMap::new()
+        }
+    };
+    Ok(areas
+        .iter()
+        .map(|area| CachedArea {
+            floor_name: area
+                .floor_id
+                .as_ref()
+                .and_then(|id| floors.get(id))
+                .cloned(),
+            ..CachedArea::from(area)
+        })
+        .collect())
 }
 
 /// Get the cache directory path
@@ -664,6 +1176,27 @@ pub fn clear_cache() -> Result<()> {
     Ok(())
 }
 
+/// Friendly names by entity ID from the entities cache file
+///
+/// Unlike [`Cache::load`] this ignores the TTL: names rarely change, and a
+/// stale name beats none. Empty when nothing is cached for `server_url`.
+pub fn cached_friendly_names(server_url: &str) -> HashMap<String, String> {
+    let Ok(dir) = cache_dir() else {
+        return HashMap::new();
+    };
+    fs::read_to_string(dir.join("entities.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<CacheFile<Vec<CachedEntity>>>(&content).ok())
+        .filter(|file| file.server_url == server_url)
+        .map(|file| {
+            file.data
+                .into_iter()
+                .filter_map(|e| e.friendly_name.map(|name| (e.entity_id, name)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 /// Get cache status information
 #[derive(Debug, Clone, Serialize)]
 pub struct CacheStatus {
@@ -672,6 +1205,7 @@ pub struct CacheStatus {
     pub areas: Option<CacheFileStatus>,
     pub services: Option<CacheFileStatus>,
     pub devices: Option<CacheFileStatus>,
+    pub labels: Option<CacheFileStatus>,
     pub total_size_bytes: u64,
 }
 
@@ -694,6 +1228,7 @@ pub fn cache_status(server_url: &str) -> Result<CacheStatus> {
     let areas = get_file_status::<Vec<CachedArea>>(&dir.join("areas.json"), server_url)?;
     let services = get_file_status::<Vec<CachedService>>(&dir.join("services.json"), server_url)?;
     let devices = get_file_status::<Vec<CachedDevice>>(&dir.join("devices.json"), server_url)?;
+    let labels = get_file_status::<Vec<CachedLabel>>(&dir.join("labels.json"), server_url)?;
 
     if let Some(ref s) = entities {
         total_size += s.size_bytes;
@@ -707,6 +1242,9 @@ pub fn cache_status(server_url: &str) -> Result<CacheStatus> {
     if let Some(ref s) = devices {
         total_size += s.size_bytes;
     }
+    if let Some(ref s) = labels {
+        total_size += s.size_bytes;
+    }
 
     Ok(CacheStatus {
         cache_dir: dir,
@@ -714,6 +1252,7 @@ pub fn cache_status(server_url: &str) -> Result<CacheStatus> {
         areas,
         services,
         devices,
+        labels,
         total_size_bytes: total_size,
     })
 }
@@ -796,6 +1335,33 @@ mod tests {
         assert!(cached.search_names.contains(&"kitchen_light".to_string()));
     }
 
+    #[test]
+    fn test_cached_entity_with_attributes() {
+        let state = EntityState {
+            entity_id: "input_select.mode".to_string(),
+            state: "home".to_string(),
+            attributes: serde_json::json!({
+                "options": ["home", "away", "night"],
+                "icon": "mdi:home",
+                "unit_of_measurement": null,
+                "supported_features": "x".repeat(2000)
+            }),
+            last_changed: "2025-01-01T00:00:00Z".to_string(),
+            last_updated: "2025-01-01T00:00:00Z".to_string(),
+            context: serde_json::Value::Null,
+        };
+        let config = CacheConfig::default();
+
+        // Only configured, non-null values under the size limit are kept
+        let cached = CachedEntity::from(&state).with_attributes(&state, &config);
+        assert_eq!(
+            cached.attributes.keys().collect::<Vec<_>>(),
+            vec!["options"]
+        );
+        assert_eq!(cached.options().unwrap(), vec!["home", "away", "night"]);
+        assert_eq!(CachedEntity::from(&state).options(), None);
+    }
+
     #[test]
     fn test_cached_entity_without_friendly_name() {
         let state = EntityState {
@@ -886,33 +1452,9 @@ mod tests {
 
         // Add some test entities
         let entities = vec![
-            CachedEntity {
-                entity_id: "light.kitchen".to_string(),
-                domain: "light".to_string(),
-                object_id: "kitchen".to_string(),
-                state: "on".to_string(),
-                friendly_name: None,
-                area_id: None,
-                search_names: vec![],
-            },
-            CachedEntity {
-                entity_id: "light.bedroom".to_string(),
-                domain: "light".to_string(),
-                object_id: "bedroom".to_string(),
-                state: "off".to_string(),
-                friendly_name: None,
-                area_id: None,
-                search_names: vec![],
-            },
-            CachedEntity {
-                entity_id: "switch.outlet".to_string(),
-                domain: "switch".to_string(),
-                object_id: "outlet".to_string(),
-                state: "on".to_string(),
-                friendly_name: None,
-                area_id: None,
-                search_names: vec![],
-            },
+            CachedEntity::for_test("light.kitchen", "on"),
+            CachedEntity::for_test("light.bedroom", "off"),
+            CachedEntity::for_test("switch.outlet", "on"),
         ];
 
         let file = CacheFile::new(entities, 60, "http://localhost:8123".to_string());
@@ -924,29 +1466,92 @@ mod tests {
         assert_eq!(domains.len(), 2);
     }
 
+    #[test]
+    fn test_cache_restricted_to_domains() {
+        let mut cache = Cache::new();
+        cache.set_entities(CacheFile::new(
+            vec![
+                CachedEntity::for_test("light.kitchen", "on"),
+                CachedEntity::for_test("sensor.kitchen", "21.5"),
+            ],
+            60,
+            "http://localhost:8123".to_string(),
+        ));
+
+        let lights = cache.restricted_to_domains(&["light"]);
+        assert_eq!(lights.entities().len(), 1);
+        assert!(lights.get_entity("light.kitchen").is_some());
+        assert!(lights.get_entity("sensor.kitchen").is_none());
+    }
+
+    #[test]
+    fn test_cache_upsert_and_remove_entity() {
+        let mut cache = Cache::new();
+        cache.set_entities(CacheFile::new(
+            vec![CachedEntity::for_test("light.kitchen", "off")],
+            60,
+            "http://localhost:8123".to_string(),
+        ));
+
+        cache.upsert_entity(CachedEntity::for_test("light.kitchen", "on"));
+        cache.upsert_entity(CachedEntity::for_test("light.hall", "off"));
+        assert_eq!(cache.entities().len(), 2);
+        assert_eq!(cache.get_entity("light.kitchen").unwrap().state, "on");
+
+        assert!(cache.remove_entity("light.kitchen"));
+        assert!(!cache.remove_entity("light.kitchen"));
+        assert_eq!(cache.entities().len(), 1);
+        assert!(cache.get_entity("light.kitchen").is_none());
+    }
+
+    #[test]
+    fn test_cache_restricted_to_area() {
+        let mut cache = Cache::new();
+        let entity =
+            |entity_id: &str, area_id: Option<&str>, device_id: Option<&str>| CachedEntity {
+                area_id: area_id.map(String::from),
+                device_id: device_id.map(String::from),
+                ..CachedEntity::for_test(entity_id, "on")
+            };
+        cache.set_entities(CacheFile::new(
+            vec![
+                entity("light.direct", Some("kitchen"), None),
+                entity("light.via_device", None, Some("dev1")),
+                entity("light.elsewhere", Some("office"), Some("dev1")),
+            ],
+            60,
+            "http://localhost:8123".to_string(),
+        ));
+        cache.set_devices(CacheFile::new(
+            vec![CachedDevice {
+                id: "dev1".to_string(),
+                name: None,
+                name_by_user: None,
+                manufacturer: None,
+                model: None,
+                area_id: Some("kitchen".to_string()),
+                sw_version: None,
+                hw_version: None,
+                search_names: vec![],
+            }],
+            60,
+            "http://localhost:8123".to_string(),
+        ));
+
+        let kitchen = cache.restricted_to_area("kitchen");
+        assert!(kitchen.get_entity("light.direct").is_some());
+        assert!(kitchen.get_entity("light.via_device").is_some());
+        // The entity's own area wins over its device's
+        assert!(kitchen.get_entity("light.elsewhere").is_none());
+    }
+
     #[test]
     fn test_cache_entities_in_domain() {
         let mut cache = Cache::new();
 
         let entities = vec![
-            CachedEntity {
-                entity_id: "light.kitchen".to_string(),
-                domain: "light".to_string(),
-                object_id: "kitchen".to_string(),
-                state: "on".to_string(),
-                friendly_name: None,
-                area_id: None,
-                search_names: vec![],
-            },
-            CachedEntity {
-                entity_id: "switch.outlet".to_string(),
-                domain: "switch".to_string(),
-                object_id: "outlet".to_string(),
-                state: "on".to_string(),
-                friendly_name: None,
-                area_id: None,
-                search_names: vec![],
-            },
+            CachedEntity::for_test("light.kitchen", "on"),
+            CachedEntity::for_test("switch.outlet", "on"),
         ];
 
         let file = CacheFile::new(entities, 60, "http://localhost:8123".to_string());
@@ -957,6 +1562,108 @@ mod tests {
         assert_eq!(lights[0].entity_id, "light.kitchen");
     }
 
+    #[test]
+    fn test_entity_context_joins_device_and_area() {
+        let mut cache = Cache::new();
+
+        let mut entity = CachedEntity::from(&EntityState {
+            entity_id: "light.kitchen".to_string(),
+            state: "on".to_string(),
+            attributes: serde_json::json!({"friendly_name": "Kitchen Light"}),
+            last_changed: String::new(),
+            last_updated: String::new(),
+            context: serde_json::Value::Null,
+        });
+        entity.apply_registry(&EntityRegistryEntry {
+            entity_id: "light.kitchen".to_string(),
+            device_id: Some("dev1".to_string()),
+            area_id: None,
+            platform: Some("hue".to_string()),
+            config_entry_id: None,
+            name: None,
+            original_name: None,
+            disabled_by: None,
+            hidden_by: None,
+            labels: vec![],
+            aliases: vec![],
+        });
+
+        let device: Device = serde_json::from_value(serde_json::json!({
+            "id": "dev1",
+            "name": "Hue Bulb",
+            "manufacturer": "Signify",
+            "sw_version": "1.2.3",
+            "area_id": "kitchen"
+        }))
+        .unwrap();
+        cache.set_devices(CacheFile::new(
+            vec![CachedDevice::from(&device)],
+            ttl::DEVICES,
+            String::new(),
+        ));
+        cache.set_areas(CacheFile::new(
+            vec![CachedArea::from(&Area {
+                area_id: "kitchen".to_string(),
+                name: "Kitchen".to_string(),
+                picture: None,
+                aliases: vec![],
+                icon: None,
+                floor_id: None,
+                labels: vec![],
+            })],
+            ttl::AREAS,
+            String::new(),
+        ));
+
+        let info = cache.entity_context(&entity);
+        assert_eq!(info.integration.as_deref(), Some("hue"));
+        let device = info.device.expect("device joined");
+        assert_eq!(device.sw_version.as_deref(), Some("1.2.3"));
+        // Area falls back to the device's area
+        assert_eq!(info.area.expect("area joined").name, "Kitchen");
+    }
+
+    #[test]
+    fn test_cache_fingerprint() {
+        let mut cache = Cache::new();
+        assert_eq!(cache.fingerprint(), None);
+
+        let entity = |id: &str, state: &str| {
+            CachedEntity::from(&EntityState {
+                entity_id: id.to_string(),
+                state: state.to_string(),
+                attributes: serde_json::json!({}),
+                last_changed: String::new(),
+                last_updated: String::new(),
+                context: serde_json::Value::Null,
+            })
+        };
+
+        cache.set_entities(CacheFile::new(
+            vec![entity("light.a", "on"), entity("light.b", "off")],
+            ttl::ENTITIES,
+            String::new(),
+        ));
+        let first = cache.fingerprint().unwrap();
+        assert_eq!(first.len(), 16);
+
+        // State changes and ordering don't affect the fingerprint
+        cache.set_entities(CacheFile::new(
+            vec![entity("light.b", "on"), entity("light.a", "off")],
+            ttl::ENTITIES,
+            String::new(),
+        ));
+        assert_eq!(cache.fingerprint().unwrap(), first);
+
+        // New entities do
+        cache.set_entities(CacheFile::new(
+            vec![entity("light.a", "on"), entity("light.c", "on")],
+            ttl::ENTITIES,
+            String::new(),
+        ));
+        assert_ne!(cache.fingerprint().unwrap(), first);
+    }
+
     #[test]
     fn test_cache_dir() {
         let dir = cache_dir().unwrap();
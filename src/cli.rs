@@ -1,9 +1,11 @@
 //! CLI argument parsing and command definitions
 
 use std::path::PathBuf;
+use std::time::Duration;
 
 use clap::{Args, Parser, Subcommand, ValueEnum};
-use clap_complete::Shell;
+
+use crate::timespec::{self, TimeSpec};
 
 /// A slim, fast CLI for Home Assistant
 #[derive(Debug, Parser)]
@@ -39,6 +41,19 @@ pub struct GlobalOpts {
     #[arg(long, global = true)]
     pub json: bool,
 
+    /// Stable, versioned line output for scripts (entity get, service call, do --plan)
+    #[arg(
+        long,
+        value_enum,
+        value_name = "VERSION",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "v1",
+        global = true,
+        conflicts_with_all = ["json", "output_format"]
+    )]
+    pub porcelain: Option<PorcelainVersion>,
+
     /// Home Assistant server URL
     #[arg(short = 's', long, env = "HASS_SERVER", global = true)]
     pub server: Option<String>,
@@ -47,8 +62,13 @@ pub struct GlobalOpts {
     #[arg(long, env = "HASS_TOKEN", global = true, hide_env_values = true)]
     pub token: Option<String>,
 
-    /// Request timeout in seconds
-    #[arg(long, global = true)]
+    /// Act with a named token from `homeassistant.tokens` (e.g. admin, kids);
+    /// overrides --token and is recorded in the command history
+    #[arg(long = "as", value_name = "PROFILE", env = "HMR_AS", global = true)]
+    pub as_profile: Option<String>,
+
+    /// Request timeout (e.g., 30, 30s, 2m); a bare number is seconds
+    #[arg(long, global = true, value_parser = timespec::parse_seconds)]
     pub timeout: Option<u64>,
 
     /// Skip SSL certificate verification
@@ -79,6 +99,10 @@ pub struct GlobalOpts {
     #[arg(long = "no-color", global = true)]
     pub no_color: bool,
 
+    /// Screen-reader friendly output: labeled "key: value" lines instead of tables, no symbols or color
+    #[arg(long, global = true)]
+    pub plain: bool,
+
     /// Custom table columns (comma-separated)
     #[arg(long, value_name = "COLUMNS", global = true)]
     pub columns: Option<String>,
@@ -87,9 +111,44 @@ pub struct GlobalOpts {
     #[arg(long, global = true)]
     pub no_headers: bool,
 
+    /// Show full cell contents instead of fitting tables to the terminal
+    #[arg(long, global = true)]
+    pub full: bool,
+
+    /// Show entities by friendly name, ID, or both in tables and watch output
+    #[arg(long, value_enum, value_name = "STYLE", global = true)]
+    pub names: Option<NameStyle>,
+
     /// Sort table output by field
     #[arg(long, value_name = "FIELD", global = true)]
     pub sort_by: Option<String>,
+
+    /// Limit entity resolution and listings to one area (fuzzy matched)
+    #[arg(short = 'r', long, value_name = "AREA", global = true)]
+    pub room: Option<String>,
+
+    /// Show temperature, length and speed values in this unit system
+    #[arg(long, value_enum, global = true)]
+    pub units: Option<UnitSystem>,
+
+    /// Wait until this time (e.g., 07:30, 7pm, 2025-01-15 08:00) before running the command
+    #[arg(long, value_name = "TIME", global = true, conflicts_with = "wait_for")]
+    pub wait_until: Option<TimeSpec>,
+
+    /// Wait this long (e.g., 45m, 1h 30m) before running the command
+    #[arg(long, value_name = "DURATION", global = true, value_parser = timespec::parse_duration)]
+    pub wait_for: Option<Duration>,
+
+    /// Log all HTTP and WebSocket traffic, secrets redacted, to FILE (--dump-http=FILE) or stderr
+    #[arg(
+        long,
+        value_name = "FILE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "-",
+        global = true
+    )]
+    pub dump_http: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
@@ -101,38 +160,151 @@ pub enum OutputFormat {
     Auto,
 }
 
+/// Unit systems for `--units`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum UnitSystem {
+    Metric,
+    Imperial,
+}
+
+/// How `--names` shows entities in tables and watch output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum NameStyle {
+    /// Friendly name, falling back to the ID
+    Friendly,
+    /// Entity ID
+    Id,
+    /// "Friendly name (entity_id)"
+    Both,
+}
+
+/// Porcelain output format versions.
+///
+/// A version's record layout never changes once released; new layouts get a new version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PorcelainVersion {
+    V1,
+}
+
 #[derive(Debug, Subcommand)]
 pub enum Command {
     /// Display Home Assistant instance information
     Info,
 
+    /// Show pinned entities and what needs attention
+    Status,
+
     /// Manage entities
     Entity {
         #[command(subcommand)]
         command: EntityCommand,
     },
 
+    /// Show where people and device trackers are
+    Who(WhoCommand),
+
+    /// Run a local command whenever someone enters or leaves a zone
+    On(OnCommand),
+
+    /// Suggest commands you usually run around this time of day
+    Suggest(SuggestCommand),
+
+    /// Pin everyday entities so they win fuzzy matches and lead `hmr status`
+    Pin {
+        #[command(subcommand)]
+        command: PinCommand,
+    },
+
+    /// Sensor readings and trends
+    Sensor {
+        #[command(subcommand)]
+        command: SensorCommand,
+    },
+
     /// Manage services
     Service {
         #[command(subcommand)]
         command: ServiceCommand,
     },
 
+    /// Call a service on many entities, one at a time
+    Bulk {
+        #[command(subcommand)]
+        command: BulkCommand,
+    },
+
     /// Manage events
     Event {
         #[command(subcommand)]
         command: EventCommand,
     },
 
+    /// Show the logbook: what happened, in Home Assistant's words
+    Logbook(LogbookCommand),
+
+    /// Show daily energy consumption, solar production and cost
+    Energy(EnergyCommand),
+
+    /// Speak a message on media players with text-to-speech
+    Say(SayCommand),
+
+    /// Work with cameras
+    Camera {
+        #[command(subcommand)]
+        command: CameraCommand,
+    },
+
+    /// List calendars and their upcoming events
+    Calendar {
+        #[command(subcommand)]
+        command: CalendarCommand,
+    },
+
+    /// Show and edit todo lists
+    Todo {
+        #[command(subcommand)]
+        command: TodoCommand,
+    },
+
+    /// Show and add to the shopping list
+    Shopping {
+        #[command(subcommand)]
+        command: ShoppingCommand,
+    },
+
+    /// Show and dismiss persistent notifications
+    Notification {
+        #[command(subcommand)]
+        command: NotificationCommand,
+    },
+
     /// Render Jinja2 templates server-side
     Template(TemplateCommand),
 
+    /// Inspect Lovelace dashboards
+    Dashboard {
+        #[command(subcommand)]
+        command: DashboardCommand,
+    },
+
+    /// List and import automation and script blueprints
+    Blueprint {
+        #[command(subcommand)]
+        command: BlueprintCommand,
+    },
+
     /// Manage areas
     Area {
         #[command(subcommand)]
         command: AreaCommand,
     },
 
+    /// List labels
+    Label {
+        #[command(subcommand)]
+        command: LabelCommand,
+    },
+
     /// Manage devices
     Device {
         #[command(subcommand)]
@@ -155,34 +327,172 @@ pub enum Command {
     #[command(name = "do", alias = "run")]
     Do(DoCommand),
 
+    /// Control a light: NAME [on|off|toggle|NN%|2700k|#rrggbb|COLOR]
+    Light(LightCommand),
+
+    /// Control a switch: NAME [on|off|toggle]
+    Switch(QuickCommand),
+
+    /// Control a cover: NAME [open|close|stop|toggle|NN%|position NN|tilt NN]
+    Cover(QuickCommand),
+
+    /// Control a fan: NAME [on|off|toggle|NN%|reverse|forward|oscillate|still]
+    Fan(QuickCommand),
+
+    /// Control a valve: NAME [open|close|stop|toggle|NN%]
+    Valve(QuickCommand),
+
+    /// Control a thermostat: NAME [TEMP] [--mode MODE] [--preset PRESET]
+    Climate(ClimateCommand),
+
+    /// Choose a select option: NAME [OPTION] (option fuzzy matched)
+    Select(QuickCommand),
+
+    /// Set a number: NAME [VALUE|+N|-N]
+    Number(QuickCommand),
+
+    /// Set a text or input_text entity: NAME [VALUE] (quote values with spaces)
+    Text(QuickCommand),
+
+    /// Press a button or input_button: NAME
+    Press(PressCommand),
+
+    /// Send commands from a remote: NAME send COMMAND...
+    Remote(RemoteCommand),
+
+    /// Sound or silence a siren: NAME [on|off|toggle] [--tone TONE] [--duration DURATION]
+    Siren(SirenCommand),
+
+    /// List and activate scenes
+    Scene {
+        #[command(subcommand)]
+        command: SceneCommand,
+    },
+
+    /// List, trigger, enable/disable and trace automations
+    Automation {
+        #[command(subcommand)]
+        command: AutomationCommand,
+    },
+
+    /// List and run scripts
+    Script {
+        #[command(subcommand)]
+        command: ScriptCommand,
+    },
+
+    /// List pending updates and install them
+    Update {
+        #[command(subcommand)]
+        command: UpdateCommand,
+    },
+
+    /// Manage Supervisor add-ons (Home Assistant OS and Supervised installs)
+    Addon {
+        #[command(subcommand)]
+        command: AddonCommand,
+    },
+
+    /// Check critical entities and run local alert commands on violations
+    Watchdog(WatchdogCommand),
+
+    /// Show the system health checks of each integration
+    Health(HealthCommand),
+
+    /// Reload YAML configuration of domains (automations, scenes, scripts, all)
+    Reload(ReloadCommand),
+
+    /// Server maintenance: reload configuration after editing it
+    System {
+        #[command(subcommand)]
+        command: SystemCommand,
+    },
+
+    /// Restart or stop Home Assistant, or check its configuration
+    Core {
+        #[command(subcommand)]
+        command: CoreCommand,
+    },
+
+    /// Find problems in the Home Assistant setup that hurt matching
+    Doctor {
+        #[command(subcommand)]
+        command: DoctorCommand,
+    },
+
+    /// Export Home Assistant data for documentation
+    Export {
+        #[command(subcommand)]
+        command: ExportCommand,
+    },
+
+    /// Inspect what the configured token is allowed to do
+    Auth {
+        #[command(subcommand)]
+        command: AuthCommand,
+    },
+
+    /// Print a short status line for shell prompts (cached for a few seconds)
+    Prompt(PromptCommand),
+
     /// View and manage command history
     History {
         #[command(subcommand)]
         command: HistoryCommand,
     },
 
+    /// Show local usage metrics (opt-in via telemetry.enabled)
+    Stats {
+        #[command(subcommand)]
+        command: StatsCommand,
+    },
+
+    /// Move local hmr state (config, history, stats) between machines
+    Migrate {
+        #[command(subcommand)]
+        command: MigrateCommand,
+    },
+
     /// Generate shell completions
     Completions {
         #[arg(value_enum)]
-        shell: Shell,
+        shell: CompletionShell,
     },
 
     /// Use Home Assistant's conversation agent for natural language processing
     #[command(name = "agent", alias = "ask")]
     Agent(AgentCommand),
+
+    /// Fire an intent directly, bypassing the conversation agent
+    Intent(IntentCommand),
+
+    /// Manage integrations (config entries)
+    Integration {
+        #[command(subcommand)]
+        command: IntegrationCommand,
+    },
+
+    /// Run Assist voice pipelines
+    Assist {
+        #[command(subcommand)]
+        command: AssistCommand,
+    },
 }
 
 #[derive(Debug, Subcommand)]
 pub enum HistoryCommand {
     /// Show recent command history
     List {
-        /// Number of entries to show
-        #[arg(short = 'n', long, default_value = "20")]
-        limit: usize,
+        /// Number of most recent entries to show (default: 20, or all with --page-size)
+        #[arg(short = 'n', long)]
+        limit: Option<usize>,
 
-        /// Filter by pattern
+        /// Filter by pattern (input, interpretation, targets) or `--as` profile
         #[arg(short, long)]
         filter: Option<String>,
+
+        #[command(flatten)]
+        paging: PageArgs,
     },
 
     /// Repeat the last command
@@ -195,10 +505,18 @@ pub enum HistoryCommand {
     ClearContext,
 
     /// Show accuracy statistics
-    Stats,
+    Stats {
+        /// Compare accuracy across hmr versions and cache refreshes
+        #[arg(long)]
+        compare: bool,
+    },
 
     /// Clear all history
-    Clear,
+    Clear {
+        /// Skip the confirmation prompt
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
 
     /// Compact history file
     Compact,
@@ -208,138 +526,1107 @@ pub enum HistoryCommand {
 }
 
 #[derive(Debug, Args)]
-pub struct DoCommand {
-    /// The natural language command to execute
-    #[arg(trailing_var_arg = true, required = true)]
+pub struct QuickCommand {
+    /// Entity name (fuzzy matched within the domain), optionally followed by an action.
+    /// Without an action the current state is shown.
+    #[arg(
+        required = true,
+        value_name = "NAME [ACTION]",
+        allow_negative_numbers = true
+    )]
     pub words: Vec<String>,
 
-    /// Show what would be done without executing
+    /// Show the service call without making it
     #[arg(long)]
     pub dry_run: bool,
+}
 
-    /// Skip confirmation for ambiguous matches
-    #[arg(long, short = 'y')]
-    pub yes: bool,
+#[derive(Debug, Args)]
+pub struct LightCommand {
+    /// Light name (fuzzy matched), optionally followed by an action.
+    /// Without an action or flags the current state is shown.
+    #[arg(required = true, value_name = "NAME [ACTION]")]
+    pub words: Vec<String>,
 
-    /// Use exact matching only (no fuzzy/typo correction)
+    /// Color as a CSS name (e.g., red, darkorange) or hex (#ff8800)
+    #[arg(long, conflicts_with = "kelvin")]
+    pub color: Option<String>,
+
+    /// Color temperature in kelvin
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1000..=10000))]
+    pub kelvin: Option<u32>,
+
+    /// Brightness as a percentage (60%) or 0-255
     #[arg(long)]
-    pub exact: bool,
+    pub brightness: Option<String>,
+
+    /// Fade time in seconds
+    #[arg(long)]
+    pub transition: Option<f64>,
+
+    /// Light effect (e.g., colorloop; see the light's effect_list)
+    #[arg(long)]
+    pub effect: Option<String>,
+
+    /// Show the service call without making it
+    #[arg(long)]
+    pub dry_run: bool,
 }
 
-#[derive(Debug, Subcommand)]
-pub enum CacheCommand {
-    /// Show cache status and statistics
-    Status,
+#[derive(Debug, Args)]
+pub struct ClimateCommand {
+    /// Thermostat name (fuzzy matched), optionally followed by a target temperature
+    /// ("living room 21.5" or "living room set 21.5"). Without a temperature, mode,
+    /// preset or humidity the current setpoint and mode are shown.
+    #[arg(required = true, value_name = "NAME [set] [TEMP]")]
+    pub words: Vec<String>,
 
-    /// Refresh cached data from Home Assistant
-    Refresh {
-        /// Refresh all caches
-        #[arg(long)]
-        all: bool,
+    /// HVAC mode to set
+    #[arg(long, value_parser = ["off", "heat", "cool", "heat_cool", "auto", "dry", "fan_only"])]
+    pub mode: Option<String>,
 
-        /// Refresh entities cache
-        #[arg(long)]
-        entities: bool,
+    /// Preset to set (e.g., eco, away, comfort)
+    #[arg(long)]
+    pub preset: Option<String>,
 
-        /// Refresh areas cache
-        #[arg(long)]
-        areas: bool,
+    /// Target humidity in percent
+    #[arg(long, value_parser = clap::value_parser!(u8).range(0..=100))]
+    pub humidity: Option<u8>,
 
-        /// Refresh services cache
-        #[arg(long)]
-        services: bool,
+    /// Show the service calls without making them
+    #[arg(long)]
+    pub dry_run: bool,
+}
 
-        /// Refresh devices cache
+#[derive(Debug, Subcommand)]
+pub enum CoreCommand {
+    /// Restart Home Assistant
+    Restart {
+        /// Skip the confirmation prompt
+        #[arg(long, short = 'y')]
+        yes: bool,
+
+        /// Show what would be done without doing it
         #[arg(long)]
-        devices: bool,
+        dry_run: bool,
     },
 
-    /// Clear all cached data
-    Clear,
-
-    /// Print cache directory path
-    Path,
+    /// Stop Home Assistant (it must be started again on the host)
+    Stop {
+        /// Skip the confirmation prompt
+        #[arg(long, short = 'y')]
+        yes: bool,
 
-    /// Lookup entity information from cache
-    EntityInfo {
-        /// Entity ID or friendly name
-        entity_id: String,
+        /// Show what would be done without doing it
+        #[arg(long)]
+        dry_run: bool,
     },
 
-    /// Lookup area information from cache
-    AreaInfo {
-        /// Area name or ID
-        area: String,
+    /// Check the configuration files for errors
+    CheckConfig {
+        /// Skip the confirmation prompt
+        #[arg(long, short = 'y')]
+        yes: bool,
     },
 }
 
-#[derive(Debug, Subcommand)]
-pub enum EntityCommand {
-    /// List entities with optional filter
-    List {
-        /// Filter by entity_id or friendly_name (fuzzy match)
-        filter: Option<String>,
-    },
+#[derive(Debug, Args)]
+pub struct SuggestCommand {
+    /// How far from now a past run may be to count (either side)
+    #[arg(long, value_name = "DURATION", default_value = "30m", value_parser = timespec::parse_duration)]
+    pub window: Duration,
 
-    /// Get detailed entity state
-    Get {
-        /// Entity ID (e.g., light.kitchen)
-        entity_id: String,
-    },
+    /// On how many different days a command must have run around now
+    #[arg(long, value_name = "N", default_value_t = 3)]
+    pub min_days: usize,
 
-    /// Update entity state
-    Set {
-        /// Entity ID to update
-        entity_id: String,
+    /// Run the top suggestion without asking
+    #[arg(long, short = 'y')]
+    pub yes: bool,
+}
 
-        /// JSON data for state and attributes
-        #[arg(long = "data", value_name = "JSON", conflicts_with = "state")]
-        data: Option<String>,
+#[derive(Debug, Args)]
+pub struct WhoCommand {
+    /// Keep running and print arrivals and departures as they happen
+    #[arg(long)]
+    pub watch: bool,
+}
 
-        /// Quick state update
-        #[arg(long)]
-        state: Option<String>,
-    },
+#[derive(Debug, Args)]
+pub struct OnCommand {
+    /// Trigger: "<person or tracker> enters|leaves [zone]" (e.g., "anna enters home");
+    /// the zone defaults to home
+    pub trigger: String,
 
-    /// Get entity history
-    History {
-        /// Entity ID
-        entity_id: String,
+    /// Command to run each time the trigger fires, after `--`; it gets
+    /// HMR_ENTITY_ID, HMR_ZONE, HMR_TRANSITION, HMR_OLD_STATE and HMR_NEW_STATE
+    #[arg(last = true, required = true, value_name = "COMMAND")]
+    pub command: Vec<String>,
 
-        /// Time duration (e.g., "2h", "1d", "30m")
-        #[arg(long, default_value = "1h")]
-        since: String,
-    },
+    /// Exit after the trigger has fired once
+    #[arg(long)]
+    pub once: bool,
 
-    /// Watch entity state changes in real-time (WebSocket)
-    Watch {
-        /// Entity IDs to watch
-        #[arg(required = true)]
-        entity_ids: Vec<String>,
-    },
+    /// Report when the trigger fires without running the command
+    #[arg(long)]
+    pub dry_run: bool,
 }
 
 #[derive(Debug, Subcommand)]
-pub enum ServiceCommand {
-    /// List available services
-    List {
-        /// Filter by domain (e.g., "light", "switch")
-        domain: Option<String>,
-    },
+pub enum SystemCommand {
+    /// Reload YAML configuration of domains (same as `hmr reload`)
+    Reload(ReloadCommand),
+}
 
-    /// Call a service
-    Call {
-        /// Service to call (e.g., light.turn_on)
-        service: String,
+#[derive(Debug, Args)]
+pub struct ReloadCommand {
+    /// Domains to reload ("automations", "scene", ...), "core", or "all"
+    #[arg(value_name = "DOMAIN", required_unless_present = "all")]
+    pub targets: Vec<String>,
 
-        /// JSON data for service call
-        #[arg(long = "data", value_name = "JSON")]
-        data: Option<String>,
+    /// Reload the core configuration and every domain with a reload
+    /// service one by one, reporting each
+    #[arg(long, conflicts_with = "targets")]
+    pub all: bool,
 
-        /// Key=value pairs for simple service calls
-        #[arg(value_name = "KEY=VALUE")]
-        args: Vec<String>,
-    },
+    /// Show which services would be called without calling them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct HealthCommand {
+    /// Only show failed checks, and exit non-zero if there are any
+    #[arg(long)]
+    pub failed: bool,
+
+    /// Only show these integrations (e.g., cloud, homeassistant)
+    #[arg(value_name = "INTEGRATION")]
+    pub domains: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct WatchdogCommand {
+    /// Rules file (TOML with `interval` and `[[rules]]`) instead of the config's [watchdog]
+    #[arg(long, value_name = "FILE")]
+    pub file: Option<PathBuf>,
+
+    /// Check interval, overriding the configured one (e.g., 10s)
+    #[arg(long, value_parser = timespec::parse_interval)]
+    pub interval: Option<Duration>,
+
+    /// Validate and list the rules without watching
+    #[arg(long)]
+    pub check: bool,
+
+    /// Report violations without running alert commands
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CameraCommand {
+    /// Save the current camera image to a file
+    Snapshot {
+        /// Camera name or entity ID (fuzzy matched)
+        camera: String,
+
+        /// Image file ("-" for stdout; defaults to <camera>-<time>.<ext>)
+        #[arg(value_name = "FILE")]
+        file: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct SayCommand {
+    /// Message to speak
+    #[arg(required = true, value_name = "MESSAGE")]
+    pub words: Vec<String>,
+
+    /// Media player name or entity ID (fuzzy matched)
+    #[arg(long, short = 'p', required_unless_present = "all")]
+    pub player: Option<String>,
+
+    /// Speak on every media player (in the --room area, if given)
+    #[arg(long, conflicts_with = "player")]
+    pub all: bool,
+
+    /// TTS engine entity, e.g. tts.piper (defaults to the first one found)
+    #[arg(long)]
+    pub engine: Option<String>,
+
+    /// Show the service call without making it
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct LogbookCommand {
+    /// How far back to look (e.g., 30m, 2h, 1d, 07:00)
+    #[arg(long, value_name = "TIME", default_value = "1h")]
+    pub since: TimeSpec,
+
+    /// Only show entries for this entity
+    #[arg(long, value_name = "ENTITY_ID")]
+    pub entity: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct EnergyCommand {
+    /// How far back to report (e.g., 7d, 30d, 2025-01-01); whole days from midnight
+    #[arg(long, value_name = "TIME", default_value = "7d")]
+    pub since: TimeSpec,
+}
+
+#[derive(Debug, Args)]
+pub struct PromptCommand {
+    /// Template overriding the configured one, e.g. "{on:light} lights · {alarm}"
+    #[arg(long)]
+    pub template: Option<String>,
+
+    /// Ask the server even if the cached text is still fresh
+    #[arg(long)]
+    pub refresh: bool,
+
+    /// Fetch and cache with no budget and no output (finishes a slow fetch)
+    #[arg(long, hide = true)]
+    pub background: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct PressCommand {
+    /// Button name (fuzzy matched among button and input_button entities)
+    #[arg(required = true, value_name = "NAME")]
+    pub words: Vec<String>,
+
+    /// Show the service call without making it
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct RemoteCommand {
+    /// Remote name (fuzzy matched), then `send` and one or more commands.
+    /// Without `send` the current state is shown.
+    #[arg(required = true, value_name = "NAME [send COMMAND...]")]
+    pub words: Vec<String>,
+
+    /// Device to send the commands to (for learned IR/RF codes)
+    #[arg(long)]
+    pub device: Option<String>,
+
+    /// Number of times to send each command
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub repeats: Option<u32>,
+
+    /// Show the service call without making it
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct SirenCommand {
+    /// Siren name (fuzzy matched), optionally followed by on, off or toggle.
+    /// Without an action, tone or duration the current state is shown.
+    #[arg(required = true, value_name = "NAME [ACTION]")]
+    pub words: Vec<String>,
+
+    /// Tone to play (one of the siren's available_tones)
+    #[arg(long)]
+    pub tone: Option<String>,
+
+    /// How long to sound (e.g., 30s, 2m)
+    #[arg(long, value_parser = timespec::parse_duration)]
+    pub duration: Option<Duration>,
+
+    /// Skip the confirmation prompt for sounding the siren
+    #[arg(long, short = 'y')]
+    pub yes: bool,
+
+    /// Show the service call without making it
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum StatsCommand {
+    /// Per-command run counts, durations, and error categories
+    ToolUsage {
+        /// Write the raw metrics as JSON to FILE, e.g. to attach to a bug report
+        #[arg(long, value_name = "FILE")]
+        export: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum MigrateCommand {
+    /// Export config (without token), history, and stats as a JSON bundle
+    Export {
+        /// Write the bundle to a file instead of stdout
+        #[arg(value_name = "FILE")]
+        file: Option<PathBuf>,
+    },
+
+    /// Import a bundle created by `migrate export`
+    Import {
+        /// Bundle file ('-' for stdin)
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Show what would be imported without writing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Skip the confirmation prompt
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct DoCommand {
+    /// The natural language command to execute
+    #[arg(trailing_var_arg = true, required = true)]
+    pub words: Vec<String>,
+
+    /// Show what would be done without executing
+    #[arg(long, visible_alias = "plan")]
+    pub dry_run: bool,
+
+    /// Apply the command to the cached states and show the predicted result,
+    /// without contacting Home Assistant
+    #[arg(long, conflicts_with = "dry_run")]
+    pub simulate: bool,
+
+    /// Skip confirmation prompts (ambiguous matches, sirens and alarm panels)
+    #[arg(long, short = 'y')]
+    pub yes: bool,
+
+    /// Use exact matching only (no fuzzy/typo correction)
+    #[arg(long)]
+    pub exact: bool,
+
+    /// How long to wait for the targets of an area- or domain-wide command to
+    /// reach their new state before summarizing them (0 to skip the summary)
+    #[arg(long, value_name = "DURATION", default_value = "3s", value_parser = timespec::parse_duration)]
+    pub verify_timeout: Duration,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CacheCommand {
+    /// Show cache status and statistics
+    Status,
+
+    /// Refresh cached data from Home Assistant
+    Refresh {
+        /// Refresh all caches
+        #[arg(long)]
+        all: bool,
+
+        /// Refresh entities cache
+        #[arg(long)]
+        entities: bool,
+
+        /// Refresh areas cache
+        #[arg(long)]
+        areas: bool,
+
+        /// Refresh services cache
+        #[arg(long)]
+        services: bool,
+
+        /// Refresh devices cache
+        #[arg(long)]
+        devices: bool,
+
+        /// Refresh labels cache
+        #[arg(long)]
+        labels: bool,
+
+        /// Stay connected and apply registry changes until interrupted
+        #[arg(long)]
+        watch: bool,
+    },
+
+    /// Clear all cached data
+    Clear {
+        /// Skip the confirmation prompt
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+
+    /// Print cache directory path
+    Path,
+
+    /// Lookup entity information from cache
+    EntityInfo {
+        /// Entity ID or friendly name
+        entity_id: String,
+    },
+
+    /// Lookup area information from cache
+    AreaInfo {
+        /// Area name or ID
+        area: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PinCommand {
+    /// Pin an entity (ID or name)
+    Add {
+        /// Entity ID or (approximate) name
+        entity: String,
+    },
+
+    /// Unpin an entity
+    Remove {
+        /// Entity ID or (approximate) name
+        entity: String,
+    },
+
+    /// List pinned entities with their current state
+    List,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum EntityCommand {
+    /// List entities with optional filter
+    List {
+        /// Filter by entity_id or friendly_name (fuzzy match)
+        filter: Option<String>,
+
+        /// Only list pinned entities
+        #[arg(long)]
+        pinned: bool,
+
+        /// Only list entities with this label (ID or name)
+        #[arg(long, value_name = "LABEL")]
+        label: Option<String>,
+
+        #[command(flatten)]
+        paging: PageArgs,
+    },
+
+    /// Get detailed entity state
+    Get {
+        /// Entity ID (e.g., light.kitchen)
+        entity_id: String,
+
+        /// Only show these attributes (comma-separated)
+        #[arg(long, value_name = "KEYS", value_delimiter = ',')]
+        attrs: Vec<String>,
+
+        /// Cut attribute values larger than this many bytes (as JSON), noting the cut
+        #[arg(long, value_name = "BYTES")]
+        max_attr_size: Option<usize>,
+    },
+
+    /// Update entity state
+    Set {
+        /// Entity ID to update
+        entity_id: String,
+
+        /// JSON data for state and attributes
+        #[arg(long = "data", value_name = "JSON", conflicts_with = "state")]
+        data: Option<String>,
+
+        /// Quick state update
+        #[arg(long)]
+        state: Option<String>,
+    },
+
+    /// Get entity history
+    History {
+        /// Entity ID
+        entity_id: String,
+
+        /// Start of the period (e.g., "2h", "1d", "07:30", "2025-01-15 08:00")
+        #[arg(long, value_name = "TIME", default_value = "1h")]
+        since: TimeSpec,
+
+        /// End of the period, counting back from now like --since (default: now)
+        #[arg(long, value_name = "TIME")]
+        until: Option<TimeSpec>,
+    },
+
+    /// Watch entity state changes in real-time (WebSocket)
+    ///
+    /// With --above/--below numeric states are checked against thresholds
+    /// (both given: inside the range, or outside it when --above is the larger).
+    Watch {
+        /// Entity IDs to watch (default with --room: every entity in the room)
+        #[arg(required_unless_present = "room")]
+        entity_ids: Vec<String>,
+
+        /// Match states above this value; a unit (e.g., 200°C) converts as needed
+        #[arg(long, allow_negative_numbers = true)]
+        above: Option<String>,
+
+        /// Match states below this value
+        #[arg(long, allow_negative_numbers = true)]
+        below: Option<String>,
+
+        /// Exit (successfully) as soon as a state matches, including the current one
+        #[arg(long)]
+        exit_on_match: bool,
+
+        /// Only report changes of these attributes (comma-separated), old -> new
+        #[arg(
+            long,
+            value_name = "KEYS",
+            value_delimiter = ',',
+            conflicts_with_all = ["above", "below", "exit_on_match"]
+        )]
+        attr: Vec<String>,
+
+        /// Only include these attributes in the reported states (comma-separated)
+        #[arg(long, value_name = "KEYS", value_delimiter = ',')]
+        attrs: Vec<String>,
+
+        /// Cut attribute values larger than this many bytes (as JSON), noting the cut
+        #[arg(long, value_name = "BYTES")]
+        max_attr_size: Option<usize>,
+
+        /// Show stream health (connection state, events/sec, skipped events, reconnects)
+        #[arg(long)]
+        stats: bool,
+    },
+
+    /// Rename an entity (sets its name in the entity registry)
+    Rename {
+        /// Entity ID or (approximate) name
+        entity: String,
+
+        /// New friendly name
+        name: String,
+    },
+
+    /// Assign an entity to an area, overriding its device's area
+    SetArea {
+        /// Entity ID or (approximate) name
+        entity: String,
+
+        /// Area name, alias, or ID (fuzzy matched)
+        area: String,
+    },
+
+    /// Manage the aliases voice assistants know an entity by
+    Alias {
+        #[command(subcommand)]
+        command: EntityAliasCommand,
+    },
+
+    /// Disable entities; they stop updating and drop out of the state list
+    Disable(EntityFlagArgs),
+
+    /// Enable disabled entities
+    Enable(EntityFlagArgs),
+
+    /// Hide entities from auto-generated dashboards
+    Hide(EntityFlagArgs),
+
+    /// Unhide hidden entities
+    Unhide(EntityFlagArgs),
+}
+
+/// Which page of a long list to show
+#[derive(Debug, Clone, Copy, Args)]
+pub struct PageArgs {
+    /// Show the list in pages of this many rows
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub page_size: Option<u32>,
+
+    /// Page to show, starting at 1
+    #[arg(long, value_name = "N", default_value_t = 1, requires = "page_size", value_parser = clap::value_parser!(u32).range(1..))]
+    pub page: u32,
+}
+
+/// Which entities `entity disable/enable/hide/unhide` change
+#[derive(Debug, Args)]
+pub struct EntityFlagArgs {
+    /// Entity ID or name; with --by-integration a config entry ID or title,
+    /// or an integration domain
+    pub target: String,
+
+    /// Change every entity of one integration config entry
+    #[arg(long)]
+    pub by_integration: bool,
+
+    /// Skip the confirmation prompt of --by-integration
+    #[arg(long, short = 'y')]
+    pub yes: bool,
+
+    /// List the entities that would change without changing them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum EntityAliasCommand {
+    /// Add an alias
+    Add {
+        /// Entity ID or (approximate) name
+        entity: String,
+
+        /// Alias to add
+        alias: String,
+    },
+
+    /// Remove an alias
+    Remove {
+        /// Entity ID or (approximate) name
+        entity: String,
+
+        /// Alias to remove (case-insensitive)
+        alias: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SceneCommand {
+    /// List scenes
+    List,
+
+    /// Activate a scene
+    Activate {
+        /// Scene name or entity ID (fuzzy matched)
+        #[arg(required = true, value_name = "NAME")]
+        words: Vec<String>,
+
+        /// Transition time in seconds for lights in the scene
+        #[arg(long)]
+        transition: Option<f64>,
+
+        /// Show the service call without making it
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CalendarCommand {
+    /// List calendars
+    List,
+
+    /// Show events from one calendar, or from all of them
+    Events {
+        /// Calendar name or entity ID (fuzzy matched); all calendars when omitted
+        #[arg(value_name = "NAME")]
+        words: Vec<String>,
+
+        /// Include events from this long ago or this time (e.g., "1d", "yesterday")
+        #[arg(long, value_name = "TIME", default_value = "now")]
+        since: TimeSpec,
+
+        /// Include events up to this far ahead or this time (e.g., "2w", "2025-02-01")
+        #[arg(long, value_name = "TIME", default_value = "7d")]
+        until: TimeSpec,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DoctorCommand {
+    /// Group entities with near-identical names on different devices
+    Duplicates {
+        /// Only check entities in this domain
+        #[arg(long)]
+        domain: Option<String>,
+    },
+
+    /// Check hmr's own files for exposed tokens and loose permissions
+    Config {
+        /// Restrict token-bearing files to their owner (chmod 600)
+        #[arg(long)]
+        fix: bool,
+
+        /// Show what --fix would change without changing it
+        #[arg(long, requires = "fix")]
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ExportCommand {
+    /// Every entity and device with its integration, area and firmware
+    Inventory {
+        /// Output format (defaults to markdown, or --json/--yaml when given)
+        #[arg(long, value_enum)]
+        format: Option<InventoryFormat>,
+    },
+}
+
+/// File formats for `export inventory`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum InventoryFormat {
+    Markdown,
+    Csv,
+    Json,
+}
+
+/// Shells `hmr completions` can generate a script for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CompletionShell {
+    Bash,
+    Elvish,
+    Fish,
+    #[value(name = "powershell")]
+    PowerShell,
+    Zsh,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TodoCommand {
+    /// Show the items on a list
+    List {
+        /// Todo list name or entity ID (fuzzy matched)
+        list: String,
+
+        /// Include completed items
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Add an item to a list
+    Add {
+        /// Todo list name or entity ID (fuzzy matched)
+        list: String,
+
+        /// Item text
+        #[arg(required = true, value_name = "ITEM")]
+        words: Vec<String>,
+
+        /// Due date (YYYY-MM-DD)
+        #[arg(long)]
+        due: Option<String>,
+
+        /// Show the service call without making it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Mark an item as completed
+    Done {
+        /// Todo list name or entity ID (fuzzy matched)
+        list: String,
+
+        /// Item text (fuzzy matched among open items)
+        #[arg(required = true, value_name = "ITEM")]
+        words: Vec<String>,
+
+        /// Show the service call without making it
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ShoppingCommand {
+    /// Show the items on the shopping list
+    List {
+        /// Include items already bought
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Add an item to the shopping list
+    Add {
+        /// Item text
+        #[arg(required = true, value_name = "ITEM")]
+        words: Vec<String>,
+
+        /// Show the request without sending it
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum NotificationCommand {
+    /// Show the persistent notifications, oldest first
+    List,
+
+    /// Dismiss a notification, or all of them
+    Dismiss {
+        /// Notification ID (see `hmr notification list`)
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        id: Option<String>,
+
+        /// Dismiss every notification
+        #[arg(long)]
+        all: bool,
+
+        /// Show what would be dismissed without dismissing it
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Skip the confirmation prompt for --all
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AuthCommand {
+    /// Probe the API and list commands the token's user can't run
+    Scopes,
+
+    /// Log in and store the tokens, so no token needs to be configured
+    Login {
+        /// How to obtain the token
+        #[arg(long, value_enum, default_value = "oauth")]
+        flow: LoginFlow,
+
+        /// Home Assistant username (prompted for if omitted)
+        #[arg(long)]
+        username: Option<String>,
+    },
+
+    /// Revoke and forget the stored login for the server
+    Logout,
+}
+
+/// Ways `auth login` obtains a token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LoginFlow {
+    /// Username, password (and MFA code); refreshes automatically
+    Oauth,
+    /// Paste a long-lived access token created in the user profile
+    Token,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AutomationCommand {
+    /// List automations with their enabled state
+    List,
+
+    /// Run an automation's actions now
+    Trigger {
+        /// Automation name or entity ID (fuzzy matched)
+        #[arg(required = true, value_name = "NAME")]
+        words: Vec<String>,
+
+        /// Run the actions even if the automation's conditions don't pass
+        #[arg(long)]
+        skip_condition: bool,
+
+        /// Show the service call without making it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Enable an automation so its triggers fire again
+    Enable {
+        /// Automation name or entity ID (fuzzy matched)
+        #[arg(required = true, value_name = "NAME")]
+        words: Vec<String>,
+
+        /// Show the service call without making it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Disable an automation
+    Disable {
+        /// Automation name or entity ID (fuzzy matched)
+        #[arg(required = true, value_name = "NAME")]
+        words: Vec<String>,
+
+        /// Show the service call without making it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Show the most recent run of an automation step by step
+    Trace {
+        /// Automation name or entity ID (fuzzy matched)
+        #[arg(required = true, value_name = "NAME")]
+        words: Vec<String>,
+
+        /// Show this run instead of the most recent one
+        #[arg(long, conflicts_with = "list")]
+        run: Option<String>,
+
+        /// List the stored runs instead of showing one
+        #[arg(long)]
+        list: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ScriptCommand {
+    /// List scripts
+    List,
+
+    /// Run a script, passing KEY=VALUE arguments as its variables
+    Run {
+        /// Script name or entity ID (fuzzy matched), followed by KEY=VALUE variables
+        #[arg(required = true, value_name = "NAME [KEY=VALUE]")]
+        args: Vec<String>,
+
+        /// Show the service call without making it
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum UpdateCommand {
+    /// List update entities with installed and latest versions
+    List,
+
+    /// Install the latest version for an update entity
+    Install {
+        /// Update name or entity ID (fuzzy matched)
+        #[arg(required = true, value_name = "NAME")]
+        words: Vec<String>,
+
+        /// Back up before installing, if the integration supports it
+        #[arg(long)]
+        backup: bool,
+
+        /// Show the service call without making it
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AddonCommand {
+    /// List installed add-ons
+    List,
+
+    /// Show details for an add-on
+    Info {
+        /// Add-on name or slug
+        #[arg(required = true, value_name = "NAME")]
+        words: Vec<String>,
+    },
+
+    /// Start an add-on
+    Start {
+        /// Add-on name or slug
+        #[arg(required = true, value_name = "NAME")]
+        words: Vec<String>,
+
+        /// Show what would be done without doing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Stop an add-on
+    Stop {
+        /// Add-on name or slug
+        #[arg(required = true, value_name = "NAME")]
+        words: Vec<String>,
+
+        /// Show what would be done without doing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Restart an add-on
+    Restart {
+        /// Add-on name or slug
+        #[arg(required = true, value_name = "NAME")]
+        words: Vec<String>,
+
+        /// Show what would be done without doing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Show an add-on's log
+    Logs {
+        /// Add-on name or slug
+        #[arg(required = true, value_name = "NAME")]
+        words: Vec<String>,
+
+        /// Only show the last N lines
+        #[arg(short = 'n', long, value_name = "N")]
+        lines: Option<usize>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SensorCommand {
+    /// List sensors with their current values
+    List {
+        /// Filter by entity_id or friendly_name (fuzzy match)
+        filter: Option<String>,
+
+        /// Add a trend column for numeric sensors covering this period (e.g., 24h)
+        #[arg(long, value_name = "DURATION", value_parser = timespec::parse_duration)]
+        sparkline: Option<Duration>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ServiceCommand {
+    /// List available services
+    List {
+        /// Filter by domain (e.g., "light", "switch")
+        domain: Option<String>,
+    },
+
+    /// Call a service
+    Call {
+        /// Service to call (e.g., light.turn_on)
+        service: String,
+
+        /// JSON data for service call
+        #[arg(long = "data", value_name = "JSON")]
+        data: Option<String>,
+
+        /// Key=value pairs; arguments without '=' are entity IDs or names (fuzzy matched)
+        #[arg(value_name = "KEY=VALUE|ENTITY")]
+        args: Vec<String>,
+
+        /// Skip the confirmation prompt for sirens and alarm panels
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum BulkCommand {
+    /// Call a service once per listed entity
+    Service {
+        /// Service to call (e.g., light.turn_off)
+        service: String,
+
+        /// File with one entity ID per line ("-" for stdin, # starts a comment)
+        #[arg(long, value_name = "FILE")]
+        targets_from: PathBuf,
+
+        /// JSON data added to every call
+        #[arg(long = "data", value_name = "JSON")]
+        data: Option<String>,
+
+        /// Pause between calls (e.g., 100ms, 1s)
+        #[arg(long, value_name = "DURATION", default_value = "100ms", value_parser = timespec::parse_duration)]
+        delay: Duration,
+
+        /// Stop at the first failed call and restore the earlier states
+        #[arg(long)]
+        rollback_on_error: bool,
+
+        /// List the calls without making them
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Skip the confirmation prompt for sirens and alarm panels
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+}
+
+/// Where an event was fired, for `event watch --origin`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[value(rename_all = "UPPER")]
+pub enum EventOrigin {
+    Local,
+    Remote,
 }
 
 #[derive(Debug, Subcommand)]
@@ -348,6 +1635,18 @@ pub enum EventCommand {
     Watch {
         /// Event type to filter (e.g., state_changed)
         event_type: Option<String>,
+
+        /// Only events that originated locally or came in through the API
+        #[arg(long, value_enum, ignore_case = true)]
+        origin: Option<EventOrigin>,
+
+        /// Only events caused by this user (name, username or user ID)
+        #[arg(long)]
+        user: Option<String>,
+
+        /// Show stream health (connection state, events/sec, skipped events, reconnects)
+        #[arg(long)]
+        stats: bool,
     },
 
     /// Fire a custom event
@@ -358,11 +1657,23 @@ pub enum EventCommand {
         /// JSON data for event payload
         #[arg(long = "data", value_name = "JSON")]
         data: Option<String>,
+
+        /// Fire the event this many times
+        #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+        repeat: u32,
+
+        /// Pause between repeated events (e.g., 500ms, 2s)
+        #[arg(long, value_name = "DURATION", default_value = "1s", value_parser = timespec::parse_duration)]
+        interval: Duration,
     },
 }
 
 #[derive(Debug, Args)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct TemplateCommand {
+    #[command(subcommand)]
+    pub command: Option<TemplateSubcommand>,
+
     /// Template string to render
     #[arg(value_name = "TEMPLATE", conflicts_with = "file")]
     pub template: Option<String>,
@@ -372,10 +1683,80 @@ pub struct TemplateCommand {
     pub file: Option<PathBuf>,
 }
 
+#[derive(Debug, Subcommand)]
+pub enum TemplateSubcommand {
+    /// Re-render a template file live as it is edited and as entities change
+    ///
+    /// Edit the file in your own editor; each render notes unknown entity IDs
+    /// with the closest cached entity.
+    Edit {
+        /// Template file to watch (created if missing)
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DashboardCommand {
+    /// List dashboards
+    List,
+
+    /// Summarize a dashboard's views and cards, or print its config
+    Show {
+        /// Dashboard URL path or title (default: the default dashboard)
+        dashboard: Option<String>,
+
+        /// Print the dashboard config as YAML instead of a summary
+        #[arg(long)]
+        raw: bool,
+    },
+
+    /// List the resources (custom cards, CSS) dashboards load
+    Resources,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum BlueprintCommand {
+    /// List installed blueprints
+    List {
+        /// Only blueprints of this kind (both by default)
+        #[arg(value_enum)]
+        domain: Option<BlueprintDomain>,
+    },
+
+    /// Install a blueprint from a URL (GitHub, Gist, or community forum)
+    Import {
+        /// Where the blueprint is published
+        url: String,
+
+        /// Replace an installed blueprint with the same file name
+        #[arg(long)]
+        force: bool,
+
+        /// Skip the confirmation prompt
+        #[arg(long, short = 'y')]
+        yes: bool,
+
+        /// Show the blueprint without installing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+/// Kinds of blueprints
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BlueprintDomain {
+    Automation,
+    Script,
+}
+
 #[derive(Debug, Subcommand)]
 pub enum AreaCommand {
     /// List all areas
-    List,
+    List {
+        #[command(flatten)]
+        paging: PageArgs,
+    },
 
     /// Create a new area
     Create {
@@ -389,28 +1770,41 @@ pub enum AreaCommand {
 
     /// Delete an area
     Delete {
-        /// Area name or ID
+        /// Area name, alias, or ID (fuzzy matched)
         name: String,
+
+        /// Skip the confirmation prompt
+        #[arg(long, short = 'y')]
+        yes: bool,
     },
 }
 
+#[derive(Debug, Subcommand)]
+pub enum LabelCommand {
+    /// List all labels with the number of entities carrying each
+    List,
+}
+
 #[derive(Debug, Subcommand)]
 pub enum DeviceCommand {
     /// List all devices
-    List,
+    List {
+        #[command(flatten)]
+        paging: PageArgs,
+    },
 
     /// Assign a device to an area
     Assign {
-        /// Area name or ID
+        /// Area name, alias, or ID (fuzzy matched)
         area: String,
 
-        /// Device ID
+        /// Device ID or name (fuzzy matched)
         device: String,
     },
 
     /// Update device metadata
     Update {
-        /// Device ID
+        /// Device ID or name (fuzzy matched)
         device_id: String,
 
         /// JSON data for device update (can also be piped via stdin)
@@ -434,24 +1828,134 @@ pub enum ConfigCommand {
     },
 
     /// Reset configuration to defaults
-    Reset,
+    Reset {
+        /// Skip the confirmation prompt
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+    /// Print the JSON Schema of the config file, for editor validation and completion
+    Schema,
+}
+
+#[derive(Debug, Args)]
+pub struct IntentCommand {
+    /// Intent name (e.g., HassTurnOn, HassLightSet, HassGetState)
+    pub name: String,
+
+    /// Slots as a JSON object (e.g., '{"name": "kitchen light"}'), @file or - for stdin
+    #[arg(long, value_name = "JSON")]
+    pub slots: Option<String>,
 }
 
 #[derive(Debug, Args)]
+#[command(args_conflicts_with_subcommands = true, disable_help_subcommand = true)]
 pub struct AgentCommand {
+    #[command(subcommand)]
+    pub command: Option<AgentSubcommand>,
+
     /// The natural language command to send to the agent
-    #[arg(trailing_var_arg = true, required = true)]
+    #[arg(trailing_var_arg = true)]
     pub words: Vec<String>,
 
     /// Language code (e.g., 'en', 'de', 'fr')
     #[arg(short = 'l', long, default_value = "en")]
     pub lang: String,
 
-    /// Conversation agent ID (defaults to built-in Home Assistant agent)
+    /// Conversation agent ID (defaults to agent.agent_id from the config, then
+    /// the built-in Home Assistant agent; see `hmr agent list`)
     #[arg(long)]
     pub agent_id: Option<String>,
 
+    /// Assist pipeline name or ID whose conversation agent and language to use
+    /// (see `hmr agent pipelines`)
+    #[arg(long, conflicts_with = "agent_id")]
+    pub pipeline: Option<String>,
+
     /// Conversation ID to continue a previous conversation
     #[arg(long)]
     pub conversation_id: Option<String>,
+
+    /// Keep one conversation going, reading each message from stdin
+    #[arg(short = 'i', long, conflicts_with = "words")]
+    pub interactive: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum IntegrationCommand {
+    /// List configured integrations (config entries) and their state
+    List {
+        /// Include discoveries that were ignored
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Reload one integration, setting it up again if it failed
+    Reload {
+        /// Config entry ID, integration title, or domain
+        entry: String,
+    },
+
+    /// Disable an integration; its entities become unavailable
+    Disable {
+        /// Exact config entry ID, integration title, or domain
+        entry: String,
+
+        /// Skip the confirmation prompt
+        #[arg(long, short = 'y')]
+        yes: bool,
+
+        /// Show what would be disabled without disabling it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Reload every integration and report its state before and after
+    ReloadAll {
+        /// Only reload integrations that failed to set up or are retrying
+        #[arg(long)]
+        only_failed: bool,
+
+        /// Show what would be reloaded without reloading
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Skip the confirmation prompt
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AssistCommand {
+    /// Run a pipeline on text or a WAV recording and show each stage
+    Run {
+        /// Pipeline name or ID (defaults to the preferred pipeline)
+        #[arg(long)]
+        pipeline: Option<String>,
+
+        /// Start at intent recognition with this sentence
+        #[arg(long, required_unless_present = "wav", conflicts_with = "wav")]
+        text: Option<String>,
+
+        /// Start at speech-to-text with this recording (16-bit mono PCM)
+        #[arg(long, value_name = "FILE")]
+        wav: Option<PathBuf>,
+
+        /// Also run text-to-speech and save the spoken reply to FILE
+        #[arg(long, value_name = "FILE")]
+        save: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AgentSubcommand {
+    /// List the conversation agents usable as --agent-id
+    List {
+        /// Only agents supporting this language (e.g., 'de')
+        #[arg(long)]
+        language: Option<String>,
+    },
+
+    /// List the Assist pipelines usable as --pipeline
+    Pipelines,
 }
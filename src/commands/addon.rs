@@ -0,0 +1,234 @@
+//! Add-on command implementations
+//!
+//! Add-ons are managed through Home Assistant's proxy to the Supervisor
+//! (`/api/hassio/...`), so they need an admin token and a Home Assistant OS or
+//! Supervised install. Add-ons are picked by slug or (partial) name.
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+use tabled::Tabled;
+
+use crate::api::{Addon, HassClient};
+use crate::cli::AddonCommand;
+use crate::config::RuntimeContext;
+use crate::error::Error;
+use crate::output::{output_for_format, print_output, print_table};
+
+#[derive(Debug, Serialize, Tabled)]
+struct AddonRow {
+    slug: String,
+    name: String,
+    state: String,
+    version: String,
+    latest: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ActionResult {
+    slug: String,
+    name: String,
+    action: String,
+    dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct AddonLog<'a> {
+    slug: String,
+    lines: Vec<&'a str>,
+}
+
+pub async fn run(ctx: &RuntimeContext, command: AddonCommand) -> Result<()> {
+    match command {
+        AddonCommand::List => list(ctx).await,
+        AddonCommand::Info { words } => info(ctx, &words.join(" ")).await,
+        AddonCommand::Start { words, dry_run } => {
+            act(ctx, &words.join(" "), "start", dry_run).await
+        }
+        AddonCommand::Stop { words, dry_run } => act(ctx, &words.join(" "), "stop", dry_run).await,
+        AddonCommand::Restart { words, dry_run } => {
+            act(ctx, &words.join(" "), "restart", dry_run).await
+        }
+        AddonCommand::Logs { words, lines } => logs(ctx, &words.join(" "), lines).await,
+    }
+}
+
+async fn list(ctx: &RuntimeContext) -> Result<()> {
+    let mut addons = HassClient::new(ctx)?.get_addons().await?;
+    addons.sort_by_key(|a| a.name.to_lowercase());
+
+    if addons.is_empty() && !ctx.is_machine_output() {
+        println!("No add-ons installed");
+        return Ok(());
+    }
+    if ctx.is_machine_output() {
+        return print_output(ctx, &addons);
+    }
+    let rows: Vec<AddonRow> = addons
+        .into_iter()
+        .map(|a| AddonRow {
+            latest: match (a.update_available, a.version_latest) {
+                (true, Some(latest)) => latest,
+                _ => String::new(),
+            },
+            slug: a.slug,
+            name: a.name,
+            state: a.state.unwrap_or_default(),
+            version: a.version.unwrap_or_default(),
+        })
+        .collect();
+    print_table(ctx, &rows)
+}
+
+async fn info(ctx: &RuntimeContext, input: &str) -> Result<()> {
+    let client = HassClient::new(ctx)?;
+    let addons = client.get_addons().await?;
+    let addon = find_addon(&addons, input)?;
+    let info = client.get_addon_info(&addon.slug).await?;
+
+    output_for_format(ctx, &info, || {
+        let field = |key: &str| match &info[key] {
+            Value::Null => String::new(),
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        println!("{} ({})", field("name"), field("slug"));
+        println!("State:     {}", field("state"));
+        println!("Version:   {}", field("version"));
+        if info["update_available"] == true {
+            println!("Latest:    {}", field("version_latest"));
+        }
+        println!("Boot:      {}", field("boot"));
+        if !field("url").is_empty() {
+            println!("URL:       {}", field("url"));
+        }
+        if !field("description").is_empty() {
+            println!("\n{}", field("description"));
+        }
+        Ok(())
+    })
+}
+
+async fn act(ctx: &RuntimeContext, input: &str, action: &str, dry_run: bool) -> Result<()> {
+    let client = HassClient::new(ctx)?;
+    let addons = client.get_addons().await?;
+    let addon = find_addon(&addons, input)?;
+
+    if !dry_run {
+        client.addon_action(&addon.slug, action).await?;
+    }
+    let result = ActionResult {
+        slug: addon.slug.clone(),
+        name: addon.name.clone(),
+        action: action.to_string(),
+        dry_run,
+    };
+    output_for_format(ctx, &result, || {
+        if dry_run {
+            println!("Would {action} {} ({})", addon.name, addon.slug);
+        } else {
+            println!("{} {}", past_tense(action), addon.name);
+        }
+        Ok(())
+    })
+}
+
+async fn logs(ctx: &RuntimeContext, input: &str, lines: Option<usize>) -> Result<()> {
+    let client = HassClient::new(ctx)?;
+    let addons = client.get_addons().await?;
+    let addon = find_addon(&addons, input)?;
+    let log = client.get_addon_logs(&addon.slug).await?;
+
+    let all: Vec<&str> = log.lines().collect();
+    let skip = lines.map_or(0, |n| all.len().saturating_sub(n));
+    let result = AddonLog {
+        slug: addon.slug.clone(),
+        lines: all[skip..].to_vec(),
+    };
+    output_for_format(ctx, &result, || {
+        for line in &result.lines {
+            println!("{line}");
+        }
+        Ok(())
+    })
+}
+
+fn past_tense(action: &str) -> &str {
+    match action {
+        "start" => "Started",
+        "stop" => "Stopped",
+        "restart" => "Restarted",
+        other => other,
+    }
+}
+
+/// Match an add-on by slug, then by name, then by part of either
+fn find_addon<'a>(addons: &'a [Addon], input: &str) -> Result<&'a Addon> {
+    let needle = input.to_lowercase();
+    if let Some(addon) = addons
+        .iter()
+        .find(|a| a.slug == needle)
+        .or_else(|| addons.iter().find(|a| a.name.to_lowercase() == needle))
+    {
+        return Ok(addon);
+    }
+
+    let partial: Vec<&Addon> = addons
+        .iter()
+        .filter(|a| a.slug.contains(&needle) || a.name.to_lowercase().contains(&needle))
+        .collect();
+    match partial.as_slice() {
+        [addon] => Ok(addon),
+        [] => {
+            let names: Vec<&str> = addons.iter().map(|a| a.name.as_str()).collect();
+            Err(Error::NotFound(format!(
+                "No add-on matches '{input}'. Installed: {}",
+                names.join(", ")
+            ))
+            .into())
+        }
+        _ => Err(Error::Ambiguous {
+            kind: "add-on".to_string(),
+            input: input.to_string(),
+            candidates: partial
+                .iter()
+                .map(|a| format!("{} ({})", a.name, a.slug))
+                .collect(),
+        }
+        .into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addon(slug: &str, name: &str) -> Addon {
+        Addon {
+            slug: slug.to_string(),
+            name: name.to_string(),
+            state: Some("started".to_string()),
+            version: None,
+            version_latest: None,
+            update_available: false,
+            description: None,
+        }
+    }
+
+    #[test]
+    fn test_find_addon() {
+        let addons = vec![
+            addon("core_mosquitto", "Mosquitto broker"),
+            addon("core_ssh", "Terminal & SSH"),
+            addon("a0d7b954_ssh", "Advanced SSH & Web Terminal"),
+        ];
+        let slug = |input| find_addon(&addons, input).map(|a| a.slug.as_str());
+
+        assert_eq!(slug("core_ssh").unwrap(), "core_ssh");
+        assert_eq!(slug("mosquitto").unwrap(), "core_mosquitto");
+        assert_eq!(slug("terminal & ssh").unwrap(), "core_ssh");
+        let err = slug("ssh").unwrap_err();
+        assert_eq!(crate::error::find(&err).unwrap().code(), "ambiguous");
+        assert!(slug("zigbee").is_err());
+    }
+}
@@ -1,30 +1,171 @@
 //! Agent command - leverage Home Assistant's conversation agent
+//!
+//! `--pipeline` routes the conversation to the agent of an Assist pipeline
+//! (often an LLM) in the pipeline's conversation language.
 
-use anyhow::Result;
+use std::io::{self, BufRead, IsTerminal, Write};
 
-use crate::{api::HassClient, cli::AgentCommand, config::RuntimeContext, output};
+use anyhow::{bail, Result};
+use serde::Serialize;
+use serde_json::Value;
+use tabled::Tabled;
+
+use crate::{
+    api::{ConversationResponse, HassClient},
+    cli::{AgentCommand, AgentSubcommand},
+    commands::assist::find_pipeline,
+    config::RuntimeContext,
+    output,
+    websocket::{AssistPipeline, WsClient},
+};
+
+#[derive(Debug, Serialize, Tabled)]
+struct PipelineRow {
+    name: String,
+    language: String,
+    agent: String,
+    stt: String,
+    tts: String,
+    preferred: String,
+    id: String,
+}
+
+impl PipelineRow {
+    fn new(pipeline: &AssistPipeline, preferred: Option<&str>) -> Self {
+        let engine = |e: &Option<String>| e.clone().unwrap_or_else(|| "-".to_string());
+        Self {
+            name: pipeline.name.clone(),
+            language: pipeline.language.clone(),
+            agent: engine(&pipeline.conversation_engine),
+            stt: engine(&pipeline.stt_engine),
+            tts: engine(&pipeline.tts_engine),
+            preferred: if Some(pipeline.id.as_str()) == preferred {
+                "yes".to_string()
+            } else {
+                String::new()
+            },
+            id: pipeline.id.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Tabled)]
+struct AgentRow {
+    id: String,
+    name: String,
+    languages: String,
+    default: String,
+}
 
 pub async fn handle(client: &HassClient, cmd: &AgentCommand, ctx: &RuntimeContext) -> Result<()> {
-    let text = cmd.words.join(" ");
+    match &cmd.command {
+        Some(AgentSubcommand::List { language }) => return list(ctx, language.as_deref()).await,
+        Some(AgentSubcommand::Pipelines) => return pipelines(ctx).await,
+        None => {}
+    }
+    if cmd.words.is_empty() && !cmd.interactive {
+        bail!("Nothing to ask: give a command, e.g. hmr agent turn on the kitchen light");
+    }
+
+    let pipeline = match cmd.pipeline.as_deref() {
+        Some(input) => {
+            let list = WsClient::connect(ctx)
+                .await?
+                .list_assist_pipelines()
+                .await?;
+            Some(find_pipeline(&list.pipelines, input)?.clone())
+        }
+        None => None,
+    };
+    let (agent_id, lang) = match &pipeline {
+        Some(pipeline) => (
+            pipeline.conversation_engine.as_deref(),
+            pipeline_language(pipeline),
+        ),
+        None => (
+            cmd.agent_id
+                .as_deref()
+                .or(ctx.config.agent.agent_id.as_deref()),
+            cmd.lang.as_str(),
+        ),
+    };
 
+    if cmd.interactive {
+        return interactive(ctx, client, agent_id, lang, cmd.conversation_id.clone()).await;
+    }
+
+    let text = cmd.words.join(" ");
     log::debug!(
         "Processing conversation: text='{}', lang={}, agent_id={:?}, conversation_id={:?}",
         text,
-        cmd.lang,
-        cmd.agent_id,
+        lang,
+        agent_id,
         cmd.conversation_id
     );
 
     let response = client
-        .process_conversation(
-            &text,
-            Some(&cmd.lang),
-            cmd.agent_id.as_deref(),
-            cmd.conversation_id.as_deref(),
-        )
+        .process_conversation(&text, Some(lang), agent_id, cmd.conversation_id.as_deref())
         .await?;
 
-    // Extract the speech response
+    print_response(ctx, &response)
+}
+
+/// A REPL: each line read from stdin goes to the agent in the same
+/// conversation, so follow-ups ("and the hallway?") keep their context.
+/// Ends on EOF, "exit" or "quit"; a failed turn is reported and skipped.
+async fn interactive(
+    ctx: &RuntimeContext,
+    client: &HassClient,
+    agent_id: Option<&str>,
+    lang: &str,
+    mut conversation_id: Option<String>,
+) -> Result<()> {
+    let prompting = io::stdin().is_terminal() && !ctx.is_machine_output();
+    if prompting {
+        println!(
+            "Talking to {} ({lang}); type exit or press Ctrl+D to quit",
+            agent_id.unwrap_or("the default agent")
+        );
+    }
+
+    let mut lines = io::stdin().lock().lines();
+    loop {
+        if prompting {
+            print!("> ");
+            io::stdout().flush()?;
+        }
+        let Some(line) = lines.next().transpose()? else {
+            break;
+        };
+        let text = line.trim();
+        if matches!(text, "exit" | "quit") {
+            break;
+        }
+        if text.is_empty() {
+            continue;
+        }
+
+        let response = client
+            .process_conversation(text, Some(lang), agent_id, conversation_id.as_deref())
+            .await;
+        match response {
+            Ok(response) => {
+                if response.conversation_id.is_some() {
+                    conversation_id.clone_from(&response.conversation_id);
+                }
+                print_response(ctx, &response)?;
+            }
+            Err(e) => output::warn(ctx, format!("Error: {e:#}")),
+        }
+    }
+    if prompting {
+        println!();
+    }
+    Ok(())
+}
+
+/// The agent's spoken reply, or the whole response for JSON/YAML
+fn print_response(ctx: &RuntimeContext, response: &ConversationResponse) -> Result<()> {
     let speech_text = response
         .response
         .speech
@@ -32,18 +173,112 @@ pub async fn handle(client: &HassClient, cmd: &AgentCommand, ctx: &RuntimeContex
         .map(|s| s.plain.speech.as_str())
         .unwrap_or("No response from agent");
 
-    // Use output_for_format to respect output format settings
-    output::output_for_format(ctx, &response, || {
-        // For human-readable output, just print the speech
+    output::output_for_format(ctx, response, || {
         println!("{speech_text}");
-
-        // If there's a conversation ID, show it for follow-up
         if let Some(conv_id) = &response.conversation_id {
             log::debug!("Conversation ID: {conv_id}");
         }
-
         Ok(())
-    })?;
+    })
+}
 
-    Ok(())
+async fn list(ctx: &RuntimeContext, language: Option<&str>) -> Result<()> {
+    let ws = WsClient::connect(ctx).await?;
+    let agents = ws.list_conversation_agents(language).await?;
+    let default = ctx.config.agent.agent_id.as_deref();
+
+    output::output_for_format(ctx, &agents, || {
+        if agents.is_empty() {
+            println!("No conversation agents found");
+            return Ok(());
+        }
+        let rows: Vec<AgentRow> = agents
+            .iter()
+            .map(|agent| AgentRow {
+                id: agent.id.clone(),
+                name: agent.name.clone(),
+                languages: describe_languages(&agent.supported_languages),
+                default: if Some(agent.id.as_str()) == default {
+                    "yes".to_string()
+                } else {
+                    String::new()
+                },
+            })
+            .collect();
+        output::print_table(ctx, &rows)
+    })
+}
+
+async fn pipelines(ctx: &RuntimeContext) -> Result<()> {
+    let list = WsClient::connect(ctx)
+        .await?
+        .list_assist_pipelines()
+        .await?;
+    let preferred = list.preferred_pipeline.as_deref();
+
+    output::output_for_format(ctx, &list, || {
+        if list.pipelines.is_empty() {
+            println!("No Assist pipelines found");
+            return Ok(());
+        }
+        let rows: Vec<PipelineRow> = list
+            .pipelines
+            .iter()
+            .map(|p| PipelineRow::new(p, preferred))
+            .collect();
+        output::print_table(ctx, &rows)
+    })
+}
+
+/// The language a pipeline talks to its agent in: its conversation language,
+/// or its own language when that is "any"
+fn pipeline_language(pipeline: &AssistPipeline) -> &str {
+    match pipeline.conversation_language.as_deref() {
+        Some(lang) if lang != "*" => lang,
+        _ => &pipeline.language,
+    }
+}
+
+/// Short summary of an agent's `supported_languages` ("*" or a list of codes)
+fn describe_languages(languages: &Value) -> String {
+    match languages {
+        Value::String(s) if s == "*" => "any".to_string(),
+        Value::Array(codes) if codes.len() > 5 => format!("{} languages", codes.len()),
+        Value::Array(codes) => codes
+            .iter()
+            .filter_map(|c| c.as_str())
+            .collect::<Vec<_>>()
+            .join(", "),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_pipeline_language() {
+        let pipeline: AssistPipeline = serde_json::from_value(json!({
+            "id": "01hx",
+            "name": "Ollama",
+            "language": "de",
+            "conversation_engine": "conversation.ollama",
+            "conversation_language": "*"
+        }))
+        .unwrap();
+        assert_eq!(pipeline_language(&pipeline), "de");
+        assert_eq!(PipelineRow::new(&pipeline, Some("01hx")).preferred, "yes");
+    }
+
+    #[test]
+    fn test_describe_languages() {
+        assert_eq!(describe_languages(&json!("*")), "any");
+        assert_eq!(describe_languages(&json!(["en", "de"])), "en, de");
+        assert_eq!(
+            describe_languages(&json!(["en", "de", "fr", "nl", "it", "es"])),
+            "6 languages"
+        );
+    }
 }
@@ -3,20 +3,23 @@
 //! Area management uses the WebSocket API to interact with Home Assistant's
 //! area registry for listing, creating, and deleting areas.
 
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use serde::Serialize;
 use tabled::Tabled;
 
-use crate::cli::AreaCommand;
+use crate::cache::{Cache, CacheManager};
+use crate::cli::{AreaCommand, PageArgs};
 use crate::config::RuntimeContext;
 use crate::output;
-use crate::websocket::{Area, CreateAreaRequest, WsClient};
+use crate::paging::Page;
+use crate::resolve::resolve_area;
+use crate::websocket::{Area, CreateAreaRequest, Device, WsClient};
 
 pub async fn run(ctx: &RuntimeContext, command: AreaCommand) -> Result<()> {
     match command {
-        AreaCommand::List => list(ctx).await,
+        AreaCommand::List { paging } => list(ctx, paging).await,
         AreaCommand::Create { name, data } => create(ctx, &name, data).await,
-        AreaCommand::Delete { name } => delete(ctx, &name).await,
+        AreaCommand::Delete { name, yes } => delete(ctx, &name, yes).await,
     }
 }
 
@@ -47,19 +50,49 @@ impl From<Area> for AreaRow {
     }
 }
 
-async fn list(ctx: &RuntimeContext) -> Result<()> {
-    let mut client = WsClient::connect(ctx).await?;
+async fn list(ctx: &RuntimeContext, paging: PageArgs) -> Result<()> {
+    let client = WsClient::connect(ctx).await?;
     let areas = client.list_areas().await?;
 
     // Convert to rows for table display
     let rows: Vec<AreaRow> = areas.into_iter().map(AreaRow::from).collect();
 
-    output::print_table(ctx, &rows)?;
+    let page = Page::of(&rows, &paging)?;
+    output::print_table(ctx, page.items)?;
+    page.print_footer(ctx, "areas");
     Ok(())
 }
 
+/// Describe what deleting an area will unassign
+fn deletion_impact(area_id: &str, devices: &[Device], cache: &Cache) -> Vec<String> {
+    let mut affected: Vec<String> = devices
+        .iter()
+        .filter(|d| d.area_id.as_deref() == Some(area_id))
+        .map(|d| {
+            let name = d
+                .name_by_user
+                .as_deref()
+                .or(d.name.as_deref())
+                .unwrap_or(&d.id);
+            format!("device {name} will be unassigned")
+        })
+        .collect();
+
+    affected.extend(
+        cache
+            .entities_in_area(area_id)
+            .iter()
+            .map(|e| format!("entity {} will be unassigned", e.entity_id)),
+    );
+
+    if affected.is_empty() {
+        affected.push("no devices or entities are assigned to this area".to_string());
+    }
+    affected
+}
+
 async fn create(ctx: &RuntimeContext, name: &str, data_input: Option<String>) -> Result<()> {
-    let mut client = WsClient::connect(ctx).await?;
+    let client = WsClient::connect(ctx).await?;
 
     // Build request from JSON input if provided, otherwise use just the name
     let request = if let Some(data_str) = data_input {
@@ -78,15 +111,27 @@ async fn create(ctx: &RuntimeContext, name: &str, data_input: Option<String>) ->
     Ok(())
 }
 
-async fn delete(ctx: &RuntimeContext, name: &str) -> Result<()> {
-    let mut client = WsClient::connect(ctx).await?;
+async fn delete(ctx: &RuntimeContext, name: &str, yes: bool) -> Result<()> {
+    let client = WsClient::connect(ctx).await?;
 
-    // First, list all areas to find the ID by name
-    let areas = client.list_areas().await?;
-    let area = areas
-        .iter()
-        .find(|a| a.name == name || a.area_id == name)
-        .ok_or_else(|| anyhow!("Area not found: {name}"))?;
+    // Resolve against a fresh area list, since this is destructive
+    let mut cache_manager = CacheManager::new(ctx)?;
+    cache_manager.refresh_areas().await?;
+    let area = resolve_area(ctx, cache_manager.cache(), name)?;
+
+    if !yes {
+        // Devices and entities are only needed to describe the impact
+        let devices = client.list_devices().await?;
+        if let Err(e) = cache_manager.ensure_entities().await {
+            log::debug!("Entity cache unavailable: {e:#}");
+        }
+        let affected = deletion_impact(&area.area_id, &devices, cache_manager.cache());
+        crate::prompt::confirm(
+            &format!("Delete area '{}' ({})?", area.name, area.area_id),
+            &affected,
+            yes,
+        )?;
+    }
 
     client.delete_area(&area.area_id).await?;
 
@@ -0,0 +1,299 @@
+//! Assist command implementations
+//!
+//! `assist run` drives an Assist pipeline over the WebSocket API the way a
+//! voice satellite would: text input starts at intent recognition, a WAV
+//! recording starts at speech-to-text and is streamed as binary frames tagged
+//! with the handler ID from `run-start`. With `--save` the pipeline continues
+//! through text-to-speech and the spoken reply is downloaded to a file.
+
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::{json, Value};
+
+use crate::api::HassClient;
+use crate::cli::{AssistCommand, OutputFormat};
+use crate::config::RuntimeContext;
+use crate::websocket::{AssistPipeline, WsClient};
+
+/// Bytes of audio per binary frame (about 30ms at 16kHz)
+const AUDIO_CHUNK: usize = 960;
+
+/// The PCM samples of a WAV file
+#[derive(Debug, PartialEq)]
+struct WavAudio<'a> {
+    sample_rate: u32,
+    data: &'a [u8],
+}
+
+pub async fn run(ctx: &RuntimeContext, command: AssistCommand) -> Result<()> {
+    match command {
+        AssistCommand::Run {
+            pipeline,
+            text,
+            wav,
+            save,
+        } => {
+            let recording = wav
+                .as_deref()
+                .map(|path| fs::read(path).with_context(|| format!("reading {}", path.display())))
+                .transpose()?;
+            let audio = recording.as_deref().map(parse_wav).transpose()?;
+            run_pipeline(ctx, pipeline.as_deref(), text, audio, save.as_deref()).await
+        }
+    }
+}
+
+async fn run_pipeline(
+    ctx: &RuntimeContext,
+    pipeline: Option<&str>,
+    text: Option<String>,
+    audio: Option<WavAudio<'_>>,
+    save: Option<&Path>,
+) -> Result<()> {
+    let ws = WsClient::connect(ctx).await?;
+    let pipeline_id = match pipeline {
+        Some(input) => {
+            let list = ws.list_assist_pipelines().await?;
+            Some(find_pipeline(&list.pipelines, input)?.id.clone())
+        }
+        None => None,
+    };
+
+    let (start_stage, input) = match (&audio, text) {
+        (Some(audio), _) => ("stt", json!({ "sample_rate": audio.sample_rate })),
+        (None, Some(text)) => ("intent", json!({ "text": text })),
+        (None, None) => bail!("Give --text or --wav"),
+    };
+    let end_stage = if save.is_some() { "tts" } else { "intent" };
+    let mut events = ws
+        .run_assist_pipeline(pipeline_id.as_deref(), start_stage, end_stage, input)
+        .await?;
+
+    let wait = Duration::from_secs(ctx.timeout());
+    let output_format = ctx.output_format();
+    loop {
+        let event = tokio::time::timeout(wait, events.next_raw())
+            .await
+            .map_err(|_| anyhow!("No pipeline event for {}s", wait.as_secs()))?
+            .ok_or_else(|| anyhow!("Connection closed before the pipeline finished"))?;
+        let kind = event["type"].as_str().unwrap_or_default().to_string();
+
+        match output_format {
+            OutputFormat::Json => println!("{}", serde_json::to_string(&event)?),
+            OutputFormat::Yaml => println!("{}", serde_yaml::to_string(&event)?),
+            _ => {
+                if let Some(line) = describe_event(&event) {
+                    println!("{kind:<14} {line}");
+                }
+            }
+        }
+
+        match kind.as_str() {
+            "run-start" => {
+                if let Some(audio) = &audio {
+                    let handler_id = event["data"]["runner_data"]["stt_binary_handler_id"]
+                        .as_u64()
+                        .and_then(|id| u8::try_from(id).ok())
+                        .ok_or_else(|| anyhow!("Pipeline did not accept audio input"))?;
+                    for chunk in audio.data.chunks(AUDIO_CHUNK) {
+                        ws.send_stt_audio(handler_id, chunk).await?;
+                    }
+                    ws.send_stt_audio(handler_id, &[]).await?;
+                }
+            }
+            "tts-end" => {
+                if let Some(path) = save {
+                    let url = event["data"]["tts_output"]["url"]
+                        .as_str()
+                        .ok_or_else(|| anyhow!("Pipeline returned no TTS audio"))?;
+                    save_audio(ctx, url, path).await?;
+                }
+            }
+            "error" => bail!(
+                "Pipeline failed: {} ({})",
+                event["data"]["message"].as_str().unwrap_or("unknown error"),
+                event["data"]["code"].as_str().unwrap_or("unknown")
+            ),
+            "run-end" => return Ok(()),
+            _ => {}
+        }
+    }
+}
+
+async fn save_audio(ctx: &RuntimeContext, url: &str, path: &Path) -> Result<()> {
+    let client = HassClient::new(ctx)?;
+    let download = client.get_tts_audio(url, |_, _| {}).await?;
+    fs::write(path, &download.bytes)
+        .with_context(|| format!("writing audio to {}", path.display()))?;
+    if !ctx.is_machine_output() {
+        println!(
+            "{:<14} {} bytes of {} to {}",
+            "saved",
+            download.bytes.len(),
+            download.content_type.as_deref().unwrap_or("audio"),
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Match a pipeline by ID, then by name (case-insensitive)
+pub fn find_pipeline<'a>(
+    pipelines: &'a [AssistPipeline],
+    input: &str,
+) -> Result<&'a AssistPipeline> {
+    pipelines
+        .iter()
+        .find(|p| p.id == input)
+        .or_else(|| {
+            pipelines
+                .iter()
+                .find(|p| p.name.eq_ignore_ascii_case(input))
+        })
+        .ok_or_else(|| {
+            let names: Vec<&str> = pipelines.iter().map(|p| p.name.as_str()).collect();
+            anyhow!(
+                "No Assist pipeline named '{input}'. Available: {}",
+                names.join(", ")
+            )
+        })
+}
+
+/// One line summarizing what a pipeline stage did, if worth showing
+fn describe_event(event: &Value) -> Option<String> {
+    let data = &event["data"];
+    let text = |value: &Value| value.as_str().unwrap_or_default().to_string();
+    let line = match event["type"].as_str()? {
+        "run-start" => format!(
+            "pipeline {} ({})",
+            text(&data["pipeline"]),
+            text(&data["language"])
+        ),
+        "stt-start" => format!("engine {}", text(&data["engine"])),
+        "stt-vad-start" => "speech started".to_string(),
+        "stt-vad-end" => "speech ended".to_string(),
+        "stt-end" => format!("heard \"{}\"", text(&data["stt_output"]["text"])),
+        "intent-start" => format!(
+            "agent {} <- \"{}\"",
+            text(&data["engine"]),
+            text(&data["intent_input"])
+        ),
+        "intent-end" => {
+            let response = &data["intent_output"]["response"];
+            format!(
+                "{} \"{}\"",
+                text(&response["response_type"]),
+                text(&response["speech"]["plain"]["speech"])
+            )
+        }
+        "tts-start" => format!(
+            "engine {} -> \"{}\"",
+            text(&data["engine"]),
+            text(&data["tts_input"])
+        ),
+        "tts-end" => text(&data["tts_output"]["url"]),
+        "run-end" => "done".to_string(),
+        "error" => format!("{} ({})", text(&data["message"]), text(&data["code"])),
+        _ => return None,
+    };
+    Some(line)
+}
+
+/// Find the PCM samples in a WAV file; Assist expects 16-bit mono audio
+fn parse_wav(bytes: &[u8]) -> Result<WavAudio<'_>> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        bail!("Not a WAV file");
+    }
+
+    let mut format = None;
+    let mut offset = 12;
+    while offset + 8 <= bytes.len() {
+        let id = &bytes[offset..offset + 4];
+        let size = u32::from_le_bytes(bytes[offset + 4..offset + 8].try_into()?) as usize;
+        let body = &bytes[offset + 8..bytes.len().min(offset + 8 + size)];
+
+        match id {
+            b"fmt " if body.len() >= 16 => {
+                let le16 = |at: usize| u16::from_le_bytes([body[at], body[at + 1]]);
+                let audio_format = le16(0);
+                let channels = le16(2);
+                let sample_rate = u32::from_le_bytes(body[4..8].try_into()?);
+                let bits = le16(14);
+                if audio_format != 1 || channels != 1 || bits != 16 {
+                    bail!(
+                        "WAV must be 16-bit mono PCM (got format {audio_format}, {channels} channels, {bits} bits)"
+                    );
+                }
+                format = Some(sample_rate);
+            }
+            b"data" => {
+                let sample_rate = format.ok_or_else(|| anyhow!("WAV data before its format"))?;
+                return Ok(WavAudio {
+                    sample_rate,
+                    data: body,
+                });
+            }
+            _ => {}
+        }
+        // Chunks are padded to an even length
+        offset += 8 + size + size % 2;
+    }
+    bail!("WAV file has no audio data")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wav(channels: u16, samples: &[u8]) -> Vec<u8> {
+        let mut fmt = Vec::new();
+        fmt.extend_from_slice(&1u16.to_le_bytes());
+        fmt.extend_from_slice(&channels.to_le_bytes());
+        fmt.extend_from_slice(&16000u32.to_le_bytes());
+        fmt.extend_from_slice(&(32000 * channels as u32).to_le_bytes());
+        fmt.extend_from_slice(&(2 * channels).to_le_bytes());
+        fmt.extend_from_slice(&16u16.to_le_bytes());
+
+        let mut out = b"RIFF\0\0\0\0WAVE".to_vec();
+        for (id, body) in [(b"fmt ", fmt.as_slice()), (b"data", samples)] {
+            out.extend_from_slice(id);
+            out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+            out.extend_from_slice(body);
+        }
+        out
+    }
+
+    #[test]
+    fn test_parse_wav() {
+        let bytes = wav(1, &[1, 2, 3, 4]);
+        assert_eq!(
+            parse_wav(&bytes).unwrap(),
+            WavAudio {
+                sample_rate: 16000,
+                data: &[1, 2, 3, 4]
+            }
+        );
+
+        assert!(parse_wav(&wav(2, &[0; 4])).is_err());
+        assert!(parse_wav(b"not audio").is_err());
+    }
+
+    #[test]
+    fn test_describe_event() {
+        let event = json!({
+            "type": "intent-end",
+            "data": {"intent_output": {"response": {
+                "response_type": "action_done",
+                "speech": {"plain": {"speech": "Turned on the lights"}}
+            }}}
+        });
+        assert_eq!(
+            describe_event(&event).unwrap(),
+            "action_done \"Turned on the lights\""
+        );
+        assert!(describe_event(&json!({"type": "wake_word-start"})).is_none());
+    }
+}
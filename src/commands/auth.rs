@@ -0,0 +1,392 @@
+//! Auth command implementations
+//!
+//! Home Assistant tokens carry no scopes of their own: what a token can do
+//! depends on whether its user is an admin. `auth scopes` probes a few cheap
+//! endpoints, asks the WebSocket API who the token belongs to, and lists the
+//! hmr commands a non-admin user will be refused, with alternatives.
+//!
+//! `auth login` runs Home Assistant's login flow (username, password, and an
+//! MFA code if enabled) to obtain a refresh token, which `HassClient` uses to
+//! renew the access token when it expires or is rejected. `--flow token`
+//! stores a long-lived access token instead.
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+use serde_json::json;
+use tabled::Tabled;
+
+use crate::api::{http_client, HassClient};
+use crate::cli::{AuthCommand, LoginFlow};
+use crate::config::RuntimeContext;
+use crate::credentials::{self, LoginStep, Store, StoredToken};
+use crate::error::Error;
+use crate::features;
+use crate::output::{output_for_format, print_output, print_table, warn};
+use crate::prompt::{ask, ask_secret};
+use crate::websocket::{CurrentUser, WsClient};
+
+/// Commands Home Assistant only allows for admin users, with what to do instead
+const ADMIN_ONLY: &[(&str, &str)] = &[
+    (
+        "hmr area create, hmr area delete",
+        "ask an admin, or manage areas in the Home Assistant UI",
+    ),
+    (
+        "hmr device assign, hmr device update",
+        "ask an admin to assign devices in Settings > Devices",
+    ),
+    (
+        "hmr entity set",
+        "call the integration's services with `hmr service call` instead of overwriting states",
+    ),
+    (
+        "hmr event fire",
+        "start the automation or script listening for it with `hmr automation trigger` or `hmr service call`",
+    ),
+    (
+        "hmr template",
+        "read states and attributes with `hmr entity get`",
+    ),
+    (
+        "hmr automation trace",
+        "see when it last ran in the last_triggered attribute (`hmr entity get`)",
+    ),
+];
+
+#[derive(Debug, Serialize, Tabled)]
+struct Check {
+    capability: String,
+    status: String,
+    detail: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Restriction {
+    command: String,
+    alternative: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ScopeReport {
+    user: Option<CurrentUser>,
+    checks: Vec<Check>,
+    /// Commands that will be refused (empty for admins or an unknown user)
+    restricted: Vec<Restriction>,
+}
+
+pub async fn run(ctx: &RuntimeContext, command: AuthCommand) -> Result<()> {
+    match command {
+        AuthCommand::Scopes => scopes(ctx).await,
+        AuthCommand::Login { flow, username } => login(ctx, flow, username).await,
+        AuthCommand::Logout => logout(ctx).await,
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct LoginResult {
+    server: String,
+    flow: String,
+    stored_in: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires_at: Option<i64>,
+}
+
+async fn login(ctx: &RuntimeContext, flow: LoginFlow, username: Option<String>) -> Result<()> {
+    let server = ctx.server_url()?.trim_end_matches('/').to_string();
+    let client = http_client(ctx)?;
+
+    let token = match flow {
+        LoginFlow::Oauth => {
+            let username = match username {
+                Some(username) => username,
+                None => ask("Username")?,
+            };
+            let password = ask_secret("Password")?;
+            let mut step = credentials::start_login(&client, &server).await?;
+            let mut submitted = false;
+            let code = loop {
+                let flow_id = step.flow_id.clone().unwrap_or_default();
+                match step.kind.as_str() {
+                    "create_entry" => break login_code(&step)?,
+                    "form" => {
+                        if submitted {
+                            check_form_errors(&step)?;
+                        }
+                        let fields = match step.step_id.as_deref() {
+                            Some("init") => json!({ "username": username, "password": password }),
+                            Some("mfa") => json!({ "code": ask("Code")? }),
+                            other => bail!(
+                                "Unsupported login step '{}'; use `--flow token`",
+                                other.unwrap_or_default()
+                            ),
+                        };
+                        step =
+                            credentials::submit_login(&client, &server, &flow_id, fields).await?;
+                        submitted = true;
+                    }
+                    "abort" => {
+                        return Err(Error::Auth(format!(
+                            "Login aborted: {}",
+                            step.reason.as_deref().unwrap_or("unknown reason")
+                        ))
+                        .into())
+                    }
+                    other => bail!("Unexpected login step '{other}'"),
+                }
+            };
+            credentials::exchange_code(&client, &server, &code).await?
+        }
+        LoginFlow::Token => {
+            let token = StoredToken {
+                access_token: ask_secret("Long-lived access token")?,
+                refresh_token: None,
+                expires_at: None,
+            };
+            verify_token(&client, &server, &token.access_token).await?;
+            token
+        }
+    };
+
+    let store = Store::new(ctx);
+    if !features::enabled("keyring") && credentials::keyring_available() {
+        warn(
+            ctx,
+            "hmr was built without the `keyring` feature; storing the token in a file instead",
+        );
+    }
+    store.save(&server, &token)?;
+    let result = LoginResult {
+        server,
+        flow: format!("{flow:?}").to_lowercase(),
+        stored_in: store.describe(),
+        expires_at: token.expires_at,
+    };
+    if ctx.is_machine_output() {
+        return print_output(ctx, &result);
+    }
+    println!(
+        "Logged in to {}; token stored in {}",
+        result.server, result.stored_in
+    );
+    Ok(())
+}
+
+/// The authorization code of a finished login flow
+fn login_code(step: &LoginStep) -> Result<String> {
+    step.result
+        .clone()
+        .ok_or_else(|| Error::Auth("Login finished without an authorization code".into()).into())
+}
+
+/// A form shown again after a submission means the input was rejected
+fn check_form_errors(step: &LoginStep) -> Result<()> {
+    let errors = step
+        .errors
+        .as_ref()
+        .and_then(|e| e.as_object())
+        .filter(|e| !e.is_empty());
+    if let Some(errors) = errors {
+        let reasons: Vec<String> = errors
+            .values()
+            .map(|v| v.as_str().unwrap_or_default().replace('_', " "))
+            .collect();
+        return Err(Error::Auth(format!("Login failed: {}", reasons.join(", "))).into());
+    }
+    Ok(())
+}
+
+async fn verify_token(client: &reqwest::Client, server: &str, token: &str) -> Result<()> {
+    let url = format!("{server}/api/");
+    let response = client
+        .get(&url)
+        .bearer_auth(token)
+        .send()
+        .await
+        .map_err(|e| Error::network(format!("request to {url}"), e))?;
+    let status = response.status();
+    if status.as_u16() == 401 || status.as_u16() == 403 {
+        return Err(Error::Auth("Home Assistant rejected the token".into()).into());
+    }
+    if !status.is_success() {
+        bail!("HTTP {status} from {url}");
+    }
+    Ok(())
+}
+
+async fn logout(ctx: &RuntimeContext) -> Result<()> {
+    let server = ctx.server_url()?.trim_end_matches('/').to_string();
+    let store = Store::new(ctx);
+    let stored = store.load(&server)?;
+    if let Some(refresh_token) = stored.as_ref().and_then(|t| t.refresh_token.as_deref()) {
+        // The local copy is removed either way, so a server that can't be
+        // reached shouldn't block logging out
+        if let Err(e) = credentials::revoke(&http_client(ctx)?, &server, refresh_token).await {
+            warn(ctx, format!("Could not revoke the refresh token: {e:#}"));
+        }
+    }
+    let removed = store.remove(&server)?;
+
+    if ctx.is_machine_output() {
+        return print_output(ctx, &json!({ "server": server, "removed": removed }));
+    }
+    if removed {
+        println!("Logged out of {server}");
+    } else {
+        println!("No stored login for {server}");
+    }
+    Ok(())
+}
+
+async fn scopes(ctx: &RuntimeContext) -> Result<()> {
+    let client = HassClient::new(ctx)?;
+    let mut checks = Vec::new();
+
+    checks.push(match client.get_info().await {
+        Ok(info) => check("REST API", "ok", format!("Home Assistant {}", info.version)),
+        Err(e) => failed("REST API", &e),
+    });
+
+    checks.push(match client.render_template("{{ 1 }}").await {
+        Ok(_) => check("Templates", "ok", "can render templates"),
+        Err(e) => failed("Templates", &e),
+    });
+
+    let mut user = None;
+    match WsClient::connect(ctx).await {
+        Ok(ws) => {
+            checks.push(check("WebSocket", "ok", "authenticated"));
+
+            match ws.current_user().await {
+                Ok(current) => {
+                    checks.push(check("User", "ok", describe_user(&current)));
+                    user = Some(current);
+                }
+                Err(e) => checks.push(failed("User", &e)),
+            }
+
+            checks.push(match ws.list_areas().await {
+                Ok(areas) => check(
+                    "Registry read",
+                    "ok",
+                    format!("{} areas visible", areas.len()),
+                ),
+                Err(e) => failed("Registry read", &e),
+            });
+        }
+        Err(e) => checks.push(failed("WebSocket", &e)),
+    }
+
+    // Registry edits can't be probed without changing something, so they're
+    // judged from the user's admin flag
+    checks.push(match &user {
+        Some(u) if u.is_admin => check("Admin commands", "ok", "registry edits allowed"),
+        Some(_) => check("Admin commands", "denied", "user is not an admin"),
+        None => check("Admin commands", "unknown", "could not look up the user"),
+    });
+
+    let restricted: Vec<Restriction> = match &user {
+        Some(u) if !u.is_admin => ADMIN_ONLY
+            .iter()
+            .map(|(command, alternative)| Restriction {
+                command: command.to_string(),
+                alternative: alternative.to_string(),
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let report = ScopeReport {
+        user,
+        checks,
+        restricted,
+    };
+
+    output_for_format(ctx, &report, || {
+        print_table(ctx, &report.checks)?;
+        if !report.restricted.is_empty() {
+            println!();
+            println!("This token's user is not an admin, so these commands will be refused:");
+            for restriction in &report.restricted {
+                println!("  {}", restriction.command);
+                println!("    instead: {}", restriction.alternative);
+            }
+        }
+        Ok(())
+    })
+}
+
+fn check(capability: &str, status: &str, detail: impl Into<String>) -> Check {
+    Check {
+        capability: capability.to_string(),
+        status: status.to_string(),
+        detail: detail.into(),
+    }
+}
+
+/// A failed probe, "denied" when the server refused the token
+fn failed(capability: &str, error: &anyhow::Error) -> Check {
+    let message = format!("{error:#}");
+    let status = if is_denied(&message) {
+        "denied"
+    } else {
+        "failed"
+    };
+    let detail = message.lines().next().unwrap_or_default().to_string();
+    check(capability, status, detail)
+}
+
+fn is_denied(message: &str) -> bool {
+    message.contains("401") || message.contains("403") || message.contains("unauthorized")
+}
+
+fn describe_user(user: &CurrentUser) -> String {
+    let name = user.name.as_deref().unwrap_or(&user.id);
+    let role = if user.is_owner {
+        "owner"
+    } else if user.is_admin {
+        "admin"
+    } else {
+        "regular user"
+    };
+    format!("{name} ({role})")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_failed_classifies_refusals() {
+        let denied = failed(
+            "Templates",
+            &anyhow!("HTTP 401 Unauthorized from http://ha/api/template\nHint: check token"),
+        );
+        assert_eq!(denied.status, "denied");
+        assert_eq!(
+            denied.detail,
+            "HTTP 401 Unauthorized from http://ha/api/template"
+        );
+
+        let refused = failed("WebSocket", &anyhow!("connection refused"));
+        assert_eq!(refused.status, "failed");
+    }
+
+    #[test]
+    fn test_check_form_errors() {
+        let step = |errors| LoginStep {
+            kind: "form".to_string(),
+            flow_id: Some("abc".to_string()),
+            step_id: Some("init".to_string()),
+            errors,
+            result: None,
+            reason: None,
+        };
+        assert!(check_form_errors(&step(None)).is_ok());
+        assert!(check_form_errors(&step(Some(json!({})))).is_ok());
+
+        let err = check_form_errors(&step(Some(json!({"base": "invalid_auth"})))).unwrap_err();
+        assert_eq!(err.to_string(), "Login failed: invalid auth");
+        assert_eq!(crate::error::exit_code(&err), 3);
+    }
+}
@@ -0,0 +1,330 @@
+//! Automation command implementations
+//!
+//! Automations are listed from the entity cache and resolved by (fuzzy) name
+//! among `automation` entities. Triggering and enabling go through the
+//! `automation` services; traces come from the WebSocket `trace/list` and
+//! `trace/get` commands, which key runs by the automation's config `id`
+//! attribute rather than its entity ID.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use serde_json::Value;
+use tabled::Tabled;
+
+use crate::api::HassClient;
+use crate::cache::{Cache, CacheManager};
+use crate::cli::AutomationCommand;
+use crate::commands::quick::{execute, resolve_in, QuickAction};
+use crate::config::RuntimeContext;
+use crate::output::{output_for_format, print_output, print_table};
+use crate::resolve::room_scope;
+use crate::websocket::{TraceSummary, WsClient};
+
+#[derive(Debug, Serialize, Tabled)]
+struct AutomationRow {
+    entity_id: String,
+    name: String,
+    state: String,
+    area: String,
+}
+
+#[derive(Debug, Serialize, Tabled)]
+struct TraceRow {
+    run_id: String,
+    started: String,
+    result: String,
+    trigger: String,
+    last_step: String,
+}
+
+/// One executed step of a trace, in the order it ran
+#[derive(Debug, PartialEq)]
+struct TraceStep {
+    timestamp: String,
+    path: String,
+    detail: String,
+}
+
+pub async fn run(ctx: &RuntimeContext, command: AutomationCommand) -> Result<()> {
+    match command {
+        AutomationCommand::List => list(ctx).await,
+        AutomationCommand::Trigger {
+            words,
+            skip_condition,
+            dry_run,
+        } => {
+            let action = QuickAction::new("trigger").with_data("skip_condition", skip_condition);
+            call(ctx, words.join(" "), action, dry_run).await
+        }
+        AutomationCommand::Enable { words, dry_run } => {
+            call(ctx, words.join(" "), QuickAction::new("turn_on"), dry_run).await
+        }
+        AutomationCommand::Disable { words, dry_run } => {
+            call(ctx, words.join(" "), QuickAction::new("turn_off"), dry_run).await
+        }
+        AutomationCommand::Trace { words, run, list } => {
+            trace(ctx, words.join(" "), run, list).await
+        }
+    }
+}
+
+async fn list(ctx: &RuntimeContext) -> Result<()> {
+    let mut cache_manager = CacheManager::new(ctx)?;
+    cache_manager.ensure_entities().await?;
+    cache_manager.ensure_areas().await?;
+    cache_manager.ensure_devices().await?;
+
+    let room = room_scope(ctx, &mut cache_manager).await?;
+    let automations = match &room {
+        Some((_, cache)) => automation_rows(cache, cache_manager.cache()),
+        None => automation_rows(cache_manager.cache(), cache_manager.cache()),
+    };
+
+    if automations.is_empty() && !ctx.is_machine_output() {
+        println!("No automations found");
+        return Ok(());
+    }
+    print_table(ctx, &automations)
+}
+
+/// Rows for the automations in `cache`, with areas looked up in the full cache
+fn automation_rows(cache: &Cache, full: &Cache) -> Vec<AutomationRow> {
+    let mut rows: Vec<AutomationRow> = cache
+        .entities_in_domain("automation")
+        .into_iter()
+        .map(|entity| {
+            let context = full.entity_context(entity);
+            AutomationRow {
+                entity_id: entity.entity_id.clone(),
+                name: entity
+                    .friendly_name
+                    .clone()
+                    .unwrap_or_else(|| entity.object_id.clone()),
+                state: entity.state.clone(),
+                area: context.area.map(|a| a.name).unwrap_or_default(),
+            }
+        })
+        .collect();
+    rows.sort_by_key(|row| row.name.to_lowercase());
+    rows
+}
+
+async fn call(
+    ctx: &RuntimeContext,
+    name: String,
+    action: QuickAction,
+    dry_run: bool,
+) -> Result<()> {
+    let entity_id = resolve_in(ctx, "automation", &["automation"], Some(name)).await?;
+    execute(ctx, "automation", &entity_id, &[action], dry_run).await
+}
+
+async fn trace(
+    ctx: &RuntimeContext,
+    name: String,
+    run_id: Option<String>,
+    list: bool,
+) -> Result<()> {
+    let entity_id = resolve_in(ctx, "automation", &["automation"], Some(name)).await?;
+
+    // Traces are stored under the automation's config id, not its entity ID
+    let state = HassClient::new(ctx)?.get_state(&entity_id).await?;
+    let item_id = state
+        .attributes
+        .get("id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| {
+            anyhow!(
+                "{entity_id} has no id attribute; Home Assistant only keeps traces \
+                 for automations with an id"
+            )
+        })?;
+
+    let client = WsClient::connect(ctx).await?;
+    let mut traces = client.list_traces("automation", item_id).await?;
+    traces.sort_by(|a, b| a.timestamp.start.cmp(&b.timestamp.start));
+
+    if list {
+        let rows: Vec<TraceRow> = traces.iter().rev().map(trace_row).collect();
+        return output_for_format(ctx, &traces, || {
+            if rows.is_empty() {
+                println!("No stored runs for {entity_id}");
+                return Ok(());
+            }
+            print_table(ctx, &rows)
+        });
+    }
+
+    let summary = match &run_id {
+        Some(run_id) => traces
+            .iter()
+            .find(|t| &t.run_id == run_id)
+            .ok_or_else(|| anyhow!("No stored run {run_id} for {entity_id}"))?,
+        None => traces
+            .last()
+            .ok_or_else(|| anyhow!("{entity_id} has no stored runs"))?,
+    };
+    let trace = client
+        .get_trace("automation", item_id, &summary.run_id)
+        .await?;
+
+    if ctx.is_machine_output() {
+        return print_output(ctx, &trace);
+    }
+
+    println!("{entity_id} run {}", summary.run_id);
+    println!("  Started:  {}", local_time(&summary.timestamp.start));
+    if let Some(finish) = &summary.timestamp.finish {
+        println!("  Finished: {}", local_time(finish));
+    }
+    println!("  Result:   {}", run_result(summary));
+    if let Some(trigger) = &summary.trigger {
+        println!("  Trigger:  {trigger}");
+    }
+    if let Some(error) = &summary.error {
+        println!("  Error:    {error}");
+    }
+
+    let steps = trace_steps(&trace);
+    if !steps.is_empty() {
+        println!();
+        for step in steps {
+            let time = DateTime::parse_from_rfc3339(&step.timestamp)
+                .map(|dt| dt.with_timezone(&Local).format("%H:%M:%S%.3f").to_string())
+                .unwrap_or(step.timestamp);
+            if step.detail.is_empty() {
+                println!("  {time}  {}", step.path);
+            } else {
+                println!("  {time}  {}  {}", step.path, step.detail);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn trace_row(summary: &TraceSummary) -> TraceRow {
+    TraceRow {
+        run_id: summary.run_id.clone(),
+        started: local_time(&summary.timestamp.start),
+        result: run_result(summary),
+        trigger: summary.trigger.clone().unwrap_or_default(),
+        last_step: summary.last_step.clone().unwrap_or_default(),
+    }
+}
+
+/// How a run ended, e.g. "finished" or "failed conditions"
+fn run_result(summary: &TraceSummary) -> String {
+    summary
+        .script_execution
+        .as_deref()
+        .unwrap_or(&summary.state)
+        .replace('_', " ")
+}
+
+fn local_time(timestamp: &str) -> String {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|dt| {
+            dt.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_else(|_| timestamp.to_string())
+}
+
+/// Flatten a `trace/get` result's per-path step lists into steps ordered by time
+fn trace_steps(trace: &Value) -> Vec<TraceStep> {
+    let Some(paths) = trace.get("trace").and_then(|t| t.as_object()) else {
+        return Vec::new();
+    };
+
+    let mut steps: Vec<TraceStep> = paths
+        .iter()
+        .flat_map(|(path, runs)| {
+            runs.as_array()
+                .into_iter()
+                .flatten()
+                .map(move |step| TraceStep {
+                    timestamp: step
+                        .get("timestamp")
+                        .and_then(|t| t.as_str())
+                        .unwrap_or_default()
+                        .to_string(),
+                    path: path.clone(),
+                    detail: step_detail(step),
+                })
+        })
+        .collect();
+    steps.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    steps
+}
+
+/// A short description of a step's outcome: a condition result, the service
+/// an action called, or the error it raised
+fn step_detail(step: &Value) -> String {
+    if let Some(error) = step.get("error").and_then(|e| e.as_str()) {
+        return format!("error: {error}");
+    }
+    let Some(result) = step.get("result") else {
+        return String::new();
+    };
+    if let Some(passed) = result.get("result").and_then(|r| r.as_bool()) {
+        return if passed { "passed" } else { "failed" }.to_string();
+    }
+    let params = result.get("params");
+    let field = |key: &str| params.and_then(|p| p.get(key)).and_then(|v| v.as_str());
+    match (field("domain"), field("service")) {
+        (Some(domain), Some(service)) => format!("{domain}.{service}"),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_trace_steps() {
+        let trace = json!({
+            "trace": {
+                "action/0": [{
+                    "path": "action/0",
+                    "timestamp": "2025-01-15T07:00:01.200000+00:00",
+                    "result": {"params": {"domain": "light", "service": "turn_on"}}
+                }],
+                "trigger/0": [{
+                    "path": "trigger/0",
+                    "timestamp": "2025-01-15T07:00:01.000000+00:00"
+                }],
+                "condition/0": [{
+                    "path": "condition/0",
+                    "timestamp": "2025-01-15T07:00:01.100000+00:00",
+                    "result": {"result": true}
+                }],
+                "action/1": [{
+                    "path": "action/1",
+                    "timestamp": "2025-01-15T07:00:01.300000+00:00",
+                    "error": "Entity not found"
+                }]
+            }
+        });
+
+        let steps = trace_steps(&trace);
+        let summary: Vec<(&str, &str)> = steps
+            .iter()
+            .map(|s| (s.path.as_str(), s.detail.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("trigger/0", ""),
+                ("condition/0", "passed"),
+                ("action/0", "light.turn_on"),
+                ("action/1", "error: Entity not found"),
+            ]
+        );
+        assert!(trace_steps(&json!({})).is_empty());
+    }
+}
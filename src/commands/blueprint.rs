@@ -0,0 +1,205 @@
+//! Blueprint command implementations
+//!
+//! `blueprint import` has Home Assistant fetch and validate a blueprint from
+//! its URL, shows what it is and which inputs it takes, and installs it after
+//! confirmation. An installed blueprint with the same file name is only
+//! replaced with `--force`.
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+use tabled::Tabled;
+
+use crate::cli::{BlueprintCommand, BlueprintDomain};
+use crate::config::RuntimeContext;
+use crate::output::{output_for_format, print_table};
+use crate::prompt::confirm;
+use crate::websocket::{Blueprint, ImportedBlueprint, WsClient};
+
+pub async fn run(ctx: &RuntimeContext, command: BlueprintCommand) -> Result<()> {
+    match command {
+        BlueprintCommand::List { domain } => list(ctx, domain).await,
+        BlueprintCommand::Import {
+            url,
+            force,
+            yes,
+            dry_run,
+        } => import(ctx, &url, force, yes, dry_run).await,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Tabled)]
+struct BlueprintRow {
+    #[tabled(rename = "DOMAIN")]
+    domain: String,
+    #[tabled(rename = "NAME")]
+    name: String,
+    #[tabled(rename = "PATH")]
+    path: String,
+    #[tabled(rename = "SOURCE")]
+    source: String,
+}
+
+impl From<&Blueprint> for BlueprintRow {
+    fn from(blueprint: &Blueprint) -> Self {
+        Self {
+            domain: blueprint.domain.clone(),
+            name: match &blueprint.error {
+                Some(error) => format!("{} (error: {error})", blueprint.name),
+                None => blueprint.name.clone(),
+            },
+            path: blueprint.path.clone(),
+            source: blueprint
+                .source_url
+                .clone()
+                .unwrap_or_else(|| "-".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ImportResult<'a> {
+    name: &'a str,
+    domain: &'a str,
+    path: &'a str,
+    source_url: &'a str,
+    inputs: Vec<&'a str>,
+    /// installed, or would install
+    status: &'static str,
+}
+
+async fn list(ctx: &RuntimeContext, domain: Option<BlueprintDomain>) -> Result<()> {
+    let domains = match domain {
+        Some(domain) => vec![domain_name(domain)],
+        None => vec!["automation", "script"],
+    };
+    let ws = WsClient::connect(ctx).await?;
+    let mut blueprints = Vec::new();
+    for domain in domains {
+        blueprints.extend(ws.list_blueprints(domain).await?);
+    }
+    blueprints.sort_by(|a, b| (&a.domain, &a.path).cmp(&(&b.domain, &b.path)));
+
+    output_for_format(ctx, &blueprints, || {
+        let rows: Vec<BlueprintRow> = blueprints.iter().map(BlueprintRow::from).collect();
+        print_table(ctx, &rows)
+    })
+}
+
+async fn import(
+    ctx: &RuntimeContext,
+    url: &str,
+    force: bool,
+    yes: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let ws = WsClient::connect(ctx).await?;
+    let imported = ws.import_blueprint(url).await?;
+    if let Some(errors) = imported
+        .validation_errors
+        .as_ref()
+        .filter(|e| !e.is_empty())
+    {
+        bail!("Blueprint at {url} is invalid: {}", errors.join("; "));
+    }
+    if imported.exists && !force && !dry_run {
+        bail!(
+            "A {} blueprint '{}' is already installed (pass --force to replace it)",
+            imported.blueprint.metadata.domain,
+            imported.suggested_filename
+        );
+    }
+
+    let mut result = describe(&imported, url);
+    if !dry_run {
+        let metadata = &imported.blueprint.metadata;
+        confirm(
+            &format!("Install {} blueprint '{}'?", metadata.domain, metadata.name),
+            &summary_lines(&result),
+            yes,
+        )?;
+        ws.save_blueprint(&imported, url, force).await?;
+        result.status = "installed";
+    }
+
+    output_for_format(ctx, &result, || {
+        match result.status {
+            "installed" => println!(
+                "Installed {} blueprint '{}' as {}",
+                result.domain, result.name, result.path
+            ),
+            _ => {
+                println!(
+                    "Would install {} blueprint '{}'",
+                    result.domain, result.name
+                );
+                for line in summary_lines(&result) {
+                    println!("  {line}");
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
+fn domain_name(domain: BlueprintDomain) -> &'static str {
+    match domain {
+        BlueprintDomain::Automation => "automation",
+        BlueprintDomain::Script => "script",
+    }
+}
+
+fn describe<'a>(imported: &'a ImportedBlueprint, url: &'a str) -> ImportResult<'a> {
+    let metadata = &imported.blueprint.metadata;
+    ImportResult {
+        name: &metadata.name,
+        domain: &metadata.domain,
+        path: &imported.suggested_filename,
+        source_url: url,
+        inputs: metadata.input.keys().map(String::as_str).collect(),
+        status: "would install",
+    }
+}
+
+/// File name and inputs of a blueprint about to be installed
+fn summary_lines(result: &ImportResult) -> Vec<String> {
+    let inputs = match result.inputs.as_slice() {
+        [] => "none".to_string(),
+        inputs => inputs.join(", "),
+    };
+    vec![
+        format!("file: {}", result.path),
+        format!("inputs: {inputs}"),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_describe_import() {
+        let imported: ImportedBlueprint = serde_json::from_value(json!({
+            "suggested_filename": "homeassistant/motion_light",
+            "raw_data": "blueprint: ...",
+            "blueprint": {"metadata": {
+                "name": "Motion-activated Light",
+                "domain": "automation",
+                "input": {"motion_entity": {}, "light_target": {}}
+            }},
+            "validation_errors": null
+        }))
+        .unwrap();
+
+        let result = describe(&imported, "https://example.com/motion_light.yaml");
+        assert_eq!(result.name, "Motion-activated Light");
+        assert!(!imported.exists);
+        assert_eq!(
+            summary_lines(&result),
+            vec![
+                "file: homeassistant/motion_light",
+                "inputs: light_target, motion_entity"
+            ]
+        );
+    }
+}
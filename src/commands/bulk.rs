@@ -0,0 +1,368 @@
+//! Bulk command implementations
+//!
+//! `bulk service` calls one service per entity listed in a file, pausing
+//! `--delay` between calls so large batches don't flood the server. With
+//! `--rollback-on-error` the targets are first captured in a temporary
+//! snapshot scene (`scene.create` with `snapshot_entities`); when a call
+//! fails, the remaining calls are skipped and the scene is applied to put
+//! every target back, so the batch either fully happens or not at all.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tabled::Tabled;
+
+use crate::api::HassClient;
+use crate::cli::BulkCommand;
+use crate::config::RuntimeContext;
+use crate::output::{output_for_format, parse_json_input, print_table, read_stdin, warn};
+use crate::progress;
+use crate::prompt::confirm_protected;
+use crate::validate;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Outcome {
+    /// Every call succeeded
+    Committed,
+    /// A call failed and the targets were restored
+    RolledBack,
+    /// A call failed and restoring the targets failed too
+    RollbackFailed,
+    /// Some calls failed; the others were kept
+    Partial,
+    DryRun,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum CallStatus {
+    Planned,
+    Ok,
+    Failed,
+    Skipped,
+    RolledBack,
+}
+
+impl CallStatus {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Planned => "planned",
+            Self::Ok => "ok",
+            Self::Failed => "failed",
+            Self::Skipped => "skipped",
+            Self::RolledBack => "rolled back",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct BulkCall {
+    entity_id: String,
+    /// State before the batch started (None for unknown entities)
+    before: Option<String>,
+    status: CallStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct BulkSummary {
+    service: String,
+    outcome: Outcome,
+    calls: Vec<BulkCall>,
+}
+
+#[derive(Debug, Serialize, Tabled)]
+struct BulkRow {
+    entity_id: String,
+    before: String,
+    result: String,
+}
+
+/// What a `bulk service` run will do
+struct BulkPlan {
+    service: String,
+    targets: Vec<String>,
+    data: Value,
+    delay: Duration,
+    rollback: bool,
+}
+
+pub async fn run(ctx: &RuntimeContext, command: BulkCommand) -> Result<()> {
+    match command {
+        BulkCommand::Service {
+            service,
+            targets_from,
+            data,
+            delay,
+            rollback_on_error,
+            dry_run,
+            yes,
+        } => {
+            let data = match data {
+                Some(input) => parse_json_input(&input).context("parsing --data")?,
+                None => json!({}),
+            };
+            if !data.is_object() {
+                bail!("Service data must be a JSON object");
+            }
+            let plan = BulkPlan {
+                service,
+                targets: read_targets(&targets_from)?,
+                data,
+                delay,
+                rollback: rollback_on_error,
+            };
+            call_each(ctx, &plan, dry_run, yes).await
+        }
+    }
+}
+
+async fn call_each(ctx: &RuntimeContext, plan: &BulkPlan, dry_run: bool, yes: bool) -> Result<()> {
+    let BulkPlan {
+        service,
+        targets,
+        data,
+        delay,
+        rollback,
+    } = plan;
+    let (domain, service_name) = validate::service(ctx, service).await?;
+    validate::entity_ids(ctx, targets).await?;
+    confirm_protected(service, targets, yes)?;
+
+    let client = HassClient::new(ctx)?;
+    let before: HashMap<String, String> = client
+        .get_states()
+        .await?
+        .into_iter()
+        .filter(|s| targets.contains(&s.entity_id))
+        .map(|s| (s.entity_id, s.state))
+        .collect();
+
+    let mut summary = BulkSummary {
+        service: service.to_string(),
+        outcome: Outcome::DryRun,
+        calls: targets
+            .iter()
+            .map(|id| BulkCall {
+                entity_id: id.clone(),
+                before: before.get(id).cloned(),
+                status: CallStatus::Planned,
+                error: None,
+            })
+            .collect(),
+    };
+    if dry_run {
+        return report(ctx, &summary, *delay);
+    }
+
+    let snapshot = if *rollback {
+        Some(create_snapshot(&client, targets).await?)
+    } else {
+        None
+    };
+
+    let mut failed = false;
+    let progress = progress::bar(
+        ctx,
+        summary.calls.len() as u64,
+        format!("{domain}.{service_name}"),
+    );
+    for (idx, call) in summary.calls.iter_mut().enumerate() {
+        progress.inc(1);
+        if failed && snapshot.is_some() {
+            call.status = CallStatus::Skipped;
+            continue;
+        }
+        if idx > 0 && !delay.is_zero() {
+            tokio::time::sleep(*delay).await;
+        }
+
+        let mut call_data = data.clone();
+        call_data["entity_id"] = json!(call.entity_id);
+        match client.call_service(domain, service_name, &call_data).await {
+            Ok(_) => call.status = CallStatus::Ok,
+            Err(e) => {
+                call.status = CallStatus::Failed;
+                call.error = Some(format!("{e:#}"));
+                failed = true;
+            }
+        }
+    }
+
+    progress.finish_and_clear();
+
+    summary.outcome = match (&snapshot, failed) {
+        (_, false) => Outcome::Committed,
+        (None, true) => Outcome::Partial,
+        (Some(scene), true) => {
+            let restore = client
+                .call_service("scene", "turn_on", &json!({ "entity_id": scene }))
+                .await;
+            match restore {
+                Ok(_) => {
+                    for call in &mut summary.calls {
+                        if call.status == CallStatus::Ok {
+                            call.status = CallStatus::RolledBack;
+                        }
+                    }
+                    Outcome::RolledBack
+                }
+                Err(e) => {
+                    warn(ctx, format!("Rollback failed: {e:#}"));
+                    Outcome::RollbackFailed
+                }
+            }
+        }
+    };
+
+    if let Some(scene) = &snapshot {
+        // Snapshot scenes live until Home Assistant restarts unless removed
+        let removed = client
+            .call_service("scene", "delete", &json!({ "entity_id": scene }))
+            .await;
+        if let Err(e) = removed {
+            log::debug!("Could not delete snapshot scene {scene}: {e:#}");
+        }
+    }
+
+    report(ctx, &summary, *delay)?;
+
+    let failures = summary
+        .calls
+        .iter()
+        .filter(|c| c.status == CallStatus::Failed)
+        .count();
+    if failures > 0 {
+        bail!(
+            "{service} failed on {failures} of {} entities",
+            targets.len()
+        );
+    }
+    Ok(())
+}
+
+/// Capture the current state of `targets` in a scene, returning its entity ID
+async fn create_snapshot(client: &HassClient, targets: &[String]) -> Result<String> {
+    let scene_id = format!(
+        "hmr_rollback_{}_{}",
+        std::process::id(),
+        chrono::Utc::now().timestamp()
+    );
+    client
+        .call_service(
+            "scene",
+            "create",
+            &json!({ "scene_id": scene_id, "snapshot_entities": targets }),
+        )
+        .await
+        .context("snapshotting targets for rollback")?;
+    Ok(format!("scene.{scene_id}"))
+}
+
+fn report(ctx: &RuntimeContext, summary: &BulkSummary, delay: Duration) -> Result<()> {
+    output_for_format(ctx, summary, || {
+        let rows: Vec<BulkRow> = summary
+            .calls
+            .iter()
+            .map(|call| BulkRow {
+                entity_id: call.entity_id.clone(),
+                before: call.before.clone().unwrap_or_else(|| "missing".to_string()),
+                result: match &call.error {
+                    Some(error) => format!("failed: {error}"),
+                    None => call.status.label().to_string(),
+                },
+            })
+            .collect();
+        print_table(ctx, &rows)?;
+        println!();
+
+        let total = summary.calls.len();
+        let count = |status| summary.calls.iter().filter(|c| c.status == status).count();
+        match summary.outcome {
+            Outcome::DryRun => println!(
+                "Would call {} on {total} entities, {} apart",
+                summary.service,
+                humantime::format_duration(delay)
+            ),
+            Outcome::Committed => println!(
+                "Committed: {} succeeded on all {total} entities",
+                summary.service
+            ),
+            Outcome::RolledBack => println!(
+                "Rolled back: {} changed entities restored, {} skipped",
+                count(CallStatus::RolledBack),
+                count(CallStatus::Skipped)
+            ),
+            Outcome::RollbackFailed => println!(
+                "Rollback failed: {} entities may be left changed",
+                count(CallStatus::Ok)
+            ),
+            Outcome::Partial => println!(
+                "Partial: {} of {total} calls succeeded and were kept",
+                count(CallStatus::Ok)
+            ),
+        }
+        Ok(())
+    })
+}
+
+/// Entity IDs from a targets file ("-" reads stdin)
+fn read_targets(path: &Path) -> Result<Vec<String>> {
+    let text = if path == Path::new("-") {
+        read_stdin()?.unwrap_or_default()
+    } else {
+        fs::read_to_string(path)
+            .with_context(|| format!("reading targets from {}", path.display()))?
+    };
+    let targets = parse_targets(&text)?;
+    if targets.is_empty() {
+        bail!("No targets in {}", path.display());
+    }
+    Ok(targets)
+}
+
+/// One entity ID per line; blank lines and `#` comments are skipped and
+/// duplicates are called once
+fn parse_targets(text: &str) -> Result<Vec<String>> {
+    let mut targets: Vec<String> = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        let target = line.split('#').next().unwrap_or("").trim();
+        if target.is_empty() {
+            continue;
+        }
+        let valid = target
+            .split_once('.')
+            .is_some_and(|(domain, object)| !domain.is_empty() && !object.is_empty());
+        if !valid {
+            bail!("Line {}: '{target}' is not an entity ID", idx + 1);
+        }
+        if !targets.iter().any(|t| t == target) {
+            targets.push(target.to_string());
+        }
+    }
+    Ok(targets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_targets() {
+        let text = "# porch lights\nlight.porch\n\nlight.garden  # back\nlight.porch\n";
+        assert_eq!(
+            parse_targets(text).unwrap(),
+            vec!["light.porch".to_string(), "light.garden".to_string()]
+        );
+
+        let err = parse_targets("light.porch\nkitchen\n").unwrap_err();
+        assert!(err.to_string().contains("Line 2"));
+    }
+}
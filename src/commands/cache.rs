@@ -1,12 +1,13 @@
 //! Cache management commands
 
-use anyhow::Result;
-use tabled::{Table, Tabled};
+use anyhow::{anyhow, Result};
+use tabled::Tabled;
 
-use crate::cache::{cache_dir, cache_status, clear_cache, CacheManager};
+use crate::cache::{cache_dir, cache_status, clear_cache, CacheManager, EntityContext};
 use crate::cli::{CacheCommand, OutputFormat};
 use crate::config::RuntimeContext;
-use crate::output::print_output;
+use crate::output::{print_output, render_rows, warn};
+use crate::websocket::WsClient;
 
 /// Execute cache commands
 pub async fn execute(ctx: &RuntimeContext, command: CacheCommand) -> Result<()> {
@@ -18,8 +19,16 @@ pub async fn execute(ctx: &RuntimeContext, command: CacheCommand) -> Result<()>
             areas,
             services,
             devices,
-        } => refresh(ctx, all, entities, areas, services, devices).await,
-        CacheCommand::Clear => clear(ctx),
+            labels,
+            watch,
+        } => {
+            refresh(ctx, all, entities, areas, services, devices, labels).await?;
+            if watch {
+                watch_registries(ctx).await?;
+            }
+            Ok(())
+        }
+        CacheCommand::Clear { yes } => clear(ctx, yes),
         CacheCommand::Path => path(ctx),
         CacheCommand::EntityInfo { entity_id } => entity_info(ctx, &entity_id).await,
         CacheCommand::AreaInfo { area } => area_info(ctx, &area).await,
@@ -51,21 +60,20 @@ async fn status(ctx: &RuntimeContext) -> Result<()> {
                 || cache.has_areas()
                 || cache.has_services()
                 || cache.has_devices()
+                || cache.has_labels()
             {
+                let mark = |ok: bool| match (ctx.global.plain, ok) {
+                    (true, true) => "yes",
+                    (true, false) => "no",
+                    (false, true) => "✓",
+                    (false, false) => "✗",
+                };
                 println!("Cache availability:");
-                println!(
-                    "  Entities:  {}",
-                    if cache.has_entities() { "✓" } else { "✗" }
-                );
-                println!("  Areas:     {}", if cache.has_areas() { "✓" } else { "✗" });
-                println!(
-                    "  Services:  {}",
-                    if cache.has_services() { "✓" } else { "✗" }
-                );
-                println!(
-                    "  Devices:   {}",
-                    if cache.has_devices() { "✓" } else { "✗" }
-                );
+                println!("  Entities:  {}", mark(cache.has_entities()));
+                println!("  Areas:     {}", mark(cache.has_areas()));
+                println!("  Services:  {}", mark(cache.has_services()));
+                println!("  Devices:   {}", mark(cache.has_devices()));
+                println!("  Labels:    {}", mark(cache.has_labels()));
                 println!();
             }
 
@@ -203,7 +211,36 @@ async fn status(ctx: &RuntimeContext) -> Result<()> {
                 });
             }
 
-            let table = Table::new(rows).to_string();
+            // Labels
+            if let Some(ref l) = status.labels {
+                let is_current_server = l.server_url == server_url;
+                rows.push(CacheRow {
+                    cache_type: "Labels".to_string(),
+                    items: format_count(&status, "labels"),
+                    size: format_bytes(l.size_bytes),
+                    age: format_duration(l.age_secs),
+                    expires: l
+                        .expires_in_secs
+                        .map(format_duration)
+                        .unwrap_or_else(|| "expired".to_string()),
+                    status: if is_current_server {
+                        "valid".to_string()
+                    } else {
+                        "different server".to_string()
+                    },
+                });
+            } else {
+                rows.push(CacheRow {
+                    cache_type: "Labels".to_string(),
+                    items: "-".to_string(),
+                    size: "-".to_string(),
+                    age: "-".to_string(),
+                    expires: "-".to_string(),
+                    status: "not cached".to_string(),
+                });
+            }
+
+            let table = render_rows(ctx, &rows);
             println!("{table}");
             println!();
             println!("Total size: {}", format_bytes(status.total_size_bytes));
@@ -220,19 +257,18 @@ async fn refresh(
     areas: bool,
     services: bool,
     devices: bool,
+    labels: bool,
 ) -> Result<()> {
     let mut manager = CacheManager::new(ctx)?;
 
     // If no specific flags, refresh all
-    let refresh_all = all || (!entities && !areas && !services && !devices);
+    let refresh_all = all || (!entities && !areas && !services && !devices && !labels);
 
     if refresh_all {
-        println!("Refreshing all caches...");
         manager.refresh_all().await?;
         println!("All caches refreshed.");
     } else {
         if entities {
-            println!("Refreshing entities...");
             manager.refresh_entities().await?;
             println!(
                 "Entities refreshed: {} items",
@@ -240,12 +276,10 @@ async fn refresh(
             );
         }
         if areas {
-            println!("Refreshing areas...");
             manager.refresh_areas().await?;
             println!("Areas refreshed: {} items", manager.cache().areas().len());
         }
         if services {
-            println!("Refreshing services...");
             manager.refresh_services().await?;
             println!(
                 "Services refreshed: {} items",
@@ -253,19 +287,67 @@ async fn refresh(
             );
         }
         if devices {
-            println!("Refreshing devices...");
             manager.refresh_devices().await?;
             println!(
                 "Devices refreshed: {} items",
                 manager.cache().devices().len()
             );
         }
+        if labels {
+            manager.refresh_labels().await?;
+            println!("Labels refreshed: {} items", manager.cache().labels().len());
+        }
+    }
+
+    Ok(())
+}
+
+/// Keep the cache files current by applying registry events until Ctrl+C
+async fn watch_registries(ctx: &RuntimeContext) -> Result<()> {
+    let mut manager = CacheManager::new(ctx)?;
+    let events = WsClient::connect(ctx).await?;
+    let mut entities = events
+        .subscribe_events(Some("entity_registry_updated"))
+        .await?;
+    let mut areas = events
+        .subscribe_events(Some("area_registry_updated"))
+        .await?;
+    let mut devices = events
+        .subscribe_events(Some("device_registry_updated"))
+        .await?;
+    let mut labels = events
+        .subscribe_events(Some("label_registry_updated"))
+        .await?;
+    let mut floors = events
+        .subscribe_events(Some("floor_registry_updated"))
+        .await?;
+    // Registry fetches get their own connection, kept for the whole watch: on
+    // the event connection their replies could queue behind a burst of events
+    let ws = WsClient::connect(ctx).await?;
+
+    println!("Watching registry changes (Ctrl+C to stop)...");
+    loop {
+        let event = tokio::select! {
+            event = entities.next_event() => event?,
+            event = areas.next_event() => event?,
+            event = devices.next_event() => event?,
+            event = labels.next_event() => event?,
+            event = floors.next_event() => event?,
+            _ = tokio::signal::ctrl_c() => break,
+        };
+        let event = event.ok_or_else(|| anyhow!("WebSocket connection closed"))?;
+
+        match manager.apply_registry_event(&ws, &event).await {
+            Ok(Some(change)) => println!("{} {change}", chrono::Local::now().format("%H:%M:%S")),
+            Ok(None) => {}
+            Err(e) => warn(ctx, format!("Could not apply {}: {e:#}", event.event_type)),
+        }
     }
 
     Ok(())
 }
 
-fn clear(ctx: &RuntimeContext) -> Result<()> {
+fn clear(ctx: &RuntimeContext, yes: bool) -> Result<()> {
     let dir = cache_dir()?;
 
     if !dir.exists() {
@@ -275,6 +357,17 @@ fn clear(ctx: &RuntimeContext) -> Result<()> {
         return Ok(());
     }
 
+    let mut files: Vec<String> = std::fs::read_dir(&dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path().display().to_string())
+        .collect();
+    files.sort();
+    crate::prompt::confirm(
+        &format!("Remove cache directory {}?", dir.display()),
+        &files,
+        yes,
+    )?;
+
     clear_cache()?;
 
     if !ctx.global.quiet {
@@ -284,7 +377,7 @@ fn clear(ctx: &RuntimeContext) -> Result<()> {
     Ok(())
 }
 
-fn path(ctx: &RuntimeContext) -> Result<()> {
+pub fn path(ctx: &RuntimeContext) -> Result<()> {
     let dir = cache_dir()?;
 
     match ctx.output_format() {
@@ -305,7 +398,8 @@ fn path(ctx: &RuntimeContext) -> Result<()> {
     Ok(())
 }
 
-fn format_bytes(bytes: u64) -> String {
+/// "512 B", "1.5 KB", "2.3 MB"
+pub fn format_bytes(bytes: u64) -> String {
     if bytes < 1024 {
         format!("{bytes} B")
     } else if bytes < 1024 * 1024 {
@@ -351,6 +445,11 @@ fn format_count(status: &crate::cache::CacheStatus, cache_type: &str) -> String
             .as_ref()
             .map(|_| "?".to_string())
             .unwrap_or("-".to_string()),
+        "labels" => status
+            .labels
+            .as_ref()
+            .map(|_| "?".to_string())
+            .unwrap_or("-".to_string()),
         _ => "-".to_string(),
     }
 }
@@ -368,29 +467,17 @@ async fn entity_info(ctx: &RuntimeContext, entity_id: &str) -> Result<()> {
         return Ok(());
     }
 
+    // Devices and areas enrich the card but are not required
+    if let Err(e) = manager.ensure_devices().await {
+        log::debug!("Device cache unavailable: {e:#}");
+    }
+    if let Err(e) = manager.ensure_areas().await {
+        log::debug!("Area cache unavailable: {e:#}");
+    }
+
     // Try direct lookup first (exact match by ID)
     if let Some(entity) = manager.cache().get_entity(entity_id) {
-        match ctx.output_format() {
-            OutputFormat::Json => {
-                print_output(ctx, entity)?;
-            }
-            OutputFormat::Yaml => {
-                println!("{}", serde_yaml::to_string(entity)?);
-            }
-            _ => {
-                println!("Entity ID: {}", entity.entity_id);
-                println!("Domain: {}", entity.domain);
-                println!("Object ID: {}", entity.object_id);
-                println!("State: {}", entity.state);
-                if let Some(ref name) = entity.friendly_name {
-                    println!("Friendly Name: {name}");
-                }
-                if let Some(ref area_id) = entity.area_id {
-                    println!("Area ID: {area_id}");
-                }
-            }
-        }
-        return Ok(());
+        return print_entity_context(ctx, &manager.cache().entity_context(entity));
     }
 
     // Fall back to fuzzy matching
@@ -401,34 +488,21 @@ async fn entity_info(ctx: &RuntimeContext, entity_id: &str) -> Result<()> {
     match result {
         crate::fuzzy::MatchResult::Single(m) => {
             // Show correction if it was a fuzzy/typo match
-            if !is_exact && !ctx.global.quiet {
-                println!("Matched: {}", format_correction(entity_id, &m.matched_on));
-                println!();
+            if !is_exact {
+                warn(
+                    ctx,
+                    format!("Matched: {}", format_correction(entity_id, &m.matched_on)),
+                );
             }
 
-            match ctx.output_format() {
-                OutputFormat::Json => {
-                    print_output(ctx, &m.item)?;
-                }
-                OutputFormat::Yaml => {
-                    println!("{}", serde_yaml::to_string(&m.item)?);
-                }
-                _ => {
-                    println!("Entity ID: {}", m.item.entity_id);
-                    println!("Domain: {}", m.item.domain);
-                    println!("Object ID: {}", m.item.object_id);
-                    println!("State: {}", m.item.state);
-                    if let Some(ref name) = m.item.friendly_name {
-                        println!("Friendly Name: {name}");
-                    }
-                    if let Some(ref area_id) = m.item.area_id {
-                        println!("Area ID: {area_id}");
-                    }
-                }
-            }
+            print_entity_context(ctx, &manager.cache().entity_context(m.item))?;
         }
         crate::fuzzy::MatchResult::Multiple(matches) => {
-            println!("Multiple matches found:");
+            warn(ctx, format!("Multiple matches found for '{entity_id}'"));
+            if ctx.is_machine_output() {
+                let items: Vec<_> = matches.iter().map(|m| m.item).collect();
+                return print_output(ctx, &items);
+            }
             for (idx, m) in matches.iter().enumerate().take(10) {
                 let name = m.item.friendly_name.as_deref().unwrap_or(&m.item.entity_id);
                 println!(
@@ -451,6 +525,73 @@ async fn entity_info(ctx: &RuntimeContext, entity_id: &str) -> Result<()> {
     Ok(())
 }
 
+fn print_entity_context(ctx: &RuntimeContext, info: &EntityContext) -> Result<()> {
+    match ctx.output_format() {
+        OutputFormat::Json => print_output(ctx, info),
+        OutputFormat::Yaml => {
+            println!("{}", serde_yaml::to_string(info)?);
+            Ok(())
+        }
+        _ => {
+            let entity = &info.entity;
+            println!("Entity ID: {}", entity.entity_id);
+            println!("Domain: {}", entity.domain);
+            println!("Object ID: {}", entity.object_id);
+            println!("State: {}", entity.state);
+            if let Some(ref name) = entity.friendly_name {
+                println!("Friendly Name: {name}");
+            }
+            if let Some(ref device_class) = entity.device_class {
+                println!("Device Class: {device_class}");
+            }
+            if let Some(ref integration) = info.integration {
+                println!("Integration: {integration}");
+            }
+
+            if let Some(ref area) = info.area {
+                println!("Area: {} ({})", area.name, area.area_id);
+            } else if let Some(ref area_id) = entity.area_id {
+                println!("Area ID: {area_id}");
+            }
+
+            if let Some(ref device) = info.device {
+                println!();
+                println!("Device:");
+                let name = device
+                    .name_by_user
+                    .as_deref()
+                    .or(device.name.as_deref())
+                    .unwrap_or(&device.id);
+                println!("  Name: {name}");
+                println!("  ID: {}", device.id);
+                if let Some(ref manufacturer) = device.manufacturer {
+                    println!("  Manufacturer: {manufacturer}");
+                }
+                if let Some(ref model) = device.model {
+                    println!("  Model: {model}");
+                }
+                if let Some(ref sw_version) = device.sw_version {
+                    println!("  Firmware: {sw_version}");
+                }
+                if let Some(ref hw_version) = device.hw_version {
+                    println!("  Hardware: {hw_version}");
+                }
+            } else if let Some(ref device_id) = entity.device_id {
+                println!("Device ID: {device_id}");
+            }
+
+            if !entity.attributes.is_empty() {
+                println!();
+                println!("Cached Attributes:");
+                for (name, value) in &entity.attributes {
+                    println!("  {name}: {value}");
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
 async fn area_info(ctx: &RuntimeContext, area: &str) -> Result<()> {
     use crate::fuzzy::{format_correction, FuzzyMatcher};
 
@@ -506,9 +647,11 @@ async fn area_info(ctx: &RuntimeContext, area: &str) -> Result<()> {
     match result {
         crate::fuzzy::MatchResult::Single(m) => {
             // Show correction if it was a fuzzy/typo match
-            if !is_exact && !ctx.global.quiet {
-                println!("Matched: {}", format_correction(area, &m.matched_on));
-                println!();
+            if !is_exact {
+                warn(
+                    ctx,
+                    format!("Matched: {}", format_correction(area, &m.matched_on)),
+                );
             }
 
             match ctx.output_format() {
@@ -542,7 +685,11 @@ async fn area_info(ctx: &RuntimeContext, area: &str) -> Result<()> {
             }
         }
         crate::fuzzy::MatchResult::Multiple(matches) => {
-            println!("Multiple matches found:");
+            warn(ctx, format!("Multiple matches found for '{area}'"));
+            if ctx.is_machine_output() {
+                let items: Vec<_> = matches.iter().map(|m| m.item).collect();
+                return print_output(ctx, &items);
+            }
             for (idx, m) in matches.iter().enumerate().take(10) {
                 println!(
                     "  {}. {} - {} ({:?})",
@@ -0,0 +1,189 @@
+//! Calendar command implementations
+//!
+//! Calendars come from `/api/calendars`; events from
+//! `/api/calendars/<entity_id>` for a window around now set by `--since`
+//! (how far back) and `--until` (how far ahead).
+
+use anyhow::Result;
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::Serialize;
+use tabled::Tabled;
+
+use crate::api::{CalendarEvent, CalendarTime, HassClient};
+use crate::cli::CalendarCommand;
+use crate::commands::quick::resolve_in;
+use crate::config::RuntimeContext;
+use crate::output::{output_for_format, print_table, warn};
+use crate::timespec::TimeSpec;
+
+#[derive(Debug, Serialize, Tabled)]
+struct CalendarRow {
+    entity_id: String,
+    name: String,
+}
+
+/// An event with the calendar it came from
+#[derive(Debug, Serialize)]
+struct CalendarEntry {
+    calendar: String,
+    #[serde(flatten)]
+    event: CalendarEvent,
+}
+
+#[derive(Debug, Serialize, Tabled)]
+struct EventRow {
+    start: String,
+    end: String,
+    summary: String,
+    calendar: String,
+    location: String,
+}
+
+pub async fn run(ctx: &RuntimeContext, command: CalendarCommand) -> Result<()> {
+    match command {
+        CalendarCommand::List => list(ctx).await,
+        CalendarCommand::Events {
+            words,
+            since,
+            until,
+        } => events(ctx, words.join(" "), since, until).await,
+    }
+}
+
+async fn list(ctx: &RuntimeContext) -> Result<()> {
+    let client = HassClient::new(ctx)?;
+    let mut calendars = client.get_calendars().await?;
+    calendars.sort_by_key(|c| c.name.to_lowercase());
+
+    let rows: Vec<CalendarRow> = calendars
+        .iter()
+        .map(|c| CalendarRow {
+            entity_id: c.entity_id.clone(),
+            name: c.name.clone(),
+        })
+        .collect();
+
+    if rows.is_empty() && !ctx.is_machine_output() {
+        println!("No calendars found");
+        return Ok(());
+    }
+    print_table(ctx, &rows)
+}
+
+async fn events(
+    ctx: &RuntimeContext,
+    name: String,
+    since: TimeSpec,
+    until: TimeSpec,
+) -> Result<()> {
+    let now = Local::now();
+    let start = since.before(now).with_timezone(&Utc).to_rfc3339();
+    let end = until.after(now).with_timezone(&Utc).to_rfc3339();
+
+    let client = HassClient::new(ctx)?;
+    let calendars = if name.is_empty() {
+        client
+            .get_calendars()
+            .await?
+            .into_iter()
+            .map(|c| c.entity_id)
+            .collect()
+    } else {
+        vec![resolve_in(ctx, "calendar", &["calendar"], Some(name)).await?]
+    };
+
+    let mut entries = Vec::new();
+    for calendar in calendars {
+        match client.get_calendar_events(&calendar, &start, &end).await {
+            Ok(events) => entries.extend(events.into_iter().map(|event| CalendarEntry {
+                calendar: calendar.clone(),
+                event,
+            })),
+            Err(e) => warn(ctx, format!("Skipping {calendar}: {e:#}")),
+        }
+    }
+    entries.sort_by_key(|entry| sort_key(&entry.event.start));
+
+    output_for_format(ctx, &entries, || {
+        if entries.is_empty() {
+            println!("No events between {} and {}", local(&start), local(&end));
+            return Ok(());
+        }
+        let rows: Vec<EventRow> = entries
+            .iter()
+            .map(|entry| EventRow {
+                start: format_time(&entry.event.start),
+                end: format_end(&entry.event.end),
+                summary: entry.event.summary.clone(),
+                calendar: entry.calendar.clone(),
+                location: entry.event.location.clone().unwrap_or_default(),
+            })
+            .collect();
+        print_table(ctx, &rows)
+    })
+}
+
+/// UTC instant an event boundary refers to (all-day dates at local midnight)
+fn sort_key(time: &CalendarTime) -> Option<DateTime<Utc>> {
+    if let Some(date_time) = &time.date_time {
+        return DateTime::parse_from_rfc3339(date_time)
+            .ok()
+            .map(|dt| dt.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(time.date.as_deref()?, "%Y-%m-%d").ok()?;
+    date.and_hms_opt(0, 0, 0)?
+        .and_local_timezone(Local)
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+fn format_time(time: &CalendarTime) -> String {
+    match (&time.date_time, &time.date) {
+        (Some(date_time), _) => local(date_time),
+        (None, Some(date)) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map(|d| format!("{} (all day)", d.format("%a %m-%d")))
+            .unwrap_or_else(|_| date.clone()),
+        (None, None) => String::new(),
+    }
+}
+
+/// All-day events end on the (exclusive) next day, which isn't worth showing
+fn format_end(time: &CalendarTime) -> String {
+    match &time.date_time {
+        Some(date_time) => local(date_time),
+        None => String::new(),
+    }
+}
+
+fn local(timestamp: &str) -> String {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|dt| {
+            dt.with_timezone(&Local)
+                .format("%a %m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|_| timestamp.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(date: &str) -> CalendarTime {
+        CalendarTime {
+            date_time: None,
+            date: Some(date.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_calendar_times() {
+        let timed = CalendarTime {
+            date_time: Some("2025-01-15T09:00:00+00:00".to_string()),
+            date: None,
+        };
+        assert!(sort_key(&date("2025-01-14")) < sort_key(&timed));
+        assert_eq!(format_time(&date("2025-01-15")), "Wed 01-15 (all day)");
+        assert_eq!(format_end(&date("2025-01-16")), "");
+    }
+}
@@ -0,0 +1,147 @@
+//! Camera command implementations
+//!
+//! `camera snapshot` downloads the current image from
+//! `/api/camera_proxy/<entity_id>` and writes it to a file (or stdout). The
+//! file extension follows the image type the camera returned. The file is a
+//! positional argument because `-o` already selects the output format.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use chrono::Local;
+use serde::Serialize;
+
+use crate::api::HassClient;
+use crate::cli::CameraCommand;
+use crate::commands::cache::format_bytes;
+use crate::commands::quick::resolve_in;
+use crate::config::RuntimeContext;
+use crate::output::{print_output, warn};
+use crate::progress;
+
+#[derive(Debug, Serialize)]
+struct SnapshotResult {
+    entity_id: String,
+    path: String,
+    content_type: Option<String>,
+    bytes: usize,
+}
+
+pub async fn run(ctx: &RuntimeContext, command: CameraCommand) -> Result<()> {
+    match command {
+        CameraCommand::Snapshot { camera, file } => snapshot(ctx, camera, file.as_deref()).await,
+    }
+}
+
+async fn snapshot(ctx: &RuntimeContext, name: String, output: Option<&Path>) -> Result<()> {
+    let entity_id = resolve_in(ctx, "camera", &["camera"], Some(name)).await?;
+    let to_stdout = output == Some(Path::new("-"));
+
+    let client = HassClient::new(ctx)?;
+    // The length is only known once the response headers are in
+    let progress = progress::bar(ctx, 0, "Downloading snapshot");
+    let download = client
+        .get_camera_image(&entity_id, |received, total| {
+            if let Some(total) = total {
+                progress.set_length(total);
+            }
+            progress.set_position(received);
+        })
+        .await?;
+    progress.finish_and_clear();
+
+    let content_type = download.content_type.as_deref();
+    if !content_type.is_some_and(|t| t.starts_with("image/")) {
+        bail!(
+            "{entity_id} returned {} instead of an image",
+            content_type.unwrap_or("an untyped response")
+        );
+    }
+
+    if to_stdout {
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(&download.bytes)?;
+        return stdout.flush().context("writing image to stdout");
+    }
+
+    let extension = extension_for(content_type.unwrap_or_default());
+    let path = match output {
+        Some(path) => {
+            let given = path.extension().and_then(|e| e.to_str());
+            if given.is_some_and(|e| !same_extension(e, extension)) {
+                warn(
+                    ctx,
+                    format!(
+                        "{entity_id} sent a .{extension} image; saving it as {}",
+                        path.display()
+                    ),
+                );
+            }
+            path.to_path_buf()
+        }
+        None => default_path(&entity_id, extension),
+    };
+    fs::write(&path, &download.bytes)
+        .with_context(|| format!("writing image to {}", path.display()))?;
+
+    let result = SnapshotResult {
+        entity_id,
+        path: path.display().to_string(),
+        content_type: download.content_type.clone(),
+        bytes: download.bytes.len(),
+    };
+    if ctx.is_machine_output() {
+        return print_output(ctx, &result);
+    }
+    println!(
+        "Saved {} ({}) to {}",
+        result.entity_id,
+        format_bytes(result.bytes as u64),
+        result.path
+    );
+    Ok(())
+}
+
+/// File extension for an image MIME type
+fn extension_for(content_type: &str) -> &str {
+    match content_type {
+        "image/jpeg" | "image/jpg" => "jpg",
+        "image/svg+xml" => "svg",
+        other => other
+            .strip_prefix("image/")
+            .filter(|e| !e.is_empty())
+            .unwrap_or("img"),
+    }
+}
+
+fn same_extension(given: &str, expected: &str) -> bool {
+    let normalize = |e: &str| match e.to_lowercase().as_str() {
+        "jpeg" => "jpg".to_string(),
+        other => other.to_string(),
+    };
+    normalize(given) == normalize(expected)
+}
+
+fn default_path(entity_id: &str, extension: &str) -> PathBuf {
+    let object_id = entity_id.split_once('.').map_or(entity_id, |(_, o)| o);
+    PathBuf::from(format!(
+        "{object_id}-{}.{extension}",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extension_for() {
+        assert_eq!(extension_for("image/jpeg"), "jpg");
+        assert_eq!(extension_for("image/png"), "png");
+        assert_eq!(extension_for("image/svg+xml"), "svg");
+        assert!(same_extension("JPEG", extension_for("image/jpeg")));
+        assert!(!same_extension("png", extension_for("image/jpeg")));
+    }
+}
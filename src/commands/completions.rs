@@ -1,15 +1,32 @@
 //! Shell completions command
 
-use std::io;
-
 use anyhow::Result;
-use clap::CommandFactory;
-use clap_complete::Shell;
 
-use crate::cli::Cli;
+use crate::cli::CompletionShell;
+use crate::features;
 
-pub fn run(shell: Shell) -> Result<()> {
-    let mut cmd = Cli::command();
-    clap_complete::generate(shell, &mut cmd, "hmr", &mut io::stdout());
+pub fn run(shell: CompletionShell) -> Result<()> {
+    features::require("completions")?;
+    generate(shell);
     Ok(())
 }
+
+#[cfg(feature = "completions")]
+fn generate(shell: CompletionShell) {
+    use clap::CommandFactory;
+    use clap_complete::Shell;
+
+    let shell = match shell {
+        CompletionShell::Bash => Shell::Bash,
+        CompletionShell::Elvish => Shell::Elvish,
+        CompletionShell::Fish => Shell::Fish,
+        CompletionShell::PowerShell => Shell::PowerShell,
+        CompletionShell::Zsh => Shell::Zsh,
+    };
+    let mut cmd = crate::cli::Cli::command();
+    clap_complete::generate(shell, &mut cmd, "hmr", &mut std::io::stdout());
+}
+
+/// Unreachable: `run` stops at the feature check first
+#[cfg(not(feature = "completions"))]
+fn generate(_shell: CompletionShell) {}
@@ -2,16 +2,20 @@
 
 use anyhow::Result;
 
-use crate::cli::ConfigCommand;
+use crate::cli::{ConfigCommand, OutputFormat};
 use crate::config::{self as app_config, RuntimeContext};
 use crate::output::print_output;
 
+/// JSON Schema of the config file, kept next to the example config
+const SCHEMA: &str = include_str!("../../examples/config.schema.json");
+
 pub fn run(ctx: &RuntimeContext, command: ConfigCommand) -> Result<()> {
     match command {
         ConfigCommand::Show => show(ctx),
         ConfigCommand::Path => path(ctx),
         ConfigCommand::Get { key } => get(ctx, key.as_deref()),
-        ConfigCommand::Reset => reset(ctx),
+        ConfigCommand::Reset { yes } => reset(ctx, yes),
+        ConfigCommand::Schema => schema(ctx),
     }
 }
 
@@ -37,8 +41,23 @@ fn get(ctx: &RuntimeContext, key: Option<&str>) -> Result<()> {
     Ok(())
 }
 
-fn reset(ctx: &RuntimeContext) -> Result<()> {
-    app_config::write_default_config(ctx.config_path())?;
+fn reset(ctx: &RuntimeContext, yes: bool) -> Result<()> {
+    let path = ctx.config_path();
+    if path.exists() {
+        let mut affected = vec![format!("{} will be overwritten", path.display())];
+        if ctx.config.homeassistant.token.is_some() {
+            affected.push("the stored access token will be removed".to_string());
+        }
+        let profiles = ctx.config.homeassistant.tokens.len();
+        if profiles > 0 {
+            affected.push(format!(
+                "{profiles} stored profile token(s) will be removed"
+            ));
+        }
+        crate::prompt::confirm("Reset configuration to defaults?", &affected, yes)?;
+    }
+
+    app_config::write_default_config(path)?;
     println!(
         "Configuration reset to defaults at: {}",
         ctx.config_path().display()
@@ -46,6 +65,21 @@ fn reset(ctx: &RuntimeContext) -> Result<()> {
     Ok(())
 }
 
+/// Print the schema as written, keeping its key order for editors; YAML
+/// output converts it
+fn schema(ctx: &RuntimeContext) -> Result<()> {
+    match ctx.output_format() {
+        OutputFormat::Yaml => {
+            let schema: serde_json::Value = serde_json::from_str(SCHEMA)?;
+            print_output(ctx, &schema)
+        }
+        _ => {
+            println!("{}", SCHEMA.trim_end());
+            Ok(())
+        }
+    }
+}
+
 fn get_config_value(config: &app_config::AppConfig, key: &str) -> Result<String> {
     // Convert config to JSON for easy traversal
     let json = serde_json::to_value(config)?;
@@ -85,4 +119,33 @@ mod tests {
         );
         assert!(get_config_value(&config, "nonexistent.key").is_err());
     }
+
+    /// Every key the config structs know is described in the schema
+    #[test]
+    fn test_schema_covers_config() {
+        fn missing(
+            value: &serde_json::Value,
+            schema: &serde_json::Value,
+            path: &str,
+        ) -> Vec<String> {
+            let Some(fields) = value.as_object() else {
+                return Vec::new();
+            };
+            fields
+                .iter()
+                .flat_map(|(key, value)| {
+                    let path = format!("{path}{key}");
+                    match schema["properties"].get(key) {
+                        Some(schema) => missing(value, schema, &format!("{path}.")),
+                        None if schema["additionalProperties"].is_object() => Vec::new(),
+                        None => vec![path],
+                    }
+                })
+                .collect()
+        }
+
+        let config = serde_json::to_value(AppConfig::default()).unwrap();
+        let schema: serde_json::Value = serde_json::from_str(SCHEMA).unwrap();
+        assert_eq!(missing(&config, &schema, ""), Vec::<String>::new());
+    }
 }
@@ -0,0 +1,168 @@
+//! Core command implementations
+//!
+//! `core restart` and `core stop` call the `homeassistant.restart` and
+//! `homeassistant.stop` services; `core check-config` asks the server to
+//! validate its YAML configuration and lists the errors it found. Each asks
+//! for confirmation unless `--yes` is given.
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::api::{ConfigCheck, HassClient};
+use crate::cli::CoreCommand;
+use crate::config::RuntimeContext;
+use crate::error::{self, Error};
+use crate::output::output_for_format;
+use crate::prompt::confirm;
+
+#[derive(Debug, Serialize)]
+struct LifecycleResult {
+    service: String,
+    dry_run: bool,
+}
+
+pub async fn run(ctx: &RuntimeContext, command: CoreCommand) -> Result<()> {
+    match command {
+        CoreCommand::Restart { yes, dry_run } => lifecycle(ctx, "restart", yes, dry_run).await,
+        CoreCommand::Stop { yes, dry_run } => lifecycle(ctx, "stop", yes, dry_run).await,
+        CoreCommand::CheckConfig { yes } => check_config(ctx, yes).await,
+    }
+}
+
+async fn lifecycle(ctx: &RuntimeContext, service: &str, yes: bool, dry_run: bool) -> Result<()> {
+    let result = LifecycleResult {
+        service: format!("homeassistant.{service}"),
+        dry_run,
+    };
+    if dry_run {
+        return output_for_format(ctx, &result, || {
+            println!("Would call {}", result.service);
+            Ok(())
+        });
+    }
+
+    let warning = match service {
+        "stop" => "Home Assistant stays down until it is started on the host".to_string(),
+        _ => "Automations and connections pause until it is back".to_string(),
+    };
+    confirm(
+        &format!(
+            "{} Home Assistant at {}?",
+            capitalize(service),
+            ctx.server_url()?
+        ),
+        &[warning],
+        yes,
+    )?;
+
+    // The server may drop the connection before answering while it shuts down
+    let client = HassClient::new(ctx)?;
+    match client
+        .call_service("homeassistant", service, &json!({}))
+        .await
+    {
+        Ok(_) => {}
+        Err(e) if dropped_after_send(&e) => {
+            log::debug!("Connection closed during {service}: {e:#}");
+        }
+        Err(e) => return Err(e),
+    }
+
+    output_for_format(ctx, &result, || {
+        match service {
+            "stop" => println!("Home Assistant is stopping"),
+            _ => println!("Home Assistant is restarting"),
+        }
+        Ok(())
+    })
+}
+
+/// Whether the server dropped the connection after taking the request, as it
+/// does while shutting down; not reaching it at all or timing out still fails
+fn dropped_after_send(e: &anyhow::Error) -> bool {
+    let Some(Error::Network { source, .. }) = error::find(e) else {
+        return false;
+    };
+    source
+        .downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| !e.is_connect() && !e.is_timeout())
+}
+
+async fn check_config(ctx: &RuntimeContext, yes: bool) -> Result<()> {
+    confirm(
+        "Check the Home Assistant configuration?",
+        &["Loads every integration's YAML on the server, which can take a while".to_string()],
+        yes,
+    )?;
+
+    let check = HassClient::new(ctx)?.check_config().await?;
+    output_for_format(ctx, &check, || {
+        print!("{}", format_check(&check));
+        Ok(())
+    })?;
+
+    if check.result != "valid" {
+        bail!("Configuration is invalid");
+    }
+    Ok(())
+}
+
+/// The check result with one bullet per error and warning
+fn format_check(check: &ConfigCheck) -> String {
+    let mut out = if check.result == "valid" {
+        "Configuration is valid\n".to_string()
+    } else {
+        "Configuration is invalid\n".to_string()
+    };
+    for (title, messages) in [("Errors", &check.errors), ("Warnings", &check.warnings)] {
+        let lines: Vec<&str> = messages
+            .as_deref()
+            .unwrap_or_default()
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect();
+        if lines.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("\n{title}:\n"));
+        for line in lines {
+            out.push_str(&format!("  - {line}\n"));
+        }
+    }
+    out
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_check() {
+        let check = ConfigCheck {
+            result: "invalid".to_string(),
+            errors: Some(
+                "Integration error: foo - Integration 'foo' not found.\n\
+                 Invalid config for 'light' at configuration.yaml, line 12\n"
+                    .to_string(),
+            ),
+            warnings: None,
+        };
+        assert_eq!(
+            format_check(&check),
+            "Configuration is invalid\n\n\
+             Errors:\n\
+             \x20 - Integration error: foo - Integration 'foo' not found.\n\
+             \x20 - Invalid config for 'light' at configuration.yaml, line 12\n"
+        );
+    }
+}
@@ -0,0 +1,273 @@
+//! Dashboard command implementations
+//!
+//! Dashboards are read over the WebSocket API: `dashboard show` fetches a
+//! dashboard's Lovelace config and summarizes each view's cards by type, or
+//! prints the whole config as YAML with `--raw` so it can be grepped or kept
+//! under version control. The default dashboard isn't in Home Assistant's
+//! dashboard list; it goes by `lovelace` here, its URL path.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+use tabled::Tabled;
+
+use crate::cli::DashboardCommand;
+use crate::config::RuntimeContext;
+use crate::error::Error;
+use crate::output;
+use crate::websocket::{Dashboard, WsClient};
+
+/// URL path of the default dashboard
+const DEFAULT_DASHBOARD: &str = "lovelace";
+
+pub async fn run(ctx: &RuntimeContext, command: DashboardCommand) -> Result<()> {
+    match command {
+        DashboardCommand::List => list(ctx).await,
+        DashboardCommand::Show { dashboard, raw } => show(ctx, dashboard.as_deref(), raw).await,
+        DashboardCommand::Resources => resources(ctx).await,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Tabled)]
+struct DashboardRow {
+    #[tabled(rename = "URL PATH")]
+    url_path: String,
+    #[tabled(rename = "TITLE")]
+    title: String,
+    #[tabled(rename = "MODE")]
+    mode: String,
+    #[tabled(rename = "SIDEBAR")]
+    show_in_sidebar: bool,
+    #[tabled(rename = "ADMIN ONLY")]
+    require_admin: bool,
+}
+
+impl From<Dashboard> for DashboardRow {
+    fn from(dashboard: Dashboard) -> Self {
+        Self {
+            url_path: dashboard.url_path,
+            title: dashboard.title.unwrap_or_else(|| "-".to_string()),
+            mode: dashboard.mode.unwrap_or_else(|| "-".to_string()),
+            show_in_sidebar: dashboard.show_in_sidebar,
+            require_admin: dashboard.require_admin,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Tabled)]
+struct ViewRow {
+    #[tabled(rename = "VIEW")]
+    title: String,
+    #[tabled(rename = "PATH")]
+    path: String,
+    #[tabled(rename = "CARDS")]
+    cards: usize,
+    #[tabled(rename = "CARD TYPES")]
+    card_types: String,
+}
+
+#[derive(Debug, Clone, Serialize, Tabled)]
+struct ResourceRow {
+    #[tabled(rename = "URL")]
+    url: String,
+    #[tabled(rename = "TYPE")]
+    resource_type: String,
+}
+
+async fn list(ctx: &RuntimeContext) -> Result<()> {
+    let ws = WsClient::connect(ctx).await?;
+    let dashboards = ws.list_dashboards().await?;
+
+    let mut rows = vec![DashboardRow {
+        url_path: DEFAULT_DASHBOARD.to_string(),
+        title: "(default)".to_string(),
+        mode: "-".to_string(),
+        show_in_sidebar: true,
+        require_admin: false,
+    }];
+    rows.extend(dashboards.into_iter().map(DashboardRow::from));
+
+    output::print_table(ctx, &rows)?;
+    Ok(())
+}
+
+async fn show(ctx: &RuntimeContext, input: Option<&str>, raw: bool) -> Result<()> {
+    let ws = WsClient::connect(ctx).await?;
+    let url_path = match input {
+        Some(input) => find_dashboard(&ws.list_dashboards().await?, input)?,
+        None => None,
+    };
+    let config = ws
+        .get_lovelace_config(url_path.as_deref())
+        .await
+        .with_context(|| {
+            format!(
+                "loading dashboard '{}' (an auto-generated dashboard has no config)",
+                url_path.as_deref().unwrap_or(DEFAULT_DASHBOARD)
+            )
+        })?;
+
+    if raw {
+        print!("{}", serde_yaml::to_string(&config)?);
+        return Ok(());
+    }
+    if ctx.is_machine_output() {
+        return output::print_output(ctx, &config);
+    }
+
+    if let Some(title) = config["title"].as_str() {
+        println!("{title}");
+    }
+    if let Some(strategy) = config["strategy"]["type"].as_str() {
+        println!("Views are generated by the '{strategy}' strategy");
+        return Ok(());
+    }
+    let rows: Vec<ViewRow> = config["views"]
+        .as_array()
+        .map(|views| {
+            views
+                .iter()
+                .enumerate()
+                .map(|(i, view)| summarize_view(i, view))
+                .collect()
+        })
+        .unwrap_or_default();
+    if rows.is_empty() {
+        println!("No views");
+        return Ok(());
+    }
+    output::print_table(ctx, &rows)?;
+    Ok(())
+}
+
+async fn resources(ctx: &RuntimeContext) -> Result<()> {
+    let ws = WsClient::connect(ctx).await?;
+    let rows: Vec<ResourceRow> = ws
+        .list_lovelace_resources()
+        .await?
+        .into_iter()
+        .map(|r| ResourceRow {
+            url: r.url,
+            resource_type: r.resource_type,
+        })
+        .collect();
+
+    output::print_table(ctx, &rows)?;
+    Ok(())
+}
+
+/// URL path of the dashboard matching `input` by URL path or title; `None`
+/// for the default dashboard
+fn find_dashboard(dashboards: &[Dashboard], input: &str) -> Result<Option<String>> {
+    if input == DEFAULT_DASHBOARD || input.eq_ignore_ascii_case("default") {
+        return Ok(None);
+    }
+    dashboards
+        .iter()
+        .find(|d| d.url_path == input)
+        .or_else(|| {
+            dashboards.iter().find(|d| {
+                d.title
+                    .as_deref()
+                    .is_some_and(|t| t.eq_ignore_ascii_case(input))
+            })
+        })
+        .map(|d| Some(d.url_path.clone()))
+        .ok_or_else(|| {
+            let mut available = vec![DEFAULT_DASHBOARD];
+            available.extend(dashboards.iter().map(|d| d.url_path.as_str()));
+            Error::NotFound(format!(
+                "No dashboard '{input}'. Available: {}",
+                available.join(", ")
+            ))
+            .into()
+        })
+}
+
+/// Count a view's cards by type, including cards nested in stacks and
+/// sections
+fn summarize_view(index: usize, view: &Value) -> ViewRow {
+    let mut types: HashMap<String, usize> = HashMap::new();
+    count_cards(&view["cards"], &mut types);
+    for section in view["sections"].as_array().into_iter().flatten() {
+        count_cards(&section["cards"], &mut types);
+    }
+
+    let mut counted: Vec<(String, usize)> = types.into_iter().collect();
+    counted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let card_types: Vec<String> = counted
+        .iter()
+        .map(|(card_type, n)| match n {
+            1 => card_type.clone(),
+            n => format!("{card_type} ({n})"),
+        })
+        .collect();
+
+    ViewRow {
+        title: view["title"]
+            .as_str()
+            .map_or_else(|| format!("View {}", index + 1), str::to_string),
+        path: view["path"]
+            .as_str()
+            .map_or_else(|| index.to_string(), str::to_string),
+        cards: counted.iter().map(|(_, n)| n).sum(),
+        card_types: card_types.join(", "),
+    }
+}
+
+fn count_cards(cards: &Value, types: &mut HashMap<String, usize>) {
+    for card in cards.as_array().into_iter().flatten() {
+        count_card(card, types);
+    }
+}
+
+fn count_card(card: &Value, types: &mut HashMap<String, usize>) {
+    let card_type = card["type"].as_str().unwrap_or("unknown");
+    *types.entry(card_type.to_string()).or_default() += 1;
+    // Stacks and grids hold "cards"; conditional cards wrap one "card"
+    count_cards(&card["cards"], types);
+    if card["card"].is_object() {
+        count_card(&card["card"], types);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_summarize_view() {
+        let view = json!({
+            "title": "Home",
+            "path": "home",
+            "cards": [
+                {"type": "tile", "entity": "light.kitchen"},
+                {"type": "vertical-stack", "cards": [
+                    {"type": "tile", "entity": "light.hall"},
+                    {"type": "custom:mushroom-light-card", "entity": "light.desk"}
+                ]},
+                {"type": "conditional", "card": {"type": "entities", "entities": []}}
+            ],
+            "sections": [{"type": "grid", "cards": [{"type": "heading"}]}]
+        });
+
+        assert_eq!(
+            summarize_view(0, &view),
+            ViewRow {
+                title: "Home".to_string(),
+                path: "home".to_string(),
+                cards: 7,
+                card_types: "tile (2), conditional, custom:mushroom-light-card, entities, heading, vertical-stack".to_string(),
+            }
+        );
+
+        let untitled = summarize_view(2, &json!({"cards": []}));
+        assert_eq!(
+            (untitled.title.as_str(), untitled.path.as_str()),
+            ("View 3", "2")
+        );
+    }
+}
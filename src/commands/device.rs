@@ -7,14 +7,16 @@ use anyhow::{anyhow, Result};
 use serde::Serialize;
 use tabled::Tabled;
 
-use crate::cli::DeviceCommand;
+use crate::cli::{DeviceCommand, PageArgs};
 use crate::config::RuntimeContext;
 use crate::output;
+use crate::paging::Page;
+use crate::resolve::{resolve_area, resolve_device};
 use crate::websocket::{Device, UpdateDeviceRequest, WsClient};
 
 pub async fn run(ctx: &RuntimeContext, command: DeviceCommand) -> Result<()> {
     match command {
-        DeviceCommand::List => list(ctx).await,
+        DeviceCommand::List { paging } => list(ctx, paging).await,
         DeviceCommand::Assign { area, device } => assign(ctx, &area, &device).await,
         DeviceCommand::Update { device_id, data } => update(ctx, &device_id, data.as_deref()).await,
     }
@@ -49,77 +51,35 @@ impl From<Device> for DeviceRow {
     }
 }
 
-async fn list(ctx: &RuntimeContext) -> Result<()> {
-    let mut client = WsClient::connect(ctx).await?;
+async fn list(ctx: &RuntimeContext, paging: PageArgs) -> Result<()> {
+    let client = WsClient::connect(ctx).await?;
     let devices = client.list_devices().await?;
 
     // Convert to rows for table display
     let rows: Vec<DeviceRow> = devices.into_iter().map(DeviceRow::from).collect();
 
-    output::print_table(ctx, &rows)?;
+    let page = Page::of(&rows, &paging)?;
+    output::print_table(ctx, page.items)?;
+    page.print_footer(ctx, "devices");
     Ok(())
 }
 
 async fn assign(ctx: &RuntimeContext, area: &str, device_id: &str) -> Result<()> {
     use crate::cache::CacheManager;
-    use crate::fuzzy::{format_correction, FuzzyMatcher, MatchResult};
 
-    let mut client = WsClient::connect(ctx).await?;
+    let client = WsClient::connect(ctx).await?;
 
     // Use fuzzy matching for area lookup
     let mut cache_manager = CacheManager::new(ctx)?;
     cache_manager.ensure_areas().await?;
+    let area_obj = resolve_area(ctx, cache_manager.cache(), area)?;
 
-    let matcher = FuzzyMatcher::new();
-    let area_result = matcher.find_area(area, cache_manager.cache());
-
-    let is_exact = area_result.is_exact();
-    let area_obj = match area_result {
-        MatchResult::Single(m) => {
-            if !is_exact && !ctx.global.quiet {
-                println!("Area matched: {}", format_correction(area, &m.matched_on));
-            }
-            m.item.clone()
-        }
-        MatchResult::Multiple(_) => {
-            return Err(anyhow!(
-                "Multiple areas match '{area}'. Please be more specific."
-            ));
-        }
-        MatchResult::None => {
-            return Err(anyhow!("Area not found: {area}"));
-        }
-    };
-
-    // Use fuzzy matching for device lookup by name
-    cache_manager.ensure_devices().await?;
-
-    let devices = cache_manager.cache().devices();
-    let matched_device = devices
-        .iter()
-        .find(|d| {
-            d.id == device_id
-                || d.name.as_deref() == Some(device_id)
-                || d.name_by_user.as_deref() == Some(device_id)
-        })
-        .or_else(|| {
-            // Try fuzzy matching on device names
-            devices.iter().find(|d| {
-                d.search_names
-                    .iter()
-                    .any(|name| name.eq_ignore_ascii_case(device_id))
-            })
-        });
-
-    let final_device_id = matched_device.map(|d| d.id.as_str()).unwrap_or(device_id);
-
-    if matched_device.is_some() && final_device_id != device_id && !ctx.global.quiet {
-        println!("Device matched: {device_id} -> {final_device_id}");
-    }
+    // Use fuzzy matching for device lookup by ID or name
+    let matched_device = resolve_device(ctx, &mut cache_manager, device_id).await?;
 
     // Update the device with the area assignment
-    let request = UpdateDeviceRequest::new(final_device_id.to_string())
-        .with_area_id(area_obj.area_id.clone());
+    let request =
+        UpdateDeviceRequest::new(matched_device.id.clone()).with_area_id(area_obj.area_id.clone());
 
     let device = client.update_device(&request).await?;
 
@@ -134,17 +94,23 @@ async fn assign(ctx: &RuntimeContext, area: &str, device_id: &str) -> Result<()>
     Ok(())
 }
 
-async fn update(ctx: &RuntimeContext, device_id: &str, data_input: Option<&str>) -> Result<()> {
-    let mut client = WsClient::connect(ctx).await?;
+async fn update(ctx: &RuntimeContext, device: &str, data_input: Option<&str>) -> Result<()> {
+    use crate::cache::CacheManager;
+
+    let client = WsClient::connect(ctx).await?;
 
     // Validate JSON input (prefer explicit --data, then piped stdin)
     let data = output::get_json_input(data_input)?
         .ok_or_else(|| anyhow!("JSON input required via --data or piped stdin"))?;
 
+    // Resolve the device by ID or (fuzzy) name
+    let mut cache_manager = CacheManager::new(ctx)?;
+    let matched_device = resolve_device(ctx, &mut cache_manager, device).await?;
+
     // Parse the update request
     let mut request: UpdateDeviceRequest = serde_json::from_value(data)?;
     // Override device_id with the positional argument
-    request.device_id = device_id.to_string();
+    request.device_id = matched_device.id;
 
     let device = client.update_device(&request).await?;
 
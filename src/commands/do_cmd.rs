@@ -1,14 +1,32 @@
 //! Natural language command execution
+//!
+//! After a call on a whole area or several entities, the targets are polled
+//! for up to `--verify-timeout` until they reach the state the call implies,
+//! and a one-line summary says where they ended up ("12 lights now off, 1
+//! unavailable: light.porch"). With `--simulate` nothing is sent: the call
+//! is applied to the cached states and the predicted result is shown, fully
+//! offline.
 
-use anyhow::{anyhow, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use tabled::Tabled;
 
 use crate::api::HassClient;
-use crate::cache::CacheManager;
+use crate::cache::{Cache, CacheManager};
 use crate::cli::{DoCommand, OutputFormat};
 use crate::config::RuntimeContext;
+use crate::error::Error;
+use crate::fuzzy::{format_correction, MATCHER_VERSION};
 use crate::history::{History, HistoryEntry};
-use crate::nl::NLParser;
-use crate::output::print_output;
+use crate::i18n::{tr, Msg};
+use crate::nl::{mentions_label, NLParser, STATUS_ACTION};
+use crate::output::{output_for_format, print_output, print_porcelain, print_table, warn};
+use crate::pins;
+use crate::resolve::room_scope;
+use crate::simulate::{expected_state, SimulatedChange, Simulator};
 
 /// Execute a natural language command
 pub async fn execute(ctx: &RuntimeContext, cmd: DoCommand) -> Result<()> {
@@ -22,24 +40,57 @@ pub async fn execute(ctx: &RuntimeContext, cmd: DoCommand) -> Result<()> {
     // Load cache (refresh if needed)
     let mut cache_manager = CacheManager::new(ctx)?;
 
+    if cmd.simulate {
+        return simulate(ctx, &input, &cache_manager);
+    }
+
     // Ensure we have cached entities and services for matching
     cache_manager.ensure_entities().await?;
 
     // Also ensure services are cached for better service matching in nl commands
     if !cache_manager.cache().has_services() && !ctx.global.quiet {
-        if !ctx.global.quiet {
-            eprintln!("Refreshing service cache...");
-        }
         cache_manager.ensure_services().await?;
     }
 
+    // Labels are only needed for "... labeled X"; older servers have no registry
+    if mentions_label(&input) {
+        if let Err(e) = cache_manager.ensure_labels().await {
+            log::debug!("Label cache unavailable: {e:#}");
+        }
+    }
+
     // Parse the natural language input
-    let parser = NLParser::new();
-    let parsed = parser.parse(&input, cache_manager.cache())?;
+    let parser = NLParser::new()
+        .with_default_action(ctx.config.nl.default_action.as_str())
+        .with_pinned(pins::pinned());
+    let room = room_scope(ctx, &mut cache_manager).await?;
+    let match_cache = room
+        .as_ref()
+        .map_or(cache_manager.cache(), |(_, cache)| cache);
+    let parsed = parser.parse(&input, match_cache)?;
+
+    if parsed.action.as_deref() == Some(STATUS_ACTION) {
+        report_match_warnings(ctx, &parsed);
+        return show_status(ctx, &parsed).await;
+    }
+
+    if ctx.porcelain().is_some() {
+        report_match_warnings(ctx, &parsed);
+        return execute_porcelain(
+            ctx,
+            &input,
+            &parsed,
+            cache_manager.cache(),
+            cmd.dry_run,
+            cmd.yes,
+        )
+        .await;
+    }
 
     // Handle output formats
     match ctx.output_format() {
         OutputFormat::Json => {
+            report_match_warnings(ctx, &parsed);
             if cmd.dry_run {
                 print_output(ctx, &parsed)?;
                 return Ok(());
@@ -50,76 +101,54 @@ pub async fn execute(ctx: &RuntimeContext, cmd: DoCommand) -> Result<()> {
             print_output(ctx, &service_call)?;
 
             if !cmd.dry_run {
+                confirm_protected(&parsed, &service_call, cmd.yes)?;
                 execute_service_call(ctx, &service_call).await?;
-                record_success(ctx, &input, &parsed, &service_call)?;
+                record_success(ctx, &input, &parsed, &service_call, cache_manager.cache())?;
+                run_for_duration(ctx, &service_call).await?;
             }
             return Ok(());
         }
-        OutputFormat::Yaml => {
-            if cmd.dry_run {
-                println!("{}", serde_yaml::to_string(&parsed)?);
-                return Ok(());
-            }
+        OutputFormat::Yaml if cmd.dry_run => {
+            report_match_warnings(ctx, &parsed);
+            println!("{}", serde_yaml::to_string(&parsed)?);
+            return Ok(());
         }
         _ => {}
     }
 
     // Check if we have actionable results
     if parsed.targets.is_empty() {
-        record_failure(&input, "No matching entities found")?;
-        return Err(anyhow!(
+        record_failure(
+            ctx,
+            &input,
+            "No matching entities found",
+            cache_manager.cache(),
+        )?;
+        return Err(Error::CacheStale(format!(
             "Could not find any matching entities for: {input}\n\
             Try refreshing the cache with: hmr cache refresh"
-        ));
+        ))
+        .into());
     }
 
-    // Check if we have low confidence matches - warn the user
-    let low_confidence_matches = parsed
-        .targets
-        .iter()
-        .filter(|t| t.match_type.contains("Fuzzy") || t.match_type.contains("domain_match"))
-        .count();
+    report_match_warnings(ctx, &parsed);
 
-    if low_confidence_matches > 0
-        && low_confidence_matches == parsed.targets.len()
-        && !ctx.global.quiet
-    {
-        eprintln!("Warning: All matches have low confidence. Results may not be what you expect.");
-        eprintln!("Consider using more specific entity names or refreshing the cache.");
+    // Track that we had an ambiguous match
+    if parsed.notes.iter().any(|n| n.contains("Multiple matches")) {
+        let mut history = History::new()?;
+        history.stats_mut().record_ambiguous();
+        history.save_stats()?;
     }
 
     // Show interpretation
     if !ctx.global.quiet {
-        println!("Interpreted as: {}", parsed.interpretation);
-
-        // Show typo corrections for non-exact matches
-        for target in &parsed.targets {
-            if target.match_type != "Exact" {
-                use crate::fuzzy::format_correction;
-                println!(
-                    "  Matched: {}",
-                    format_correction(&target.matched_input, &target.entity_id)
-                );
-            }
-        }
-
-        if !parsed.notes.is_empty() {
-            for note in &parsed.notes {
-                println!("Note: {note}");
-            }
-            // Track that we had an ambiguous match
-            if parsed.notes.iter().any(|n| n.contains("Multiple matches")) {
-                let mut history = History::new()?;
-                history.stats_mut().record_ambiguous();
-                history.save_stats()?;
-            }
-        }
+        println!("{}", tr(Msg::InterpretedAs, &[&parsed.interpretation]));
     }
 
     // Show what would be done
     if !ctx.global.quiet || cmd.dry_run {
         println!();
-        println!("Targets ({}):", parsed.targets.len());
+        println!("{}", tr(Msg::Targets, &[&parsed.targets.len().to_string()]));
         for target in &parsed.targets {
             let name = target.friendly_name.as_deref().unwrap_or(&target.entity_id);
             let match_info = if target.match_type == "Exact" {
@@ -132,7 +161,7 @@ pub async fn execute(ctx: &RuntimeContext, cmd: DoCommand) -> Result<()> {
 
         if !parsed.parameters.is_empty() {
             println!();
-            println!("Parameters:");
+            println!("{}", tr(Msg::Parameters, &[]));
             for (key, value) in &parsed.parameters {
                 println!("  {key}: {value}");
             }
@@ -142,32 +171,35 @@ pub async fn execute(ctx: &RuntimeContext, cmd: DoCommand) -> Result<()> {
     // Dry run stops here
     if cmd.dry_run {
         println!();
-        println!("(dry run - no action taken)");
+        println!("{}", tr(Msg::DryRun, &[]));
         return Ok(());
     }
 
     // Execute the service call
     let service_call = parsed.to_service_call()?;
+    confirm_protected(&parsed, &service_call, cmd.yes)?;
 
     if !ctx.global.quiet {
         println!();
-        println!(
-            "Calling {}.{} on {} entities...",
-            service_call.domain,
-            service_call.service,
-            service_call.target.entity_id.len()
-        );
+        let service = format!("{}.{}", service_call.domain, service_call.service);
+        let count = service_call.target.entity_id.len().to_string();
+        println!("{}", tr(Msg::Calling, &[&service, &count]));
     }
 
     match execute_service_call(ctx, &service_call).await {
         Ok(()) => {
-            record_success(ctx, &input, &parsed, &service_call)?;
+            record_success(ctx, &input, &parsed, &service_call, cache_manager.cache())?;
             if !ctx.global.quiet {
-                println!("Done.");
+                println!("{}", tr(Msg::Done, &[]));
+                let wide = parsed.matched_area.is_some() || service_call.target.entity_id.len() > 1;
+                if wide && !cmd.verify_timeout.is_zero() {
+                    report_outcome(ctx, &service_call, cmd.verify_timeout).await?;
+                }
             }
+            run_for_duration(ctx, &service_call).await?;
         }
         Err(e) => {
-            record_failure(&input, &e.to_string())?;
+            record_failure(ctx, &input, &e.to_string(), cache_manager.cache())?;
             return Err(e);
         }
     }
@@ -175,6 +207,471 @@ pub async fn execute(ctx: &RuntimeContext, cmd: DoCommand) -> Result<()> {
     Ok(())
 }
 
+/// Parse `input` against the cache and apply it to the cached states
+///
+/// Stays offline, so `--room` and labels only work when already cached.
+fn simulate(ctx: &RuntimeContext, input: &str, cache_manager: &CacheManager) -> Result<()> {
+    let cache = cache_manager.cache();
+    if !cache.has_entities() {
+        return Err(Error::CacheStale(
+            "No cached entities to simulate against; run: hmr cache refresh".to_string(),
+        )
+        .into());
+    }
+    let scoped = match ctx.global.room.as_deref() {
+        Some(room) => {
+            let area = crate::resolve::resolve_area(ctx, cache, room)?;
+            Some(cache.restricted_to_area(&area.area_id))
+        }
+        None => None,
+    };
+
+    let parser = NLParser::new()
+        .with_default_action(ctx.config.nl.default_action.as_str())
+        .with_pinned(pins::pinned());
+    let parsed = parser.parse(input, scoped.as_ref().unwrap_or(cache))?;
+    report_match_warnings(ctx, &parsed);
+    if parsed.action.as_deref() == Some(STATUS_ACTION) {
+        return Err(anyhow!("Nothing to simulate: '{input}' only shows states"));
+    }
+    if parsed.targets.is_empty() {
+        return Err(Error::CacheStale(format!(
+            "Could not find any matching entities for: {input}"
+        ))
+        .into());
+    }
+
+    let call = parsed.to_service_call()?;
+    let mut simulator = Simulator::new(cache);
+    let mut changes = simulator.apply(&call);
+    if let (Some(secs), Some(stop)) = (call.duration_secs, call.stop_call()) {
+        let after = humantime::format_duration(Duration::from_secs(secs));
+        changes.extend(
+            simulator
+                .apply(&stop)
+                .into_iter()
+                .map(|change| SimulatedChange {
+                    step: format!("{} after {after}", change.step),
+                    ..change
+                }),
+        );
+    }
+
+    output_for_format(ctx, &changes, || {
+        if !ctx.global.quiet {
+            println!("{}", tr(Msg::InterpretedAs, &[&parsed.interpretation]));
+            println!();
+        }
+        let rows: Vec<SimulatedRow> = changes.iter().map(SimulatedRow::from).collect();
+        print_table(ctx, &rows)?;
+        println!();
+        println!("{}", tr(Msg::Simulated, &[]));
+        Ok(())
+    })
+}
+
+#[derive(Debug, Serialize, Tabled)]
+struct SimulatedRow {
+    #[tabled(rename = "STEP")]
+    step: String,
+    #[tabled(rename = "ENTITY ID")]
+    entity_id: String,
+    #[tabled(rename = "BEFORE")]
+    before: String,
+    #[tabled(rename = "AFTER")]
+    after: String,
+}
+
+impl From<&SimulatedChange> for SimulatedRow {
+    fn from(change: &SimulatedChange) -> Self {
+        Self {
+            step: change.step.clone(),
+            entity_id: change.entity_id.clone(),
+            before: change.before.clone(),
+            after: if change.predicted {
+                change.after.clone()
+            } else {
+                format!("{} (unpredicted)", change.after)
+            },
+        }
+    }
+}
+
+/// Print the current state of each target
+async fn show_status(ctx: &RuntimeContext, parsed: &crate::nl::ParsedCommand) -> Result<()> {
+    let client = HassClient::new(ctx)?;
+    let mut states = Vec::with_capacity(parsed.targets.len());
+    for target in &parsed.targets {
+        states.push(client.get_state(&target.entity_id).await?);
+    }
+
+    if ctx.porcelain().is_some() {
+        for state in &states {
+            print_porcelain(
+                "state",
+                &[
+                    &state.entity_id,
+                    &state.last_changed,
+                    &state.last_updated,
+                    &state.state,
+                ],
+            );
+        }
+        return Ok(());
+    }
+
+    match ctx.output_format() {
+        OutputFormat::Json => print_output(ctx, &states)?,
+        OutputFormat::Yaml => println!("{}", serde_yaml::to_string(&states)?),
+        _ => {
+            if let Some(asked) = parsed.parameters.get("state").and_then(|v| v.as_str()) {
+                let named: Vec<(&str, &str)> = states
+                    .iter()
+                    .map(|state| {
+                        let name = state
+                            .attributes
+                            .get("friendly_name")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or(&state.entity_id);
+                        (name, state.state.as_str())
+                    })
+                    .collect();
+                println!("{}", answer(&named, asked));
+            }
+            for state in &states {
+                let name = state
+                    .attributes
+                    .get("friendly_name")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or(&state.entity_id);
+                println!("{name} ({}): {}", state.entity_id, state.state);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether `state` is the state a question asked about; sensors report
+/// open doors and windows as "on"
+fn in_state(state: &str, asked: &str) -> bool {
+    state == asked
+        || matches!(
+            (asked, state),
+            ("open", "on") | ("closed", "off") | ("away", "not_home")
+        )
+}
+
+/// "Yes: 2 of 5 are open (Front Door, Patio Door)" for `(name, state)` pairs
+fn answer(states: &[(&str, &str)], asked: &str) -> String {
+    let matching: Vec<&str> = states
+        .iter()
+        .filter(|(_, state)| in_state(state, asked))
+        .map(|(name, _)| *name)
+        .collect();
+    match (matching.len(), states.len()) {
+        (_, 0) => format!("No: nothing found to be {asked}"),
+        (0, 1) => format!("No: {} is {}", states[0].0, states[0].1),
+        (0, total) => format!("No: none of {total} are {asked}"),
+        (_, 1) => format!("Yes: {} is {asked}", matching[0]),
+        (count, total) => {
+            let verb = if count == 1 { "is" } else { "are" };
+            format!(
+                "Yes: {count} of {total} {verb} {asked} ({})",
+                matching.join(", ")
+            )
+        }
+    }
+}
+
+/// `--verify-timeout` when the command isn't parsed from the command line
+pub const DEFAULT_VERIFY_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Time between polls while waiting for targets to settle
+const VERIFY_POLL: Duration = Duration::from_millis(250);
+
+/// States an entity passes through on its way to a new one
+const TRANSITIONAL: &[&str] = &["opening", "closing", "locking", "unlocking"];
+
+/// Wait for the targets of a call to settle, then print where they ended up
+async fn report_outcome(
+    ctx: &RuntimeContext,
+    call: &crate::nl::ServiceCall,
+    timeout: Duration,
+) -> Result<()> {
+    let client = HassClient::new(ctx)?;
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        let states = client
+            .get_states()
+            .await
+            .context("checking the outcome of the call")?;
+        let states: HashMap<&str, &str> = states
+            .iter()
+            .map(|s| (s.entity_id.as_str(), s.state.as_str()))
+            .collect();
+        let outcomes: Vec<Outcome> = call
+            .target
+            .entity_id
+            .iter()
+            .map(|entity_id| Outcome {
+                entity_id: entity_id.clone(),
+                // A target that has gone missing is as good as unavailable
+                state: states
+                    .get(entity_id.as_str())
+                    .unwrap_or(&"unavailable")
+                    .to_string(),
+                expected: expected_state(entity_domain(entity_id), &call.service),
+            })
+            .collect();
+
+        if outcomes.iter().all(Outcome::settled) || tokio::time::Instant::now() >= deadline {
+            println!("{}", summarize_outcome(&outcomes));
+            return Ok(());
+        }
+        tokio::time::sleep(VERIFY_POLL).await;
+    }
+}
+
+/// Where one target of a call ended up
+#[derive(Debug)]
+struct Outcome {
+    entity_id: String,
+    state: String,
+    /// The state the call should leave it in, when that is predictable
+    expected: Option<&'static str>,
+}
+
+impl Outcome {
+    fn settled(&self) -> bool {
+        match self.expected {
+            _ if self.state == "unavailable" => true,
+            Some(expected) => self.state == expected,
+            None => !TRANSITIONAL.contains(&self.state.as_str()),
+        }
+    }
+}
+
+/// "12 lights now off, 1 still on: light.hall, 1 unavailable: light.porch"
+fn summarize_outcome(outcomes: &[Outcome]) -> String {
+    let (unavailable, available): (Vec<&Outcome>, Vec<&Outcome>) =
+        outcomes.iter().partition(|o| o.state == "unavailable");
+
+    let mut by_state: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for outcome in &available {
+        by_state
+            .entry(outcome.state.as_str())
+            .or_default()
+            .push(outcome.entity_id.as_str());
+    }
+    // The expected state leads, then the most common ones
+    let mut groups: Vec<(&str, Vec<&str>)> = by_state.into_iter().collect();
+    groups.sort_by_key(|(state, ids)| {
+        let reached = available
+            .iter()
+            .any(|o| o.expected == Some(*state) && o.state == *state);
+        (!reached, std::cmp::Reverse(ids.len()))
+    });
+    let any_expected = outcomes.iter().any(|o| o.expected.is_some());
+
+    let mut parts = Vec::new();
+    for (i, (state, ids)) in groups.iter().enumerate() {
+        let count = ids.len().to_string();
+        parts.push(if i == 0 {
+            let noun = plural_noun(outcomes, ids.len());
+            tr(Msg::NowState, &[&format!("{count} {noun}"), state])
+        } else if any_expected {
+            tr(Msg::StillState, &[&count, state, &ids.join(", ")])
+        } else {
+            tr(Msg::NowStateIds, &[&count, state, &ids.join(", ")])
+        });
+    }
+    if !unavailable.is_empty() {
+        let ids: Vec<&str> = unavailable.iter().map(|o| o.entity_id.as_str()).collect();
+        parts.push(tr(
+            Msg::Unavailable,
+            &[&ids.len().to_string(), &ids.join(", ")],
+        ));
+    }
+    parts.join(", ")
+}
+
+/// "lights" when every target is a light, otherwise "entities"
+fn plural_noun(outcomes: &[Outcome], count: usize) -> String {
+    let mut domains = outcomes.iter().map(|o| entity_domain(&o.entity_id));
+    let first = domains.next().unwrap_or_default();
+    let noun = if domains.all(|d| d == first) {
+        first.replace('_', " ")
+    } else {
+        "entity".to_string()
+    };
+    match count {
+        1 => noun,
+        _ if noun.ends_with('y') => format!("{}ies", &noun[..noun.len() - 1]),
+        _ if noun.ends_with("ch") || noun.ends_with('s') => format!("{noun}es"),
+        _ => format!("{noun}s"),
+    }
+}
+
+fn entity_domain(entity_id: &str) -> &str {
+    entity_id
+        .split_once('.')
+        .map_or(entity_id, |(domain, _)| domain)
+}
+
+/// Report low-confidence matches, typo corrections, and parser notes as warnings
+fn report_match_warnings(ctx: &RuntimeContext, parsed: &crate::nl::ParsedCommand) {
+    // Check if we have low confidence matches - warn the user
+    let low_confidence_matches = parsed
+        .targets
+        .iter()
+        .filter(|t| t.match_type.contains("Fuzzy") || t.match_type.contains("domain_match"))
+        .count();
+
+    if low_confidence_matches > 0 && low_confidence_matches == parsed.targets.len() {
+        warn(
+            ctx,
+            "Warning: All matches have low confidence. Results may not be what you expect.",
+        );
+        warn(
+            ctx,
+            "Consider using more specific entity names or refreshing the cache.",
+        );
+    }
+
+    // Show typo corrections for non-exact matches
+    for target in &parsed.targets {
+        if target.match_type != "Exact" {
+            warn(
+                ctx,
+                format!(
+                    "Matched: {}",
+                    format_correction(&target.matched_input, &target.entity_id)
+                ),
+            );
+        }
+    }
+
+    for note in &parsed.notes {
+        warn(ctx, format!("Note: {note}"));
+    }
+}
+
+/// Porcelain (v1) flow: print the plan as stable records, then execute unless dry-run.
+///
+/// Records:
+///   `plan <domain>.<service>`
+///   `target <entity_id> <match_type> <friendly_name>`
+///   `data <key> <json>`
+///   `done <domain>.<service>` (only when the call was executed)
+async fn execute_porcelain(
+    ctx: &RuntimeContext,
+    input: &str,
+    parsed: &crate::nl::ParsedCommand,
+    cache: &Cache,
+    dry_run: bool,
+    yes: bool,
+) -> Result<()> {
+    if parsed.targets.is_empty() {
+        record_failure(ctx, input, "No matching entities found", cache)?;
+        return Err(Error::CacheStale(format!(
+            "Could not find any matching entities for: {input}"
+        ))
+        .into());
+    }
+
+    let service_call = parsed.to_service_call()?;
+    let service = format!("{}.{}", service_call.domain, service_call.service);
+
+    print_porcelain("plan", &[&service]);
+    for target in &parsed.targets {
+        print_porcelain(
+            "target",
+            &[
+                &target.entity_id,
+                &target.match_type,
+                target.friendly_name.as_deref().unwrap_or(""),
+            ],
+        );
+    }
+    for (key, value) in &service_call.data {
+        print_porcelain("data", &[key, &value.to_string()]);
+    }
+
+    if dry_run {
+        return Ok(());
+    }
+
+    confirm_protected(parsed, &service_call, yes)?;
+    match execute_service_call(ctx, &service_call).await {
+        Ok(()) => {
+            record_success(ctx, input, parsed, &service_call, cache)?;
+            print_porcelain("done", &[&service]);
+            if let Some(stop) = run_for_duration(ctx, &service_call).await? {
+                print_porcelain("done", &[&format!("{}.{}", stop.domain, stop.service)]);
+            }
+            Ok(())
+        }
+        Err(e) => {
+            record_failure(ctx, input, &e.to_string(), cache)?;
+            Err(e)
+        }
+    }
+}
+
+/// Hold a timed call ("water the garden for 10 minutes") and then reverse it
+///
+/// Waits in the foreground; Ctrl+C ends the run early. Returns the stop call
+/// that was made, if the call had a duration.
+async fn run_for_duration(
+    ctx: &RuntimeContext,
+    call: &crate::nl::ServiceCall,
+) -> Result<Option<crate::nl::ServiceCall>> {
+    let (Some(secs), Some(stop)) = (call.duration_secs, call.stop_call()) else {
+        return Ok(None);
+    };
+    let duration = std::time::Duration::from_secs(secs);
+
+    let stop_service = format!("{}.{}", stop.domain, stop.service);
+    warn(
+        ctx,
+        tr(
+            Msg::RunningFor,
+            &[
+                &humantime::format_duration(duration).to_string(),
+                &stop_service,
+            ],
+        ),
+    );
+
+    tokio::select! {
+        _ = tokio::time::sleep(duration) => {}
+        _ = tokio::signal::ctrl_c() => {
+            log::debug!("Received Ctrl+C, ending timed run early");
+        }
+    }
+
+    execute_service_call(ctx, &stop).await?;
+    if !ctx.is_machine_output() && !ctx.global.quiet {
+        println!("{}", tr(Msg::RunFinished, &[&stop_service]));
+    }
+    Ok(Some(stop))
+}
+
+/// Confirm calls that would sound a siren or change an alarm panel
+fn confirm_protected(
+    parsed: &crate::nl::ParsedCommand,
+    call: &crate::nl::ServiceCall,
+    yes: bool,
+) -> Result<()> {
+    crate::prompt::confirm_protected_request(
+        &parsed.interpretation,
+        &format!("{}.{}", call.domain, call.service),
+        &call.target.entity_id,
+        yes,
+    )
+}
+
 async fn execute_service_call(ctx: &RuntimeContext, call: &crate::nl::ServiceCall) -> Result<()> {
     let client = HassClient::new(ctx)?;
 
@@ -215,10 +712,11 @@ async fn execute_service_call(ctx: &RuntimeContext, call: &crate::nl::ServiceCal
 }
 
 fn record_success(
-    _ctx: &RuntimeContext,
+    ctx: &RuntimeContext,
     input: &str,
     parsed: &crate::nl::ParsedCommand,
     service_call: &crate::nl::ServiceCall,
+    cache: &Cache,
 ) -> Result<()> {
     let mut history = History::new()?;
 
@@ -234,6 +732,8 @@ fn record_success(
         .with_service(&service_call.domain, &service_call.service)
         .with_targets(service_call.target.entity_id.clone())
         .with_match_type(match_type)
+        .with_matcher(MATCHER_VERSION, cache.fingerprint())
+        .with_profile(ctx.token_profile())
         .with_success();
 
     history.append(&entry)?;
@@ -274,10 +774,13 @@ fn record_success(
     Ok(())
 }
 
-fn record_failure(input: &str, error: &str) -> Result<()> {
+fn record_failure(ctx: &RuntimeContext, input: &str, error: &str, cache: &Cache) -> Result<()> {
     let mut history = History::new()?;
 
-    let entry = HistoryEntry::new(input, "").with_error(error);
+    let entry = HistoryEntry::new(input, "")
+        .with_matcher(MATCHER_VERSION, cache.fingerprint())
+        .with_profile(ctx.token_profile())
+        .with_error(error);
 
     history.append(&entry)?;
 
@@ -287,3 +790,60 @@ fn record_failure(input: &str, error: &str) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(entity_id: &str, state: &str, service: &str) -> Outcome {
+        let domain = entity_domain(entity_id);
+        Outcome {
+            entity_id: entity_id.to_string(),
+            state: state.to_string(),
+            expected: expected_state(domain, service),
+        }
+    }
+
+    #[test]
+    fn test_summarize_outcome() {
+        let outcomes = vec![
+            outcome("light.kitchen", "off", "turn_off"),
+            outcome("light.hall", "on", "turn_off"),
+            outcome("light.desk", "off", "turn_off"),
+            outcome("light.porch", "unavailable", "turn_off"),
+        ];
+        assert_eq!(
+            summarize_outcome(&outcomes),
+            "2 lights now off, 1 still on: light.hall, 1 unavailable: light.porch"
+        );
+        assert!(!outcomes[1].settled());
+        assert!(outcomes[3].settled());
+
+        let mixed = vec![
+            outcome("switch.fan", "on", "toggle"),
+            outcome("media_player.tv", "idle", "toggle"),
+        ];
+        assert_eq!(
+            summarize_outcome(&mixed),
+            "1 entity now idle, 1 now on: switch.fan"
+        );
+    }
+
+    #[test]
+    fn test_answer() {
+        let doors = [
+            ("Front Door", "on"),
+            ("Garage Door", "closed"),
+            ("Patio Door", "open"),
+        ];
+        assert_eq!(
+            answer(&doors, "open"),
+            "Yes: 2 of 3 are open (Front Door, Patio Door)"
+        );
+        assert_eq!(answer(&doors, "locked"), "No: none of 3 are locked");
+        assert_eq!(
+            answer(&[("Garage Door", "closed")], "open"),
+            "No: Garage Door is closed"
+        );
+    }
+}
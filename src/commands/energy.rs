@@ -0,0 +1,288 @@
+//! Energy command implementation
+//!
+//! Reads the energy dashboard configuration and sums the daily change of
+//! its statistics: grid import and export, solar production, battery charge
+//! and discharge, and the cost statistics Home Assistant keeps for priced
+//! sources. Consumption is computed the way the dashboard does: import +
+//! solar + discharge - export - charge. Cost is what was paid for grid
+//! import and gas less the compensation for export.
+
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{bail, Result};
+use chrono::{Local, NaiveDate};
+use serde::Serialize;
+use tabled::Tabled;
+
+use crate::api::HassClient;
+use crate::cli::EnergyCommand;
+use crate::config::RuntimeContext;
+use crate::output::{output_for_format, print_table};
+use crate::progress;
+use crate::websocket::{EnergyPrefs, StatisticPoint, WsClient};
+
+/// Statistic IDs of the energy dashboard, by what they measure
+#[derive(Debug, Default, PartialEq)]
+struct EnergyStats {
+    grid_import: Vec<String>,
+    grid_export: Vec<String>,
+    solar: Vec<String>,
+    battery_charge: Vec<String>,
+    battery_discharge: Vec<String>,
+    costs: Vec<String>,
+    compensations: Vec<String>,
+}
+
+impl EnergyStats {
+    fn from_prefs(prefs: &EnergyPrefs, cost_sensors: &HashMap<String, String>) -> Self {
+        let mut stats = Self::default();
+        // A configured cost statistic, else the one Home Assistant derives from a price
+        let cost_of = |explicit: &Option<String>, energy: &Option<String>| {
+            explicit
+                .clone()
+                .or_else(|| energy.as_ref().and_then(|e| cost_sensors.get(e).cloned()))
+        };
+        for source in &prefs.energy_sources {
+            match source.kind.as_str() {
+                "grid" => {
+                    for flow in &source.flow_from {
+                        stats.grid_import.extend(flow.stat_energy_from.clone());
+                        stats
+                            .costs
+                            .extend(cost_of(&flow.stat_cost, &flow.stat_energy_from));
+                    }
+                    for flow in &source.flow_to {
+                        stats.grid_export.extend(flow.stat_energy_to.clone());
+                        stats
+                            .compensations
+                            .extend(cost_of(&flow.stat_compensation, &flow.stat_energy_to));
+                    }
+                }
+                "solar" => stats.solar.extend(source.stat_energy_from.clone()),
+                "battery" => {
+                    stats
+                        .battery_discharge
+                        .extend(source.stat_energy_from.clone());
+                    stats.battery_charge.extend(source.stat_energy_to.clone());
+                }
+                "gas" => stats
+                    .costs
+                    .extend(cost_of(&source.stat_cost, &source.stat_energy_from)),
+                _ => {}
+            }
+        }
+        stats
+    }
+
+    fn ids(&self) -> Vec<String> {
+        [
+            &self.grid_import,
+            &self.grid_export,
+            &self.solar,
+            &self.battery_charge,
+            &self.battery_discharge,
+            &self.costs,
+            &self.compensations,
+        ]
+        .into_iter()
+        .flatten()
+        .cloned()
+        .collect()
+    }
+}
+
+/// Totals of one day, energy in kWh
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+struct EnergyDay {
+    date: String,
+    consumption: f64,
+    solar: f64,
+    grid_import: f64,
+    grid_export: f64,
+    /// None when no source has a price
+    cost: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Tabled)]
+struct EnergyRow {
+    #[tabled(rename = "DATE")]
+    date: String,
+    #[tabled(rename = "CONSUMED")]
+    consumption: String,
+    #[tabled(rename = "SOLAR")]
+    solar: String,
+    #[tabled(rename = "IMPORTED")]
+    grid_import: String,
+    #[tabled(rename = "EXPORTED")]
+    grid_export: String,
+    #[tabled(rename = "COST")]
+    cost: String,
+}
+
+impl EnergyRow {
+    fn new(day: &EnergyDay, currency: &str) -> Self {
+        let kwh = |v: f64| format!("{v:.1} kWh");
+        Self {
+            date: day.date.clone(),
+            consumption: kwh(day.consumption),
+            solar: kwh(day.solar),
+            grid_import: kwh(day.grid_import),
+            grid_export: kwh(day.grid_export),
+            cost: day
+                .cost
+                .map(|c| format!("{c:.2} {currency}").trim_end().to_string())
+                .unwrap_or_else(|| "-".to_string()),
+        }
+    }
+}
+
+pub async fn run(ctx: &RuntimeContext, command: EnergyCommand) -> Result<()> {
+    let ws = WsClient::connect(ctx).await?;
+    let prefs = ws.get_energy_prefs().await?;
+    let cost_sensors = ws.get_energy_cost_sensors().await?;
+    let stats = EnergyStats::from_prefs(&prefs, &cost_sensors);
+    let ids = stats.ids();
+    if ids.is_empty() {
+        bail!("The energy dashboard isn't set up (Settings > Dashboards > Energy)");
+    }
+
+    // Whole days, the first one starting at local midnight
+    let now = Local::now();
+    let first_day = command.since.before(now).date_naive();
+    let start = first_day
+        .and_hms_opt(0, 0, 0)
+        .and_then(|t| t.and_local_timezone(Local).earliest())
+        .unwrap_or(now);
+
+    let spinner = progress::spinner(ctx, "Fetching energy statistics");
+    let series = ws
+        .statistics_during_period(&ids, &start.to_rfc3339(), &now.to_rfc3339(), "day")
+        .await?;
+    let currency = HassClient::new(ctx)?
+        .get_config()
+        .await
+        .ok()
+        .and_then(|c| c.currency)
+        .unwrap_or_default();
+    spinner.finish_and_clear();
+
+    let days = daily_totals(&stats, &series);
+    output_for_format(ctx, &days, || {
+        if days.is_empty() {
+            println!("No energy statistics recorded since {first_day}");
+            return Ok(());
+        }
+        let mut rows: Vec<EnergyRow> = days.iter().map(|d| EnergyRow::new(d, &currency)).collect();
+        if days.len() > 1 {
+            rows.push(EnergyRow::new(&total(&days), &currency));
+        }
+        print_table(ctx, &rows)
+    })
+}
+
+/// Sum each day's change of every statistic into per-day totals, oldest first
+fn daily_totals(
+    stats: &EnergyStats,
+    series: &HashMap<String, Vec<StatisticPoint>>,
+) -> Vec<EnergyDay> {
+    let mut days: BTreeMap<NaiveDate, EnergyDay> = BTreeMap::new();
+    let mut add = |ids: &[String], apply: fn(&mut EnergyDay, f64)| {
+        for point in ids.iter().filter_map(|id| series.get(id)).flatten() {
+            let (Some(start), Some(change)) = (point.start_time(), point.change) else {
+                continue;
+            };
+            let date = start.with_timezone(&Local).date_naive();
+            let day = days.entry(date).or_insert_with(|| EnergyDay {
+                date: date.to_string(),
+                ..EnergyDay::default()
+            });
+            apply(day, change);
+        }
+    };
+    add(&stats.grid_import, |d, v| d.grid_import += v);
+    add(&stats.grid_export, |d, v| d.grid_export += v);
+    add(&stats.solar, |d, v| d.solar += v);
+    add(&stats.battery_discharge, |d, v| d.consumption += v);
+    add(&stats.battery_charge, |d, v| d.consumption -= v);
+    add(&stats.costs, |d, v| *d.cost.get_or_insert(0.0) += v);
+    add(&stats.compensations, |d, v| *d.cost.get_or_insert(0.0) -= v);
+
+    days.into_values()
+        .map(|mut day| {
+            day.consumption += day.grid_import + day.solar - day.grid_export;
+            day
+        })
+        .collect()
+}
+
+/// All days added up, as the table's last row
+fn total(days: &[EnergyDay]) -> EnergyDay {
+    days.iter().fold(
+        EnergyDay {
+            date: "total".to_string(),
+            ..EnergyDay::default()
+        },
+        |mut total, day| {
+            total.consumption += day.consumption;
+            total.solar += day.solar;
+            total.grid_import += day.grid_import;
+            total.grid_export += day.grid_export;
+            if let Some(cost) = day.cost {
+                *total.cost.get_or_insert(0.0) += cost;
+            }
+            total
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_daily_totals() {
+        let prefs: EnergyPrefs = serde_json::from_value(json!({
+            "energy_sources": [
+                {
+                    "type": "grid",
+                    "flow_from": [{"stat_energy_from": "sensor.grid_in", "stat_cost": null}],
+                    "flow_to": [{"stat_energy_to": "sensor.grid_out", "stat_compensation": null}]
+                },
+                {"type": "solar", "stat_energy_from": "sensor.solar"}
+            ]
+        }))
+        .unwrap();
+        let cost_sensors = HashMap::from([(
+            "sensor.grid_in".to_string(),
+            "sensor.grid_in_cost".to_string(),
+        )]);
+        let stats = EnergyStats::from_prefs(&prefs, &cost_sensors);
+        assert_eq!(stats.costs, vec!["sensor.grid_in_cost"]);
+        assert!(stats.compensations.is_empty());
+
+        // Local noon, well clear of midnight on either side
+        let noon = Local::now()
+            .date_naive()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_local_timezone(Local)
+            .unwrap();
+        let point = |change: f64| StatisticPoint {
+            start: json!(noon.timestamp_millis()),
+            change: Some(change),
+        };
+        let series = HashMap::from([
+            ("sensor.grid_in".to_string(), vec![point(4.0)]),
+            ("sensor.grid_out".to_string(), vec![point(1.5)]),
+            ("sensor.solar".to_string(), vec![point(6.0)]),
+            ("sensor.grid_in_cost".to_string(), vec![point(1.2)]),
+        ]);
+
+        let days = daily_totals(&stats, &series);
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[0].consumption, 8.5);
+        assert_eq!(days[0].cost, Some(1.2));
+        assert_eq!(EnergyRow::new(&days[0], "EUR").cost, "1.20 EUR");
+    }
+}
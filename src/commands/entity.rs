@@ -1,18 +1,41 @@
 //! Entity command implementations
 
-use anyhow::{Context, Result};
-use chrono::{Duration, Utc};
+use std::collections::HashSet;
+
+use anyhow::{bail, Context, Result};
+use chrono::{Local, Utc};
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
 use serde::Serialize;
-use serde_json::json;
+use serde_json::{json, Value};
 use tabled::Tabled;
 
 use crate::api::{EntityState, HassClient};
-use crate::cli::{EntityCommand, OutputFormat};
+use crate::cache::CacheManager;
+use crate::cli::{
+    EntityAliasCommand, EntityCommand, EntityFlagArgs, OutputFormat, PageArgs, UnitSystem,
+};
+use crate::commands::cache::format_bytes;
 use crate::config::RuntimeContext;
-use crate::output::{get_json_input, output_for_format, print_output, print_table};
-use crate::websocket;
+use crate::error::Error;
+use crate::names::EntityNames;
+use crate::output::{
+    get_json_input, hidden_columns, output_for_format, print_output, print_porcelain,
+    print_table_without, warn,
+};
+use crate::paging::Page;
+use crate::pins::Pins;
+use crate::progress;
+use crate::prompt::confirm;
+use crate::resolve::{
+    label_entity_ids, resolve_area, resolve_config_entry, resolve_entity, room_entity_ids,
+};
+use crate::timespec::TimeSpec;
+use crate::units::{
+    display_state, display_value, numeric_value, parse_quantity, with_unit, Quantity,
+};
+use crate::validate;
+use crate::websocket::{self, EntityRegistryEntry, UpdateEntityRequest, WsClient};
 
 #[derive(Debug, Tabled, Serialize)]
 struct EntityRow {
@@ -20,17 +43,23 @@ struct EntityRow {
     state: String,
     #[tabled(rename = "friendly_name")]
     friendly_name: String,
+    device_class: String,
+    unit: String,
+    icon: String,
     last_changed: String,
 }
 
-impl From<&EntityState> for EntityRow {
-    fn from(state: &EntityState) -> Self {
-        let friendly_name = state
-            .attributes
-            .get("friendly_name")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
+impl EntityRow {
+    fn new(state: &EntityState, units: Option<UnitSystem>) -> Self {
+        let attr = |key: &str| {
+            state
+                .attributes
+                .get(key)
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string()
+        };
+        let (value, unit) = display_value(state, units);
 
         // Format the timestamp for display
         let last_changed = state
@@ -41,33 +70,130 @@ impl From<&EntityState> for EntityRow {
 
         Self {
             entity_id: state.entity_id.clone(),
-            state: state.state.clone(),
-            friendly_name,
+            state: with_unit(&value, &unit),
+            friendly_name: attr("friendly_name"),
+            device_class: attr("device_class"),
+            unit,
+            icon: attr("icon"),
             last_changed: last_changed.to_string(),
         }
     }
 }
 
+/// All `entity list` table columns, in display order
+const ENTITY_COLUMNS: &[&str] = &[
+    "entity_id",
+    "state",
+    "friendly_name",
+    "device_class",
+    "unit",
+    "icon",
+    "last_changed",
+];
+
+/// Columns shown when `--columns` is not given
+const DEFAULT_ENTITY_COLUMNS: &[&str] = &["entity_id", "state", "friendly_name", "last_changed"];
+
 pub async fn run(ctx: &RuntimeContext, command: EntityCommand) -> Result<()> {
     match command {
-        EntityCommand::List { filter } => list(ctx, filter).await,
-        EntityCommand::Get { entity_id } => get(ctx, &entity_id).await,
+        EntityCommand::List {
+            filter,
+            pinned,
+            label,
+            paging,
+        } => list(ctx, filter, pinned, label.as_deref(), paging).await,
+        EntityCommand::Get {
+            entity_id,
+            attrs,
+            max_attr_size,
+        } => get(ctx, &entity_id, &attrs, max_attr_size).await,
         EntityCommand::Set {
             entity_id,
             data,
             state,
         } => set(ctx, &entity_id, data.as_deref(), state.as_deref()).await,
-        EntityCommand::History { entity_id, since } => history(ctx, &entity_id, &since).await,
-        EntityCommand::Watch { entity_ids } => watch(ctx, &entity_ids).await,
+        EntityCommand::History {
+            entity_id,
+            since,
+            until,
+        } => history(ctx, &entity_id, since, until).await,
+        EntityCommand::Watch {
+            entity_ids,
+            above,
+            below,
+            exit_on_match,
+            attr,
+            attrs,
+            max_attr_size,
+            stats,
+        } => {
+            let thresholds = Thresholds {
+                above: above.as_deref().map(parse_quantity).transpose()?,
+                below: below.as_deref().map(parse_quantity).transpose()?,
+            };
+            if exit_on_match && thresholds.is_empty() {
+                bail!("--exit-on-match needs --above or --below");
+            }
+            watch(
+                ctx,
+                &entity_ids,
+                &thresholds,
+                exit_on_match,
+                &attr,
+                &AttributeFilter {
+                    keys: &attrs,
+                    max_size: max_attr_size,
+                },
+                stats,
+            )
+            .await
+        }
+        EntityCommand::Rename { entity, name } => rename(ctx, &entity, &name).await,
+        EntityCommand::SetArea { entity, area } => set_area(ctx, &entity, &area).await,
+        EntityCommand::Alias { command } => match command {
+            EntityAliasCommand::Add { entity, alias } => {
+                edit_aliases(ctx, &entity, &alias, true).await
+            }
+            EntityAliasCommand::Remove { entity, alias } => {
+                edit_aliases(ctx, &entity, &alias, false).await
+            }
+        },
+        EntityCommand::Disable(args) => set_flag(ctx, RegistryFlag::Disable, &args).await,
+        EntityCommand::Enable(args) => set_flag(ctx, RegistryFlag::Enable, &args).await,
+        EntityCommand::Hide(args) => set_flag(ctx, RegistryFlag::Hide, &args).await,
+        EntityCommand::Unhide(args) => set_flag(ctx, RegistryFlag::Unhide, &args).await,
     }
 }
 
-async fn list(ctx: &RuntimeContext, filter: Option<String>) -> Result<()> {
+async fn list(
+    ctx: &RuntimeContext,
+    filter: Option<String>,
+    pinned: bool,
+    label: Option<&str>,
+    paging: PageArgs,
+) -> Result<()> {
+    let hidden = hidden_columns(
+        ctx.global.columns.as_deref(),
+        ENTITY_COLUMNS,
+        DEFAULT_ENTITY_COLUMNS,
+    )?;
+
     let client = HassClient::new(ctx)?;
     // Note: Home Assistant API doesn't support server-side filtering, so we must
     // load all entities and filter client-side. For large installations, this is
     // the only option without caching or a local database.
-    let states = client.get_states().await?;
+    let mut states = client.get_states().await?;
+    if let Some(room) = room_entity_ids(ctx).await? {
+        states.retain(|s| room.contains(&s.entity_id));
+    }
+    if pinned {
+        let pins = Pins::load()?;
+        states.retain(|s| pins.contains(&s.entity_id));
+    }
+    if let Some(label) = label {
+        let labeled = label_entity_ids(ctx, label).await?;
+        states.retain(|s| labeled.contains(&s.entity_id));
+    }
 
     let filtered: Vec<_> = if let Some(ref filter) = filter {
         let matcher = SkimMatcherV2::default();
@@ -87,28 +213,66 @@ async fn list(ctx: &RuntimeContext, filter: Option<String>) -> Result<()> {
         states.iter().collect()
     };
 
-    output_for_format(ctx, &filtered, || {
-        let rows: Vec<EntityRow> = filtered.iter().map(|s| EntityRow::from(*s)).collect();
+    let page = Page::of(&filtered, &paging)?;
+    output_for_format(ctx, &page.items, || {
+        let rows: Vec<EntityRow> = page
+            .items
+            .iter()
+            .map(|s| EntityRow::new(s, ctx.global.units))
+            .collect();
         if rows.is_empty() {
-            if filter.is_some() {
+            if pinned && filter.is_none() {
+                println!("No pinned entities (pin one with `hmr pin add`)");
+            } else if let Some(label) = label.filter(|_| filter.is_none()) {
+                println!("No entities labeled '{label}'");
+            } else if filter.is_some() {
                 println!("No entities found matching filter");
             } else {
                 println!("No entities found");
             }
         } else {
-            print_table(ctx, &rows)?;
+            print_table_without(ctx, &rows, &hidden)?;
+            page.print_footer(ctx, "entities");
         }
         Ok(())
     })
 }
 
-async fn get(ctx: &RuntimeContext, entity_id: &str) -> Result<()> {
+async fn get(
+    ctx: &RuntimeContext,
+    entity_id: &str,
+    attrs: &[String],
+    max_attr_size: Option<usize>,
+) -> Result<()> {
+    validate::entity_ids(ctx, &[entity_id.to_string()]).await?;
     let client = HassClient::new(ctx)?;
-    let state = client.get_state(entity_id).await?;
+    let mut state = client.get_state(entity_id).await?;
+    let cut = limit_attributes(&mut state.attributes, attrs, max_attr_size);
+    if let Some(max) = max_attr_size.filter(|_| !cut.is_empty()) {
+        warn(ctx, describe_cut(&state.entity_id, &cut, max));
+    }
+
+    if ctx.porcelain().is_some() {
+        print_porcelain(
+            "state",
+            &[
+                &state.entity_id,
+                &state.last_changed,
+                &state.last_updated,
+                &state.state,
+            ],
+        );
+        if let Some(attrs) = state.attributes.as_object() {
+            for (key, value) in attrs {
+                print_porcelain("attr", &[&state.entity_id, key, &value.to_string()]);
+            }
+        }
+        return Ok(());
+    }
 
     output_for_format(ctx, &state, || {
         println!("Entity: {}", state.entity_id);
-        println!("State:  {}", state.state);
+        println!("State:  {}", display_state(&state, ctx.global.units));
         println!();
         println!("Attributes:");
         if let Some(attrs) = state.attributes.as_object() {
@@ -192,34 +356,493 @@ fn map_state_to_service(entity_id: &str, desired_state: &str) -> Option<(String,
     }
 }
 
-async fn history(ctx: &RuntimeContext, entity_id: &str, since: &str) -> Result<()> {
+async fn rename(ctx: &RuntimeContext, input: &str, name: &str) -> Result<()> {
+    let mut cache_manager = CacheManager::new(ctx)?;
+    cache_manager.ensure_entities().await?;
+    let entity = resolve_entity(ctx, cache_manager.cache(), input, None)?;
+
+    let ws = WsClient::connect(ctx).await?;
+    let request = UpdateEntityRequest::new(entity.entity_id.clone()).with_name(name.to_string());
+    let entry = update_registry(&ws, &mut cache_manager, &request).await?;
+
+    if ctx.is_machine_output() {
+        return print_output(ctx, &entry);
+    }
+    println!("Renamed {} to '{name}'", entity.entity_id);
+    Ok(())
+}
+
+async fn set_area(ctx: &RuntimeContext, input: &str, area: &str) -> Result<()> {
+    let mut cache_manager = CacheManager::new(ctx)?;
+    cache_manager.ensure_entities().await?;
+    cache_manager.ensure_areas().await?;
+    let entity = resolve_entity(ctx, cache_manager.cache(), input, None)?;
+    let area = resolve_area(ctx, cache_manager.cache(), area)?;
+
+    let ws = WsClient::connect(ctx).await?;
+    let request =
+        UpdateEntityRequest::new(entity.entity_id.clone()).with_area_id(area.area_id.clone());
+    let entry = update_registry(&ws, &mut cache_manager, &request).await?;
+
+    if ctx.is_machine_output() {
+        return print_output(ctx, &entry);
+    }
+    println!(
+        "Entity '{}' assigned to area '{}'",
+        entity.entity_id, area.name
+    );
+    Ok(())
+}
+
+async fn edit_aliases(ctx: &RuntimeContext, input: &str, alias: &str, add: bool) -> Result<()> {
+    let mut cache_manager = CacheManager::new(ctx)?;
+    cache_manager.ensure_entities().await?;
+    let entity = resolve_entity(ctx, cache_manager.cache(), input, None)?;
+
+    let ws = WsClient::connect(ctx).await?;
+    let current = ws.get_entity_registry_entry(&entity.entity_id).await?;
+    let aliases = if add {
+        with_alias(&current.aliases, alias)
+    } else {
+        without_alias(&current.aliases, alias)
+    };
+    let entry = match aliases {
+        Some(aliases) => {
+            let request = UpdateEntityRequest::new(entity.entity_id.clone()).with_aliases(aliases);
+            update_registry(&ws, &mut cache_manager, &request).await?
+        }
+        None if add => current,
+        None => {
+            return Err(
+                Error::NotFound(format!("{} has no alias '{alias}'", entity.entity_id)).into(),
+            )
+        }
+    };
+
+    if ctx.is_machine_output() {
+        return print_output(ctx, &entry);
+    }
+    if entry.aliases.is_empty() {
+        println!("{} has no aliases", entity.entity_id);
+    } else {
+        println!("{} aliases: {}", entity.entity_id, entry.aliases.join(", "));
+    }
+    Ok(())
+}
+
+/// Send a registry update and bring the cached entity up to date
+async fn update_registry(
+    ws: &WsClient,
+    cache_manager: &mut CacheManager<'_>,
+    request: &UpdateEntityRequest,
+) -> Result<EntityRegistryEntry> {
+    let entry = ws.update_entity_registry(request).await?;
+    if let Err(e) = cache_manager.refresh_entity(&entry).await {
+        log::debug!("Could not update cached {}: {e:#}", entry.entity_id);
+    }
+    Ok(entry)
+}
+
+/// The registry flag `entity disable/enable/hide/unhide` sets or clears
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RegistryFlag {
+    Disable,
+    Enable,
+    Hide,
+    Unhide,
+}
+
+impl RegistryFlag {
+    /// Imperative and past tense, e.g. ("Disable", "Disabled")
+    fn verb(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Disable => ("Disable", "Disabled"),
+            Self::Enable => ("Enable", "Enabled"),
+            Self::Hide => ("Hide", "Hidden"),
+            Self::Unhide => ("Unhide", "Unhidden"),
+        }
+    }
+
+    /// Whether `entry` is already how this change would leave it
+    fn is_applied(self, entry: &EntityRegistryEntry) -> bool {
+        match self {
+            Self::Disable => entry.disabled_by.is_some(),
+            Self::Enable => entry.disabled_by.is_none(),
+            Self::Hide => entry.hidden_by.is_some(),
+            Self::Unhide => entry.hidden_by.is_none(),
+        }
+    }
+
+    fn request(self, entity_id: &str) -> UpdateEntityRequest {
+        let request = UpdateEntityRequest::new(entity_id.to_string());
+        match self {
+            Self::Disable => request.with_disabled(true),
+            Self::Enable => request.with_disabled(false),
+            Self::Hide => request.with_hidden(true),
+            Self::Unhide => request.with_hidden(false),
+        }
+    }
+}
+
+async fn set_flag(ctx: &RuntimeContext, flag: RegistryFlag, args: &EntityFlagArgs) -> Result<()> {
+    let (verb, done) = flag.verb();
+    let ws = WsClient::connect(ctx).await?;
+    let registry = ws.list_entity_registry().await?;
+
+    let (targets, scope) = if args.by_integration {
+        let entries = HassClient::new(ctx)?.get_config_entries().await?;
+        let entry = resolve_config_entry(ctx, &entries, &args.target)?;
+        let targets: Vec<&EntityRegistryEntry> = registry
+            .iter()
+            .filter(|e| e.config_entry_id.as_deref() == Some(entry.entry_id.as_str()))
+            .collect();
+        (targets, format!("{} ({})", entry.title, entry.domain))
+    } else {
+        let entry = find_registry_entry(ctx, &registry, &args.target).await?;
+        (vec![entry], entry.entity_id.clone())
+    };
+    let entity_ids: Vec<String> = targets
+        .iter()
+        .filter(|e| !flag.is_applied(e))
+        .map(|e| e.entity_id.clone())
+        .collect();
+
+    if entity_ids.is_empty() || args.dry_run {
+        return output_for_format(ctx, &entity_ids, || {
+            if entity_ids.is_empty() {
+                println!("Nothing to do: {scope} is already {}", done.to_lowercase());
+                return Ok(());
+            }
+            println!("Would {}:", verb.to_lowercase());
+            for entity_id in &entity_ids {
+                println!("  {entity_id}");
+            }
+            Ok(())
+        });
+    }
+    if args.by_integration {
+        confirm(
+            &format!("{verb} {} entities of {scope}?", entity_ids.len()),
+            &entity_ids,
+            args.yes,
+        )?;
+    }
+
+    let mut cache_manager = CacheManager::new(ctx)?;
+    let progress = progress::bar(ctx, entity_ids.len() as u64, format!("{verb} {scope}"));
+    let mut updated = Vec::with_capacity(entity_ids.len());
+    for entity_id in &entity_ids {
+        progress.inc(1);
+        let request = flag.request(entity_id);
+        // Disabled entities lose their state, so they leave the cache instead
+        let entry = if flag == RegistryFlag::Disable {
+            ws.update_entity_registry(&request).await
+        } else {
+            update_registry(&ws, &mut cache_manager, &request).await
+        }
+        .with_context(|| format!("{} {entity_id}", verb.to_lowercase()))?;
+        updated.push(entry);
+    }
+    progress.finish_and_clear();
+    if flag == RegistryFlag::Disable {
+        let cache = cache_manager.cache_mut();
+        for entity_id in &entity_ids {
+            cache.remove_entity(entity_id);
+        }
+        if let Err(e) = cache.save() {
+            log::debug!("Could not save entity cache: {e:#}");
+        }
+    }
+
+    if ctx.is_machine_output() {
+        return print_output(ctx, &updated);
+    }
+    if args.by_integration {
+        println!("{done} {} entities of {scope}", updated.len());
+    } else {
+        println!("{done} {scope}");
+    }
+    if flag == RegistryFlag::Enable {
+        println!("Home Assistant adds them back once the integration reloads (about 30 seconds)");
+    }
+    Ok(())
+}
+
+/// The registry entry `input` names by entity ID or registry name, which
+/// also finds disabled entities (they have no state to resolve against),
+/// falling back to the usual fuzzy resolution
+async fn find_registry_entry<'a>(
+    ctx: &RuntimeContext,
+    registry: &'a [EntityRegistryEntry],
+    input: &str,
+) -> Result<&'a EntityRegistryEntry> {
+    if let Some(entry) = registry.iter().find(|e| e.entity_id == input) {
+        return Ok(entry);
+    }
+    let named: Vec<&EntityRegistryEntry> = registry
+        .iter()
+        .filter(|e| {
+            e.name
+                .as_deref()
+                .or(e.original_name.as_deref())
+                .is_some_and(|name| name.eq_ignore_ascii_case(input))
+        })
+        .collect();
+    match named.as_slice() {
+        [entry] => return Ok(entry),
+        [] => {}
+        _ => {
+            return Err(Error::Ambiguous {
+                kind: "entity".to_string(),
+                input: input.to_string(),
+                candidates: named.iter().map(|e| e.entity_id.clone()).collect(),
+            }
+            .into())
+        }
+    }
+
+    let mut cache_manager = CacheManager::new(ctx)?;
+    cache_manager.ensure_entities().await?;
+    let entity = resolve_entity(ctx, cache_manager.cache(), input, None)?;
+    registry
+        .iter()
+        .find(|e| e.entity_id == entity.entity_id)
+        .ok_or_else(|| {
+            Error::NotFound(format!(
+                "{} is not in the entity registry",
+                entity.entity_id
+            ))
+            .into()
+        })
+}
+
+/// `aliases` plus `alias`, or None when it is already there (case-insensitive)
+fn with_alias(aliases: &[String], alias: &str) -> Option<Vec<String>> {
+    if aliases.iter().any(|a| a.eq_ignore_ascii_case(alias)) {
+        return None;
+    }
+    let mut updated = aliases.to_vec();
+    updated.push(alias.to_string());
+    Some(updated)
+}
+
+/// `aliases` without `alias`, or None when it isn't there (case-insensitive)
+fn without_alias(aliases: &[String], alias: &str) -> Option<Vec<String>> {
+    let updated: Vec<String> = aliases
+        .iter()
+        .filter(|a| !a.eq_ignore_ascii_case(alias))
+        .cloned()
+        .collect();
+    (updated.len() < aliases.len()).then_some(updated)
+}
+
+async fn history(
+    ctx: &RuntimeContext,
+    entity_id: &str,
+    since: TimeSpec,
+    until: Option<TimeSpec>,
+) -> Result<()> {
+    validate::entity_ids(ctx, &[entity_id.to_string()]).await?;
     let client = HassClient::new(ctx)?;
 
-    // Parse duration string (e.g., "2h", "1d", "30m")
-    let duration = parse_duration(since)?;
-    let start_time = Utc::now() - duration;
+    let now = Local::now();
+    let start_time = since.before(now).with_timezone(&Utc);
     let start_str = start_time.format("%Y-%m-%dT%H:%M:%S").to_string();
+    let end_str = until.map(|until| until.before(now).with_timezone(&Utc).to_rfc3339());
 
-    let history = client.get_history(entity_id, &start_str).await?;
+    let spinner = progress::spinner(ctx, format!("Fetching history of {entity_id}"));
+    let history = match &end_str {
+        Some(end) => {
+            client
+                .get_history_window(&[entity_id.to_string()], &start_str, end)
+                .await?
+        }
+        None => client.get_history(entity_id, &start_str).await?,
+    };
+    spinner.finish_and_clear();
 
     output_for_format(ctx, &history, || {
         if history.is_empty() || history[0].is_empty() {
-            println!("No history found for {entity_id} in the last {since}");
+            println!("No history found for {entity_id} since {since}");
         } else {
-            let rows: Vec<EntityRow> = history[0].iter().map(EntityRow::from).collect();
-            print_table(ctx, &rows)?;
+            let rows: Vec<EntityRow> = history[0]
+                .iter()
+                .map(|s| EntityRow::new(s, ctx.global.units))
+                .collect();
+            let hidden = hidden_columns(
+                ctx.global.columns.as_deref(),
+                ENTITY_COLUMNS,
+                DEFAULT_ENTITY_COLUMNS,
+            )?;
+            print_table_without(ctx, &rows, &hidden)?;
         }
         Ok(())
     })
 }
 
-async fn watch(ctx: &RuntimeContext, entity_ids: &[String]) -> Result<()> {
-    println!("Watching entities: {}", entity_ids.join(", "));
-    println!("Press Ctrl+C to stop\n");
+/// Numeric thresholds for `entity watch`
+struct Thresholds {
+    above: Option<Quantity>,
+    below: Option<Quantity>,
+}
+
+impl Thresholds {
+    fn is_empty(&self) -> bool {
+        self.above.is_none() && self.below.is_none()
+    }
+
+    /// Describe how `state` matches, or None if it doesn't
+    fn check(&self, state: &EntityState, units: Option<UnitSystem>) -> Result<Option<String>> {
+        if numeric_value(state, None, units)?.is_none() {
+            return Ok(None);
+        }
+        let compare = |threshold: &Option<Quantity>, exceeds: fn(f64, f64) -> bool| {
+            threshold
+                .as_ref()
+                .map(|q| {
+                    let value = numeric_value(state, q.unit.as_deref(), units)?;
+                    Ok::<_, anyhow::Error>(value.is_some_and(|v| exceeds(v, q.value)))
+                })
+                .transpose()
+        };
+        let is_above = compare(&self.above, |v, limit| v > limit)?;
+        let is_below = compare(&self.below, |v, limit| v < limit)?;
+
+        let matched = match (is_above, is_below) {
+            (Some(a), Some(b)) if self.outside_range() => a || b,
+            (Some(a), Some(b)) => a && b,
+            (Some(a), None) => a,
+            (None, Some(b)) => b,
+            (None, None) => false,
+        };
+        if !matched {
+            return Ok(None);
+        }
+
+        let mut reasons = Vec::new();
+        if let (Some(true), Some(q)) = (is_above, &self.above) {
+            reasons.push(format!("above {q}"));
+        }
+        if let (Some(true), Some(q)) = (is_below, &self.below) {
+            reasons.push(format!("below {q}"));
+        }
+        Ok(Some(reasons.join(" and ")))
+    }
+
+    /// `--above 30 --below 10` matches outside 10..30 rather than nothing
+    fn outside_range(&self) -> bool {
+        match (&self.above, &self.below) {
+            (Some(above), Some(below)) => above.value >= below.value,
+            _ => false,
+        }
+    }
+}
+
+async fn watch(
+    ctx: &RuntimeContext,
+    entity_ids: &[String],
+    thresholds: &Thresholds,
+    exit_on_match: bool,
+    changes: &[String],
+    filter: &AttributeFilter<'_>,
+    stats: bool,
+) -> Result<()> {
+    let room_ids;
+    let entity_ids = match room_entity_ids(ctx).await? {
+        Some(room) if entity_ids.is_empty() => {
+            let mut ids: Vec<String> = room.into_iter().collect();
+            ids.sort();
+            room_ids = ids;
+            if room_ids.is_empty() {
+                anyhow::bail!("No entities in that room");
+            }
+            &room_ids
+        }
+        _ => {
+            validate::entity_ids(ctx, entity_ids).await?;
+            entity_ids
+        }
+    };
+    let units = ctx.global.units;
+    let names = EntityNames::load(ctx);
+
+    // The current states count too, so a threshold that is already met
+    // doesn't wait for the next change
+    if !thresholds.is_empty() {
+        let client = HassClient::new(ctx)?;
+        for entity_id in entity_ids {
+            let state = client.get_state(entity_id).await?;
+            if let Some(reason) = thresholds.check(&state, units)? {
+                let data = json!({ "entity_id": entity_id, "new_state": state });
+                output_for_format(ctx, &data, || {
+                    println!(
+                        "{}: {} ({reason})",
+                        names.display(entity_id),
+                        display_state(&state, units)
+                    );
+                    Ok(())
+                })?;
+                if exit_on_match {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    if !ctx.is_machine_output() {
+        let watched: Vec<String> = entity_ids.iter().map(|id| names.display(id)).collect();
+        println!("Watching entities: {}", watched.join(", "));
+        if !changes.is_empty() {
+            println!("Attributes: {}", changes.join(", "));
+        }
+        println!("Press Ctrl+C to stop\n");
+    }
 
     let output_format = ctx.output_format();
+    let mut matched = false;
+    // Entity and attribute pairs already reported as cut
+    let mut noted_cuts: HashSet<(String, String)> = HashSet::new();
+
+    websocket::watch_entities(ctx, entity_ids, stats, |data| {
+        let limited;
+        let data = if filter.is_empty() {
+            data
+        } else {
+            let mut copy = data.clone();
+            let entity_id = data["entity_id"].as_str().unwrap_or("?");
+            for side in ["old_state", "new_state"] {
+                let Some(attributes) = copy.get_mut(side).and_then(|s| s.get_mut("attributes"))
+                else {
+                    continue;
+                };
+                let cut: Vec<CutAttribute> =
+                    limit_attributes(attributes, filter.keys, filter.max_size)
+                        .into_iter()
+                        .filter(|c| noted_cuts.insert((entity_id.to_string(), c.key.clone())))
+                        .collect();
+                if let Some(max) = filter.max_size.filter(|_| !cut.is_empty()) {
+                    warn(ctx, describe_cut(entity_id, &cut, max));
+                }
+            }
+            limited = copy;
+            &limited
+        };
+        if !changes.is_empty() {
+            print_attribute_changes(data, changes, output_format, &names)?;
+            return Ok(true);
+        }
+
+        let new_state: Option<EntityState> = data
+            .get("new_state")
+            .filter(|v| !v.is_null())
+            .map(|v| serde_json::from_value(v.clone()))
+            .transpose()
+            .context("parsing new state")?;
+        let reason = match &new_state {
+            Some(state) => thresholds.check(state, units)?,
+            None => None,
+        };
 
-    websocket::watch_entities(ctx, entity_ids, |data| {
         match output_format {
             OutputFormat::Json => {
                 println!("{}", serde_json::to_string(data)?);
@@ -228,10 +851,11 @@ async fn watch(ctx: &RuntimeContext, entity_ids: &[String]) -> Result<()> {
                 println!("{}", serde_yaml::to_string(data)?);
             }
             OutputFormat::Table | OutputFormat::Auto => {
-                let entity_id = data
-                    .get("entity_id")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("?");
+                let entity_id = names.display(
+                    data.get("entity_id")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("?"),
+                );
                 let new_state = data
                     .get("new_state")
                     .and_then(|v| v.get("state"))
@@ -243,33 +867,169 @@ async fn watch(ctx: &RuntimeContext, entity_ids: &[String]) -> Result<()> {
                     .and_then(|v| v.as_str())
                     .unwrap_or("?");
 
-                println!("{entity_id}: {old_state} -> {new_state}");
+                let replayed = if data["replayed"] == true {
+                    " [replayed]"
+                } else {
+                    ""
+                };
+                match &reason {
+                    Some(reason) => {
+                        println!("{entity_id}: {old_state} -> {new_state} ({reason}){replayed}")
+                    }
+                    None => println!("{entity_id}: {old_state} -> {new_state}{replayed}"),
+                }
             }
         }
-        Ok(true) // Continue watching
+
+        matched = reason.is_some();
+        Ok(!(matched && exit_on_match))
     })
-    .await
+    .await?;
+
+    if exit_on_match && !matched {
+        bail!("Stopped before any state matched");
+    }
+    Ok(())
+}
+
+/// Which attributes `watch` reports, from --attrs and --max-attr-size
+struct AttributeFilter<'a> {
+    /// Attributes to keep (all when empty)
+    keys: &'a [String],
+    max_size: Option<usize>,
+}
+
+impl AttributeFilter<'_> {
+    fn is_empty(&self) -> bool {
+        self.keys.is_empty() && self.max_size.is_none()
+    }
+}
+
+/// An attribute value replaced by a note because of --max-attr-size
+#[derive(Debug, PartialEq)]
+struct CutAttribute {
+    key: String,
+    /// Size of the value as JSON, in bytes
+    size: usize,
+}
+
+/// Keep only the attributes in `keys` (all when empty) and replace values
+/// larger than `max_size` bytes as JSON with a note of their size
+fn limit_attributes(
+    attributes: &mut Value,
+    keys: &[String],
+    max_size: Option<usize>,
+) -> Vec<CutAttribute> {
+    let Some(attributes) = attributes.as_object_mut() else {
+        return Vec::new();
+    };
+    if !keys.is_empty() {
+        attributes.retain(|key, _| keys.contains(key));
+    }
+    let Some(max_size) = max_size else {
+        return Vec::new();
+    };
+    let mut cut = Vec::new();
+    for (key, value) in attributes.iter_mut() {
+        let size = value.to_string().len();
+        if size > max_size {
+            *value = Value::String(format!("<cut, {}>", format_bytes(size as u64)));
+            cut.push(CutAttribute {
+                key: key.clone(),
+                size,
+            });
+        }
+    }
+    cut
+}
+
+/// "weather.home: cut attributes over 1.0 KB: forecast (1.2 MB); ..."
+fn describe_cut(entity_id: &str, cut: &[CutAttribute], max_size: usize) -> String {
+    let cut: Vec<String> = cut
+        .iter()
+        .map(|c| format!("{} ({})", c.key, format_bytes(c.size as u64)))
+        .collect();
+    format!(
+        "{entity_id}: cut attributes over {}: {}; use --attrs to pick attributes",
+        format_bytes(max_size as u64),
+        cut.join(", ")
+    )
+}
+
+/// One attribute that differs between two states
+#[derive(Debug, PartialEq, Serialize)]
+struct AttributeChange {
+    attribute: String,
+    old: Value,
+    new: Value,
 }
 
-fn parse_duration(s: &str) -> Result<Duration> {
-    let duration =
-        humantime::parse_duration(s).with_context(|| format!("parsing duration '{s}'"))?;
+/// The `keys` whose values differ between the old and new attributes; a
+/// missing attribute counts as null
+fn diff_attributes(old: &Value, new: &Value, keys: &[String]) -> Vec<AttributeChange> {
+    keys.iter()
+        .filter_map(|key| {
+            let before = old.get(key).cloned().unwrap_or(Value::Null);
+            let after = new.get(key).cloned().unwrap_or(Value::Null);
+            (before != after).then(|| AttributeChange {
+                attribute: key.clone(),
+                old: before,
+                new: after,
+            })
+        })
+        .collect()
+}
 
-    Ok(Duration::from_std(duration)?)
+/// Print a state_changed event's changes to the watched attributes, if any
+fn print_attribute_changes(
+    data: &Value,
+    keys: &[String],
+    format: OutputFormat,
+    names: &EntityNames,
+) -> Result<()> {
+    let changes = diff_attributes(
+        &data["old_state"]["attributes"],
+        &data["new_state"]["attributes"],
+        keys,
+    );
+    if changes.is_empty() {
+        return Ok(());
+    }
+    let entity_id = data["entity_id"].as_str().unwrap_or("?");
+    let record = json!({
+        "entity_id": entity_id,
+        "changes": changes,
+        "last_updated": data["new_state"]["last_updated"],
+        "replayed": data["replayed"] == true,
+    });
+
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string(&record)?),
+        OutputFormat::Yaml => println!("{}", serde_yaml::to_string(&record)?),
+        OutputFormat::Table | OutputFormat::Auto => {
+            let described: Vec<String> = changes
+                .iter()
+                .map(|c| format!("{} {} -> {}", c.attribute, c.old, c.new))
+                .collect();
+            let replayed = if data["replayed"] == true {
+                " [replayed]"
+            } else {
+                ""
+            };
+            println!(
+                "{}: {}{replayed}",
+                names.display(entity_id),
+                described.join(", ")
+            );
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_parse_duration() {
-        assert!(parse_duration("1h").is_ok());
-        assert!(parse_duration("2h30m").is_ok());
-        assert!(parse_duration("1d").is_ok());
-        assert!(parse_duration("invalid").is_err());
-    }
-
     #[test]
     fn test_entity_row_from_state() {
         let state = EntityState {
@@ -281,12 +1041,125 @@ mod tests {
             context: serde_json::Value::Null,
         };
 
-        let row = EntityRow::from(&state);
+        let row = EntityRow::new(&state, None);
         assert_eq!(row.entity_id, "light.kitchen");
         assert_eq!(row.state, "on");
         assert_eq!(row.friendly_name, "Kitchen Light");
     }
 
+    #[test]
+    fn test_edit_aliases() {
+        let aliases = vec!["Ceiling".to_string(), "Big light".to_string()];
+        assert_eq!(
+            with_alias(&aliases, "Main light"),
+            Some(vec![
+                "Ceiling".to_string(),
+                "Big light".to_string(),
+                "Main light".to_string()
+            ])
+        );
+        assert_eq!(with_alias(&aliases, "ceiling"), None);
+        assert_eq!(
+            without_alias(&aliases, "big LIGHT"),
+            Some(vec!["Ceiling".to_string()])
+        );
+        assert_eq!(without_alias(&aliases, "Lamp"), None);
+    }
+
+    #[test]
+    fn test_registry_flag() {
+        let entry: EntityRegistryEntry = serde_json::from_value(json!({
+            "entity_id": "sensor.signal",
+            "disabled_by": "integration",
+        }))
+        .unwrap();
+        assert!(RegistryFlag::Disable.is_applied(&entry));
+        assert!(!RegistryFlag::Enable.is_applied(&entry));
+        assert!(RegistryFlag::Unhide.is_applied(&entry));
+
+        let enable = RegistryFlag::Enable.request("sensor.signal");
+        assert_eq!(enable.disabled_by, Some(None));
+        assert_eq!(enable.hidden_by, None);
+        let hide = RegistryFlag::Hide.request("sensor.signal");
+        assert_eq!(hide.hidden_by, Some(Some("user".to_string())));
+    }
+
+    #[test]
+    fn test_diff_attributes() {
+        let old = json!({"brightness": 120, "color_temp": 370, "friendly_name": "Desk"});
+        let new = json!({"brightness": 180, "color_temp": 370});
+        let keys = vec![
+            "brightness".to_string(),
+            "color_temp".to_string(),
+            "rgb_color".to_string(),
+        ];
+        assert_eq!(
+            diff_attributes(&old, &new, &keys),
+            vec![AttributeChange {
+                attribute: "brightness".to_string(),
+                old: json!(120),
+                new: json!(180),
+            }]
+        );
+        assert!(diff_attributes(&old, &new, &["friendly_name".to_string()]).len() == 1);
+        assert!(diff_attributes(&old, &old, &keys).is_empty());
+    }
+
+    #[test]
+    fn test_limit_attributes() {
+        let forecast: Vec<Value> = (0..100).map(|i| json!({"hour": i, "temp": 20})).collect();
+        let mut attributes = json!({
+            "friendly_name": "Home",
+            "temperature": 21,
+            "forecast": forecast
+        });
+        let cut = limit_attributes(&mut attributes, &[], Some(100));
+        assert_eq!(cut.len(), 1);
+        assert_eq!(cut[0].key, "forecast");
+        assert_eq!(attributes["forecast"], json!("<cut, 2.1 KB>"));
+        assert_eq!(attributes["temperature"], json!(21));
+        assert_eq!(
+            describe_cut("weather.home", &cut, 100),
+            "weather.home: cut attributes over 100 B: forecast (2.1 KB); use --attrs to pick attributes"
+        );
+
+        let keys = vec!["temperature".to_string()];
+        assert!(limit_attributes(&mut attributes, &keys, None).is_empty());
+        assert_eq!(attributes, json!({"temperature": 21}));
+    }
+
+    #[test]
+    fn test_thresholds_check() {
+        let sensor = |state: &str| EntityState {
+            entity_id: "sensor.oven".to_string(),
+            state: state.to_string(),
+            attributes: serde_json::json!({"unit_of_measurement": "°C"}),
+            last_changed: String::new(),
+            last_updated: String::new(),
+            context: serde_json::Value::Null,
+        };
+        let thresholds = |above: Option<&str>, below: Option<&str>| Thresholds {
+            above: above.map(|a| parse_quantity(a).unwrap()),
+            below: below.map(|b| parse_quantity(b).unwrap()),
+        };
+
+        let oven = thresholds(Some("392F"), None);
+        assert_eq!(
+            oven.check(&sensor("201"), None).unwrap().as_deref(),
+            Some("above 392 °F")
+        );
+        assert!(oven.check(&sensor("199"), None).unwrap().is_none());
+        assert!(oven.check(&sensor("unavailable"), None).unwrap().is_none());
+
+        let outside = thresholds(Some("30"), Some("10"));
+        assert!(outside.check(&sensor("5"), None).unwrap().is_some());
+        assert!(outside.check(&sensor("20"), None).unwrap().is_none());
+
+        let inside = thresholds(Some("10"), Some("30"));
+        assert!(inside.check(&sensor("20"), None).unwrap().is_some());
+        assert!(inside.check(&sensor("35"), None).unwrap().is_none());
+    }
+
     #[test]
     fn test_map_state_to_service_light() {
         assert_eq!(
@@ -1,31 +1,80 @@
 //! Event command implementations
 
-use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
 
 use crate::api::HassClient;
-use crate::cli::{EventCommand, OutputFormat};
+use crate::cli::{EventCommand, EventOrigin, OutputFormat};
 use crate::config::RuntimeContext;
-use crate::output::{get_json_input, output_for_format};
-use crate::websocket;
+use crate::names::EntityNames;
+use crate::output::{get_json_input, output_for_format, warn};
+use crate::websocket::{self, User, WsClient};
 
 pub async fn run(ctx: &RuntimeContext, command: EventCommand) -> Result<()> {
     match command {
-        EventCommand::Watch { event_type } => watch(ctx, event_type.as_deref()).await,
-        EventCommand::Fire { event_type, data } => fire(ctx, &event_type, data.as_deref()).await,
+        EventCommand::Watch {
+            event_type,
+            origin,
+            user,
+            stats,
+        } => watch(ctx, event_type.as_deref(), origin, user.as_deref(), stats).await,
+        EventCommand::Fire {
+            event_type,
+            data,
+            repeat,
+            interval,
+        } => fire(ctx, &event_type, data.as_deref(), repeat, interval).await,
     }
 }
 
-async fn watch(ctx: &RuntimeContext, event_type: Option<&str>) -> Result<()> {
+async fn watch(
+    ctx: &RuntimeContext,
+    event_type: Option<&str>,
+    origin: Option<EventOrigin>,
+    user: Option<&str>,
+    stats: bool,
+) -> Result<()> {
+    // User names attribute events to people; listing them needs admin rights,
+    // so only --user makes a failure fatal
+    let users = match WsClient::connect(ctx).await?.list_users().await {
+        Ok(users) => users,
+        Err(e) if user.is_some() => return Err(e.context("listing users to resolve --user")),
+        Err(e) => {
+            log::debug!("Not attributing events to users: {e:#}");
+            Vec::new()
+        }
+    };
+    let user_filter = user.map(|input| find_user(&users, input)).transpose()?;
+    let user_names: HashMap<&str, String> = users
+        .iter()
+        .map(|u| (u.id.as_str(), user_name(u)))
+        .collect();
+
     if let Some(et) = event_type {
         println!("Watching events of type: {et}");
     } else {
         println!("Watching all events");
     }
+    if let Some(u) = user_filter {
+        println!("Only events caused by {}", user_name(u));
+    }
     println!("Press Ctrl+C to stop\n");
 
     let output_format = ctx.output_format();
+    let names = EntityNames::load(ctx);
+
+    websocket::watch_events(ctx, event_type, stats, |event| {
+        let event_user = event.context.get("user_id").and_then(|v| v.as_str());
+        if origin.is_some_and(|o| !event.origin.eq_ignore_ascii_case(origin_name(o))) {
+            return Ok(true);
+        }
+        if user_filter.is_some_and(|u| event_user != Some(u.id.as_str())) {
+            return Ok(true);
+        }
 
-    websocket::watch_events(ctx, event_type, |event| {
         match output_format {
             OutputFormat::Json => {
                 println!("{}", serde_json::to_string(event)?);
@@ -34,8 +83,14 @@ async fn watch(ctx: &RuntimeContext, event_type: Option<&str>) -> Result<()> {
                 println!("{}", serde_yaml::to_string(event)?);
             }
             OutputFormat::Table | OutputFormat::Auto => {
+                let by = event_user
+                    .map(|id| {
+                        let name = user_names.get(id).map_or(id, String::as_str);
+                        format!(" by {name}")
+                    })
+                    .unwrap_or_default();
                 println!(
-                    "[{}] {} ({})",
+                    "[{}] {} ({}){by}",
                     event
                         .time_fired
                         .split('.')
@@ -47,7 +102,7 @@ async fn watch(ctx: &RuntimeContext, event_type: Option<&str>) -> Result<()> {
                 if !event.data.is_null() && event.data != serde_json::json!({}) {
                     // Print compact data summary
                     if let Some(entity_id) = event.data.get("entity_id").and_then(|v| v.as_str()) {
-                        println!("  entity: {entity_id}");
+                        println!("  entity: {}", names.display(entity_id));
                     }
                     if let Some(domain) = event.data.get("domain").and_then(|v| v.as_str()) {
                         println!("  domain: {domain}");
@@ -63,7 +118,61 @@ async fn watch(ctx: &RuntimeContext, event_type: Option<&str>) -> Result<()> {
     .await
 }
 
-async fn fire(ctx: &RuntimeContext, event_type: &str, data_input: Option<&str>) -> Result<()> {
+/// The value Home Assistant puts in an event's `origin`
+fn origin_name(origin: EventOrigin) -> &'static str {
+    match origin {
+        EventOrigin::Local => "LOCAL",
+        EventOrigin::Remote => "REMOTE",
+    }
+}
+
+/// Find a user by ID, or by name or username ignoring case
+fn find_user<'a>(users: &'a [User], input: &str) -> Result<&'a User> {
+    users
+        .iter()
+        .find(|u| u.id == input)
+        .or_else(|| {
+            users.iter().find(|u| {
+                [&u.name, &u.username]
+                    .into_iter()
+                    .flatten()
+                    .any(|n| n.eq_ignore_ascii_case(input))
+            })
+        })
+        .ok_or_else(|| {
+            let known: Vec<String> = users
+                .iter()
+                .filter(|u| !u.system_generated)
+                .map(user_name)
+                .collect();
+            anyhow!("No user '{input}'. Known users: {}", known.join(", "))
+        })
+}
+
+fn user_name(user: &User) -> String {
+    user.name
+        .clone()
+        .or_else(|| user.username.clone())
+        .unwrap_or_else(|| user.id.clone())
+}
+
+/// Outcome of firing an event repeatedly
+#[derive(Debug, Serialize)]
+struct FireSummary {
+    event_type: String,
+    fired: u32,
+    accepted: u32,
+    failed: u32,
+    interval_ms: u128,
+}
+
+async fn fire(
+    ctx: &RuntimeContext,
+    event_type: &str,
+    data_input: Option<&str>,
+    repeat: u32,
+    interval: Duration,
+) -> Result<()> {
     let client = HassClient::new(ctx)?;
 
     // Build data: prefer explicit --data, then piped stdin, otherwise empty object
@@ -73,10 +182,81 @@ async fn fire(ctx: &RuntimeContext, event_type: &str, data_input: Option<&str>)
 
     log::debug!("Firing event {event_type} with data: {data:?}");
 
-    let result = client.fire_event(event_type, &data).await?;
+    if repeat == 1 {
+        let result = client.fire_event(event_type, &data).await?;
+
+        return output_for_format(ctx, &result, || {
+            println!("Event '{event_type}' fired successfully");
+            Ok(())
+        });
+    }
+
+    let mut summary = FireSummary {
+        event_type: event_type.to_string(),
+        fired: 0,
+        accepted: 0,
+        failed: 0,
+        interval_ms: interval.as_millis(),
+    };
 
-    output_for_format(ctx, &result, || {
-        println!("Event '{event_type}' fired successfully");
+    for n in 1..=repeat {
+        if n > 1 {
+            tokio::time::sleep(interval).await;
+        }
+
+        summary.fired += 1;
+        match client.fire_event(event_type, &data).await {
+            Ok(_) => summary.accepted += 1,
+            Err(e) => {
+                summary.failed += 1;
+                warn(ctx, format!("Event {n}/{repeat} failed: {e}"));
+            }
+        }
+    }
+
+    output_for_format(ctx, &summary, || {
+        println!(
+            "Fired '{event_type}' {} times: {} accepted, {} failed",
+            summary.fired, summary.accepted, summary.failed
+        );
         Ok(())
-    })
+    })?;
+
+    if summary.failed > 0 {
+        return Err(anyhow!(
+            "{} of {} events failed",
+            summary.failed,
+            summary.fired
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(id: &str, name: &str, username: Option<&str>) -> User {
+        User {
+            id: id.to_string(),
+            name: Some(name.to_string()),
+            username: username.map(String::from),
+            system_generated: false,
+        }
+    }
+
+    #[test]
+    fn test_find_user() {
+        let users = [
+            user("abc123", "Alice", Some("alice")),
+            user("def456", "Bob", None),
+        ];
+        assert_eq!(find_user(&users, "def456").unwrap().id, "def456");
+        assert_eq!(find_user(&users, "alice").unwrap().id, "abc123");
+        assert_eq!(find_user(&users, "BOB").unwrap().id, "def456");
+
+        let err = find_user(&users, "carol").unwrap_err();
+        assert!(err.to_string().contains("Alice, Bob"));
+    }
 }
@@ -0,0 +1,217 @@
+//! Export command implementations
+//!
+//! `export inventory` joins the cached entities, devices and areas into one
+//! record per entity (plus one per device without entities), with the
+//! integration, manufacturer, model and firmware versions. Rendered as a
+//! markdown table, CSV or JSON for insurance records or network documentation.
+
+use std::collections::HashSet;
+
+use anyhow::Result;
+use serde::Serialize;
+use tabled::builder::Builder;
+use tabled::settings::Style;
+
+use crate::cache::{Cache, CacheManager, CachedDevice};
+use crate::cli::{ExportCommand, InventoryFormat};
+use crate::config::RuntimeContext;
+use crate::output::print_output;
+
+const HEADERS: [&str; 10] = [
+    "area",
+    "device",
+    "manufacturer",
+    "model",
+    "sw_version",
+    "hw_version",
+    "integration",
+    "entity_id",
+    "name",
+    "domain",
+];
+
+#[derive(Debug, Default, Serialize)]
+struct InventoryRecord {
+    area: Option<String>,
+    device: Option<String>,
+    manufacturer: Option<String>,
+    model: Option<String>,
+    sw_version: Option<String>,
+    hw_version: Option<String>,
+    integration: Option<String>,
+    entity_id: Option<String>,
+    name: Option<String>,
+    domain: Option<String>,
+}
+
+impl InventoryRecord {
+    fn with_device(mut self, device: &CachedDevice, cache: &Cache) -> Self {
+        self.device = Some(device_name(device));
+        self.manufacturer = device.manufacturer.clone();
+        self.model = device.model.clone();
+        self.sw_version = device.sw_version.clone();
+        self.hw_version = device.hw_version.clone();
+        if self.area.is_none() {
+            self.area = device
+                .area_id
+                .as_deref()
+                .and_then(|id| cache.get_area(id))
+                .map(|a| a.name.clone());
+        }
+        self
+    }
+
+    /// Cells in [`HEADERS`] order
+    fn cells(&self) -> Vec<String> {
+        [
+            &self.area,
+            &self.device,
+            &self.manufacturer,
+            &self.model,
+            &self.sw_version,
+            &self.hw_version,
+            &self.integration,
+            &self.entity_id,
+            &self.name,
+            &self.domain,
+        ]
+        .into_iter()
+        .map(|cell| cell.clone().unwrap_or_default())
+        .collect()
+    }
+}
+
+pub async fn run(ctx: &RuntimeContext, command: ExportCommand) -> Result<()> {
+    match command {
+        ExportCommand::Inventory { format } => inventory(ctx, format).await,
+    }
+}
+
+async fn inventory(ctx: &RuntimeContext, format: Option<InventoryFormat>) -> Result<()> {
+    let mut cache_manager = CacheManager::new(ctx)?;
+    cache_manager.ensure_entities().await?;
+    cache_manager.ensure_devices().await?;
+    cache_manager.ensure_areas().await?;
+    let records = build_inventory(cache_manager.cache());
+
+    match format {
+        Some(InventoryFormat::Markdown) => print!("{}", to_markdown(&records)),
+        Some(InventoryFormat::Csv) => print!("{}", to_csv(&records)),
+        Some(InventoryFormat::Json) => println!("{}", serde_json::to_string_pretty(&records)?),
+        None if ctx.is_machine_output() => print_output(ctx, &records)?,
+        None => print!("{}", to_markdown(&records)),
+    }
+    Ok(())
+}
+
+/// One record per entity and per device without entities, sorted by area,
+/// device and entity ID
+fn build_inventory(cache: &Cache) -> Vec<InventoryRecord> {
+    let mut records = Vec::new();
+    let mut devices_with_entities = HashSet::new();
+
+    for entity in cache.entities() {
+        let context = cache.entity_context(entity);
+        let record = InventoryRecord {
+            area: context.area.map(|a| a.name),
+            integration: context.integration,
+            entity_id: Some(entity.entity_id.clone()),
+            name: entity.friendly_name.clone(),
+            domain: Some(entity.domain.clone()),
+            ..Default::default()
+        };
+        records.push(match context.device {
+            Some(device) => {
+                devices_with_entities.insert(device.id.clone());
+                record.with_device(&device, cache)
+            }
+            None => record,
+        });
+    }
+
+    for device in cache.devices() {
+        if !devices_with_entities.contains(&device.id) {
+            records.push(InventoryRecord::default().with_device(device, cache));
+        }
+    }
+
+    records.sort_by(|a, b| {
+        (&a.area, &a.device, &a.entity_id).cmp(&(&b.area, &b.device, &b.entity_id))
+    });
+    records
+}
+
+fn device_name(device: &CachedDevice) -> String {
+    device
+        .name_by_user
+        .clone()
+        .or_else(|| device.name.clone())
+        .unwrap_or_else(|| device.id.clone())
+}
+
+fn to_markdown(records: &[InventoryRecord]) -> String {
+    let mut builder = Builder::default();
+    builder.push_record(HEADERS);
+    for record in records {
+        // Pipes would end the cell early
+        builder.push_record(record.cells().iter().map(|c| c.replace('|', "\\|")));
+    }
+    let mut table = builder.build();
+    table.with(Style::markdown());
+    format!("{table}\n")
+}
+
+fn to_csv(records: &[InventoryRecord]) -> String {
+    let mut out = csv_line(HEADERS.iter().map(|h| h.to_string()));
+    for record in records {
+        out.push_str(&csv_line(record.cells().into_iter()));
+    }
+    out
+}
+
+fn csv_line(cells: impl Iterator<Item = String>) -> String {
+    let escaped: Vec<String> = cells
+        .map(|cell| {
+            if cell.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell
+            }
+        })
+        .collect();
+    format!("{}\n", escaped.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(entity_id: &str, name: &str) -> InventoryRecord {
+        InventoryRecord {
+            entity_id: Some(entity_id.to_string()),
+            name: Some(name.to_string()),
+            model: Some("LCT015".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let csv = to_csv(&[record("light.desk", "Desk, \"big\" lamp")]);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], HEADERS.join(","));
+        assert_eq!(
+            lines[1],
+            ",,,LCT015,,,,light.desk,\"Desk, \"\"big\"\" lamp\","
+        );
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let markdown = to_markdown(&[record("light.desk", "Desk | lamp")]);
+        let lines: Vec<&str> = markdown.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("| area"));
+        assert!(lines[2].contains("Desk \\| lamp"));
+    }
+}
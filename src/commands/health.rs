@@ -0,0 +1,215 @@
+//! Health command implementation
+//!
+//! `hmr health` runs `system_health/info` over the WebSocket API, the data
+//! behind Settings > System > Repairs > System information. Integrations answer
+//! slow checks (like reaching the Nabu Casa cloud) asynchronously, so updates
+//! are collected until Home Assistant reports it is finished or the timeout
+//! passes; checks still pending then are shown as such.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use serde::Serialize;
+use serde_json::Value;
+use tabled::Tabled;
+
+use crate::cli::HealthCommand;
+use crate::config::RuntimeContext;
+use crate::output::{output_for_format, print_table};
+use crate::websocket::WsClient;
+
+/// One check of one integration
+#[derive(Debug, Clone, PartialEq, Serialize, Tabled)]
+struct HealthCheck {
+    integration: String,
+    check: String,
+    value: String,
+    status: CheckStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum CheckStatus {
+    Ok,
+    Failed,
+    Pending,
+}
+
+impl std::fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Ok => "ok",
+            Self::Failed => "failed",
+            Self::Pending => "pending",
+        })
+    }
+}
+
+pub async fn run(ctx: &RuntimeContext, command: HealthCommand) -> Result<()> {
+    let ws = WsClient::connect(ctx).await?;
+    let mut events = ws.system_health_info().await?;
+
+    let wait = Duration::from_secs(ctx.timeout());
+    // integration -> check -> raw value
+    let mut info: BTreeMap<String, BTreeMap<String, Value>> = BTreeMap::new();
+    let deadline = tokio::time::sleep(wait);
+    tokio::pin!(deadline);
+    loop {
+        let event = tokio::select! {
+            event = events.next_raw() => {
+                event.ok_or_else(|| anyhow!("Connection closed before system health finished"))?
+            }
+            _ = &mut deadline => {
+                log::debug!("System health still pending after {}s", wait.as_secs());
+                break;
+            }
+        };
+        if !apply_event(&mut info, &event) {
+            break;
+        }
+    }
+
+    let checks: Vec<HealthCheck> = health_checks(&info)
+        .into_iter()
+        .filter(|c| command.domains.is_empty() || command.domains.contains(&c.integration))
+        .filter(|c| !command.failed || c.status == CheckStatus::Failed)
+        .collect();
+
+    output_for_format(ctx, &checks, || {
+        if checks.is_empty() {
+            println!(
+                "{}",
+                if command.failed {
+                    "No failed checks"
+                } else {
+                    "No system health info"
+                }
+            );
+            return Ok(());
+        }
+        print_table(ctx, &checks)
+    })?;
+
+    if command.failed && !checks.is_empty() {
+        bail!("{} system health checks failed", checks.len());
+    }
+    Ok(())
+}
+
+/// Fold one `system_health/info` payload into `info`; false once finished
+fn apply_event(info: &mut BTreeMap<String, BTreeMap<String, Value>>, event: &Value) -> bool {
+    match event["type"].as_str() {
+        Some("initial") => {
+            for (domain, domain_info) in event["data"].as_object().into_iter().flatten() {
+                let checks = domain_info["info"]
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect();
+                info.insert(domain.clone(), checks);
+            }
+            true
+        }
+        Some("update") => {
+            let (Some(domain), Some(key)) = (event["domain"].as_str(), event["key"].as_str())
+            else {
+                return true;
+            };
+            let value = if event["success"] == true {
+                event["data"].clone()
+            } else {
+                serde_json::json!({
+                    "type": "failed",
+                    "error": event["error"]["msg"].as_str().unwrap_or("unknown error"),
+                })
+            };
+            info.entry(domain.to_string())
+                .or_default()
+                .insert(key.to_string(), value);
+            true
+        }
+        Some("finish") => false,
+        _ => true,
+    }
+}
+
+fn health_checks(info: &BTreeMap<String, BTreeMap<String, Value>>) -> Vec<HealthCheck> {
+    info.iter()
+        .flat_map(|(domain, checks)| {
+            checks.iter().map(move |(key, value)| {
+                let (value, status) = describe(value);
+                HealthCheck {
+                    integration: domain.clone(),
+                    check: key.clone(),
+                    value,
+                    status,
+                }
+            })
+        })
+        .collect()
+}
+
+/// Display text and status for a check value
+fn describe(value: &Value) -> (String, CheckStatus) {
+    match value {
+        // Flags like `remote_enabled` are settings, not verdicts
+        Value::Bool(flag) => (
+            if *flag { "yes" } else { "no" }.to_string(),
+            CheckStatus::Ok,
+        ),
+        Value::String(s) => (s.clone(), CheckStatus::Ok),
+        Value::Null => (String::new(), CheckStatus::Ok),
+        Value::Object(obj) => match obj.get("type").and_then(Value::as_str) {
+            Some("pending") => ("pending".to_string(), CheckStatus::Pending),
+            Some("failed") => (
+                obj.get("error")
+                    .and_then(Value::as_str)
+                    .unwrap_or("failed")
+                    .to_string(),
+                CheckStatus::Failed,
+            ),
+            _ => (value.to_string(), CheckStatus::Ok),
+        },
+        other => (other.to_string(), CheckStatus::Ok),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_apply_events() {
+        let mut info = BTreeMap::new();
+        assert!(apply_event(
+            &mut info,
+            &json!({"type": "initial", "data": {
+                "cloud": {"info": {"logged_in": true, "can_reach_cloud": {"type": "pending"}}},
+                "homeassistant": {"info": {"version": "2024.6.0"}}
+            }})
+        ));
+        assert!(apply_event(
+            &mut info,
+            &json!({"type": "update", "domain": "cloud", "key": "can_reach_cloud",
+                    "success": false, "error": {"msg": "timeout"}})
+        ));
+        assert!(!apply_event(&mut info, &json!({"type": "finish"})));
+
+        let checks = health_checks(&info);
+        let summary: Vec<(&str, &str, CheckStatus)> = checks
+            .iter()
+            .map(|c| (c.check.as_str(), c.value.as_str(), c.status))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("can_reach_cloud", "timeout", CheckStatus::Failed),
+                ("logged_in", "yes", CheckStatus::Ok),
+                ("version", "2024.6.0", CheckStatus::Ok),
+            ]
+        );
+    }
+}
@@ -2,31 +2,47 @@
 
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Local, Utc};
-use tabled::{Table, Tabled};
+use tabled::Tabled;
 
-use crate::cli::{HistoryCommand, OutputFormat};
+use crate::cli::{HistoryCommand, OutputFormat, PageArgs};
 use crate::config::RuntimeContext;
-use crate::history::History;
-use crate::output::print_output;
+use crate::history::{compare_segments, History};
+use crate::output::{print_output, render_rows};
+use crate::paging::Page;
 
 /// Execute history commands
 pub async fn execute(ctx: &RuntimeContext, command: HistoryCommand) -> Result<()> {
     match command {
-        HistoryCommand::List { limit, filter } => list(ctx, limit, filter),
+        HistoryCommand::List {
+            limit,
+            filter,
+            paging,
+        } => list(ctx, limit, filter, paging),
         HistoryCommand::Again => again(ctx).await,
         HistoryCommand::Context => context(ctx),
         HistoryCommand::ClearContext => clear_context(ctx),
-        HistoryCommand::Stats => stats(ctx),
-        HistoryCommand::Clear => clear(ctx),
+        HistoryCommand::Stats { compare: false } => stats(ctx),
+        HistoryCommand::Stats { compare: true } => stats_compare(ctx),
+        HistoryCommand::Clear { yes } => clear(ctx, yes),
         HistoryCommand::Compact => compact(ctx),
         HistoryCommand::Path => path(ctx),
     }
 }
 
-fn list(ctx: &RuntimeContext, limit: usize, filter: Option<String>) -> Result<()> {
+pub fn list(
+    ctx: &RuntimeContext,
+    limit: Option<usize>,
+    filter: Option<String>,
+    paging: PageArgs,
+) -> Result<()> {
     let history = History::new()?;
 
-    let entries = if let Some(ref pattern) = filter {
+    let limit = match (limit, paging.page_size) {
+        (Some(limit), _) => limit,
+        (None, Some(_)) => usize::MAX,
+        (None, None) => 20,
+    };
+    let mut entries = if let Some(ref pattern) = filter {
         history.search(pattern)?
     } else {
         history.recent(limit)?
@@ -39,6 +55,11 @@ fn list(ctx: &RuntimeContext, limit: usize, filter: Option<String>) -> Result<()
         return Ok(());
     }
 
+    // Page 1 is the most recent entries, each page still oldest first
+    entries.reverse();
+    let page = Page::of(&entries, &paging)?;
+    let entries: Vec<_> = page.items.iter().rev().cloned().collect();
+
     match ctx.output_format() {
         OutputFormat::Json => {
             print_output(ctx, &entries)?;
@@ -57,6 +78,8 @@ fn list(ctx: &RuntimeContext, limit: usize, filter: Option<String>) -> Result<()
                 targets: String,
                 #[tabled(rename = "Status")]
                 status: String,
+                #[tabled(rename = "By")]
+                by: String,
             }
 
             let rows: Vec<HistoryRow> = entries
@@ -88,12 +111,14 @@ fn list(ctx: &RuntimeContext, limit: usize, filter: Option<String>) -> Result<()
                         input: truncate(&e.input, 40),
                         targets,
                         status,
+                        by: e.profile.clone().unwrap_or_else(|| "-".to_string()),
                     }
                 })
                 .collect();
 
-            let table = Table::new(rows).to_string();
+            let table = render_rows(ctx, &rows);
             println!("{table}");
+            page.print_footer(ctx, "entries");
         }
     }
 
@@ -117,8 +142,10 @@ async fn again(ctx: &RuntimeContext) -> Result<()> {
     let cmd = crate::cli::DoCommand {
         words,
         dry_run: false,
+        simulate: false,
         yes: true,
         exact: false,
+        verify_timeout: crate::commands::do_cmd::DEFAULT_VERIFY_TIMEOUT,
     };
 
     crate::commands::do_cmd::execute(ctx, cmd).await
@@ -226,8 +253,111 @@ fn stats(ctx: &RuntimeContext) -> Result<()> {
     Ok(())
 }
 
-fn clear(ctx: &RuntimeContext) -> Result<()> {
+fn stats_compare(ctx: &RuntimeContext) -> Result<()> {
+    let history = History::new()?;
+    let segments = compare_segments(&history.recent(usize::MAX)?);
+
+    if segments.is_empty() {
+        if !ctx.global.quiet {
+            println!("No history entries found.");
+        }
+        return Ok(());
+    }
+
+    match ctx.output_format() {
+        OutputFormat::Json => {
+            print_output(ctx, &segments)?;
+        }
+        OutputFormat::Yaml => {
+            println!("{}", serde_yaml::to_string(&segments)?);
+        }
+        _ => {
+            #[derive(Tabled)]
+            struct SegmentRow {
+                #[tabled(rename = "Since")]
+                since: String,
+                #[tabled(rename = "hmr")]
+                version: String,
+                #[tabled(rename = "Cache")]
+                cache: String,
+                #[tabled(rename = "Commands")]
+                commands: u64,
+                #[tabled(rename = "Success")]
+                success: String,
+                #[tabled(rename = "Change")]
+                change: String,
+                #[tabled(rename = "Exact")]
+                exact: u64,
+                #[tabled(rename = "Typo")]
+                typo: u64,
+                #[tabled(rename = "Fuzzy")]
+                fuzzy: u64,
+            }
+
+            let mut previous: Option<f64> = None;
+            let rows: Vec<SegmentRow> = segments
+                .iter()
+                .map(|seg| {
+                    let rate = seg.success_rate();
+                    let change = previous
+                        .map(|prev| format!("{:+.1}", rate - prev))
+                        .unwrap_or_else(|| "-".to_string());
+                    previous = Some(rate);
+
+                    let since = DateTime::<Utc>::from_timestamp(seg.since as i64, 0)
+                        .map(|dt| dt.with_timezone(&Local))
+                        .map(|dt| dt.format("%m-%d %H:%M").to_string())
+                        .unwrap_or_else(|| "?".to_string());
+
+                    SegmentRow {
+                        since,
+                        version: seg
+                            .matcher_version
+                            .clone()
+                            .unwrap_or_else(|| "unknown".to_string()),
+                        cache: seg
+                            .cache_fingerprint
+                            .as_deref()
+                            .map(|fp| fp.chars().take(8).collect())
+                            .unwrap_or_else(|| "-".to_string()),
+                        commands: seg.commands,
+                        success: format!("{rate:.1}%"),
+                        change,
+                        exact: seg.exact,
+                        typo: seg.typo,
+                        fuzzy: seg.fuzzy,
+                    }
+                })
+                .collect();
+
+            let table = render_rows(ctx, &rows);
+            println!("{table}");
+        }
+    }
+
+    Ok(())
+}
+
+fn clear(ctx: &RuntimeContext, yes: bool) -> Result<()> {
     let history = History::new()?;
+
+    let count = history.count()?;
+    if count == 0 {
+        if !ctx.global.quiet {
+            println!("History is already empty.");
+        }
+        return Ok(());
+    }
+
+    crate::prompt::confirm(
+        "Clear command history?",
+        &[format!(
+            "{count} entries in {}",
+            crate::history::history_path()?.display()
+        )],
+        yes,
+    )?;
+
     history.clear()?;
 
     if !ctx.global.quiet {
@@ -252,7 +382,7 @@ fn compact(ctx: &RuntimeContext) -> Result<()> {
     Ok(())
 }
 
-fn path(ctx: &RuntimeContext) -> Result<()> {
+pub fn path(ctx: &RuntimeContext) -> Result<()> {
     use crate::history::history_path;
 
     let path = history_path()?;
@@ -0,0 +1,370 @@
+//! Integration command implementations
+//!
+//! Integrations are Home Assistant's config entries; `list`, `reload` and
+//! `disable` take an entry ID, a title ("Living Room Hue"), or a domain.
+//! `list` and `reload` match titles fuzzily; `disable` needs an exact match,
+//! since a wrong guess would take a working integration offline.
+//!
+//! `integration reload-all` reloads config entries one by one and compares
+//! their state before and after, e.g. to recover integrations that gave up
+//! during a network outage. With `--only-failed` it only touches entries in
+//! a failed or retry state and needs no confirmation, so it can run from cron;
+//! it exits non-zero while any reloaded entry is still failing.
+
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+use tabled::Tabled;
+
+use crate::api::{ConfigEntry, HassClient};
+use crate::cli::IntegrationCommand;
+use crate::config::RuntimeContext;
+use crate::output::{self, output_for_format, print_output, print_table};
+use crate::prompt::confirm;
+use crate::resolve::{resolve_config_entry, resolve_config_entry_exact};
+use crate::websocket::WsClient;
+
+#[derive(Debug, Serialize)]
+struct ReloadResult {
+    entry_id: String,
+    domain: String,
+    title: String,
+    before: String,
+    /// None on a dry run
+    after: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Tabled)]
+struct ReloadRow {
+    domain: String,
+    title: String,
+    before: String,
+    after: String,
+}
+
+#[derive(Debug, Serialize, Tabled)]
+struct IntegrationRow {
+    #[tabled(rename = "TITLE")]
+    title: String,
+    #[tabled(rename = "DOMAIN")]
+    domain: String,
+    #[tabled(rename = "STATE")]
+    state: String,
+    #[tabled(rename = "ENTRY ID")]
+    entry_id: String,
+}
+
+impl From<&ConfigEntry> for IntegrationRow {
+    fn from(entry: &ConfigEntry) -> Self {
+        let state = match (&entry.disabled_by, &entry.reason) {
+            (Some(by), _) => format!("disabled by {by}"),
+            (None, Some(reason)) => format!("{} ({reason})", entry.state),
+            (None, None) => entry.state.clone(),
+        };
+        Self {
+            title: entry.title.clone(),
+            domain: entry.domain.clone(),
+            state,
+            entry_id: entry.entry_id.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct DisableResult {
+    entry_id: String,
+    domain: String,
+    title: String,
+    /// disabled, already disabled, or would disable
+    status: String,
+    require_restart: bool,
+}
+
+pub async fn run(ctx: &RuntimeContext, command: IntegrationCommand) -> Result<()> {
+    match command {
+        IntegrationCommand::List { all } => list(ctx, all).await,
+        IntegrationCommand::Reload { entry } => reload(ctx, &entry).await,
+        IntegrationCommand::Disable {
+            entry,
+            yes,
+            dry_run,
+        } => disable(ctx, &entry, yes, dry_run).await,
+        IntegrationCommand::ReloadAll {
+            only_failed,
+            dry_run,
+            yes,
+        } => reload_all(ctx, only_failed, dry_run, yes).await,
+    }
+}
+
+async fn list(ctx: &RuntimeContext, all: bool) -> Result<()> {
+    let mut entries = HassClient::new(ctx)?.get_config_entries().await?;
+    if !all {
+        entries.retain(|e| e.source.as_deref() != Some("ignore"));
+    }
+    entries.sort_by(|a, b| {
+        a.domain
+            .cmp(&b.domain)
+            .then_with(|| a.title.to_lowercase().cmp(&b.title.to_lowercase()))
+    });
+    if ctx.is_machine_output() {
+        return print_output(ctx, &entries);
+    }
+    let rows: Vec<IntegrationRow> = entries.iter().map(IntegrationRow::from).collect();
+    print_table(ctx, &rows)
+}
+
+async fn reload(ctx: &RuntimeContext, input: &str) -> Result<()> {
+    let client = HassClient::new(ctx)?;
+    let entry = resolve_config_entry(ctx, &client.get_config_entries().await?, input)?;
+    let response = client.reload_config_entry(&entry.entry_id).await?;
+    if response["require_restart"].as_bool() == Some(true) {
+        output::warn(
+            ctx,
+            format!(
+                "{} only reloads on a restart of Home Assistant",
+                entry.title
+            ),
+        );
+    }
+
+    let after = client
+        .get_config_entries()
+        .await?
+        .into_iter()
+        .find(|e| e.entry_id == entry.entry_id)
+        .map_or_else(|| "removed".to_string(), |e| e.state);
+    let result = ReloadResult {
+        entry_id: entry.entry_id,
+        domain: entry.domain,
+        title: entry.title,
+        before: entry.state,
+        after: Some(after.clone()),
+        error: None,
+    };
+    output_for_format(ctx, &result, || {
+        println!(
+            "Reloaded {} ({}): {} -> {after}",
+            result.title, result.domain, result.before
+        );
+        Ok(())
+    })?;
+
+    if is_failed_state(&after) {
+        bail!("{} is still failing ({after})", result.title);
+    }
+    Ok(())
+}
+
+async fn disable(ctx: &RuntimeContext, input: &str, yes: bool, dry_run: bool) -> Result<()> {
+    let client = HassClient::new(ctx)?;
+    let entry = resolve_config_entry_exact(ctx, &client.get_config_entries().await?, input)?;
+    let name = format!("{} ({})", entry.title, entry.domain);
+    let mut result = DisableResult {
+        entry_id: entry.entry_id.clone(),
+        domain: entry.domain.clone(),
+        title: entry.title.clone(),
+        status: "would disable".to_string(),
+        require_restart: false,
+    };
+
+    if entry.disabled_by.is_some() {
+        result.status = "already disabled".to_string();
+    } else if !dry_run {
+        confirm(
+            &format!("Disable {name}?"),
+            &["Its devices and entities stay unavailable until it is enabled again".to_string()],
+            yes,
+        )?;
+        let ws = WsClient::connect(ctx).await?;
+        result.require_restart = ws.set_config_entry_disabled(&entry.entry_id, true).await?;
+        result.status = "disabled".to_string();
+    }
+
+    output_for_format(ctx, &result, || {
+        match result.status.as_str() {
+            "disabled" => println!("Disabled {name}"),
+            "already disabled" => println!("{name} is already disabled"),
+            _ => println!("Would disable {name}"),
+        }
+        if result.require_restart {
+            println!("Restart Home Assistant to finish (hmr core restart)");
+        }
+        Ok(())
+    })
+}
+
+async fn reload_all(
+    ctx: &RuntimeContext,
+    only_failed: bool,
+    dry_run: bool,
+    yes: bool,
+) -> Result<()> {
+    let client = HassClient::new(ctx)?;
+    let entries = client.get_config_entries().await?;
+    let targets = select_targets(&entries, only_failed);
+    if targets.is_empty() {
+        return output_for_format(ctx, &Vec::<ReloadResult>::new(), || {
+            if only_failed {
+                println!("No failed integrations");
+            } else {
+                println!("No integrations to reload");
+            }
+            Ok(())
+        });
+    }
+
+    let mut results: Vec<ReloadResult> = targets
+        .iter()
+        .map(|entry| ReloadResult {
+            entry_id: entry.entry_id.clone(),
+            domain: entry.domain.clone(),
+            title: entry.title.clone(),
+            before: entry.state.clone(),
+            after: None,
+            error: None,
+        })
+        .collect();
+    if dry_run {
+        return report(ctx, &results);
+    }
+    if !only_failed {
+        let affected: Vec<String> = targets
+            .iter()
+            .map(|e| format!("{} ({})", e.title, e.domain))
+            .collect();
+        confirm(
+            &format!("Reload {} integrations?", affected.len()),
+            &affected,
+            yes,
+        )?;
+    }
+
+    for result in &mut results {
+        if let Err(e) = client.reload_config_entry(&result.entry_id).await {
+            result.error = Some(format!("{e:#}"));
+        }
+    }
+
+    let states: HashMap<String, String> = client
+        .get_config_entries()
+        .await?
+        .into_iter()
+        .map(|e| (e.entry_id, e.state))
+        .collect();
+    for result in &mut results {
+        result.after = Some(
+            states
+                .get(&result.entry_id)
+                .cloned()
+                .unwrap_or_else(|| "removed".to_string()),
+        );
+    }
+    report(ctx, &results)?;
+
+    let still_failing = results
+        .iter()
+        .filter(|r| r.error.is_some() || r.after.as_deref().is_some_and(is_failed_state))
+        .count();
+    if still_failing > 0 {
+        bail!(
+            "{still_failing} of {} integrations are still failing",
+            results.len()
+        );
+    }
+    Ok(())
+}
+
+/// Enabled entries the user hasn't dismissed, optionally only failed ones
+fn select_targets(entries: &[ConfigEntry], only_failed: bool) -> Vec<&ConfigEntry> {
+    entries
+        .iter()
+        .filter(|e| e.disabled_by.is_none() && e.source.as_deref() != Some("ignore"))
+        .filter(|e| !only_failed || is_failed_state(&e.state))
+        .collect()
+}
+
+/// Setup failed or is being retried, so a reload may help
+fn is_failed_state(state: &str) -> bool {
+    matches!(
+        state,
+        "setup_error" | "setup_retry" | "migration_error" | "failed_unload"
+    )
+}
+
+fn report(ctx: &RuntimeContext, results: &[ReloadResult]) -> Result<()> {
+    output_for_format(ctx, &results, || {
+        let rows: Vec<ReloadRow> = results
+            .iter()
+            .map(|r| ReloadRow {
+                domain: r.domain.clone(),
+                title: r.title.clone(),
+                before: r.before.clone(),
+                after: match (&r.error, &r.after) {
+                    (Some(error), _) => format!("reload failed: {error}"),
+                    (None, Some(after)) => after.clone(),
+                    (None, None) => "would reload".to_string(),
+                },
+            })
+            .collect();
+        print_table(ctx, &rows)?;
+
+        if results.iter().all(|r| r.after.is_none()) {
+            return Ok(());
+        }
+        let was_failed = results.iter().filter(|r| is_failed_state(&r.before));
+        let recovered = was_failed
+            .clone()
+            .filter(|r| r.after.as_deref().is_some_and(|s| !is_failed_state(s)))
+            .count();
+        println!(
+            "\nReloaded {} integrations; {recovered} of {} failed ones recovered",
+            results.len(),
+            was_failed.count()
+        );
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, state: &str) -> ConfigEntry {
+        ConfigEntry {
+            entry_id: id.to_string(),
+            domain: "hue".to_string(),
+            title: id.to_string(),
+            source: Some("user".to_string()),
+            state: state.to_string(),
+            disabled_by: None,
+            reason: None,
+        }
+    }
+
+    #[test]
+    fn test_select_targets() {
+        let mut disabled = entry("disabled", "setup_error");
+        disabled.disabled_by = Some("user".to_string());
+        let mut ignored = entry("ignored", "not_loaded");
+        ignored.source = Some("ignore".to_string());
+        let entries = vec![
+            entry("ok", "loaded"),
+            entry("retry", "setup_retry"),
+            disabled,
+            ignored,
+        ];
+
+        let ids = |only_failed| -> Vec<String> {
+            select_targets(&entries, only_failed)
+                .iter()
+                .map(|e| e.entry_id.clone())
+                .collect()
+        };
+        assert_eq!(ids(false), vec!["ok", "retry"]);
+        assert_eq!(ids(true), vec!["retry"]);
+    }
+}
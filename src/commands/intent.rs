@@ -0,0 +1,125 @@
+//! Intent command implementation
+//!
+//! Fires an intent through `/api/intent/handle` with the slots given, the
+//! way a voice pipeline would after recognizing a sentence, so intents can be
+//! tested without the conversation agent. Home Assistant only serves the
+//! endpoint when `intent:` is in configuration.yaml.
+
+use anyhow::{bail, Result};
+use serde_json::{json, Value};
+
+use crate::api::{ConversationResponseData, HassClient};
+use crate::cli::IntentCommand;
+use crate::config::RuntimeContext;
+use crate::error::{self, Error};
+use crate::output::{output_for_format, parse_json_input};
+
+pub async fn run(ctx: &RuntimeContext, command: IntentCommand) -> Result<()> {
+    let slots = match command.slots.as_deref() {
+        Some(input) => parse_json_input(input)?,
+        None => json!({}),
+    };
+    if !slots.is_object() {
+        bail!("--slots must be a JSON object, e.g. '{{\"name\": \"kitchen light\"}}'");
+    }
+
+    let client = HassClient::new(ctx)?;
+    let response = match client.handle_intent(&command.name, &slots).await {
+        Err(e) if matches!(error::find(&e), Some(Error::NotFound(_))) => {
+            return Err(e.context(
+                "The intent API isn't enabled; add `intent:` to configuration.yaml and restart",
+            ));
+        }
+        result => result?,
+    };
+    if response.response_type == "error" {
+        let code = response.data.as_ref().and_then(|d| d["code"].as_str());
+        bail!(
+            "Intent {} failed{}: {}",
+            command.name,
+            code.map(|c| format!(" ({c})")).unwrap_or_default(),
+            speech(&response).unwrap_or("no details given")
+        );
+    }
+
+    output_for_format(ctx, &response, || {
+        for line in describe(&command.name, &response) {
+            println!("{line}");
+        }
+        Ok(())
+    })
+}
+
+fn speech(response: &ConversationResponseData) -> Option<&str> {
+    response
+        .speech
+        .as_ref()
+        .map(|s| s.plain.speech.as_str())
+        .filter(|s| !s.is_empty())
+}
+
+/// The spoken reply, then which targets the intent acted on or failed for
+fn describe(name: &str, response: &ConversationResponseData) -> Vec<String> {
+    let mut lines = vec![speech(response)
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{name}: {}", response.response_type))];
+    let targets = |key: &str| -> Vec<String> {
+        response
+            .data
+            .as_ref()
+            .and_then(|d| d[key].as_array())
+            .map(|targets| {
+                targets
+                    .iter()
+                    .map(|t| match (t["name"].as_str(), t["id"].as_str()) {
+                        (Some(name), Some(id)) if name != id => format!("{name} ({id})"),
+                        (_, Some(id)) => id.to_string(),
+                        (Some(name), None) => name.to_string(),
+                        (None, None) => Value::to_string(t),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    for (key, label) in [("success", "Succeeded"), ("failed", "Failed")] {
+        let targets = targets(key);
+        if !targets.is_empty() {
+            lines.push(format!("{label}: {}", targets.join(", ")));
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe() {
+        let response: ConversationResponseData = serde_json::from_value(json!({
+            "response_type": "action_done",
+            "speech": {"plain": {"speech": "Turned on the light", "extra_data": null}},
+            "language": "en",
+            "data": {
+                "targets": [],
+                "success": [{"name": "Kitchen Light", "type": "entity", "id": "light.kitchen"}],
+                "failed": []
+            }
+        }))
+        .unwrap();
+        assert_eq!(
+            describe("HassTurnOn", &response),
+            vec![
+                "Turned on the light",
+                "Succeeded: Kitchen Light (light.kitchen)"
+            ]
+        );
+
+        let silent: ConversationResponseData =
+            serde_json::from_value(json!({"response_type": "query_answer"})).unwrap();
+        assert_eq!(
+            describe("HassGetState", &silent),
+            vec!["HassGetState: query_answer"]
+        );
+    }
+}
@@ -0,0 +1,66 @@
+//! Label command implementations
+//!
+//! Labels come from Home Assistant's label registry. Listing refreshes the
+//! label cache, so `entity list --label` and "... labeled X" in `hmr do` see
+//! new labels right away.
+
+use anyhow::Result;
+use serde::Serialize;
+use tabled::Tabled;
+
+use crate::cache::{Cache, CacheManager, CachedLabel};
+use crate::cli::LabelCommand;
+use crate::config::RuntimeContext;
+use crate::output;
+
+pub async fn run(ctx: &RuntimeContext, command: LabelCommand) -> Result<()> {
+    match command {
+        LabelCommand::List => list(ctx).await,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Tabled)]
+struct LabelRow {
+    #[tabled(rename = "LABEL ID")]
+    label_id: String,
+    #[tabled(rename = "NAME")]
+    name: String,
+    #[tabled(rename = "COLOR")]
+    color: String,
+    #[tabled(rename = "ENTITIES")]
+    entities: usize,
+    #[tabled(rename = "DESCRIPTION")]
+    description: String,
+}
+
+impl LabelRow {
+    fn new(label: &CachedLabel, cache: &Cache) -> Self {
+        Self {
+            label_id: label.label_id.clone(),
+            name: label.name.clone(),
+            color: label.color.clone().unwrap_or_else(|| "-".to_string()),
+            entities: cache.entities_with_label(&label.label_id).len(),
+            description: label.description.clone().unwrap_or_else(|| "-".to_string()),
+        }
+    }
+}
+
+async fn list(ctx: &RuntimeContext) -> Result<()> {
+    let mut cache_manager = CacheManager::new(ctx)?;
+    cache_manager.refresh_labels().await?;
+    // Entities are only needed for the counts
+    if let Err(e) = cache_manager.ensure_entities().await {
+        log::debug!("Entity cache unavailable: {e:#}");
+    }
+
+    let cache = cache_manager.cache();
+    let mut rows: Vec<LabelRow> = cache
+        .labels()
+        .iter()
+        .map(|label| LabelRow::new(label, cache))
+        .collect();
+    rows.sort_by_key(|row| row.name.to_lowercase());
+
+    output::print_table(ctx, &rows)?;
+    Ok(())
+}
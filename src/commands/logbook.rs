@@ -0,0 +1,130 @@
+//! Logbook command implementation
+//!
+//! Shows Home Assistant's human-readable event log ("Kitchen light turned on
+//! triggered by automation Morning") for a recent period, optionally for one
+//! entity or, with `--room`, for the entities in one area.
+
+use anyhow::Result;
+use chrono::{DateTime, Local, Utc};
+use serde::Serialize;
+use tabled::Tabled;
+
+use crate::api::{HassClient, LogbookEntry};
+use crate::cli::LogbookCommand;
+use crate::config::RuntimeContext;
+use crate::output::{output_for_format, print_table};
+use crate::progress;
+use crate::resolve::room_entity_ids;
+
+#[derive(Debug, Serialize, Tabled)]
+struct LogbookRow {
+    time: String,
+    name: String,
+    message: String,
+    entity_id: String,
+}
+
+impl LogbookRow {
+    fn new(entry: &LogbookEntry) -> Self {
+        let time = DateTime::parse_from_rfc3339(&entry.when)
+            .map(|dt| {
+                dt.with_timezone(&Local)
+                    .format("%m-%d %H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_else(|_| entry.when.clone());
+
+        Self {
+            time,
+            name: entry.name.clone().unwrap_or_default(),
+            message: describe(entry),
+            entity_id: entry.entity_id.clone().unwrap_or_default(),
+        }
+    }
+}
+
+pub async fn run(ctx: &RuntimeContext, command: LogbookCommand) -> Result<()> {
+    let client = HassClient::new(ctx)?;
+
+    let now = Local::now();
+    let start = command.since.before(now).with_timezone(&Utc);
+    let start_str = start.format("%Y-%m-%dT%H:%M:%S%:z").to_string();
+    // Home Assistant ends the window a day after the start unless told otherwise
+    let end_str = now
+        .with_timezone(&Utc)
+        .format("%Y-%m-%dT%H:%M:%S%:z")
+        .to_string();
+
+    let spinner = progress::spinner(ctx, "Fetching logbook");
+    let mut entries = client
+        .get_logbook(&start_str, Some(&end_str), command.entity.as_deref())
+        .await?;
+    spinner.finish_and_clear();
+    if let Some(room) = room_entity_ids(ctx).await? {
+        entries.retain(|e| e.entity_id.as_ref().is_some_and(|id| room.contains(id)));
+    }
+
+    output_for_format(ctx, &entries, || {
+        if entries.is_empty() {
+            println!("No logbook entries since {}", command.since);
+            return Ok(());
+        }
+        let rows: Vec<LogbookRow> = entries.iter().map(LogbookRow::new).collect();
+        print_table(ctx, &rows)
+    })
+}
+
+/// What an entry says happened, including what caused it when known
+fn describe(entry: &LogbookEntry) -> String {
+    let mut message = match (&entry.message, &entry.state) {
+        (Some(message), _) => message.clone(),
+        (None, Some(state)) => format!("changed to {state}"),
+        (None, None) => String::new(),
+    };
+
+    let context = |key: &str| entry.context.get(key).and_then(|v| v.as_str());
+    let cause = match (context("context_event_type"), context("context_name")) {
+        (Some("automation_triggered"), Some(name)) => Some(format!("automation {name}")),
+        (Some("script_started"), Some(name)) => Some(format!("script {name}")),
+        (Some("call_service"), _) => context("context_domain")
+            .zip(context("context_service"))
+            .map(|(domain, service)| format!("service {domain}.{service}")),
+        _ => None,
+    };
+    if let Some(cause) = cause {
+        message.push_str(&format!(" (triggered by {cause})"));
+    }
+
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_describe() {
+        let entry: LogbookEntry = serde_json::from_value(json!({
+            "when": "2025-01-15T07:00:00+00:00",
+            "name": "Kitchen",
+            "state": "on",
+            "entity_id": "light.kitchen",
+            "context_event_type": "automation_triggered",
+            "context_name": "Morning",
+        }))
+        .unwrap();
+        assert_eq!(
+            describe(&entry),
+            "changed to on (triggered by automation Morning)"
+        );
+
+        let entry: LogbookEntry = serde_json::from_value(json!({
+            "when": "2025-01-15T07:00:00+00:00",
+            "name": "Home Assistant",
+            "message": "started",
+        }))
+        .unwrap();
+        assert_eq!(describe(&entry), "started");
+    }
+}
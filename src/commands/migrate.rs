@@ -0,0 +1,269 @@
+//! Migrate command implementations
+//!
+//! Bundles local hmr state (config, command history, accuracy stats) into a
+//! single JSON file so a personalized setup can be moved to another machine.
+//! Access tokens, including `--as` profile tokens, are never exported; the
+//! importing machine keeps its own.
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::cli::{MigrateCommand, OutputFormat};
+use crate::config::{write_config, AppConfig, RuntimeContext};
+use crate::history::{AccuracyStats, History, HistoryEntry};
+use crate::output::{print_output, read_stdin, warn};
+
+/// Bundle format identifier
+const BUNDLE_FORMAT: &str = "hmr-bundle";
+
+/// Bundle format version, bumped on incompatible changes
+const BUNDLE_VERSION: u32 = 1;
+
+/// Portable snapshot of local hmr state
+#[derive(Debug, Serialize, Deserialize)]
+struct Bundle {
+    format: String,
+    version: u32,
+    hmr_version: String,
+    created: String,
+    config: AppConfig,
+    history: Vec<HistoryEntry>,
+    stats: AccuracyStats,
+}
+
+/// What an import changed (or would change, for --dry-run)
+#[derive(Debug, Serialize)]
+struct ImportSummary {
+    config: String,
+    history_added: usize,
+    history_total: usize,
+    stats_commands: u64,
+    dry_run: bool,
+}
+
+pub fn run(ctx: &RuntimeContext, command: MigrateCommand) -> Result<()> {
+    match command {
+        MigrateCommand::Export { file } => export(ctx, file.as_deref()),
+        MigrateCommand::Import { file, dry_run, yes } => import(ctx, &file, dry_run, yes),
+    }
+}
+
+fn export(ctx: &RuntimeContext, file: Option<&Path>) -> Result<()> {
+    let history = History::new()?;
+
+    let mut config = ctx.config.clone();
+    if config.homeassistant.token.take().is_some() {
+        warn(
+            ctx,
+            "Access token not exported; set HASS_TOKEN or add it to the config on the new machine",
+        );
+    }
+    let profiles = std::mem::take(&mut config.homeassistant.tokens);
+    if !profiles.is_empty() {
+        let names: Vec<&str> = profiles.keys().map(String::as_str).collect();
+        warn(
+            ctx,
+            format!(
+                "Profile tokens not exported ({}); add them to the config on the new machine",
+                names.join(", ")
+            ),
+        );
+    }
+
+    let bundle = Bundle {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        hmr_version: env!("CARGO_PKG_VERSION").to_string(),
+        created: Utc::now().to_rfc3339(),
+        config,
+        history: history.recent(usize::MAX)?,
+        stats: history.stats().clone(),
+    };
+
+    let json = serde_json::to_string_pretty(&bundle)?;
+
+    match file {
+        None => println!("{json}"),
+        Some(path) => {
+            // History may reveal household routines; keep the bundle private
+            // from the moment it exists
+            let mut options = fs::OpenOptions::new();
+            options.write(true).create(true).truncate(true);
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::OpenOptionsExt;
+                options.mode(0o600);
+            }
+            let mut out = options
+                .open(path)
+                .with_context(|| format!("writing {}", path.display()))?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                out.set_permissions(fs::Permissions::from_mode(0o600))
+                    .with_context(|| format!("setting permissions on {}", path.display()))?;
+            }
+            out.write_all(json.as_bytes())
+                .with_context(|| format!("writing {}", path.display()))?;
+
+            if !ctx.global.quiet {
+                eprintln!(
+                    "Exported config, {} history entries and stats to {}",
+                    bundle.history.len(),
+                    path.display()
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn import(ctx: &RuntimeContext, file: &Path, dry_run: bool, yes: bool) -> Result<()> {
+    let bundle = read_bundle(file)?;
+
+    // Keep this machine's tokens; bundles never carry any
+    let mut config = bundle.config;
+    config.homeassistant.token = ctx.config.homeassistant.token.clone();
+    config.homeassistant.tokens = ctx.config.homeassistant.tokens.clone();
+
+    let mut history = History::new()?;
+    let existing = history.count()?;
+    let config_path = ctx.config_path();
+
+    let summary = ImportSummary {
+        config: config_path.display().to_string(),
+        history_added: 0,
+        history_total: bundle.history.len(),
+        stats_commands: bundle.stats.total_commands,
+        dry_run,
+    };
+
+    if dry_run {
+        return print_summary(ctx, &summary);
+    }
+
+    let mut affected = vec![format!("{} will be overwritten", config_path.display())];
+    if existing > 0 {
+        affected.push(format!(
+            "{} history entries will be merged into {existing} existing entries",
+            bundle.history.len()
+        ));
+    }
+    affected.push("accuracy statistics will be replaced".to_string());
+    crate::prompt::confirm("Import hmr state bundle?", &affected, yes)?;
+
+    write_config(config_path, &config)?;
+    let added = history.merge(&bundle.history)?;
+    *history.stats_mut() = bundle.stats;
+    history.save_stats()?;
+
+    print_summary(
+        ctx,
+        &ImportSummary {
+            history_added: added,
+            ..summary
+        },
+    )
+}
+
+fn read_bundle(file: &Path) -> Result<Bundle> {
+    let content = if file == Path::new("-") {
+        read_stdin()?.ok_or_else(|| anyhow!("No bundle on stdin"))?
+    } else {
+        fs::read_to_string(file).with_context(|| format!("reading {}", file.display()))?
+    };
+
+    parse_bundle(&content)
+}
+
+fn parse_bundle(content: &str) -> Result<Bundle> {
+    let bundle: Bundle = serde_json::from_str(content).context("parsing hmr bundle")?;
+
+    if bundle.format != BUNDLE_FORMAT {
+        return Err(anyhow!("Not an hmr bundle (format '{}')", bundle.format));
+    }
+    if bundle.version > BUNDLE_VERSION {
+        return Err(anyhow!(
+            "Bundle version {} was written by hmr {}; this hmr supports up to version {BUNDLE_VERSION}",
+            bundle.version,
+            bundle.hmr_version
+        ));
+    }
+
+    Ok(bundle)
+}
+
+fn print_summary(ctx: &RuntimeContext, summary: &ImportSummary) -> Result<()> {
+    match ctx.output_format() {
+        OutputFormat::Json => print_output(ctx, summary)?,
+        OutputFormat::Yaml => println!("{}", serde_yaml::to_string(summary)?),
+        _ => {
+            if summary.dry_run {
+                println!("Would import:");
+                println!("  config:  {}", summary.config);
+                println!("  history: {} entries", summary.history_total);
+                println!("  stats:   {} commands", summary.stats_commands);
+                println!();
+                println!("(dry run - nothing written)");
+            } else {
+                println!("Imported:");
+                println!("  config:  {}", summary.config);
+                println!(
+                    "  history: {} of {} entries added",
+                    summary.history_added, summary.history_total
+                );
+                println!("  stats:   {} commands", summary.stats_commands);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_bundle() -> Bundle {
+        let mut config = AppConfig::default();
+        config.homeassistant.server = Some("http://ha.local:8123".to_string());
+
+        Bundle {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            hmr_version: "0.0.0".to_string(),
+            created: "2026-01-01T00:00:00Z".to_string(),
+            config,
+            history: vec![HistoryEntry::new("kitchen on", "turn_on kitchen")],
+            stats: AccuracyStats::default(),
+        }
+    }
+
+    #[test]
+    fn test_bundle_roundtrip() {
+        let json = serde_json::to_string(&sample_bundle()).unwrap();
+        let parsed = parse_bundle(&json).unwrap();
+        assert_eq!(
+            parsed.config.homeassistant.server.as_deref(),
+            Some("http://ha.local:8123")
+        );
+        assert_eq!(parsed.history.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_bundle_rejects_foreign_format_and_newer_version() {
+        let mut bundle = sample_bundle();
+        bundle.format = "other".to_string();
+        assert!(parse_bundle(&serde_json::to_string(&bundle).unwrap()).is_err());
+
+        let mut bundle = sample_bundle();
+        bundle.version = BUNDLE_VERSION + 1;
+        let err = parse_bundle(&serde_json::to_string(&bundle).unwrap()).unwrap_err();
+        assert!(err.to_string().contains("supports up to version"));
+    }
+}
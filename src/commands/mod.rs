@@ -1,15 +1,52 @@
 //! Command implementations
 
+pub mod addon;
 pub mod agent;
 pub mod area;
+pub mod assist;
+pub mod auth;
+pub mod automation;
+pub mod blueprint;
+pub mod bulk;
 pub mod cache;
+pub mod calendar;
+pub mod camera;
 pub mod completions;
 pub mod config;
+pub mod core;
+pub mod dashboard;
 pub mod device;
 pub mod do_cmd;
+pub mod doctor;
+pub mod energy;
 pub mod entity;
 pub mod event;
+pub mod export;
+pub mod health;
 pub mod history;
 pub mod info;
+pub mod integration;
+pub mod intent;
+pub mod label;
+pub mod logbook;
+pub mod migrate;
+pub mod notification;
+pub mod on;
+pub mod pin;
+pub mod prompt;
+pub mod quick;
+pub mod reload;
+pub mod say;
+pub mod scene;
+pub mod script;
+pub mod sensor;
 pub mod service;
+pub mod shopping;
+pub mod stats;
+pub mod status;
+pub mod suggest;
 pub mod template;
+pub mod todo;
+pub mod update;
+pub mod watchdog;
+pub mod who;
@@ -0,0 +1,173 @@
+//! Persistent notification command implementations
+//!
+//! Lists the notifications shown in the sidebar and dismisses them through
+//! the `persistent_notification` services, for triage from the terminal.
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use serde_json::json;
+use tabled::Tabled;
+
+use crate::cli::NotificationCommand;
+use crate::config::RuntimeContext;
+use crate::error::Error;
+use crate::output::{output_for_format, print_output, print_table};
+use crate::websocket::{Notification, WsClient};
+
+#[derive(Debug, Serialize, Tabled)]
+struct NotificationRow {
+    #[tabled(rename = "ID")]
+    id: String,
+    #[tabled(rename = "CREATED")]
+    created: String,
+    #[tabled(rename = "TITLE")]
+    title: String,
+    #[tabled(rename = "MESSAGE")]
+    message: String,
+}
+
+impl NotificationRow {
+    fn new(notification: &Notification) -> Self {
+        let created = DateTime::parse_from_rfc3339(&notification.created_at)
+            .map(|at| {
+                at.with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_else(|_| notification.created_at.clone());
+        Self {
+            id: notification.notification_id.clone(),
+            created,
+            title: notification.title.clone().unwrap_or_default(),
+            // Messages are markdown; the first line is enough to triage by
+            message: notification
+                .message
+                .lines()
+                .find(|line| !line.trim().is_empty())
+                .unwrap_or_default()
+                .trim()
+                .to_string(),
+        }
+    }
+}
+
+pub async fn run(ctx: &RuntimeContext, command: NotificationCommand) -> Result<()> {
+    match command {
+        NotificationCommand::List => list(ctx).await,
+        NotificationCommand::Dismiss {
+            id,
+            all,
+            dry_run,
+            yes,
+        } => dismiss(ctx, id, all, dry_run, yes).await,
+    }
+}
+
+async fn list(ctx: &RuntimeContext) -> Result<()> {
+    let client = WsClient::connect(ctx).await?;
+    let notifications = client.list_notifications().await?;
+
+    output_for_format(ctx, &notifications, || {
+        if notifications.is_empty() {
+            println!("No notifications");
+            return Ok(());
+        }
+        let rows: Vec<NotificationRow> = notifications.iter().map(NotificationRow::new).collect();
+        print_table(ctx, &rows)
+    })
+}
+
+async fn dismiss(
+    ctx: &RuntimeContext,
+    id: Option<String>,
+    all: bool,
+    dry_run: bool,
+    yes: bool,
+) -> Result<()> {
+    let client = WsClient::connect(ctx).await?;
+    let mut notifications = client.list_notifications().await?;
+    if let Some(id) = &id {
+        // Dismissing an unknown ID succeeds silently, so check it first
+        notifications.retain(|n| &n.notification_id == id);
+        if notifications.is_empty() {
+            return Err(Error::NotFound(format!(
+                "No notification '{id}'. See: hmr notification list"
+            ))
+            .into());
+        }
+    }
+    let affected: Vec<String> = notifications
+        .iter()
+        .map(|n| match &n.title {
+            Some(title) => format!("{} ({title})", n.notification_id),
+            None => n.notification_id.clone(),
+        })
+        .collect();
+
+    if dry_run {
+        if ctx.is_machine_output() {
+            return print_output(ctx, &json!({ "dry_run": true, "dismiss": notifications }));
+        }
+        if affected.is_empty() {
+            println!("No notifications to dismiss");
+        }
+        for item in &affected {
+            println!("Would dismiss {item}");
+        }
+        return Ok(());
+    }
+
+    if all {
+        if notifications.is_empty() {
+            println!("No notifications to dismiss");
+            return Ok(());
+        }
+        crate::prompt::confirm(
+            &format!("Dismiss {} notifications?", notifications.len()),
+            &affected,
+            yes,
+        )?;
+        client.dismiss_all_notifications().await?;
+    } else {
+        for notification in &notifications {
+            client
+                .dismiss_notification(&notification.notification_id)
+                .await?;
+        }
+    }
+
+    if ctx.is_machine_output() {
+        return print_output(ctx, &json!({ "dismissed": notifications }));
+    }
+    for item in &affected {
+        println!("Dismissed {item}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notification_row() {
+        let notification: Notification = serde_json::from_value(json!({
+            "notification_id": "config_entry_discovery",
+            "title": "New devices discovered",
+            "message": "\nWe have discovered new devices on your network.\n[Check it out](/config)",
+            "created_at": "2025-01-15T08:30:00+00:00"
+        }))
+        .unwrap();
+        let row = NotificationRow::new(&notification);
+        assert_eq!(row.id, "config_entry_discovery");
+        assert_eq!(
+            row.message,
+            "We have discovered new devices on your network."
+        );
+        let created = DateTime::parse_from_rfc3339("2025-01-15T08:30:00+00:00")
+            .unwrap()
+            .with_timezone(&Local);
+        assert_eq!(row.created, created.format("%Y-%m-%d %H:%M").to_string());
+    }
+}
@@ -0,0 +1,266 @@
+//! On command implementation
+//!
+//! `hmr on "anna enters home" -- ./welcome.sh` watches a person or device
+//! tracker and runs a local command each time it enters or leaves a zone.
+//! A tracker's state is the name of the zone it is in ("home" for zone.home,
+//! the zone's name otherwise), so a zone is entered when the state becomes
+//! that name and left when it stops being it. Changes from or to "unknown"
+//! or "unavailable" say nothing about where someone went (a restart, a dead
+//! phone) and never fire.
+
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use chrono::Local;
+use serde::Serialize;
+
+use crate::cache::{CacheManager, CachedEntity};
+use crate::cli::{OnCommand, OutputFormat};
+use crate::config::RuntimeContext;
+use crate::output::warn;
+use crate::resolve::resolve_domain_entity;
+use crate::websocket;
+
+const TRACKER_DOMAINS: &[&str] = &["person", "device_tracker"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Transition {
+    Enter,
+    Leave,
+}
+
+/// A person or tracker entering or leaving one zone
+#[derive(Debug)]
+struct ZoneTrigger {
+    entity_id: String,
+    name: String,
+    zone_id: String,
+    /// The tracker state while in the zone
+    zone_state: String,
+    transition: Transition,
+}
+
+impl ZoneTrigger {
+    fn new(tracker: &CachedEntity, zone: &CachedEntity, transition: Transition) -> Self {
+        let zone_state = match zone.entity_id.as_str() {
+            "zone.home" => "home".to_string(),
+            _ => zone
+                .friendly_name
+                .clone()
+                .unwrap_or_else(|| zone.object_id.clone()),
+        };
+        Self {
+            entity_id: tracker.entity_id.clone(),
+            name: tracker
+                .friendly_name
+                .clone()
+                .unwrap_or_else(|| tracker.entity_id.clone()),
+            zone_id: zone.entity_id.clone(),
+            zone_state,
+            transition,
+        }
+    }
+
+    /// Whether a change from `old` to `new` is this trigger's transition
+    fn fires(&self, old: &str, new: &str) -> bool {
+        let unknown = |s: &str| matches!(s, "unknown" | "unavailable");
+        if old == new || unknown(old) || unknown(new) {
+            return false;
+        }
+        let inside = |s: &str| s.eq_ignore_ascii_case(&self.zone_state);
+        match self.transition {
+            Transition::Enter => inside(new),
+            Transition::Leave => inside(old),
+        }
+    }
+
+    fn describe(&self) -> String {
+        let verb = match self.transition {
+            Transition::Enter => "enters",
+            Transition::Leave => "leaves",
+        };
+        format!("{} ({}) {verb} {}", self.name, self.entity_id, self.zone_id)
+    }
+}
+
+/// One firing of the trigger
+#[derive(Debug, Serialize)]
+struct Firing<'a> {
+    entity_id: &'a str,
+    zone: &'a str,
+    transition: Transition,
+    old_state: &'a str,
+    new_state: &'a str,
+    /// The command's exit code; None on a dry run or when killed by a signal
+    exit_code: Option<i32>,
+}
+
+pub async fn run(ctx: &RuntimeContext, command: OnCommand) -> Result<()> {
+    let (who, transition, zone) = split_trigger(&command.trigger)?;
+    let mut cache_manager = CacheManager::new(ctx)?;
+    cache_manager.ensure_entities().await?;
+    let cache = cache_manager.cache();
+    let tracker = resolve_domain_entity(ctx, cache, &who, TRACKER_DOMAINS)?;
+    let zone = zone.as_deref().unwrap_or("home");
+    let zone = resolve_domain_entity(ctx, cache, zone, &["zone"])?;
+    let trigger = ZoneTrigger::new(&tracker, &zone, transition);
+
+    if !ctx.is_machine_output() {
+        println!("Waiting until {}", trigger.describe());
+        println!("Press Ctrl+C to stop\n");
+    }
+
+    let output_format = ctx.output_format();
+    let entity_ids = [trigger.entity_id.clone()];
+    websocket::watch_entities(ctx, &entity_ids, false, |data| {
+        let old = data["old_state"]["state"].as_str().unwrap_or("unknown");
+        let new = data["new_state"]["state"].as_str().unwrap_or("unknown");
+        if !trigger.fires(old, new) {
+            return Ok(true);
+        }
+
+        let exit_code = if command.dry_run {
+            None
+        } else {
+            run_hook(ctx, &command.command, &trigger, old, new)?
+        };
+        let firing = Firing {
+            entity_id: &trigger.entity_id,
+            zone: &trigger.zone_id,
+            transition: trigger.transition,
+            old_state: old,
+            new_state: new,
+            exit_code,
+        };
+        match output_format {
+            OutputFormat::Json => println!("{}", serde_json::to_string(&firing)?),
+            OutputFormat::Yaml => println!("{}", serde_yaml::to_string(&firing)?),
+            OutputFormat::Table | OutputFormat::Auto => {
+                let at = Local::now().format("%H:%M");
+                let action = if command.dry_run { "would run" } else { "ran" };
+                println!(
+                    "[{at}] {}: {old} -> {new}, {action} {}",
+                    trigger.name,
+                    command.command.join(" ")
+                );
+            }
+        }
+        Ok(!command.once)
+    })
+    .await
+}
+
+/// Split "anna enters home" into who, the transition and the zone, if given
+fn split_trigger(text: &str) -> Result<(String, Transition, Option<String>)> {
+    const VERBS: &[(&[&str], Transition)] = &[
+        (&["arrives", "at"], Transition::Enter),
+        (&["gets", "to"], Transition::Enter),
+        (&["enters"], Transition::Enter),
+        (&["arrives"], Transition::Enter),
+        (&["leaves"], Transition::Leave),
+        (&["exits"], Transition::Leave),
+    ];
+    let words: Vec<&str> = text.split_whitespace().collect();
+    for i in 1..words.len() {
+        let verb = VERBS.iter().find(|(verb, _)| {
+            words[i..]
+                .iter()
+                .zip(verb.iter())
+                .filter(|(word, v)| word.eq_ignore_ascii_case(v))
+                .count()
+                == verb.len()
+        });
+        if let Some((verb, transition)) = verb {
+            let mut zone = &words[i + verb.len()..];
+            if zone.first().is_some_and(|w| w.eq_ignore_ascii_case("zone")) {
+                zone = &zone[1..];
+            }
+            let zone = (!zone.is_empty()).then(|| zone.join(" "));
+            return Ok((words[..i].join(" "), *transition, zone));
+        }
+    }
+    bail!(
+        "Can't read trigger '{text}': expected \"<person> enters <zone>\" or \"<person> leaves <zone>\""
+    )
+}
+
+/// Run the hook command with the firing in its environment; returns its exit code
+fn run_hook(
+    ctx: &RuntimeContext,
+    command: &[String],
+    trigger: &ZoneTrigger,
+    old: &str,
+    new: &str,
+) -> Result<Option<i32>> {
+    let (program, args) = command.split_first().context("No command to run")?;
+    let transition = match trigger.transition {
+        Transition::Enter => "enter",
+        Transition::Leave => "leave",
+    };
+    let status = Command::new(program)
+        .args(args)
+        .env("HMR_ENTITY_ID", &trigger.entity_id)
+        .env("HMR_ZONE", &trigger.zone_id)
+        .env("HMR_TRANSITION", transition)
+        .env("HMR_OLD_STATE", old)
+        .env("HMR_NEW_STATE", new)
+        .status()
+        .with_context(|| format!("running {program}"))?;
+    if !status.success() {
+        warn(ctx, format!("{program} exited with {status}"));
+    }
+    Ok(status.code())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_trigger() {
+        assert_eq!(
+            split_trigger("person.anna enters home").unwrap(),
+            (
+                "person.anna".to_string(),
+                Transition::Enter,
+                Some("home".to_string())
+            )
+        );
+        assert_eq!(
+            split_trigger("Anna arrives at Work").unwrap(),
+            (
+                "Anna".to_string(),
+                Transition::Enter,
+                Some("Work".to_string())
+            )
+        );
+        assert_eq!(
+            split_trigger("anna's phone leaves").unwrap(),
+            ("anna's phone".to_string(), Transition::Leave, None)
+        );
+        assert!(split_trigger("anna is home").is_err());
+        assert!(split_trigger("leaves home").is_err());
+    }
+
+    #[test]
+    fn test_zone_trigger_fires() {
+        let trigger = |zone_state: &str, transition| ZoneTrigger {
+            entity_id: "person.anna".to_string(),
+            name: "Anna".to_string(),
+            zone_id: "zone.work".to_string(),
+            zone_state: zone_state.to_string(),
+            transition,
+        };
+        let enters_work = trigger("Work", Transition::Enter);
+        assert!(enters_work.fires("not_home", "Work"));
+        assert!(!enters_work.fires("Work", "not_home"));
+        assert!(!enters_work.fires("unavailable", "Work"));
+
+        let leaves_home = trigger("home", Transition::Leave);
+        assert!(leaves_home.fires("home", "not_home"));
+        assert!(leaves_home.fires("home", "Work"));
+        assert!(!leaves_home.fires("home", "unknown"));
+        assert!(!leaves_home.fires("not_home", "Work"));
+    }
+}
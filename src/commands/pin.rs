@@ -0,0 +1,147 @@
+//! Pin command implementations
+//!
+//! Pinned entities are the everyday ones: they settle ambiguous names in
+//! their favor, and `pin list` and `hmr status` show them with their state.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use serde::Serialize;
+use tabled::Tabled;
+
+use crate::api::{EntityState, HassClient};
+use crate::cache::CacheManager;
+use crate::cli::{PinCommand, UnitSystem};
+use crate::config::RuntimeContext;
+use crate::output::{output_for_format, print_table};
+use crate::pins::Pins;
+use crate::resolve::resolve_entity;
+use crate::units::{display_value, with_unit};
+
+#[derive(Debug, Serialize)]
+struct PinResult {
+    entity_id: String,
+    /// Whether the pins changed (false if already pinned / not pinned)
+    changed: bool,
+}
+
+/// A pinned entity with its current state
+#[derive(Debug, Serialize, Tabled)]
+pub struct PinnedRow {
+    pub entity_id: String,
+    pub name: String,
+    pub state: String,
+    pub last_changed: String,
+}
+
+impl PinnedRow {
+    /// Rows for the pinned entities in pin order; entities that no longer
+    /// exist are shown as "missing"
+    pub fn for_pins(pins: &Pins, states: &[EntityState], units: Option<UnitSystem>) -> Vec<Self> {
+        let by_id: HashMap<&str, &EntityState> =
+            states.iter().map(|s| (s.entity_id.as_str(), s)).collect();
+        pins.entities
+            .iter()
+            .map(|entity_id| match by_id.get(entity_id.as_str()) {
+                Some(state) => {
+                    let (value, unit) = display_value(state, units);
+                    Self {
+                        entity_id: entity_id.clone(),
+                        name: state
+                            .attributes
+                            .get("friendly_name")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or_default()
+                            .to_string(),
+                        state: with_unit(&value, &unit),
+                        last_changed: state
+                            .last_changed
+                            .split('.')
+                            .next()
+                            .unwrap_or_default()
+                            .to_string(),
+                    }
+                }
+                None => Self {
+                    entity_id: entity_id.clone(),
+                    name: String::new(),
+                    state: "missing".to_string(),
+                    last_changed: String::new(),
+                },
+            })
+            .collect()
+    }
+}
+
+pub async fn run(ctx: &RuntimeContext, command: PinCommand) -> Result<()> {
+    match command {
+        PinCommand::Add { entity } => add(ctx, &entity).await,
+        PinCommand::Remove { entity } => remove(ctx, &entity).await,
+        PinCommand::List => list(ctx).await,
+    }
+}
+
+async fn add(ctx: &RuntimeContext, input: &str) -> Result<()> {
+    let entity_id = resolve(ctx, input).await?;
+    let mut pins = Pins::load()?;
+    let changed = pins.add(&entity_id);
+    if changed {
+        pins.save()?;
+    }
+    let result = PinResult { entity_id, changed };
+    output_for_format(ctx, &result, || {
+        if result.changed {
+            println!("Pinned {}", result.entity_id);
+        } else {
+            println!("{} is already pinned", result.entity_id);
+        }
+        Ok(())
+    })
+}
+
+async fn remove(ctx: &RuntimeContext, input: &str) -> Result<()> {
+    let mut pins = Pins::load()?;
+    // A pinned ID is removed as given, even if the entity no longer exists
+    let entity_id = if pins.contains(input) {
+        input.to_string()
+    } else {
+        resolve(ctx, input).await?
+    };
+    let changed = pins.remove(&entity_id);
+    if changed {
+        pins.save()?;
+    }
+    let result = PinResult { entity_id, changed };
+    output_for_format(ctx, &result, || {
+        if result.changed {
+            println!("Unpinned {}", result.entity_id);
+        } else {
+            println!("{} was not pinned", result.entity_id);
+        }
+        Ok(())
+    })
+}
+
+async fn list(ctx: &RuntimeContext) -> Result<()> {
+    let pins = Pins::load()?;
+    let states = if pins.entities.is_empty() {
+        Vec::new()
+    } else {
+        HassClient::new(ctx)?.get_states().await?
+    };
+    let rows = PinnedRow::for_pins(&pins, &states, ctx.global.units);
+    output_for_format(ctx, &rows, || {
+        if rows.is_empty() {
+            println!("No pinned entities (pin one with `hmr pin add`)");
+            return Ok(());
+        }
+        print_table(ctx, &rows)
+    })
+}
+
+async fn resolve(ctx: &RuntimeContext, input: &str) -> Result<String> {
+    let mut cache_manager = CacheManager::new(ctx)?;
+    cache_manager.ensure_entities().await?;
+    let entity = resolve_entity(ctx, cache_manager.cache(), input, None)?;
+    Ok(entity.entity_id)
+}
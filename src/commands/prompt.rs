@@ -0,0 +1,321 @@
+//! Prompt command implementation
+//!
+//! `hmr prompt` renders a one-line status for embedding in shell prompts. The
+//! rendered text is kept in a small cache file and reused for `prompt.ttl`, so
+//! most prompt renders never touch the network. When the cache is stale the
+//! server gets `prompt.budget` to answer; a slower or failed fetch prints the
+//! last rendered text (or nothing) rather than stalling the shell. A fetch
+//! that runs over the budget is finished by a detached `hmr prompt
+//! --background`, so the next prompt picks up its result.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Serialize;
+
+use crate::api::{EntityState, HassClient};
+use crate::cache::{cache_dir, CacheFile};
+use crate::cli::{PromptCommand, UnitSystem};
+use crate::config::RuntimeContext;
+use crate::output::{plain_text, print_output};
+use crate::timespec;
+use crate::units::display_state;
+
+const PROMPT_CACHE_FILE: &str = "prompt.json";
+
+/// Rendered text by the template/units it was rendered with
+type PromptCache = BTreeMap<String, CacheFile<String>>;
+
+#[derive(Debug, Serialize)]
+struct PromptOutput {
+    text: String,
+    cached: bool,
+    age_secs: u64,
+}
+
+pub async fn run(ctx: &RuntimeContext, command: PromptCommand) -> Result<()> {
+    let config = &ctx.config.prompt;
+    let template = command.template.as_deref().unwrap_or(&config.template);
+    let placeholders = parse_template(template)?;
+    let ttl = timespec::parse_duration(&config.ttl)
+        .with_context(|| format!("invalid prompt.ttl '{}'", config.ttl))?;
+    let budget = timespec::parse_duration(&config.budget)
+        .with_context(|| format!("invalid prompt.budget '{}'", config.budget))?;
+
+    let server_url = ctx.server_url()?.to_string();
+    let key = format!("{template}\n{:?}", ctx.global.units);
+
+    if command.background {
+        let states = HassClient::new(ctx)?.get_states().await?;
+        let text = render(&placeholders, &states, ctx.global.units);
+        return save_cached(&key, CacheFile::new(text, ttl.as_secs(), server_url));
+    }
+
+    let cached = load_cached(&server_url, &key);
+    if let Some(file) = cached
+        .as_ref()
+        .filter(|f| !command.refresh && f.age() < ttl)
+    {
+        return print_prompt(ctx, &file.data, Some(file.age()));
+    }
+
+    let client = HassClient::new(ctx)?;
+    let fetched = match tokio::time::timeout(budget, client.get_states()).await {
+        Ok(states) => states,
+        Err(_) => {
+            finish_in_background();
+            Err(anyhow!(
+                "server did not answer within {}",
+                humantime::format_duration(budget)
+            ))
+        }
+    };
+    match fetched {
+        Ok(states) => {
+            let text = render(&placeholders, &states, ctx.global.units);
+            let file = CacheFile::new(text.clone(), ttl.as_secs(), server_url);
+            if let Err(e) = save_cached(&key, file) {
+                log::debug!("Could not save prompt cache: {e:#}");
+            }
+            print_prompt(ctx, &text, None)
+        }
+        Err(e) => {
+            log::debug!("Falling back to cached prompt: {e:#}");
+            match cached {
+                Some(file) => print_prompt(ctx, &file.data, Some(file.age())),
+                None => print_prompt(ctx, "", None),
+            }
+        }
+    }
+}
+
+/// Rerun this command detached with `--background`, so a fetch that ran over
+/// the budget still updates the cache without holding up the shell
+fn finish_in_background() {
+    let spawned = std::env::current_exe().and_then(|exe| {
+        Command::new(exe)
+            .args(std::env::args_os().skip(1))
+            .arg("--background")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+    });
+    if let Err(e) = spawned {
+        log::debug!("Could not start background prompt fetch: {e}");
+    }
+}
+
+fn print_prompt(ctx: &RuntimeContext, text: &str, age: Option<Duration>) -> Result<()> {
+    if ctx.is_machine_output() {
+        return print_output(
+            ctx,
+            &PromptOutput {
+                text: text.to_string(),
+                cached: age.is_some(),
+                age_secs: age.map_or(0, |a| a.as_secs()),
+            },
+        );
+    }
+    if ctx.global.plain {
+        println!("{}", plain_text(text));
+    } else {
+        println!("{text}");
+    }
+    Ok(())
+}
+
+/// The cached prompt for this server and key, fresh or not
+fn load_cached(server_url: &str, key: &str) -> Option<CacheFile<String>> {
+    load_cache()
+        .remove(key)
+        .filter(|file| file.server_url == server_url)
+}
+
+fn load_cache() -> PromptCache {
+    cache_dir()
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join(PROMPT_CACHE_FILE)).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_cached(key: &str, file: CacheFile<String>) -> Result<()> {
+    let mut cache = load_cache();
+    cache.insert(key.to_string(), file);
+
+    let dir = cache_dir()?;
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("creating cache directory {}", dir.display()))?;
+    let path = dir.join(PROMPT_CACHE_FILE);
+    fs::write(&path, serde_json::to_string(&cache)?)
+        .with_context(|| format!("writing {}", path.display()))
+}
+
+/// A piece of a prompt template
+#[derive(Debug, PartialEq)]
+enum Placeholder {
+    Text(String),
+    /// Entities in a domain with the given state
+    Count {
+        domain: String,
+        state: String,
+    },
+    /// One entity's state with its unit
+    State(String),
+    /// State of the first alarm control panel
+    Alarm,
+}
+
+/// Split a template into text and placeholders, rejecting unknown placeholders
+fn parse_template(template: &str) -> Result<Vec<Placeholder>> {
+    let mut parts = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            bail!("unclosed '{{' in prompt template");
+        };
+        if start > 0 {
+            parts.push(Placeholder::Text(rest[..start].to_string()));
+        }
+        let name = &rest[start + 1..start + len];
+        parts.push(parse_placeholder(name)?);
+        rest = &rest[start + len + 1..];
+    }
+    if !rest.is_empty() {
+        parts.push(Placeholder::Text(rest.to_string()));
+    }
+
+    Ok(parts)
+}
+
+fn parse_placeholder(name: &str) -> Result<Placeholder> {
+    let placeholder = match name.split_once(':') {
+        None if name == "alarm" => Placeholder::Alarm,
+        Some(("on", domain)) if !domain.is_empty() => Placeholder::Count {
+            domain: domain.to_string(),
+            state: "on".to_string(),
+        },
+        Some(("count", spec)) => match spec.split_once('=') {
+            Some((domain, state)) if !domain.is_empty() && !state.is_empty() => {
+                Placeholder::Count {
+                    domain: domain.to_string(),
+                    state: state.to_string(),
+                }
+            }
+            _ => bail!("prompt placeholder '{{{name}}}' should look like {{count:DOMAIN=STATE}}"),
+        },
+        Some(("state", entity_id)) if entity_id.contains('.') => {
+            Placeholder::State(entity_id.to_string())
+        }
+        _ => bail!(
+            "unknown prompt placeholder '{{{name}}}' \
+             (expected {{on:DOMAIN}}, {{count:DOMAIN=STATE}}, {{state:ENTITY_ID}} or {{alarm}})"
+        ),
+    };
+    Ok(placeholder)
+}
+
+/// Fill in a parsed template from the current states
+///
+/// Missing entities render as "?" so a renamed entity shows up in the prompt
+/// instead of silently disappearing.
+fn render(parts: &[Placeholder], states: &[EntityState], units: Option<UnitSystem>) -> String {
+    parts
+        .iter()
+        .map(|part| match part {
+            Placeholder::Text(text) => text.clone(),
+            Placeholder::Count { domain, state } => in_domain(states, domain)
+                .filter(|s| &s.state == state)
+                .count()
+                .to_string(),
+            Placeholder::State(entity_id) => states
+                .iter()
+                .find(|s| &s.entity_id == entity_id)
+                .map_or_else(|| "?".to_string(), |s| display_state(s, units)),
+            Placeholder::Alarm => in_domain(states, "alarm_control_panel")
+                .next()
+                .map_or_else(|| "?".to_string(), |s| s.state.replace('_', " ")),
+        })
+        .collect()
+}
+
+fn in_domain<'a>(
+    states: &'a [EntityState],
+    domain: &'a str,
+) -> impl Iterator<Item = &'a EntityState> + 'a {
+    states.iter().filter(move |s| {
+        s.entity_id
+            .split_once('.')
+            .is_some_and(|(d, _)| d == domain)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn state(entity_id: &str, state: &str, attributes: serde_json::Value) -> EntityState {
+        serde_json::from_value(json!({
+            "entity_id": entity_id,
+            "state": state,
+            "attributes": attributes,
+            "last_changed": "2025-01-15T00:00:00Z",
+            "last_updated": "2025-01-15T00:00:00Z",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_template() {
+        assert_eq!(
+            parse_template("🏠 {on:light} lights · {alarm}").unwrap(),
+            vec![
+                Placeholder::Text("🏠 ".to_string()),
+                Placeholder::Count {
+                    domain: "light".to_string(),
+                    state: "on".to_string()
+                },
+                Placeholder::Text(" lights · ".to_string()),
+                Placeholder::Alarm,
+            ]
+        );
+        assert!(parse_template("{on:}").is_err());
+        assert!(parse_template("{count:cover}").is_err());
+        assert!(parse_template("{state:temperature}").is_err());
+        assert!(parse_template("{lights").is_err());
+    }
+
+    #[test]
+    fn test_render() {
+        let states = vec![
+            state("light.kitchen", "on", json!({})),
+            state("light.hall", "off", json!({})),
+            state("light.desk", "on", json!({})),
+            state("cover.garage", "open", json!({})),
+            state(
+                "sensor.living_temp",
+                "21.4",
+                json!({"unit_of_measurement": "°C", "device_class": "temperature"}),
+            ),
+            state("alarm_control_panel.home", "armed_away", json!({})),
+        ];
+        let parts = parse_template(
+            "{on:light} lights · {count:cover=open} open · {state:sensor.living_temp} · {alarm}",
+        )
+        .unwrap();
+
+        assert_eq!(
+            render(&parts, &states, None),
+            "2 lights · 1 open · 21.4 °C · armed away"
+        );
+
+        let parts = parse_template("{state:sensor.missing} {alarm}").unwrap();
+        assert_eq!(render(&parts, &states[..1], None), "? ?");
+    }
+}
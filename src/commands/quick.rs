@@ -0,0 +1,1239 @@
+//! Per-domain quick commands
+//!
+//! `hmr light kitchen 50%`, `hmr cover garage close`: a name followed by an
+//! optional action word. Names only resolve against entities of the command's
+//! domain, so a quick command never acts on something unexpected. Without an
+//! action the current state is shown.
+
+use anyhow::{anyhow, Result};
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use crate::api::{EntityState, HassClient};
+use crate::cache::{Cache, CacheManager, CachedEntity};
+use crate::cli::{
+    ClimateCommand, LightCommand, OutputFormat, PressCommand, QuickCommand, RemoteCommand,
+    SirenCommand, UnitSystem,
+};
+use crate::colors::{parse_color, parse_hex};
+use crate::config::RuntimeContext;
+use crate::output::{print_output, print_porcelain, warn};
+use crate::resolve::{match_domain_entity, resolve_domain_entity, room_scope};
+use crate::units::display_state;
+
+/// Color names accepted as a light action (all valid Home Assistant `color_name`s)
+const LIGHT_COLORS: &[&str] = &[
+    "red",
+    "green",
+    "blue",
+    "white",
+    "yellow",
+    "orange",
+    "purple",
+    "pink",
+    "cyan",
+    "magenta",
+    "lime",
+    "teal",
+    "violet",
+    "gold",
+    "turquoise",
+    "indigo",
+    "coral",
+    "salmon",
+    "crimson",
+    "lavender",
+];
+
+/// A service call chosen by an action word
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuickAction {
+    pub service: String,
+    pub data: Map<String, Value>,
+}
+
+impl QuickAction {
+    pub fn new(service: &str) -> Self {
+        Self {
+            service: service.to_string(),
+            data: Map::new(),
+        }
+    }
+
+    pub fn with_data(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.data.insert(key.to_string(), value.into());
+        self
+    }
+}
+
+/// Everything needed to run a quick command for one domain
+pub struct QuickDomain {
+    pub domain: &'static str,
+    /// Attributes shown next to the state when no action is given
+    pub attributes: &'static [&'static str],
+    pub parse: fn(&str) -> Option<QuickAction>,
+}
+
+pub const LIGHT: QuickDomain = QuickDomain {
+    domain: "light",
+    attributes: &["brightness", "color_temp_kelvin", "rgb_color"],
+    parse: parse_light_action,
+};
+
+pub const SWITCH: QuickDomain = QuickDomain {
+    domain: "switch",
+    attributes: &[],
+    parse: parse_switch_action,
+};
+
+pub const CLIMATE: QuickDomain = QuickDomain {
+    domain: "climate",
+    attributes: &[
+        "current_temperature",
+        "temperature",
+        "target_temp_low",
+        "target_temp_high",
+        "hvac_action",
+        "preset_mode",
+        "current_humidity",
+        "humidity",
+    ],
+    parse: parse_climate_action,
+};
+
+pub const FAN: QuickDomain = QuickDomain {
+    domain: "fan",
+    attributes: &["percentage", "oscillating", "direction", "preset_mode"],
+    parse: parse_fan_action,
+};
+
+pub const VALVE: QuickDomain = QuickDomain {
+    domain: "valve",
+    attributes: &["current_position"],
+    parse: parse_valve_action,
+};
+
+pub const COVER: QuickDomain = QuickDomain {
+    domain: "cover",
+    attributes: &["current_position", "current_tilt_position"],
+    parse: parse_cover_action,
+};
+
+pub const SIREN: QuickDomain = QuickDomain {
+    domain: "siren",
+    attributes: &["tone", "duration", "available_tones"],
+    parse: parse_power,
+};
+
+pub const REMOTE: QuickDomain = QuickDomain {
+    domain: "remote",
+    attributes: &["activity_list", "current_activity"],
+    parse: no_action,
+};
+
+pub const TEXT: QuickDomain = QuickDomain {
+    domain: "text",
+    attributes: &["min", "max", "pattern", "mode"],
+    parse: no_action,
+};
+
+pub const SELECT: QuickDomain = QuickDomain {
+    domain: "select",
+    attributes: &["options"],
+    parse: no_action,
+};
+
+pub const NUMBER: QuickDomain = QuickDomain {
+    domain: "number",
+    attributes: &["min", "max", "step", "unit_of_measurement"],
+    parse: no_action,
+};
+
+/// Run a quick command against `spec`'s domain
+pub async fn run(ctx: &RuntimeContext, spec: &QuickDomain, cmd: QuickCommand) -> Result<()> {
+    let (name, action) = split_action(&cmd.words, spec.parse);
+    let entity_id = resolve(ctx, spec, name).await?;
+
+    match action {
+        None => show_state(ctx, &entity_id, spec.attributes).await,
+        Some(action) => execute(ctx, spec.domain, &entity_id, &[action], cmd.dry_run).await,
+    }
+}
+
+/// `hmr cover NAME [open|close|stop|toggle|NN%|position NN|tilt NN]`
+pub async fn cover(ctx: &RuntimeContext, cmd: QuickCommand) -> Result<()> {
+    let (name, action) = match split_cover_setting(&cmd.words)? {
+        Some((name, action)) => (name, Some(action)),
+        None => split_action(&cmd.words, COVER.parse),
+    };
+    let entity_id = resolve(ctx, &COVER, name).await?;
+
+    match action {
+        None => show_state(ctx, &entity_id, COVER.attributes).await,
+        Some(action) => execute(ctx, COVER.domain, &entity_id, &[action], cmd.dry_run).await,
+    }
+}
+
+/// A trailing "position NN" or "tilt NN" (NN may end in %)
+fn split_cover_setting(words: &[String]) -> Result<Option<(Option<String>, QuickAction)>> {
+    let [name @ .., keyword, value] = words else {
+        return Ok(None);
+    };
+    let (service, key) = match keyword.to_lowercase().as_str() {
+        "position" => ("set_cover_position", "position"),
+        "tilt" => ("set_cover_tilt_position", "tilt_position"),
+        _ => return Ok(None),
+    };
+    let pct = value
+        .strip_suffix('%')
+        .unwrap_or(value)
+        .parse::<u8>()
+        .ok()
+        .filter(|pct| *pct <= 100)
+        .ok_or_else(|| anyhow!("Cover {keyword} must be 0-100%, got '{value}'"))?;
+
+    let name = (!name.is_empty()).then(|| name.join(" "));
+    Ok(Some((name, QuickAction::new(service).with_data(key, pct))))
+}
+
+/// `hmr climate NAME [TEMP] [--mode MODE] [--preset PRESET]`
+pub async fn climate(ctx: &RuntimeContext, cmd: ClimateCommand) -> Result<()> {
+    let (name, temperature) = split_action(&cmd.words, CLIMATE.parse);
+    let name = strip_set_keyword(name);
+
+    let mut actions = Vec::new();
+    match (temperature, cmd.mode.as_deref()) {
+        // set_temperature takes the mode too, so one call does both
+        (Some(action), Some(mode)) => actions.push(action.with_data("hvac_mode", mode)),
+        (Some(action), None) => actions.push(action),
+        (None, Some(mode)) => {
+            actions.push(QuickAction::new("set_hvac_mode").with_data("hvac_mode", mode))
+        }
+        (None, None) => {}
+    }
+    if let Some(preset) = cmd.preset.as_deref() {
+        actions.push(QuickAction::new("set_preset_mode").with_data("preset_mode", preset));
+    }
+    if let Some(humidity) = cmd.humidity {
+        actions.push(QuickAction::new("set_humidity").with_data("humidity", humidity));
+    }
+
+    let entity_id = resolve(ctx, &CLIMATE, name).await?;
+    if actions.is_empty() {
+        return show_state(ctx, &entity_id, CLIMATE.attributes).await;
+    }
+    execute(ctx, CLIMATE.domain, &entity_id, &actions, cmd.dry_run).await
+}
+
+/// `hmr light NAME [ACTION] [--color C] [--kelvin K] [--brightness B] [--transition S] [--effect E]`
+///
+/// The flags all go into one `light.turn_on` call; with off only
+/// `--transition` applies.
+pub async fn light(ctx: &RuntimeContext, cmd: LightCommand) -> Result<()> {
+    let (name, action) = split_action(&cmd.words, LIGHT.parse);
+    let action = light_action(action, &cmd)?;
+
+    let entity_id = resolve(ctx, &LIGHT, name).await?;
+    match action {
+        None => show_state(ctx, &entity_id, LIGHT.attributes).await,
+        Some(action) => execute(ctx, LIGHT.domain, &entity_id, &[action], cmd.dry_run).await,
+    }
+}
+
+/// Merge the light flags into the action word's service call
+fn light_action(action: Option<QuickAction>, cmd: &LightCommand) -> Result<Option<QuickAction>> {
+    let mut data = Map::new();
+    if let Some(color) = cmd.color.as_deref() {
+        let rgb = parse_color(color)
+            .ok_or_else(|| anyhow!("Unknown color '{color}' (use a CSS color name or #rrggbb)"))?;
+        data.insert("rgb_color".to_string(), json!(rgb));
+    }
+    if let Some(kelvin) = cmd.kelvin {
+        data.insert("color_temp_kelvin".to_string(), json!(kelvin));
+    }
+    if let Some(brightness) = cmd.brightness.as_deref() {
+        let (key, value) = parse_brightness(brightness)?;
+        data.insert(key.to_string(), json!(value));
+    }
+    if let Some(effect) = cmd.effect.as_deref() {
+        data.insert("effect".to_string(), json!(effect));
+    }
+
+    let mut action = match action {
+        None if data.is_empty() && cmd.transition.is_none() => return Ok(None),
+        None => QuickAction::new("turn_on"),
+        Some(action) if action.service == "turn_on" => action,
+        Some(action) if !data.is_empty() => {
+            return Err(anyhow!(
+                "--color, --kelvin, --brightness and --effect only apply when turning a light on, not {}",
+                action.service
+            ));
+        }
+        Some(action) => action,
+    };
+    if data.contains_key("rgb_color") || data.contains_key("color_temp_kelvin") {
+        // The action word's color would conflict with the flag's
+        action.data.remove("rgb_color");
+        action.data.remove("color_name");
+        action.data.remove("color_temp_kelvin");
+    }
+    action.data.extend(data);
+    if let Some(transition) = cmd.transition {
+        if !(transition.is_finite() && transition >= 0.0) {
+            return Err(anyhow!("--transition must be a number of seconds"));
+        }
+        action = action.with_data("transition", transition);
+    }
+    Ok(Some(action))
+}
+
+/// "60%" sets brightness_pct, a plain number the 0-255 brightness
+fn parse_brightness(input: &str) -> Result<(&'static str, u8)> {
+    if let Some(pct) = parse_percent(input) {
+        return Ok(("brightness_pct", pct));
+    }
+    input
+        .parse::<u8>()
+        .map(|value| ("brightness", value))
+        .map_err(|_| anyhow!("Invalid brightness: {input} (expected NN% or 0-255)"))
+}
+
+/// `hmr siren NAME [on|off|toggle] [--tone TONE] [--duration DURATION]`
+///
+/// Sounding a siren asks for confirmation unless `--yes` is given.
+pub async fn siren(ctx: &RuntimeContext, cmd: SirenCommand) -> Result<()> {
+    let (name, action) = split_action(&cmd.words, SIREN.parse);
+
+    // A tone or duration means "sound it"
+    let action = match action {
+        None if cmd.tone.is_none() && cmd.duration.is_none() => None,
+        None => Some(QuickAction::new("turn_on")),
+        Some(action) if action.service == "turn_on" => Some(action),
+        Some(action) if cmd.tone.is_some() || cmd.duration.is_some() => {
+            return Err(anyhow!(
+                "--tone and --duration only apply when turning a siren on, not {}",
+                action.service
+            ));
+        }
+        Some(action) => Some(action),
+    };
+    let action = action.map(|mut action| {
+        if let Some(tone) = cmd.tone {
+            action = action.with_data("tone", tone);
+        }
+        if let Some(duration) = cmd.duration {
+            action = action.with_data("duration", duration.as_secs());
+        }
+        action
+    });
+
+    let entity_id = resolve(ctx, &SIREN, name).await?;
+    let Some(action) = action else {
+        return show_state(ctx, &entity_id, SIREN.attributes).await;
+    };
+
+    if !cmd.dry_run {
+        crate::prompt::confirm_protected(
+            &format!("siren.{}", action.service),
+            std::slice::from_ref(&entity_id),
+            cmd.yes,
+        )?;
+    }
+    execute(ctx, SIREN.domain, &entity_id, &[action], cmd.dry_run).await
+}
+
+/// `hmr select NAME [OPTION]`, for select and input_select entities
+///
+/// The option is matched against the entity's `options` attribute: the
+/// cached one when it matches, else the current one from the server.
+pub async fn select(ctx: &RuntimeContext, cmd: QuickCommand) -> Result<()> {
+    let is_option = |entity: &CachedEntity, word: &str| {
+        entity
+            .options()
+            .is_some_and(|options| match_option(word, &options).is_ok())
+    };
+    let (entity, option) = resolve_with_value(
+        ctx,
+        SELECT.domain,
+        &["select", "input_select"],
+        &cmd.words,
+        is_option,
+    )
+    .await?;
+    let entity_id = entity.entity_id.clone();
+
+    let Some(option) = option else {
+        return show_state(ctx, &entity_id, SELECT.attributes).await;
+    };
+    let cached = entity
+        .options()
+        .and_then(|options| match_option(&option, &options).ok());
+    let option = match cached {
+        Some(option) => option,
+        None => {
+            let state = HassClient::new(ctx)?.get_state(&entity_id).await?;
+            let options: Vec<String> = state.attributes["options"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect();
+            match_option(&option, &options)?
+        }
+    };
+
+    let action = QuickAction::new("select_option").with_data("option", option);
+    execute(
+        ctx,
+        entity_domain(&entity_id),
+        &entity_id,
+        &[action],
+        cmd.dry_run,
+    )
+    .await
+}
+
+/// `hmr number NAME [VALUE|+N|-N]`, for number and input_number entities
+///
+/// A signed value adjusts the current value; the result must lie within the
+/// entity's `min` and `max`.
+pub async fn number(ctx: &RuntimeContext, cmd: QuickCommand) -> Result<()> {
+    let is_number = |_: &CachedEntity, word: &str| word.parse::<f64>().is_ok();
+    let (entity, value) = resolve_with_value(
+        ctx,
+        NUMBER.domain,
+        &["number", "input_number"],
+        &cmd.words,
+        is_number,
+    )
+    .await?;
+    let entity_id = entity.entity_id;
+
+    let Some(value) = value else {
+        return show_state(ctx, &entity_id, NUMBER.attributes).await;
+    };
+    let state = HassClient::new(ctx)?.get_state(&entity_id).await?;
+    let bound = |key: &str| state.attributes[key].as_f64();
+    let value = number_target(&value, state.state.parse().ok(), bound("min"), bound("max"))?;
+
+    let action = QuickAction::new("set_value").with_data("value", value);
+    execute(
+        ctx,
+        entity_domain(&entity_id),
+        &entity_id,
+        &[action],
+        cmd.dry_run,
+    )
+    .await
+}
+
+/// `hmr text NAME [VALUE]`, for text and input_text entities
+///
+/// The value is checked against the entity's `min`/`max` length and `pattern`
+/// before the call, so the error names the constraint instead of failing remotely.
+pub async fn text(ctx: &RuntimeContext, cmd: QuickCommand) -> Result<()> {
+    // Any word can be a text value, so only the names decide
+    let (entity, value) = resolve_with_value(
+        ctx,
+        TEXT.domain,
+        &["text", "input_text"],
+        &cmd.words,
+        |_, _| false,
+    )
+    .await?;
+    let entity_id = entity.entity_id;
+
+    let Some(value) = value else {
+        return show_state(ctx, &entity_id, TEXT.attributes).await;
+    };
+    let state = HassClient::new(ctx)?.get_state(&entity_id).await?;
+    let attrs = &state.attributes;
+    let pattern = attrs["pattern"].as_str().filter(|p| !p.is_empty());
+    let check = check_text(
+        &value,
+        attrs["min"].as_u64(),
+        attrs["max"].as_u64(),
+        pattern,
+    )?;
+    if let TextCheck::PatternUnsupported(pattern) = check {
+        warn(
+            ctx,
+            format!("Cannot check pattern '{pattern}' locally; Home Assistant will validate it"),
+        );
+    }
+
+    let action = QuickAction::new("set_value").with_data("value", value);
+    execute(
+        ctx,
+        entity_domain(&entity_id),
+        &entity_id,
+        &[action],
+        cmd.dry_run,
+    )
+    .await
+}
+
+/// `hmr press NAME`, for button and input_button entities
+pub async fn press(ctx: &RuntimeContext, cmd: PressCommand) -> Result<()> {
+    let name = cmd.words.join(" ");
+    let entity_id = resolve_in(ctx, "button", &["button", "input_button"], Some(name)).await?;
+
+    let action = QuickAction::new("press");
+    execute(
+        ctx,
+        entity_domain(&entity_id),
+        &entity_id,
+        &[action],
+        cmd.dry_run,
+    )
+    .await
+}
+
+/// `hmr remote NAME send COMMAND... [--device DEVICE] [--repeats N]`
+pub async fn remote(ctx: &RuntimeContext, cmd: RemoteCommand) -> Result<()> {
+    let (name, commands) = split_remote(&cmd.words)?;
+    let entity_id = resolve(ctx, &REMOTE, Some(name)).await?;
+
+    if commands.is_empty() {
+        return show_state(ctx, &entity_id, REMOTE.attributes).await;
+    }
+
+    let mut action = QuickAction::new("send_command").with_data("command", commands);
+    if let Some(device) = cmd.device {
+        action = action.with_data("device", device);
+    }
+    if let Some(repeats) = cmd.repeats {
+        action = action.with_data("num_repeats", repeats);
+    }
+    execute(ctx, REMOTE.domain, &entity_id, &[action], cmd.dry_run).await
+}
+
+/// Split `NAME send COMMAND...` into the name and the commands
+fn split_remote(words: &[String]) -> Result<(String, Vec<String>)> {
+    let Some(send) = words.iter().position(|w| w.eq_ignore_ascii_case("send")) else {
+        return Ok((words.join(" "), Vec::new()));
+    };
+    if send == 0 {
+        return Err(anyhow!("No remote name given before 'send'"));
+    }
+    if send + 1 == words.len() {
+        return Err(anyhow!("No commands given after 'send'"));
+    }
+    Ok((words[..send].join(" "), words[send + 1..].to_vec()))
+}
+
+/// Resolve a name within the domain to an entity ID
+async fn resolve(ctx: &RuntimeContext, spec: &QuickDomain, name: Option<String>) -> Result<String> {
+    resolve_in(ctx, spec.domain, &[spec.domain], name).await
+}
+
+/// Resolve a name to an entity ID within any of `domains`
+///
+/// `kind` names the target in errors ("No scene name given"). Honors `--room`.
+pub async fn resolve_in(
+    ctx: &RuntimeContext,
+    kind: &str,
+    domains: &[&str],
+    name: Option<String>,
+) -> Result<String> {
+    Ok(resolve_entity_in(ctx, kind, domains, name).await?.entity_id)
+}
+
+/// Like [`resolve_in`], returning the cached entity with its attributes
+async fn resolve_entity_in(
+    ctx: &RuntimeContext,
+    kind: &str,
+    domains: &[&str],
+    name: Option<String>,
+) -> Result<CachedEntity> {
+    let name = name.ok_or_else(|| anyhow!("No {kind} name given"))?;
+
+    let mut cache_manager = CacheManager::new(ctx)?;
+    cache_manager.ensure_entities().await?;
+    let room = room_scope(ctx, &mut cache_manager).await?;
+    let cache = room
+        .as_ref()
+        .map_or(cache_manager.cache(), |(_, cache)| cache);
+    resolve_domain_entity(ctx, cache, &name, domains)
+}
+
+fn entity_domain(entity_id: &str) -> &str {
+    entity_id
+        .split_once('.')
+        .map_or(entity_id, |(domain, _)| domain)
+}
+
+/// Resolve the entity `words` name within `domains`, and a trailing value
+///
+/// Honors `--room`. See [`split_value`] for when the last word is the value.
+async fn resolve_with_value(
+    ctx: &RuntimeContext,
+    kind: &str,
+    domains: &[&str],
+    words: &[String],
+    is_value: impl Fn(&CachedEntity, &str) -> bool,
+) -> Result<(CachedEntity, Option<String>)> {
+    if words.is_empty() {
+        return Err(anyhow!("No {kind} name given"));
+    }
+
+    let mut cache_manager = CacheManager::new(ctx)?;
+    cache_manager.ensure_entities().await?;
+    let room = room_scope(ctx, &mut cache_manager).await?;
+    let cache = room
+        .as_ref()
+        .map_or(cache_manager.cache(), |(_, cache)| cache);
+    let (name, value) = split_value(cache, domains, words, is_value);
+    Ok((resolve_domain_entity(ctx, cache, &name, domains)?, value))
+}
+
+/// Split words into a name and a trailing value
+///
+/// The last word is the value when `is_value` accepts it for the entity the
+/// other words name, or when all the words together aren't exactly an
+/// entity's name; so "living room mode" stays the name of "Living Room Mode".
+fn split_value(
+    cache: &Cache,
+    domains: &[&str],
+    words: &[String],
+    is_value: impl Fn(&CachedEntity, &str) -> bool,
+) -> (String, Option<String>) {
+    let whole = words.join(" ");
+    let [name @ .., value] = words else {
+        return (whole, None);
+    };
+    if name.is_empty() {
+        return (whole, None);
+    }
+    let name = name.join(" ");
+    let fits = match_domain_entity(cache, &name, domains)
+        .is_some_and(|(entity, _)| is_value(&entity, value));
+    let names_entity = match_domain_entity(cache, &whole, domains).is_some_and(|(_, exact)| exact);
+    if fits || !names_entity {
+        (name, Some(value.clone()))
+    } else {
+        (whole, None)
+    }
+}
+
+/// Drop a trailing "set" from a name ("living room set 21.5")
+fn strip_set_keyword(name: Option<String>) -> Option<String> {
+    let name = name?;
+    match name.rsplit_once(' ') {
+        Some((rest, last)) if last.eq_ignore_ascii_case("set") => Some(rest.to_string()),
+        _ => Some(name),
+    }
+}
+
+/// Split words into a name and a trailing action word, if the last word is one
+fn split_action(
+    words: &[String],
+    parse: fn(&str) -> Option<QuickAction>,
+) -> (Option<String>, Option<QuickAction>) {
+    let action = match words {
+        [rest @ .., last] if !rest.is_empty() => parse(last),
+        _ => None,
+    };
+    let name_words = if action.is_some() {
+        &words[..words.len() - 1]
+    } else {
+        words
+    };
+    let name = (!name_words.is_empty()).then(|| name_words.join(" "));
+    (name, action)
+}
+
+async fn show_state(ctx: &RuntimeContext, entity_id: &str, attributes: &[&str]) -> Result<()> {
+    let client = HassClient::new(ctx)?;
+    let state = client.get_state(entity_id).await?;
+
+    if ctx.porcelain().is_some() {
+        print_porcelain(
+            "state",
+            &[
+                &state.entity_id,
+                &state.last_changed,
+                &state.last_updated,
+                &state.state,
+            ],
+        );
+        return Ok(());
+    }
+
+    match ctx.output_format() {
+        OutputFormat::Json => print_output(ctx, &state)?,
+        OutputFormat::Yaml => println!("{}", serde_yaml::to_string(&state)?),
+        _ => print_state_line(&state, attributes, ctx.global.units),
+    }
+
+    Ok(())
+}
+
+fn print_state_line(state: &EntityState, attributes: &[&str], units: Option<UnitSystem>) {
+    let name = state
+        .attributes
+        .get("friendly_name")
+        .and_then(|v| v.as_str())
+        .unwrap_or(&state.entity_id);
+    println!(
+        "{name} ({}): {}",
+        state.entity_id,
+        display_state(state, units)
+    );
+
+    for key in attributes {
+        if let Some(value) = state.attributes.get(*key).filter(|v| !v.is_null()) {
+            println!("  {key}: {value}");
+        }
+    }
+}
+
+/// Call the actions' services on one entity, in order
+pub async fn execute(
+    ctx: &RuntimeContext,
+    domain: &str,
+    entity_id: &str,
+    actions: &[QuickAction],
+    dry_run: bool,
+) -> Result<()> {
+    let calls: Vec<Value> = actions
+        .iter()
+        .map(|action| {
+            let mut data = action.data.clone();
+            data.insert("entity_id".to_string(), json!(entity_id));
+            json!({ "domain": domain, "service": action.service, "data": data })
+        })
+        .collect();
+
+    if dry_run {
+        match ctx.output_format() {
+            OutputFormat::Json => print_output(ctx, &calls)?,
+            _ => {
+                for call in &calls {
+                    println!(
+                        "Would call {domain}.{} with {}",
+                        call["service"].as_str().unwrap_or_default(),
+                        call["data"]
+                    );
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    let client = HassClient::new(ctx)?;
+    let mut results = Vec::with_capacity(actions.len());
+    for (action, call) in actions.iter().zip(&calls) {
+        let result = client
+            .call_service(domain, &action.service, &call["data"])
+            .await?;
+        results.push((format!("{domain}.{}", action.service), result));
+    }
+
+    if ctx.porcelain().is_some() {
+        for (service, result) in &results {
+            print_porcelain("called", &[service]);
+            for changed in result.as_array().into_iter().flatten() {
+                if let Some(id) = changed.get("entity_id").and_then(|v| v.as_str()) {
+                    let state = changed.get("state").and_then(|v| v.as_str()).unwrap_or("");
+                    print_porcelain("changed", &[id, state]);
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    let values: Vec<&Value> = results.iter().map(|(_, result)| result).collect();
+    match (ctx.output_format(), values.as_slice()) {
+        (OutputFormat::Json, [single]) => print_output(ctx, single)?,
+        (OutputFormat::Json, _) => print_output(ctx, &values)?,
+        (OutputFormat::Yaml, [single]) => println!("{}", serde_yaml::to_string(single)?),
+        (OutputFormat::Yaml, _) => println!("{}", serde_yaml::to_string(&values)?),
+        _ => {
+            for (service, result) in &results {
+                let new_state = result
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .find(|s| s.get("entity_id").and_then(|v| v.as_str()) == Some(entity_id))
+                    .and_then(|s| s.get("state"))
+                    .and_then(|v| v.as_str());
+                match new_state {
+                    Some(state) => println!("{service} {entity_id} -> {state}"),
+                    None => println!("{service} {entity_id}"),
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Domains whose action depends on the entity's attributes parse nothing up front
+fn no_action(_word: &str) -> Option<QuickAction> {
+    None
+}
+
+/// Match `input` against a select's options: exact, then unique prefix, then fuzzy
+fn match_option(input: &str, options: &[String]) -> Result<String> {
+    let lower = input.to_lowercase();
+    if let Some(option) = options.iter().find(|o| o.to_lowercase() == lower) {
+        return Ok(option.clone());
+    }
+
+    let prefixed: Vec<&String> = options
+        .iter()
+        .filter(|o| o.to_lowercase().starts_with(&lower))
+        .collect();
+    if let [option] = prefixed.as_slice() {
+        return Ok((*option).clone());
+    }
+
+    let matcher = SkimMatcherV2::default().ignore_case();
+    let mut scored: Vec<(i64, &String)> = options
+        .iter()
+        .filter_map(|o| matcher.fuzzy_match(o, input).map(|score| (score, o)))
+        .collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    match scored.as_slice() {
+        [(_, option)] => Ok((*option).clone()),
+        [(best, option), (second, _), ..] if best > second => Ok((*option).clone()),
+        _ => Err(anyhow!(
+            "'{input}' does not match one option; choose from: {}",
+            options.join(", ")
+        )),
+    }
+}
+
+/// Outcome of validating a text value
+#[derive(Debug, PartialEq)]
+enum TextCheck<'a> {
+    Valid,
+    /// The pattern uses syntax the regex crate lacks (e.g., lookaround)
+    PatternUnsupported(&'a str),
+}
+
+/// Validate a text value against length limits and pattern
+///
+/// Home Assistant matches the pattern at the start of the value (Python's
+/// `re.match`), so it is anchored the same way here.
+fn check_text<'a>(
+    value: &str,
+    min: Option<u64>,
+    max: Option<u64>,
+    pattern: Option<&'a str>,
+) -> Result<TextCheck<'a>> {
+    let len = value.chars().count() as u64;
+    if let Some(min) = min.filter(|&min| len < min) {
+        return Err(anyhow!(
+            "Value is {len} characters; at least {min} required"
+        ));
+    }
+    if let Some(max) = max.filter(|&max| len > max) {
+        return Err(anyhow!("Value is {len} characters; at most {max} allowed"));
+    }
+
+    let Some(pattern) = pattern else {
+        return Ok(TextCheck::Valid);
+    };
+    match Regex::new(&format!("^(?:{pattern})")) {
+        Ok(re) if re.is_match(value) => Ok(TextCheck::Valid),
+        Ok(_) => Err(anyhow!("Value does not match pattern '{pattern}'")),
+        Err(_) => Ok(TextCheck::PatternUnsupported(pattern)),
+    }
+}
+
+/// Target value for `hmr number`: absolute, or relative when signed (+1, -0.5)
+fn number_target(
+    input: &str,
+    current: Option<f64>,
+    min: Option<f64>,
+    max: Option<f64>,
+) -> Result<f64> {
+    let parse = |s: &str| -> Result<f64> {
+        s.parse()
+            .map_err(|_| anyhow!("Invalid number: {input} (expected VALUE, +N or -N)"))
+    };
+
+    let value = if input.starts_with(['+', '-']) {
+        let current =
+            current.ok_or_else(|| anyhow!("Current value is unknown; give an absolute value"))?;
+        current + parse(input)?
+    } else {
+        parse(input)?
+    };
+
+    if min.is_some_and(|min| value < min) || max.is_some_and(|max| value > max) {
+        return Err(anyhow!(
+            "{value} is out of range ({} to {})",
+            min.map_or("-".to_string(), |v| v.to_string()),
+            max.map_or("-".to_string(), |v| v.to_string())
+        ));
+    }
+    Ok(value)
+}
+
+/// Parse "NN%" into a percentage
+fn parse_percent(word: &str) -> Option<u8> {
+    let pct: u8 = word.strip_suffix('%')?.parse().ok()?;
+    (pct <= 100).then_some(pct)
+}
+
+/// Parse on/off/toggle
+fn parse_power(word: &str) -> Option<QuickAction> {
+    match word.to_lowercase().as_str() {
+        "on" => Some(QuickAction::new("turn_on")),
+        "off" => Some(QuickAction::new("turn_off")),
+        "toggle" => Some(QuickAction::new("toggle")),
+        _ => None,
+    }
+}
+
+/// on, off, toggle, 50%, 2700k, #ff8800, or a color name
+fn parse_light_action(word: &str) -> Option<QuickAction> {
+    if let Some(action) = parse_power(word) {
+        return Some(action);
+    }
+    if let Some(pct) = parse_percent(word) {
+        return Some(QuickAction::new("turn_on").with_data("brightness_pct", pct));
+    }
+
+    let lower = word.to_lowercase();
+    if let Some(kelvin) = lower
+        .strip_suffix('k')
+        .and_then(|k| k.parse::<u32>().ok())
+        .filter(|k| (1000..=10000).contains(k))
+    {
+        return Some(QuickAction::new("turn_on").with_data("color_temp_kelvin", kelvin));
+    }
+    if let Some(rgb) = lower.strip_prefix('#').and_then(parse_hex) {
+        return Some(QuickAction::new("turn_on").with_data("rgb_color", json!(rgb)));
+    }
+    if LIGHT_COLORS.contains(&lower.as_str()) {
+        return Some(QuickAction::new("turn_on").with_data("color_name", lower));
+    }
+
+    None
+}
+
+/// on, off, toggle
+fn parse_switch_action(word: &str) -> Option<QuickAction> {
+    parse_power(word)
+}
+
+/// on, off, toggle, a speed like 40%, reverse, forward, oscillate, or still
+fn parse_fan_action(word: &str) -> Option<QuickAction> {
+    if let Some(action) = parse_power(word) {
+        return Some(action);
+    }
+    if let Some(pct) = parse_percent(word) {
+        return Some(QuickAction::new("set_percentage").with_data("percentage", pct));
+    }
+
+    match word.to_lowercase().as_str() {
+        direction @ ("reverse" | "forward") => {
+            Some(QuickAction::new("set_direction").with_data("direction", direction))
+        }
+        "oscillate" => Some(QuickAction::new("oscillate").with_data("oscillating", true)),
+        "still" => Some(QuickAction::new("oscillate").with_data("oscillating", false)),
+        _ => None,
+    }
+}
+
+/// open, close, stop, toggle, or a position like 40%
+fn parse_valve_action(word: &str) -> Option<QuickAction> {
+    if let Some(pct) = parse_percent(word) {
+        return Some(QuickAction::new("set_valve_position").with_data("position", pct));
+    }
+
+    match word.to_lowercase().as_str() {
+        "open" | "on" => Some(QuickAction::new("open_valve")),
+        "close" | "off" => Some(QuickAction::new("close_valve")),
+        "stop" => Some(QuickAction::new("stop_valve")),
+        "toggle" => Some(QuickAction::new("toggle")),
+        _ => None,
+    }
+}
+
+/// open, close, stop, toggle, or a position like 40%
+fn parse_cover_action(word: &str) -> Option<QuickAction> {
+    if let Some(pct) = parse_percent(word) {
+        return Some(QuickAction::new("set_cover_position").with_data("position", pct));
+    }
+
+    match word.to_lowercase().as_str() {
+        "open" | "up" => Some(QuickAction::new("open_cover")),
+        "close" | "down" => Some(QuickAction::new("close_cover")),
+        "stop" => Some(QuickAction::new("stop_cover")),
+        "toggle" => Some(QuickAction::new("toggle")),
+        _ => None,
+    }
+}
+
+/// A target temperature like 21, 21.5 or 70°F
+fn parse_climate_action(word: &str) -> Option<QuickAction> {
+    parse_temperature(word).map(|t| QuickAction::new("set_temperature").with_data("temperature", t))
+}
+
+/// Parse a setpoint, ignoring a trailing unit. Only plausible setpoints count,
+/// so the number in a name like "floor 2" stays part of the name.
+fn parse_temperature(word: &str) -> Option<f64> {
+    let number = word
+        .trim_end_matches(['c', 'C', 'f', 'F'])
+        .trim_end_matches('°');
+    let value: f64 = number.parse().ok()?;
+    (5.0..=95.0).contains(&value).then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(input: &str) -> Vec<String> {
+        input.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_split_action() {
+        let (name, action) = split_action(&words("living room 50%"), parse_light_action);
+        assert_eq!(name.as_deref(), Some("living room"));
+        assert_eq!(action.unwrap().data["brightness_pct"], json!(50));
+
+        // A lone word is always the name
+        let (name, action) = split_action(&words("red"), parse_light_action);
+        assert_eq!(name.as_deref(), Some("red"));
+        assert!(action.is_none());
+
+        let (name, action) = split_action(&words("kitchen"), parse_light_action);
+        assert_eq!(name.as_deref(), Some("kitchen"));
+        assert!(action.is_none());
+    }
+
+    #[test]
+    fn test_climate_set_keyword() {
+        let (name, action) = split_action(&words("living room set 21.5"), parse_climate_action);
+        assert_eq!(strip_set_keyword(name).as_deref(), Some("living room"));
+        assert_eq!(action.unwrap().data["temperature"], json!(21.5));
+
+        let (name, _) = split_action(&words("sunset room"), parse_climate_action);
+        assert_eq!(strip_set_keyword(name).as_deref(), Some("sunset room"));
+        assert_eq!(
+            strip_set_keyword(Some("set".to_string())).as_deref(),
+            Some("set")
+        );
+    }
+
+    #[test]
+    fn test_split_remote() {
+        let (name, commands) = split_remote(&words("living room tv send volume_up mute")).unwrap();
+        assert_eq!(name, "living room tv");
+        assert_eq!(commands, vec!["volume_up", "mute"]);
+
+        let (name, commands) = split_remote(&words("living room tv")).unwrap();
+        assert_eq!(name, "living room tv");
+        assert!(commands.is_empty());
+
+        assert!(split_remote(&words("send power")).is_err());
+        assert!(split_remote(&words("tv send")).is_err());
+    }
+
+    #[test]
+    fn test_check_text() {
+        assert_eq!(
+            check_text("hello", Some(0), Some(10), None).unwrap(),
+            TextCheck::Valid
+        );
+        assert!(check_text("hello", Some(0), Some(3), None).is_err());
+        assert!(check_text("", Some(1), None, None).is_err());
+
+        let pattern = Some("[0-9]{4}");
+        assert_eq!(
+            check_text("1234", None, None, pattern).unwrap(),
+            TextCheck::Valid
+        );
+        assert!(check_text("x1234", None, None, pattern).is_err());
+        assert_eq!(
+            check_text("abc", None, None, Some("(?!x)abc")).unwrap(),
+            TextCheck::PatternUnsupported("(?!x)abc")
+        );
+    }
+
+    #[test]
+    fn test_split_value() {
+        let select = |entity_id: &str, name: &str, options: Value| {
+            let state: EntityState = serde_json::from_value(json!({
+                "entity_id": entity_id,
+                "state": "eco",
+                "attributes": {"friendly_name": name, "options": options},
+                "last_changed": "2025-01-15T00:00:00Z",
+                "last_updated": "2025-01-15T00:00:00Z",
+            }))
+            .unwrap();
+            CachedEntity::from(&state).with_attributes(&state, &Default::default())
+        };
+        let mut cache = Cache::new();
+        cache.set_entities(crate::cache::CacheFile::new(
+            vec![
+                select(
+                    "select.living_room",
+                    "Living Room",
+                    json!(["eco", "comfort"]),
+                ),
+                select(
+                    "select.living_room_mode",
+                    "Living Room Mode",
+                    json!(["day"]),
+                ),
+            ],
+            3600,
+            "http://localhost:8123".to_string(),
+        ));
+        let is_option = |entity: &CachedEntity, word: &str| {
+            entity
+                .options()
+                .is_some_and(|options| match_option(word, &options).is_ok())
+        };
+        let split = |input: &str| split_value(&cache, &["select"], &words(input), is_option);
+
+        assert_eq!(
+            split("living room mode"),
+            ("living room mode".to_string(), None)
+        );
+        assert_eq!(
+            split("living room comfort"),
+            ("living room".to_string(), Some("comfort".to_string()))
+        );
+        // Not a name as a whole, so the last word is taken as the value anyway
+        assert_eq!(
+            split("living room warm"),
+            ("living room".to_string(), Some("warm".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_match_option() {
+        let options = words("Eco Comfort Boost Comfort+");
+        assert_eq!(match_option("eco", &options).unwrap(), "Eco");
+        assert_eq!(match_option("bo", &options).unwrap(), "Boost");
+        assert_eq!(match_option("comfort", &options).unwrap(), "Comfort");
+        assert_eq!(match_option("bst", &options).unwrap(), "Boost");
+        assert!(match_option("turbo", &options).is_err());
+    }
+
+    #[test]
+    fn test_number_target() {
+        assert_eq!(
+            number_target("20", None, Some(0.0), Some(100.0)).unwrap(),
+            20.0
+        );
+        assert_eq!(number_target("+1", Some(20.0), None, None).unwrap(), 21.0);
+        assert_eq!(number_target("-0.5", Some(20.0), None, None).unwrap(), 19.5);
+        assert!(number_target("-1", Some(0.0), Some(0.0), None).is_err());
+        assert!(number_target("+1", None, None, None).is_err());
+        assert!(number_target("lots", None, None, None).is_err());
+    }
+
+    #[test]
+    fn test_parse_light_action() {
+        assert_eq!(parse_light_action("OFF").unwrap().service, "turn_off");
+        assert_eq!(
+            parse_light_action("2700k").unwrap().data["color_temp_kelvin"],
+            json!(2700)
+        );
+        assert_eq!(
+            parse_light_action("#ff8800").unwrap().data["rgb_color"],
+            json!([255, 136, 0])
+        );
+        assert_eq!(
+            parse_light_action("Blue").unwrap().data["color_name"],
+            json!("blue")
+        );
+        assert!(parse_light_action("150%").is_none());
+        assert!(parse_light_action("lamp").is_none());
+    }
+
+    #[test]
+    fn test_light_action() {
+        let cmd = LightCommand {
+            words: vec![],
+            color: Some("red".to_string()),
+            kelvin: None,
+            brightness: Some("60%".to_string()),
+            transition: Some(2.0),
+            effect: None,
+            dry_run: false,
+        };
+        let action = light_action(parse_light_action("2700k"), &cmd)
+            .unwrap()
+            .unwrap();
+        assert_eq!(action.service, "turn_on");
+        assert_eq!(action.data["rgb_color"], json!([255, 0, 0]));
+        assert_eq!(action.data["brightness_pct"], json!(60));
+        assert_eq!(action.data["transition"], json!(2.0));
+        assert!(!action.data.contains_key("color_temp_kelvin"));
+
+        assert!(light_action(parse_light_action("off"), &cmd).is_err());
+        assert!(parse_brightness("300").is_err());
+    }
+
+    #[test]
+    fn test_split_cover_setting() {
+        let words = |s: &str| s.split(' ').map(String::from).collect::<Vec<_>>();
+
+        let (name, action) = split_cover_setting(&words("living room blind tilt 30%"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(name.as_deref(), Some("living room blind"));
+        assert_eq!(action.service, "set_cover_tilt_position");
+        assert_eq!(action.data["tilt_position"], json!(30));
+
+        let (_, action) = split_cover_setting(&words("garage position 50"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(action.data["position"], json!(50));
+
+        assert!(split_cover_setting(&words("garage position 150")).is_err());
+        assert!(split_cover_setting(&words("garage open"))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_parse_fan_action() {
+        let action = parse_fan_action("30%").unwrap();
+        assert_eq!(action.service, "set_percentage");
+        assert_eq!(action.data["percentage"], json!(30));
+        assert_eq!(
+            parse_fan_action("reverse").unwrap().data["direction"],
+            json!("reverse")
+        );
+        assert_eq!(
+            parse_fan_action("still").unwrap().data["oscillating"],
+            json!(false)
+        );
+    }
+
+    #[test]
+    fn test_parse_cover_action() {
+        assert_eq!(parse_cover_action("close").unwrap().service, "close_cover");
+        let action = parse_cover_action("40%").unwrap();
+        assert_eq!(action.service, "set_cover_position");
+        assert_eq!(action.data["position"], json!(40));
+        assert!(parse_cover_action("on").is_none());
+    }
+
+    #[test]
+    fn test_parse_temperature() {
+        assert_eq!(parse_temperature("21.5"), Some(21.5));
+        assert_eq!(parse_temperature("70°F"), Some(70.0));
+        assert_eq!(parse_temperature("2"), None);
+        assert_eq!(parse_temperature("warm"), None);
+
+        let (name, action) = split_action(&words("floor 2"), parse_climate_action);
+        assert_eq!(name.as_deref(), Some("floor 2"));
+        assert!(action.is_none());
+    }
+}
@@ -0,0 +1,164 @@
+//! Reload command implementation
+//!
+//! `hmr reload automations scenes` calls each domain's `reload` service so
+//! edited YAML takes effect without a restart; `hmr reload all` calls
+//! `homeassistant.reload_all` and `hmr reload core` reloads the core
+//! configuration (name, location, units). Domains are matched against the
+//! ones that offer a `reload` service, so singular, plural, and unique
+//! prefixes all work ("automation", "automations", "auto"). `--all` sweeps
+//! the core configuration and every reloadable domain separately, so the
+//! table shows which one rejected its YAML. `hmr system reload` is the same
+//! command.
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+use serde_json::json;
+use tabled::Tabled;
+
+use crate::api::HassClient;
+use crate::cli::ReloadCommand;
+use crate::config::RuntimeContext;
+use crate::error::Error;
+use crate::output::{output_for_format, print_table};
+
+/// Targets that map to `homeassistant` services rather than a domain's `reload`
+const SPECIAL: &[(&str, &str)] = &[
+    ("all", "reload_all"),
+    ("core", "reload_core_config"),
+    ("core_config", "reload_core_config"),
+];
+
+#[derive(Debug, Serialize, Tabled)]
+struct ReloadResult {
+    target: String,
+    service: String,
+    status: String,
+}
+
+pub async fn run(ctx: &RuntimeContext, command: ReloadCommand) -> Result<()> {
+    let client = HassClient::new(ctx)?;
+    let mut reloadable: Vec<String> = client
+        .get_services()
+        .await?
+        .into_iter()
+        .filter(|d| d.services.contains_key("reload"))
+        .map(|d| d.domain)
+        .collect();
+    reloadable.sort();
+
+    let targets = if command.all {
+        let mut sweep = vec!["core".to_string()];
+        sweep.extend(reloadable.iter().cloned());
+        sweep
+    } else {
+        command.targets
+    };
+
+    let mut results = Vec::new();
+    for input in &targets {
+        let (target, service) = match_target(input, &reloadable)?;
+        if results.iter().any(|r: &ReloadResult| r.service == service) {
+            continue;
+        }
+        results.push(ReloadResult {
+            target,
+            service,
+            status: "would reload".to_string(),
+        });
+    }
+
+    if !command.dry_run {
+        for result in &mut results {
+            let (domain, service) = result.service.split_once('.').unwrap_or_default();
+            result.status = match client.call_service(domain, service, &json!({})).await {
+                Ok(_) => "reloaded".to_string(),
+                Err(e) => format!("failed: {e:#}"),
+            };
+        }
+    }
+
+    output_for_format(ctx, &results, || print_table(ctx, &results))?;
+
+    let failed = results
+        .iter()
+        .filter(|r| r.status.starts_with("failed"))
+        .count();
+    if failed > 0 {
+        bail!("{failed} of {} reloads failed", results.len());
+    }
+    Ok(())
+}
+
+/// Resolve a target to (name, service): "all"/"core", a reloadable domain
+/// (also plural), or a unique prefix of one
+fn match_target(input: &str, reloadable: &[String]) -> Result<(String, String)> {
+    let needle = input.trim().to_lowercase().replace(['-', ' '], "_");
+    if let Some((name, service)) = SPECIAL.iter().find(|(name, _)| *name == needle) {
+        return Ok((name.to_string(), format!("homeassistant.{service}")));
+    }
+
+    let singular = [
+        Some(needle.as_str()),
+        needle.strip_suffix('s'),
+        needle.strip_suffix("es"),
+    ];
+    let found = singular
+        .iter()
+        .flatten()
+        .find_map(|name| reloadable.iter().find(|d| d == name));
+    let domain = match found {
+        Some(domain) => domain,
+        None => {
+            let prefixed: Vec<&String> = reloadable
+                .iter()
+                .filter(|d| d.starts_with(&needle))
+                .collect();
+            match prefixed.as_slice() {
+                [domain] => *domain,
+                [] => {
+                    return Err(Error::NotFound(format!(
+                        "Nothing to reload for '{input}'. Reloadable: all, core, {}",
+                        reloadable.join(", ")
+                    ))
+                    .into())
+                }
+                _ => {
+                    return Err(Error::Ambiguous {
+                        kind: "domain".to_string(),
+                        input: input.to_string(),
+                        candidates: prefixed.iter().map(|d| d.to_string()).collect(),
+                    }
+                    .into())
+                }
+            }
+        }
+    };
+    Ok((domain.clone(), format!("{domain}.reload")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_target() {
+        let reloadable: Vec<String> = ["automation", "input_boolean", "input_number", "scene"]
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+        let service = |input| match_target(input, &reloadable).map(|(_, s)| s).ok();
+
+        assert_eq!(service("automation").unwrap(), "automation.reload");
+        assert_eq!(service("Automations").unwrap(), "automation.reload");
+        assert_eq!(service("scenes").unwrap(), "scene.reload");
+        assert_eq!(service("auto").unwrap(), "automation.reload");
+        assert_eq!(service("input-booleans").unwrap(), "input_boolean.reload");
+        assert_eq!(service("all").unwrap(), "homeassistant.reload_all");
+        assert_eq!(
+            service("core-config").unwrap(),
+            "homeassistant.reload_core_config"
+        );
+        assert!(service("input").is_none());
+        assert!(service("zone").is_none());
+    }
+}
@@ -0,0 +1,63 @@
+//! Say command implementation
+//!
+//! Speaks a message through `tts.speak`. The TTS engine is the service
+//! target; the media players go in `media_player_entity_id`. Players are
+//! resolved within `--room` when given, engines never are (they have no area).
+
+use anyhow::{anyhow, bail, Result};
+use serde_json::json;
+
+use crate::cache::CacheManager;
+use crate::cli::SayCommand;
+use crate::commands::quick::{execute, QuickAction};
+use crate::config::RuntimeContext;
+use crate::resolve::{resolve_domain_entity, room_scope};
+
+pub async fn run(ctx: &RuntimeContext, command: SayCommand) -> Result<()> {
+    let message = command.words.join(" ");
+
+    let mut cache_manager = CacheManager::new(ctx)?;
+    cache_manager.ensure_entities().await?;
+    let room = room_scope(ctx, &mut cache_manager).await?;
+    let cache = cache_manager.cache();
+
+    let engine = match command.engine.as_deref() {
+        Some(name) => resolve_domain_entity(ctx, cache, name, &["tts"])?.entity_id,
+        None => {
+            let mut engines = cache.entities_in_domain("tts");
+            engines.sort_by(|a, b| a.entity_id.cmp(&b.entity_id));
+            engines
+                .first()
+                .map(|e| e.entity_id.clone())
+                .ok_or_else(|| anyhow!("No TTS engine found; set one up or pass --engine"))?
+        }
+    };
+
+    let scope = room.as_ref().map_or(cache, |(_, cache)| cache);
+    let players: Vec<String> = if command.all {
+        let mut players: Vec<String> = scope
+            .entities_in_domain("media_player")
+            .into_iter()
+            .map(|e| e.entity_id.clone())
+            .collect();
+        players.sort();
+        players
+    } else {
+        let name = command
+            .player
+            .as_deref()
+            .ok_or_else(|| anyhow!("No media player name given"))?;
+        vec![resolve_domain_entity(ctx, scope, name, &["media_player"])?.entity_id]
+    };
+    if players.is_empty() {
+        match &room {
+            Some((area, _)) => bail!("No media players in {}", area.name),
+            None => bail!("No media players found"),
+        }
+    }
+
+    let action = QuickAction::new("speak")
+        .with_data("media_player_entity_id", json!(players))
+        .with_data("message", message);
+    execute(ctx, "tts", &engine, &[action], command.dry_run).await
+}
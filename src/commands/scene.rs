@@ -0,0 +1,88 @@
+//! Scene command implementations
+//!
+//! Scenes are listed from the entity cache and activated by (fuzzy) name,
+//! resolving only against entities in the `scene` domain.
+
+use anyhow::Result;
+use serde::Serialize;
+use tabled::Tabled;
+
+use crate::cache::{Cache, CacheManager};
+use crate::cli::SceneCommand;
+use crate::commands::quick::{execute, resolve_in, QuickAction};
+use crate::config::RuntimeContext;
+use crate::output::print_table;
+use crate::resolve::room_scope;
+
+#[derive(Debug, Serialize, Tabled)]
+struct SceneRow {
+    entity_id: String,
+    name: String,
+    area: String,
+}
+
+pub async fn run(ctx: &RuntimeContext, command: SceneCommand) -> Result<()> {
+    match command {
+        SceneCommand::List => list(ctx).await,
+        SceneCommand::Activate {
+            words,
+            transition,
+            dry_run,
+        } => activate(ctx, words.join(" "), transition, dry_run).await,
+    }
+}
+
+async fn list(ctx: &RuntimeContext) -> Result<()> {
+    let mut cache_manager = CacheManager::new(ctx)?;
+    cache_manager.ensure_entities().await?;
+    cache_manager.ensure_areas().await?;
+    cache_manager.ensure_devices().await?;
+
+    let room = room_scope(ctx, &mut cache_manager).await?;
+    let scenes = match &room {
+        Some((_, cache)) => scene_rows(cache, cache_manager.cache()),
+        None => scene_rows(cache_manager.cache(), cache_manager.cache()),
+    };
+
+    if scenes.is_empty() && !ctx.is_machine_output() {
+        println!("No scenes found");
+        return Ok(());
+    }
+    print_table(ctx, &scenes)
+}
+
+/// Rows for the scenes in `cache`, with areas looked up in the full cache
+fn scene_rows(cache: &Cache, full: &Cache) -> Vec<SceneRow> {
+    let mut rows: Vec<SceneRow> = cache
+        .entities_in_domain("scene")
+        .into_iter()
+        .map(|entity| {
+            let context = full.entity_context(entity);
+            SceneRow {
+                entity_id: entity.entity_id.clone(),
+                name: entity
+                    .friendly_name
+                    .clone()
+                    .unwrap_or_else(|| entity.object_id.clone()),
+                area: context.area.map(|a| a.name).unwrap_or_default(),
+            }
+        })
+        .collect();
+    rows.sort_by_key(|row| row.name.to_lowercase());
+    rows
+}
+
+async fn activate(
+    ctx: &RuntimeContext,
+    name: String,
+    transition: Option<f64>,
+    dry_run: bool,
+) -> Result<()> {
+    let entity_id = resolve_in(ctx, "scene", &["scene"], Some(name)).await?;
+
+    let mut action = QuickAction::new("turn_on");
+    if let Some(transition) = transition {
+        action = action.with_data("transition", transition);
+    }
+    execute(ctx, "scene", &entity_id, &[action], dry_run).await
+}
@@ -0,0 +1,114 @@
+//! Script command implementations
+//!
+//! Scripts are listed from the entity cache and run by (fuzzy) name among
+//! `script` entities. Trailing KEY=VALUE arguments become the script's
+//! variables, passed through `script.turn_on`.
+
+use anyhow::Result;
+use serde::Serialize;
+use tabled::Tabled;
+
+use crate::cache::{Cache, CacheManager};
+use crate::cli::ScriptCommand;
+use crate::commands::quick::{execute, resolve_in, QuickAction};
+use crate::config::RuntimeContext;
+use crate::output::{parse_key_value_args, print_table};
+use crate::resolve::room_scope;
+
+#[derive(Debug, Serialize, Tabled)]
+struct ScriptRow {
+    entity_id: String,
+    name: String,
+    state: String,
+    area: String,
+}
+
+pub async fn run(ctx: &RuntimeContext, command: ScriptCommand) -> Result<()> {
+    match command {
+        ScriptCommand::List => list(ctx).await,
+        ScriptCommand::Run { args, dry_run } => run_script(ctx, &args, dry_run).await,
+    }
+}
+
+async fn list(ctx: &RuntimeContext) -> Result<()> {
+    let mut cache_manager = CacheManager::new(ctx)?;
+    cache_manager.ensure_entities().await?;
+    cache_manager.ensure_areas().await?;
+    cache_manager.ensure_devices().await?;
+
+    let room = room_scope(ctx, &mut cache_manager).await?;
+    let scripts = match &room {
+        Some((_, cache)) => script_rows(cache, cache_manager.cache()),
+        None => script_rows(cache_manager.cache(), cache_manager.cache()),
+    };
+
+    if scripts.is_empty() && !ctx.is_machine_output() {
+        println!("No scripts found");
+        return Ok(());
+    }
+    print_table(ctx, &scripts)
+}
+
+/// Rows for the scripts in `cache`, with areas looked up in the full cache
+fn script_rows(cache: &Cache, full: &Cache) -> Vec<ScriptRow> {
+    let mut rows: Vec<ScriptRow> = cache
+        .entities_in_domain("script")
+        .into_iter()
+        .map(|entity| {
+            let context = full.entity_context(entity);
+            ScriptRow {
+                entity_id: entity.entity_id.clone(),
+                name: entity
+                    .friendly_name
+                    .clone()
+                    .unwrap_or_else(|| entity.object_id.clone()),
+                state: entity.state.clone(),
+                area: context.area.map(|a| a.name).unwrap_or_default(),
+            }
+        })
+        .collect();
+    rows.sort_by_key(|row| row.name.to_lowercase());
+    rows
+}
+
+async fn run_script(ctx: &RuntimeContext, args: &[String], dry_run: bool) -> Result<()> {
+    let (name, variables) = split_variables(args);
+    let variables = parse_key_value_args(variables)?;
+    let name = Some(name).filter(|n| !n.is_empty());
+    let entity_id = resolve_in(ctx, "script", &["script"], name).await?;
+
+    let mut action = QuickAction::new("turn_on");
+    if variables.as_object().is_some_and(|v| !v.is_empty()) {
+        action = action.with_data("variables", variables);
+    }
+    execute(ctx, "script", &entity_id, &[action], dry_run).await
+}
+
+/// Split arguments into the script name and the KEY=VALUE variables after it
+fn split_variables(args: &[String]) -> (String, &[String]) {
+    let split = args
+        .iter()
+        .position(|arg| arg.contains('='))
+        .unwrap_or(args.len());
+    (args[..split].join(" "), &args[split..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_variables() {
+        let args: Vec<String> = ["good", "night", "mode=quiet", "delay=5"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let (name, variables) = split_variables(&args);
+        assert_eq!(name, "good night");
+        assert_eq!(variables, &args[2..]);
+
+        let (name, variables) = split_variables(&args[..1]);
+        assert_eq!(name, "good");
+        assert!(variables.is_empty());
+    }
+}
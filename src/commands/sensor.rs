@@ -0,0 +1,301 @@
+//! Sensor command implementations
+//!
+//! `sensor list --sparkline 24h` fetches compact history for numeric sensors in
+//! batches (several requests in flight) and draws each trend as a row of block
+//! characters next to the current value.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use futures_util::{stream, StreamExt};
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use serde::Serialize;
+use tabled::Tabled;
+
+use crate::api::{EntityState, HassClient, HistoryPoint};
+use crate::cli::SensorCommand;
+use crate::config::RuntimeContext;
+use crate::output::{output_for_format, print_table_without, warn};
+use crate::progress;
+use crate::resolve::room_entity_ids;
+use crate::units::{display_value, with_unit};
+
+/// Entities per history request
+const HISTORY_BATCH: usize = 25;
+
+/// History requests in flight at once
+const HISTORY_CONCURRENCY: usize = 4;
+
+/// Characters in a sparkline
+const SPARKLINE_WIDTH: usize = 24;
+
+const SPARK_CHARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[derive(Debug, Tabled, Serialize)]
+struct SensorRow {
+    entity_id: String,
+    state: String,
+    #[tabled(skip)]
+    unit: String,
+    friendly_name: String,
+    #[serde(skip)]
+    trend: String,
+    /// Resampled values behind the sparkline (JSON/YAML only)
+    #[tabled(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    history: Option<Vec<Option<f64>>>,
+}
+
+pub async fn run(ctx: &RuntimeContext, command: SensorCommand) -> Result<()> {
+    match command {
+        SensorCommand::List { filter, sparkline } => list(ctx, filter, sparkline).await,
+    }
+}
+
+async fn list(
+    ctx: &RuntimeContext,
+    filter: Option<String>,
+    period: Option<Duration>,
+) -> Result<()> {
+    let client = HassClient::new(ctx)?;
+    let mut states = client.get_states().await?;
+    if let Some(room) = room_entity_ids(ctx).await? {
+        states.retain(|s| room.contains(&s.entity_id));
+    }
+
+    let matcher = SkimMatcherV2::default();
+    let sensors: Vec<&EntityState> = states
+        .iter()
+        .filter(|s| s.entity_id.starts_with("sensor."))
+        .filter(|s| match filter.as_deref() {
+            None => true,
+            Some(filter) => {
+                let friendly = s
+                    .attributes
+                    .get("friendly_name")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("");
+                matcher.fuzzy_match(&s.entity_id, filter).is_some()
+                    || matcher.fuzzy_match(friendly, filter).is_some()
+            }
+        })
+        .collect();
+
+    let trends = match period {
+        Some(period) => fetch_trends(ctx, &client, &sensors, period).await?,
+        None => HashMap::new(),
+    };
+
+    let rows: Vec<SensorRow> = sensors
+        .iter()
+        .map(|s| {
+            let (value, unit) = display_value(s, ctx.global.units);
+            let history = trends.get(&s.entity_id).cloned();
+            SensorRow {
+                entity_id: s.entity_id.clone(),
+                state: with_unit(&value, &unit),
+                unit,
+                friendly_name: s
+                    .attributes
+                    .get("friendly_name")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string(),
+                trend: match history.as_deref() {
+                    Some(values) if ctx.global.plain => describe_trend(values),
+                    Some(values) => render_sparkline(values),
+                    None => String::new(),
+                },
+                history,
+            }
+        })
+        .collect();
+
+    output_for_format(ctx, &rows, || {
+        if rows.is_empty() {
+            println!("No sensors found");
+            return Ok(());
+        }
+        let hidden: &[&str] = if period.is_some() { &[] } else { &["trend"] };
+        print_table_without(ctx, &rows, hidden)
+    })
+}
+
+/// Fetch and resample history for the numeric sensors among `sensors`
+async fn fetch_trends(
+    ctx: &RuntimeContext,
+    client: &HassClient,
+    sensors: &[&EntityState],
+    period: Duration,
+) -> Result<HashMap<String, Vec<Option<f64>>>> {
+    let end = Utc::now();
+    let start = end - chrono::Duration::from_std(period)?;
+    let start_str = start.format("%Y-%m-%dT%H:%M:%S").to_string();
+    // Without an end Home Assistant returns a day from the start, which for
+    // long periods stops short of now
+    let end_str = end.format("%Y-%m-%dT%H:%M:%S").to_string();
+
+    let numeric: Vec<String> = sensors
+        .iter()
+        .filter(|s| s.state.parse::<f64>().is_ok())
+        .map(|s| s.entity_id.clone())
+        .collect();
+
+    let mut batches = stream::iter(numeric.chunks(HISTORY_BATCH))
+        .map(|batch| client.get_history_compact(batch, &start_str, &end_str))
+        .buffer_unordered(HISTORY_CONCURRENCY);
+
+    let progress = progress::bar(
+        ctx,
+        numeric.len().div_ceil(HISTORY_BATCH) as u64,
+        "Fetching history",
+    );
+    let mut trends = HashMap::new();
+    while let Some(result) = batches.next().await {
+        progress.inc(1);
+        match result {
+            Ok(history) => {
+                for points in history {
+                    if let Some((entity_id, values)) = resample(&points, start, end) {
+                        trends.insert(entity_id, values);
+                    }
+                }
+            }
+            Err(e) => warn(
+                ctx,
+                format!("Skipping trends for a batch of sensors: {e:#}"),
+            ),
+        }
+    }
+
+    Ok(trends)
+}
+
+/// Resample one entity's state changes into `SPARKLINE_WIDTH` evenly spaced values
+///
+/// Each slot holds the value in effect at the end of the slot; slots before the
+/// first numeric state, or while the sensor was unavailable, are `None`.
+fn resample(
+    points: &[HistoryPoint],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Option<(String, Vec<Option<f64>>)> {
+    let entity_id = points.first()?.entity_id.clone()?;
+    let changes: Vec<(DateTime<Utc>, Option<f64>)> = points
+        .iter()
+        .filter_map(|p| {
+            let at = DateTime::parse_from_rfc3339(&p.last_changed).ok()?;
+            Some((at.with_timezone(&Utc), p.state.parse().ok()))
+        })
+        .collect();
+
+    let slot = (end - start) / SPARKLINE_WIDTH as i32;
+    let values = (1..=SPARKLINE_WIDTH)
+        .map(|i| {
+            let slot_end = start + slot * i as i32;
+            changes
+                .iter()
+                .take_while(|(at, _)| *at <= slot_end)
+                .last()
+                .and_then(|(_, value)| *value)
+        })
+        .collect();
+
+    Some((entity_id, values))
+}
+
+/// Draw values as block characters scaled between their minimum and maximum
+fn render_sparkline(values: &[Option<f64>]) -> String {
+    let known = values.iter().flatten();
+    let min = known.clone().copied().fold(f64::INFINITY, f64::min);
+    let max = known.copied().fold(f64::NEG_INFINITY, f64::max);
+    let range = max - min;
+
+    values
+        .iter()
+        .map(|value| match value {
+            None => ' ',
+            Some(_) if range <= f64::EPSILON => SPARK_CHARS[SPARK_CHARS.len() / 2],
+            Some(v) => {
+                let level = ((v - min) / range * (SPARK_CHARS.len() - 1) as f64).round();
+                SPARK_CHARS[level as usize]
+            }
+        })
+        .collect()
+}
+
+/// Describe a trend in words for `--plain` output, e.g. "rising from 20 to 22 (min 19.5, max 22)"
+fn describe_trend(values: &[Option<f64>]) -> String {
+    let known: Vec<f64> = values.iter().flatten().copied().collect();
+    let (Some(first), Some(last)) = (known.first(), known.last()) else {
+        return String::new();
+    };
+    let min = known.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = known.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+    let direction = if (last - first).abs() <= f64::EPSILON {
+        format!("steady at {last}")
+    } else if last > first {
+        format!("rising from {first} to {last}")
+    } else {
+        format!("falling from {first} to {last}")
+    };
+    format!("{direction} (min {min}, max {max})")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(entity_id: Option<&str>, state: &str, at: &str) -> HistoryPoint {
+        HistoryPoint {
+            entity_id: entity_id.map(String::from),
+            state: state.to_string(),
+            last_changed: at.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_render_sparkline() {
+        assert_eq!(
+            render_sparkline(&[Some(0.0), Some(7.0), None, Some(3.5)]),
+            "▁█ ▅"
+        );
+        assert_eq!(render_sparkline(&[Some(2.0), Some(2.0)]), "▅▅");
+    }
+
+    #[test]
+    fn test_describe_trend() {
+        assert_eq!(
+            describe_trend(&[Some(20.0), None, Some(19.5), Some(22.0)]),
+            "rising from 20 to 22 (min 19.5, max 22)"
+        );
+        assert_eq!(
+            describe_trend(&[Some(3.0), Some(3.0)]),
+            "steady at 3 (min 3, max 3)"
+        );
+        assert_eq!(describe_trend(&[None, None]), "");
+    }
+
+    #[test]
+    fn test_resample() {
+        let start: DateTime<Utc> = "2025-01-15T00:00:00Z".parse().unwrap();
+        let end: DateTime<Utc> = "2025-01-16T00:00:00Z".parse().unwrap();
+        let points = [
+            point(Some("sensor.temp"), "20", "2025-01-15T00:00:00Z"),
+            point(None, "unavailable", "2025-01-15T06:30:00Z"),
+            point(None, "22", "2025-01-15T12:30:00Z"),
+        ];
+
+        let (entity_id, values) = resample(&points, start, end).unwrap();
+        assert_eq!(entity_id, "sensor.temp");
+        assert_eq!(values.len(), SPARKLINE_WIDTH);
+        assert_eq!(values[0], Some(20.0));
+        assert_eq!(values[6], None);
+        assert_eq!(values[12], Some(22.0));
+        assert_eq!(values[23], Some(22.0));
+    }
+}
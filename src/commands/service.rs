@@ -5,11 +5,14 @@ use serde::Serialize;
 use tabled::Tabled;
 
 use crate::api::HassClient;
+use crate::cache::CacheManager;
 use crate::cli::ServiceCommand;
 use crate::config::RuntimeContext;
 use crate::output::{
-    get_json_input, output_for_format, parse_key_value_args, print_table, truncate,
+    get_json_input, output_for_format, parse_key_value_args, print_porcelain, print_table, truncate,
 };
+use crate::resolve::{resolve_entity, room_scope};
+use crate::validate;
 
 #[derive(Debug, Tabled, Serialize)]
 struct ServiceRow {
@@ -25,7 +28,8 @@ pub async fn run(ctx: &RuntimeContext, command: ServiceCommand) -> Result<()> {
             service,
             data,
             args,
-        } => call(ctx, &service, data.as_deref(), &args).await,
+            yes,
+        } => call(ctx, &service, data.as_deref(), &args, yes).await,
     }
 }
 
@@ -72,29 +76,79 @@ async fn call(
     service: &str,
     data_input: Option<&str>,
     args: &[String],
+    yes: bool,
 ) -> Result<()> {
     let client = HassClient::new(ctx)?;
 
-    // Parse service name (domain.service)
-    let (domain, service_name) = service.split_once('.').ok_or_else(|| {
-        anyhow::anyhow!(
-            "Invalid service format: {service}. Expected format: domain.service (e.g., light.turn_on)"
-        )
-    })?;
+    let (domain, service_name) = validate::service(ctx, service).await?;
+
+    // Free arguments without '=' name target entities
+    let (pairs, names) = split_target_args(args);
 
     // Build service data: prefer explicit --data, then key=value args, then piped stdin
-    let data = if let Some(json_value) = get_json_input(data_input).context("parsing JSON input")? {
-        json_value
-    } else if !args.is_empty() {
-        parse_key_value_args(args).context("parsing key=value arguments")?
-    } else {
-        serde_json::json!({})
+    let mut data =
+        if let Some(json_value) = get_json_input(data_input).context("parsing JSON input")? {
+            json_value
+        } else if !pairs.is_empty() {
+            parse_key_value_args(&pairs).context("parsing key=value arguments")?
+        } else {
+            serde_json::json!({})
+        };
+
+    if !names.is_empty() {
+        let entity_ids = resolve_targets(ctx, domain, &names).await?;
+        let obj = data
+            .as_object_mut()
+            .ok_or_else(|| anyhow::anyhow!("Service data must be a JSON object"))?;
+        if obj.contains_key("entity_id") {
+            anyhow::bail!("entity_id given both explicitly and as target names: {names:?}");
+        }
+        let value = match entity_ids.as_slice() {
+            [single] => serde_json::Value::String(single.clone()),
+            _ => serde_json::json!(entity_ids),
+        };
+        obj.insert("entity_id".to_string(), value);
+    } else if ctx.global.room.is_some() {
+        // No targets named: the call covers the whole room
+        let obj = data
+            .as_object_mut()
+            .ok_or_else(|| anyhow::anyhow!("Service data must be a JSON object"))?;
+        let targeted = ["entity_id", "device_id", "area_id"]
+            .iter()
+            .any(|key| obj.contains_key(*key));
+        if !targeted {
+            let mut cache_manager = CacheManager::new(ctx)?;
+            if let Some((area, _)) = room_scope(ctx, &mut cache_manager).await? {
+                obj.insert("area_id".to_string(), serde_json::json!(area.area_id));
+            }
+        }
+    }
+
+    let targets: Vec<String> = match data.get("entity_id") {
+        Some(serde_json::Value::String(id)) => vec![id.clone()],
+        Some(serde_json::Value::Array(ids)) => ids
+            .iter()
+            .filter_map(|v| v.as_str().map(String::from))
+            .collect(),
+        _ => Vec::new(),
     };
+    crate::prompt::confirm_protected(service, &targets, yes)?;
 
     log::debug!("Calling {domain}.{service_name} with data: {data:?}");
 
     let result = client.call_service(domain, service_name, &data).await?;
 
+    if ctx.porcelain().is_some() {
+        print_porcelain("called", &[service]);
+        for entity in result.as_array().into_iter().flatten() {
+            if let Some(id) = entity.get("entity_id").and_then(|v| v.as_str()) {
+                let state = entity.get("state").and_then(|v| v.as_str()).unwrap_or("");
+                print_porcelain("changed", &[id, state]);
+            }
+        }
+        return Ok(());
+    }
+
     output_for_format(ctx, &result, || {
         // For table output, show a success message
         if result.is_array() && !result.as_array().unwrap().is_empty() {
@@ -114,3 +168,43 @@ async fn call(
         Ok(())
     })
 }
+
+/// Split call arguments into KEY=VALUE pairs and target names
+fn split_target_args(args: &[String]) -> (Vec<String>, Vec<String>) {
+    args.iter().cloned().partition(|arg| arg.contains('='))
+}
+
+/// Resolve target names to entity IDs, preferring the service's domain
+async fn resolve_targets(
+    ctx: &RuntimeContext,
+    domain: &str,
+    names: &[String],
+) -> Result<Vec<String>> {
+    let mut cache_manager = CacheManager::new(ctx)?;
+    cache_manager.ensure_entities().await?;
+    let room = room_scope(ctx, &mut cache_manager).await?;
+    let cache = room
+        .as_ref()
+        .map_or(cache_manager.cache(), |(_, cache)| cache);
+
+    names
+        .iter()
+        .map(|name| resolve_entity(ctx, cache, name, Some(domain)).map(|entity| entity.entity_id))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_target_args() {
+        let args: Vec<String> = ["kitchen", "brightness=128", "Living Room Lamp"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let (pairs, names) = split_target_args(&args);
+        assert_eq!(pairs, vec!["brightness=128"]);
+        assert_eq!(names, vec!["kitchen", "Living Room Lamp"]);
+    }
+}
@@ -0,0 +1,69 @@
+//! Shopping list command implementations
+//!
+//! Talks to the `shopping_list` integration over its WebSocket commands, so
+//! no list entity needs to be resolved.
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::json;
+use tabled::Tabled;
+
+use crate::cli::ShoppingCommand;
+use crate::config::RuntimeContext;
+use crate::output::{output_for_format, print_output, print_table};
+use crate::websocket::WsClient;
+
+#[derive(Debug, Serialize, Tabled)]
+struct ShoppingRow {
+    name: String,
+    bought: String,
+}
+
+pub async fn run(ctx: &RuntimeContext, command: ShoppingCommand) -> Result<()> {
+    match command {
+        ShoppingCommand::List { all } => list(ctx, all).await,
+        ShoppingCommand::Add { words, dry_run } => add(ctx, words.join(" "), dry_run).await,
+    }
+}
+
+async fn list(ctx: &RuntimeContext, all: bool) -> Result<()> {
+    let client = WsClient::connect(ctx).await?;
+    let mut items = client.list_shopping_items().await?;
+    if !all {
+        items.retain(|item| !item.complete);
+    }
+
+    output_for_format(ctx, &items, || {
+        if items.is_empty() {
+            println!("The shopping list is empty");
+            return Ok(());
+        }
+        let rows: Vec<ShoppingRow> = items
+            .iter()
+            .map(|item| ShoppingRow {
+                name: item.name.clone(),
+                bought: if item.complete { "yes" } else { "no" }.to_string(),
+            })
+            .collect();
+        print_table(ctx, &rows)
+    })
+}
+
+async fn add(ctx: &RuntimeContext, name: String, dry_run: bool) -> Result<()> {
+    if dry_run {
+        let request = json!({ "type": "shopping_list/items/add", "name": name });
+        if ctx.is_machine_output() {
+            return print_output(ctx, &request);
+        }
+        println!("Would send {request}");
+        return Ok(());
+    }
+
+    let client = WsClient::connect(ctx).await?;
+    let item = client.add_shopping_item(&name).await?;
+    if ctx.is_machine_output() {
+        return print_output(ctx, &item);
+    }
+    println!("Added '{}' to the shopping list", item.name);
+    Ok(())
+}
@@ -0,0 +1,117 @@
+//! Stats command implementations
+//!
+//! `stats tool-usage` shows the local usage metrics recorded while
+//! `telemetry.enabled` is on: which commands run, how long they take, and how
+//! they fail. `--export` writes the raw file plus version info for bug reports.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use tabled::Tabled;
+
+use crate::cli::StatsCommand;
+use crate::config::RuntimeContext;
+use crate::output::{output_for_format, print_table};
+use crate::usage::{usage_path, UsageStats};
+
+#[derive(Debug, Serialize, Tabled)]
+struct UsageRow {
+    command: String,
+    runs: u64,
+    avg_ms: u64,
+    max_ms: u64,
+    errors: String,
+}
+
+#[derive(Debug, Serialize)]
+struct UsageExport<'a> {
+    hmr_version: &'static str,
+    os: &'static str,
+    arch: &'static str,
+    #[serde(flatten)]
+    stats: &'a UsageStats,
+}
+
+pub fn run(ctx: &RuntimeContext, command: StatsCommand) -> Result<()> {
+    match command {
+        StatsCommand::ToolUsage { export } => tool_usage(ctx, export.as_deref()),
+    }
+}
+
+fn tool_usage(ctx: &RuntimeContext, export: Option<&Path>) -> Result<()> {
+    let stats = UsageStats::load()?;
+
+    if let Some(path) = export {
+        let bundle = UsageExport {
+            hmr_version: env!("CARGO_PKG_VERSION"),
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            stats: &stats,
+        };
+        fs::write(path, serde_json::to_string_pretty(&bundle)?)
+            .with_context(|| format!("writing usage to {}", path.display()))?;
+        if !ctx.is_machine_output() {
+            println!("Wrote usage metrics to {}", path.display());
+        }
+        return Ok(());
+    }
+
+    output_for_format(ctx, &stats, || {
+        if stats.commands.is_empty() {
+            if ctx.config.telemetry.enabled {
+                println!("No usage recorded yet ({})", usage_path()?.display());
+            } else {
+                println!("Usage metrics are off. Set `enabled = true` under [telemetry] to record them locally");
+            }
+            return Ok(());
+        }
+        print_table(ctx, &usage_rows(&stats))?;
+        if let Some(since) = &stats.since {
+            println!("\nRecorded since {since}");
+        }
+        Ok(())
+    })
+}
+
+/// One row per command, slowest in total first
+fn usage_rows(stats: &UsageStats) -> Vec<UsageRow> {
+    let mut commands: Vec<_> = stats.commands.iter().collect();
+    commands.sort_by_key(|(_, usage)| std::cmp::Reverse(usage.total_ms));
+    commands
+        .into_iter()
+        .map(|(command, usage)| UsageRow {
+            command: command.clone(),
+            runs: usage.count,
+            avg_ms: usage.total_ms / usage.count.max(1),
+            max_ms: usage.max_ms,
+            errors: usage
+                .errors
+                .iter()
+                .map(|(code, count)| format!("{code}: {count}"))
+                .collect::<Vec<_>>()
+                .join(", "),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_usage_rows() {
+        let mut stats = UsageStats::default();
+        stats.record("info", Duration::from_millis(10), None);
+        stats.record("entity watch", Duration::from_millis(500), Some("network"));
+        stats.record("entity watch", Duration::from_millis(300), None);
+
+        let rows = usage_rows(&stats);
+        assert_eq!(rows[0].command, "entity watch");
+        assert_eq!(rows[0].avg_ms, 400);
+        assert_eq!(rows[0].errors, "network: 1");
+        assert_eq!(rows[1].command, "info");
+    }
+}
@@ -0,0 +1,94 @@
+//! Status command implementation
+//!
+//! `hmr status` is the at-a-glance view: the pinned entities with their
+//! current state first, then the instance and how many entities are
+//! unavailable.
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::api::HassClient;
+use crate::commands::pin::PinnedRow;
+use crate::config::RuntimeContext;
+use crate::output::{output_for_format, print_table};
+use crate::pins::Pins;
+
+/// Unavailable entities named in the summary before it says "and N more"
+const MAX_LISTED: usize = 5;
+
+#[derive(Debug, Serialize)]
+struct StatusReport {
+    pinned: Vec<PinnedRow>,
+    version: String,
+    location: String,
+    state: String,
+    entities: usize,
+    unavailable: Vec<String>,
+}
+
+pub async fn run(ctx: &RuntimeContext) -> Result<()> {
+    let client = HassClient::new(ctx)?;
+    let info = client.get_info().await?;
+    let states = client.get_states().await?;
+    let pins = Pins::load()?;
+
+    let mut unavailable: Vec<String> = states
+        .iter()
+        .filter(|s| s.state == "unavailable")
+        .map(|s| s.entity_id.clone())
+        .collect();
+    unavailable.sort();
+    let report = StatusReport {
+        pinned: PinnedRow::for_pins(&pins, &states, ctx.global.units),
+        version: info.version,
+        location: info.location_name,
+        state: info.state,
+        entities: states.len(),
+        unavailable,
+    };
+
+    output_for_format(ctx, &report, || {
+        if !report.pinned.is_empty() {
+            print_table(ctx, &report.pinned)?;
+            println!();
+        }
+        println!(
+            "Home Assistant {} at {} ({})",
+            report.version, report.location, report.state
+        );
+        print!(
+            "{} entities, {} unavailable",
+            report.entities,
+            report.unavailable.len()
+        );
+        if !report.unavailable.is_empty() {
+            print!(": {}", summarize(&report.unavailable));
+        }
+        println!();
+        Ok(())
+    })
+}
+
+/// The first few IDs, and how many more there are
+fn summarize(ids: &[String]) -> String {
+    let shown = ids[..ids.len().min(MAX_LISTED)].join(", ");
+    match ids.len().saturating_sub(MAX_LISTED) {
+        0 => shown,
+        more => format!("{shown} and {more} more"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize() {
+        let ids: Vec<String> = (1..=7).map(|i| format!("sensor.s{i}")).collect();
+        assert_eq!(summarize(&ids[..2]), "sensor.s1, sensor.s2");
+        assert_eq!(
+            summarize(&ids),
+            "sensor.s1, sensor.s2, sensor.s3, sensor.s4, sensor.s5 and 2 more"
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! Suggest command implementation
+//!
+//! `hmr suggest` looks through the `hmr do` history for commands that ran
+//! around the current time of day on several different days ("you usually
+//! turn off the office light around 22:10") and offers to run the most
+//! regular one. Runs are grouped by the service and targets they resolved to,
+//! so differently worded inputs for the same action count together.
+
+use std::collections::{BTreeSet, HashMap};
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, Timelike};
+use serde::Serialize;
+use tabled::Tabled;
+
+use crate::cli::{DoCommand, SuggestCommand};
+use crate::commands::do_cmd;
+use crate::config::RuntimeContext;
+use crate::history::{History, HistoryEntry};
+use crate::output::{output_for_format, print_table};
+use crate::prompt::ask_yes;
+
+const MINUTES_PER_DAY: i64 = 24 * 60;
+
+/// Suggestions shown at most
+const MAX_SUGGESTIONS: usize = 5;
+
+/// A past run and its local time
+type Run<'a> = (NaiveDateTime, &'a HistoryEntry);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Tabled)]
+struct Suggestion {
+    /// The most recent wording of the command
+    command: String,
+    #[tabled(skip)]
+    interpretation: String,
+    /// Typical time of day ("22:10")
+    usually: String,
+    /// Different days it ran within the window
+    days: usize,
+    /// Runs within the window
+    runs: usize,
+    #[tabled(skip)]
+    last_run: String,
+}
+
+pub async fn run(ctx: &RuntimeContext, command: SuggestCommand) -> Result<()> {
+    let entries = History::new()?.recent(usize::MAX)?;
+    let suggestions = suggest(
+        &entries,
+        Local::now().naive_local(),
+        command.window,
+        command.min_days,
+        local_time,
+    );
+
+    output_for_format(ctx, &suggestions, || {
+        if suggestions.is_empty() {
+            println!("Nothing you usually run around this time");
+            return Ok(());
+        }
+        print_table(ctx, &suggestions)
+    })?;
+
+    let Some(top) = suggestions.first() else {
+        return Ok(());
+    };
+    if ctx.is_machine_output() && !command.yes {
+        return Ok(());
+    }
+    let question = format!(
+        "You usually {} around {} - run it?",
+        describe(top),
+        top.usually
+    );
+    if !command.yes && !ask_yes(&question)? {
+        return Ok(());
+    }
+
+    let cmd = DoCommand {
+        words: top.command.split_whitespace().map(String::from).collect(),
+        dry_run: false,
+        simulate: false,
+        yes: true,
+        exact: false,
+        verify_timeout: do_cmd::DEFAULT_VERIFY_TIMEOUT,
+    };
+    do_cmd::execute(ctx, cmd).await
+}
+
+/// Lower-case interpretation for the question, or the command itself
+fn describe(suggestion: &Suggestion) -> String {
+    let text = if suggestion.interpretation.is_empty() {
+        &suggestion.command
+    } else {
+        &suggestion.interpretation
+    };
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn local_time(timestamp: u64) -> Option<NaiveDateTime> {
+    let utc = DateTime::from_timestamp(i64::try_from(timestamp).ok()?, 0)?;
+    Some(utc.with_timezone(&Local).naive_local())
+}
+
+/// Successful commands that ran within `window` of `now`'s time of day on at
+/// least `min_days` earlier days, most regular first; commands that already
+/// ran today are left out
+fn suggest(
+    entries: &[HistoryEntry],
+    now: NaiveDateTime,
+    window: Duration,
+    min_days: usize,
+    to_local: impl Fn(u64) -> Option<NaiveDateTime>,
+) -> Vec<Suggestion> {
+    let window = i64::try_from(window.as_secs() / 60).unwrap_or(MINUTES_PER_DAY);
+    let now_minute = minute_of_day(now);
+
+    // (service, targets) -> runs near now
+    let mut groups: HashMap<(String, Vec<String>), Vec<Run>> = HashMap::new();
+    for entry in entries.iter().filter(|e| e.success) {
+        let (Some(service), Some(at)) = (&entry.service, to_local(entry.timestamp)) else {
+            continue;
+        };
+        if clock_distance(minute_of_day(at), now_minute) > window {
+            continue;
+        }
+        let mut targets = entry.targets.clone();
+        targets.sort();
+        groups
+            .entry((service.clone(), targets))
+            .or_default()
+            .push((at, entry));
+    }
+
+    let mut suggestions: Vec<Suggestion> = groups
+        .into_values()
+        .filter_map(|runs| {
+            let days: BTreeSet<NaiveDate> = runs.iter().map(|(at, _)| at.date()).collect();
+            if days.contains(&now.date()) || days.len() < min_days {
+                return None;
+            }
+            let (last_at, last) = runs.iter().max_by_key(|(at, _)| *at)?;
+            let minutes: Vec<i64> = runs.iter().map(|(at, _)| minute_of_day(*at)).collect();
+            let usually = typical_minute(&minutes);
+            Some(Suggestion {
+                command: last.input.clone(),
+                interpretation: last.interpretation.clone(),
+                usually: format!("{:02}:{:02}", usually / 60, usually % 60),
+                days: days.len(),
+                runs: runs.len(),
+                last_run: last_at.format("%Y-%m-%d %H:%M").to_string(),
+            })
+        })
+        .collect();
+    suggestions.sort_by(|a, b| {
+        b.days
+            .cmp(&a.days)
+            .then(b.runs.cmp(&a.runs))
+            .then(b.last_run.cmp(&a.last_run))
+    });
+    suggestions.truncate(MAX_SUGGESTIONS);
+    suggestions
+}
+
+fn minute_of_day(at: NaiveDateTime) -> i64 {
+    i64::from(at.hour() * 60 + at.minute())
+}
+
+/// Minutes between two times of day, across midnight if that is shorter
+fn clock_distance(a: i64, b: i64) -> i64 {
+    let d = (a - b).rem_euclid(MINUTES_PER_DAY);
+    d.min(MINUTES_PER_DAY - d)
+}
+
+/// Mean time of day on the clock face, so 23:50 and 00:10 average to 00:00
+fn typical_minute(minutes: &[i64]) -> i64 {
+    let (sin, cos) = minutes.iter().fold((0.0, 0.0), |(sin, cos), &m| {
+        let angle = m as f64 / MINUTES_PER_DAY as f64 * std::f64::consts::TAU;
+        (sin + angle.sin(), cos + angle.cos())
+    });
+    let angle = sin.atan2(cos).rem_euclid(std::f64::consts::TAU);
+    (angle / std::f64::consts::TAU * MINUTES_PER_DAY as f64).round() as i64 % MINUTES_PER_DAY
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 5, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn run_at(input: &str, when: NaiveDateTime) -> HistoryEntry {
+        let mut entry = HistoryEntry::new(input, "Turn off office light")
+            .with_service("light", "turn_off")
+            .with_targets(vec!["light.office".to_string()])
+            .with_success();
+        entry.timestamp = when.and_utc().timestamp() as u64;
+        entry
+    }
+
+    #[test]
+    fn test_suggest() {
+        let entries = vec![
+            run_at("office light off", at(1, 22, 5)),
+            run_at("turn off office light", at(2, 22, 20)),
+            run_at("office off", at(3, 21, 55)),
+            // Too far from now
+            run_at("office off", at(3, 8, 0)),
+        ];
+        let to_local = |ts: u64| DateTime::from_timestamp(ts as i64, 0).map(|t| t.naive_utc());
+        let window = Duration::from_secs(30 * 60);
+
+        let found = suggest(&entries, at(10, 22, 0), window, 3, to_local);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].command, "office off");
+        assert_eq!(found[0].usually, "22:07");
+        assert_eq!((found[0].days, found[0].runs), (3, 3));
+
+        assert!(suggest(&entries, at(10, 22, 0), window, 4, to_local).is_empty());
+        assert!(suggest(&entries, at(3, 22, 0), window, 1, to_local).is_empty());
+        assert!(suggest(&entries, at(10, 15, 0), window, 1, to_local).is_empty());
+    }
+
+    #[test]
+    fn test_clock_math() {
+        assert_eq!(clock_distance(23 * 60 + 50, 10), 20);
+        assert_eq!(typical_minute(&[23 * 60 + 50, 10]), 0);
+    }
+}
@@ -1,15 +1,40 @@
 //! Template command implementation
+//!
+//! `template edit` is a playground for a template file edited in the user's
+//! own editor, in another pane: every save and every change to an entity the
+//! template reads re-renders it. hmr has no editor of its own, so there is
+//! no in-pane entity completion; instead each render lists the entity IDs
+//! the cache doesn't know, with the closest cached entity as a suggestion.
 
+use std::collections::BTreeSet;
 use std::fs;
+use std::io::IsTerminal;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
+use chrono::Local;
+use serde_json::Value;
 
 use crate::api::HassClient;
-use crate::cli::TemplateCommand;
+use crate::cache::{Cache, CacheManager};
+use crate::cli::{OutputFormat, TemplateCommand, TemplateSubcommand};
 use crate::config::RuntimeContext;
-use crate::output::read_stdin;
+use crate::fuzzy::FuzzyMatcher;
+use crate::output::{print_output, read_stdin, warn};
+use crate::websocket::{Subscription, WsClient};
+
+/// How often `template edit` checks the file for changes
+const FILE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Contents of a newly created template file
+const NEW_TEMPLATE: &str = "{{ now().strftime('%H:%M') }}\n";
 
 pub async fn run(ctx: &RuntimeContext, cmd: TemplateCommand) -> Result<()> {
+    if let Some(TemplateSubcommand::Edit { file }) = cmd.command {
+        return edit(ctx, &file).await;
+    }
+
     let client = HassClient::new(ctx)?;
 
     let template = if let Some(ref file_path) = cmd.file {
@@ -27,7 +52,360 @@ pub async fn run(ctx: &RuntimeContext, cmd: TemplateCommand) -> Result<()> {
     };
 
     let result = client.render_template(&template).await?;
-    println!("{result}");
+
+    // Structured output preserves the result type; otherwise print the text as rendered
+    match ctx.output_format() {
+        OutputFormat::Json | OutputFormat::Yaml => {
+            print_output(ctx, &parse_template_result(&result))?
+        }
+        _ => println!("{result}"),
+    }
+
+    Ok(())
+}
+
+/// Watch a template file and re-render it on every save and entity change
+///
+/// Rendering uses the WebSocket `render_template` subscription, so Home Assistant
+/// pushes a new result whenever an entity the template reads changes. Saving the
+/// file replaces the subscription.
+async fn edit(ctx: &RuntimeContext, path: &Path) -> Result<()> {
+    if !path.exists() {
+        fs::write(path, NEW_TEMPLATE).with_context(|| format!("creating {}", path.display()))?;
+    }
+
+    let mut cache_manager = CacheManager::new(ctx)?;
+    cache_manager.ensure_entities().await?;
+    let cache = cache_manager.cache();
+
+    let client = WsClient::connect(ctx).await?;
+
+    let mut source = read_template(path)?;
+    let mut modified = modified_time(path);
+    let mut notes = entity_reference_notes(&source, cache);
+    let mut subscription = start_render(ctx, &client, path, &source, &notes).await;
+
+    if !ctx.global.quiet {
+        eprintln!(
+            "Watching {} - edit it in another pane, Ctrl+C to stop",
+            path.display()
+        );
+    }
+
+    let mut ticker = tokio::time::interval(FILE_POLL_INTERVAL);
+    loop {
+        tokio::select! {
+            event = next_render(&mut subscription) => {
+                let event = event.ok_or_else(|| anyhow!("WebSocket connection closed"))?;
+                show_render(ctx, path, &event, &notes)?;
+            }
+            _ = ticker.tick() => {
+                let current = modified_time(path);
+                if current == modified {
+                    continue;
+                }
+                modified = current;
+
+                let updated = read_template(path)?;
+                if updated == source {
+                    continue;
+                }
+                source = updated;
+
+                if let Some(old) = subscription.take() {
+                    client.unsubscribe(old).await?;
+                }
+                notes = entity_reference_notes(&source, cache);
+                subscription = start_render(ctx, &client, path, &source, &notes).await;
+            }
+            _ = tokio::signal::ctrl_c() => {
+                log::debug!("Received Ctrl+C, stopping template edit");
+                break;
+            }
+        }
+    }
 
     Ok(())
 }
+
+fn read_template(path: &Path) -> Result<String> {
+    fs::read_to_string(path).with_context(|| format!("reading template file: {}", path.display()))
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Subscribe to renders of `source`; a rejected template is shown as an error
+async fn start_render(
+    ctx: &RuntimeContext,
+    client: &WsClient,
+    path: &Path,
+    source: &str,
+    notes: &[String],
+) -> Option<Subscription> {
+    if ctx.is_machine_output() {
+        for note in notes {
+            warn(ctx, note.clone());
+        }
+    }
+
+    match client.subscribe_template(source).await {
+        Ok(subscription) => Some(subscription),
+        Err(e) => {
+            let event = serde_json::json!({ "error": e.to_string() });
+            if let Err(e) = show_render(ctx, path, &event, notes) {
+                log::debug!("Failed to show render error: {e}");
+            }
+            None
+        }
+    }
+}
+
+/// Wait for the next render, or forever while the template is broken
+async fn next_render(subscription: &mut Option<Subscription>) -> Option<Value> {
+    match subscription {
+        Some(subscription) => subscription.next_raw().await,
+        None => std::future::pending().await,
+    }
+}
+
+fn show_render(ctx: &RuntimeContext, path: &Path, event: &Value, notes: &[String]) -> Result<()> {
+    if ctx.is_machine_output() {
+        println!("{}", serde_json::to_string(event)?);
+        return Ok(());
+    }
+
+    if std::io::stdout().is_terminal() {
+        // Clear the screen so the pane always shows the current render
+        print!("\x1b[2J\x1b[H");
+    }
+
+    println!(
+        "== {} @ {} ==",
+        path.display(),
+        Local::now().format("%H:%M:%S")
+    );
+
+    match (event.get("result"), event.get("error")) {
+        (Some(Value::String(text)), _) => println!("{text}"),
+        (Some(result), _) => println!("{result}"),
+        (None, Some(error)) => {
+            let message = error
+                .as_str()
+                .map(String::from)
+                .unwrap_or(error.to_string());
+            println!("Error: {message}");
+        }
+        (None, None) => println!("{event}"),
+    }
+
+    if !notes.is_empty() {
+        println!();
+        for note in notes {
+            println!("note: {note}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Check entity IDs referenced by a template against the cache
+///
+/// Unknown or partially typed IDs get the closest cached entity as a suggestion.
+fn entity_reference_notes(template: &str, cache: &Cache) -> Vec<String> {
+    let matcher = FuzzyMatcher::new();
+
+    entity_references(template, cache)
+        .into_iter()
+        .filter(|id| cache.get_entity(id).is_none())
+        .map(|id| match matcher.find_entity(&id, cache).best() {
+            Some(m) => format!("unknown entity '{id}', did you mean {}?", m.item.entity_id),
+            None => format!("unknown entity '{id}'"),
+        })
+        .collect()
+}
+
+/// Find `domain.object_id` references for domains known to the cache
+///
+/// Handles quoted IDs (`states('light.kitchen')`) as well as attribute access
+/// (`states.light.kitchen.state`).
+fn entity_references(template: &str, cache: &Cache) -> BTreeSet<String> {
+    let domains = cache.domains();
+
+    template
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+        .filter_map(|token| {
+            let token = token.strip_prefix("states.").unwrap_or(token);
+            let mut parts = token.split('.');
+            let domain = parts.next()?;
+            let object_id = parts.next().filter(|id| !id.is_empty())?;
+            domains
+                .contains(&domain)
+                .then(|| format!("{domain}.{object_id}"))
+        })
+        .collect()
+}
+
+/// Interpret rendered template text as a typed value.
+///
+/// Home Assistant renders numbers, booleans, lists and dicts as text (lists and
+/// dicts in Python notation). Anything that doesn't parse stays a string.
+fn parse_template_result(text: &str) -> Value {
+    let trimmed = text.trim();
+
+    match trimmed {
+        "True" => return Value::Bool(true),
+        "False" => return Value::Bool(false),
+        "None" => return Value::Null,
+        _ => {}
+    }
+
+    if let Ok(value) = serde_json::from_str::<Value>(trimmed) {
+        // Quoted JSON strings are kept verbatim, quotes included
+        if !value.is_string() {
+            return value;
+        }
+    }
+
+    let is_collection = (trimmed.starts_with('[') && trimmed.ends_with(']'))
+        || (trimmed.starts_with('{') && trimmed.ends_with('}'));
+    if is_collection {
+        if let Some(value) = python_literal_to_json(trimmed)
+            .and_then(|json| serde_json::from_str::<Value>(&json).ok())
+        {
+            return value;
+        }
+    }
+
+    Value::String(text.to_string())
+}
+
+/// Rewrite a Python list/dict literal as JSON text.
+///
+/// Converts single-quoted strings and the `True`/`False`/`None` keywords outside
+/// of strings. Returns None for unterminated strings.
+fn python_literal_to_json(text: &str) -> Option<String> {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' | '"' => {
+                let quote = c;
+                let mut literal = String::new();
+                loop {
+                    match chars.next()? {
+                        '\\' => literal.push(chars.next()?),
+                        ch if ch == quote => break,
+                        ch => literal.push(ch),
+                    }
+                }
+                out.push_str(&serde_json::to_string(&literal).ok()?);
+            }
+            c if c.is_ascii_alphabetic() => {
+                let mut word = String::from(c);
+                while let Some(&next) = chars.peek() {
+                    if !next.is_ascii_alphanumeric() && next != '_' {
+                        break;
+                    }
+                    word.push(next);
+                    chars.next();
+                }
+                out.push_str(match word.as_str() {
+                    "True" => "true",
+                    "False" => "false",
+                    "None" => "null",
+                    other => other,
+                });
+            }
+            c => out.push(c),
+        }
+    }
+
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    use crate::cache::{CacheFile, CachedEntity};
+
+    fn test_cache() -> Cache {
+        let mut cache = Cache::new();
+        let entities = vec![CachedEntity {
+            friendly_name: Some("Kitchen Light".to_string()),
+            search_names: vec!["light.kitchen".to_string(), "kitchen".to_string()],
+            ..CachedEntity::for_test("light.kitchen", "on")
+        }];
+        cache.set_entities(CacheFile::new(
+            entities,
+            3600,
+            "http://localhost:8123".to_string(),
+        ));
+        cache
+    }
+
+    #[test]
+    fn test_entity_references() {
+        let cache = test_cache();
+        let refs = entity_references(
+            "{{ states('light.kitchen') }} {{ states.light.hall.state }} {{ foo.bar }}",
+            &cache,
+        );
+        assert_eq!(
+            refs.into_iter().collect::<Vec<_>>(),
+            vec!["light.hall", "light.kitchen"]
+        );
+    }
+
+    #[test]
+    fn test_entity_reference_notes_suggest_cached_entity() {
+        let cache = test_cache();
+        let notes = entity_reference_notes("{{ is_state('light.kitchn', 'on') }}", &cache);
+        assert_eq!(
+            notes,
+            vec!["unknown entity 'light.kitchn', did you mean light.kitchen?"]
+        );
+        assert!(entity_reference_notes("{{ states('light.kitchen') }}", &cache).is_empty());
+    }
+
+    #[test]
+    fn test_parse_template_result_scalars() {
+        assert_eq!(parse_template_result("42"), json!(42));
+        assert_eq!(parse_template_result("21.5\n"), json!(21.5));
+        assert_eq!(parse_template_result("true"), json!(true));
+        assert_eq!(parse_template_result("False"), json!(false));
+        assert_eq!(parse_template_result("None"), json!(null));
+    }
+
+    #[test]
+    fn test_parse_template_result_collections() {
+        assert_eq!(parse_template_result("[1, 2, 3]"), json!([1, 2, 3]));
+        assert_eq!(
+            parse_template_result("['light.kitchen', 'light.hall']"),
+            json!(["light.kitchen", "light.hall"])
+        );
+        assert_eq!(
+            parse_template_result("{'on': True, 'level': None}"),
+            json!({"on": true, "level": null})
+        );
+        assert_eq!(
+            parse_template_result("['TrueNAS', \"it's\"]"),
+            json!(["TrueNAS", "it's"])
+        );
+    }
+
+    #[test]
+    fn test_parse_template_result_strings() {
+        assert_eq!(parse_template_result("on"), json!("on"));
+        assert_eq!(parse_template_result("007"), json!("007"));
+        assert_eq!(parse_template_result("\"quoted\""), json!("\"quoted\""));
+        assert_eq!(
+            parse_template_result("[it's broken]"),
+            json!("[it's broken]")
+        );
+    }
+}
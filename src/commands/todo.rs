@@ -0,0 +1,220 @@
+//! Todo command implementations
+//!
+//! Lists are `todo` entities, resolved by (fuzzy) name. Items are read with the
+//! WebSocket `todo/item/list` command and changed through the `todo.add_item`
+//! and `todo.update_item` services on the same connection.
+
+use anyhow::{anyhow, bail, Result};
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tabled::Tabled;
+
+use crate::cli::TodoCommand;
+use crate::commands::quick::resolve_in;
+use crate::config::RuntimeContext;
+use crate::output::{output_for_format, print_output, print_table};
+use crate::prompt::{is_interactive, pick};
+use crate::websocket::{TodoItem, WsClient};
+
+#[derive(Debug, Serialize, Tabled)]
+struct TodoRow {
+    summary: String,
+    status: String,
+    due: String,
+}
+
+pub async fn run(ctx: &RuntimeContext, command: TodoCommand) -> Result<()> {
+    match command {
+        TodoCommand::List { list, all } => list_items(ctx, list, all).await,
+        TodoCommand::Add {
+            list,
+            words,
+            due,
+            dry_run,
+        } => add(ctx, list, words.join(" "), due, dry_run).await,
+        TodoCommand::Done {
+            list,
+            words,
+            dry_run,
+        } => done(ctx, list, words.join(" "), dry_run).await,
+    }
+}
+
+async fn list_items(ctx: &RuntimeContext, list: String, all: bool) -> Result<()> {
+    let entity_id = resolve_in(ctx, "todo list", &["todo"], Some(list)).await?;
+    let client = WsClient::connect(ctx).await?;
+    let mut items = client.list_todo_items(&entity_id).await?;
+    if !all {
+        items.retain(is_open);
+    }
+
+    output_for_format(ctx, &items, || {
+        if items.is_empty() {
+            println!("Nothing to do on {entity_id}");
+            return Ok(());
+        }
+        let rows: Vec<TodoRow> = items
+            .iter()
+            .map(|item| TodoRow {
+                summary: item.summary.clone(),
+                status: item.status.replace('_', " "),
+                due: item.due.clone().unwrap_or_default(),
+            })
+            .collect();
+        print_table(ctx, &rows)
+    })
+}
+
+async fn add(
+    ctx: &RuntimeContext,
+    list: String,
+    item: String,
+    due: Option<String>,
+    dry_run: bool,
+) -> Result<()> {
+    let entity_id = resolve_in(ctx, "todo list", &["todo"], Some(list)).await?;
+    let mut data = json!({ "item": item });
+    if let Some(due) = due {
+        data["due_date"] = json!(due);
+    }
+    call(ctx, &entity_id, "add_item", data, dry_run).await
+}
+
+async fn done(ctx: &RuntimeContext, list: String, item: String, dry_run: bool) -> Result<()> {
+    let entity_id = resolve_in(ctx, "todo list", &["todo"], Some(list)).await?;
+    let client = WsClient::connect(ctx).await?;
+    let items: Vec<TodoItem> = client
+        .list_todo_items(&entity_id)
+        .await?
+        .into_iter()
+        .filter(is_open)
+        .collect();
+
+    let candidates = match_items(&items, &item);
+    let chosen = match candidates.as_slice() {
+        [] => bail!("No open item on {entity_id} matches '{item}'"),
+        [only] => *only,
+        several if is_interactive() => {
+            let options: Vec<String> = several.iter().map(|i| i.summary.clone()).collect();
+            several[pick(&format!("Several items match '{item}':"), &options)?]
+        }
+        several => {
+            let names: Vec<&str> = several.iter().map(|i| i.summary.as_str()).collect();
+            return Err(anyhow!(
+                "'{item}' matches several items: {}",
+                names.join(", ")
+            ));
+        }
+    };
+
+    let data = json!({ "item": chosen.uid, "status": "completed" });
+    if dry_run {
+        return print_call(ctx, &entity_id, "update_item", &data);
+    }
+    client
+        .call_service("todo", "update_item", &entity_id, &data)
+        .await?;
+    if !ctx.is_machine_output() {
+        println!("Completed '{}' on {entity_id}", chosen.summary);
+    }
+    Ok(())
+}
+
+async fn call(
+    ctx: &RuntimeContext,
+    entity_id: &str,
+    service: &str,
+    data: Value,
+    dry_run: bool,
+) -> Result<()> {
+    if dry_run {
+        return print_call(ctx, entity_id, service, &data);
+    }
+    let client = WsClient::connect(ctx).await?;
+    let result = client
+        .call_service("todo", service, entity_id, &data)
+        .await?;
+    if ctx.is_machine_output() {
+        print_output(ctx, &result)
+    } else {
+        println!("Called todo.{service} on {entity_id}");
+        Ok(())
+    }
+}
+
+fn print_call(ctx: &RuntimeContext, entity_id: &str, service: &str, data: &Value) -> Result<()> {
+    if ctx.is_machine_output() {
+        return print_output(
+            ctx,
+            &json!({
+                "domain": "todo",
+                "service": service,
+                "target": { "entity_id": entity_id },
+                "data": data,
+            }),
+        );
+    }
+    println!("Would call todo.{service} on {entity_id} with {data}");
+    Ok(())
+}
+
+fn is_open(item: &TodoItem) -> bool {
+    item.status != "completed"
+}
+
+/// Items matching `input`: exact summaries (ignoring case) if any, otherwise
+/// fuzzy matches with the best score
+fn match_items<'a>(items: &'a [TodoItem], input: &str) -> Vec<&'a TodoItem> {
+    let exact: Vec<&TodoItem> = items
+        .iter()
+        .filter(|i| i.summary.eq_ignore_ascii_case(input))
+        .collect();
+    if !exact.is_empty() {
+        return exact;
+    }
+
+    let matcher = SkimMatcherV2::default();
+    let scored: Vec<(i64, &TodoItem)> = items
+        .iter()
+        .filter_map(|i| matcher.fuzzy_match(&i.summary, input).map(|s| (s, i)))
+        .collect();
+    let Some(best) = scored.iter().map(|(score, _)| *score).max() else {
+        return Vec::new();
+    };
+    scored
+        .into_iter()
+        .filter(|(score, _)| *score == best)
+        .map(|(_, item)| item)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(summary: &str) -> TodoItem {
+        TodoItem {
+            uid: summary.to_lowercase(),
+            summary: summary.to_string(),
+            status: "needs_action".to_string(),
+            due: None,
+            description: None,
+        }
+    }
+
+    #[test]
+    fn test_match_items() {
+        let items = [item("Milk"), item("Oat milk"), item("Eggs")];
+
+        let found = match_items(&items, "milk");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].summary, "Milk");
+
+        let found = match_items(&items, "egs");
+        assert_eq!(found[0].summary, "Eggs");
+
+        assert!(match_items(&items, "bread").is_empty());
+    }
+}
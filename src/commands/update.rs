@@ -0,0 +1,145 @@
+//! Update command implementations
+//!
+//! `update list` shows every `update` entity with its installed and latest
+//! version; names and areas come from the entity cache, versions from the live
+//! states since the cache doesn't keep attributes. `update install` calls
+//! `update.install`, optionally asking the integration to back up first.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+use tabled::Tabled;
+
+use crate::api::{EntityState, HassClient};
+use crate::cache::{Cache, CacheManager};
+use crate::cli::UpdateCommand;
+use crate::commands::quick::{execute, resolve_in, QuickAction};
+use crate::config::RuntimeContext;
+use crate::output::print_table;
+use crate::resolve::room_scope;
+
+#[derive(Debug, Serialize, Tabled)]
+struct UpdateRow {
+    entity_id: String,
+    name: String,
+    installed: String,
+    latest: String,
+    status: String,
+    area: String,
+}
+
+pub async fn run(ctx: &RuntimeContext, command: UpdateCommand) -> Result<()> {
+    match command {
+        UpdateCommand::List => list(ctx).await,
+        UpdateCommand::Install {
+            words,
+            backup,
+            dry_run,
+        } => install(ctx, words.join(" "), backup, dry_run).await,
+    }
+}
+
+async fn list(ctx: &RuntimeContext) -> Result<()> {
+    let mut cache_manager = CacheManager::new(ctx)?;
+    cache_manager.ensure_entities().await?;
+    cache_manager.ensure_areas().await?;
+    cache_manager.ensure_devices().await?;
+
+    let room = room_scope(ctx, &mut cache_manager).await?;
+    let states = HassClient::new(ctx)?.get_states().await?;
+    let updates = match &room {
+        Some((_, cache)) => update_rows(cache, cache_manager.cache(), &states),
+        None => update_rows(cache_manager.cache(), cache_manager.cache(), &states),
+    };
+
+    if updates.is_empty() && !ctx.is_machine_output() {
+        println!("No update entities found");
+        return Ok(());
+    }
+    print_table(ctx, &updates)
+}
+
+/// Rows for the updates in `cache`, pending ones first, with areas looked up
+/// in the full cache and versions taken from `states`
+fn update_rows(cache: &Cache, full: &Cache, states: &[EntityState]) -> Vec<UpdateRow> {
+    let states: HashMap<&str, &EntityState> =
+        states.iter().map(|s| (s.entity_id.as_str(), s)).collect();
+
+    let mut rows: Vec<UpdateRow> = cache
+        .entities_in_domain("update")
+        .into_iter()
+        .map(|entity| {
+            let live = states.get(entity.entity_id.as_str());
+            let attributes = live.map_or(&Value::Null, |s| &s.attributes);
+            let version = |key: &str| attributes[key].as_str().unwrap_or_default().to_string();
+            let context = full.entity_context(entity);
+            UpdateRow {
+                entity_id: entity.entity_id.clone(),
+                name: entity
+                    .friendly_name
+                    .clone()
+                    .unwrap_or_else(|| entity.object_id.clone()),
+                installed: version("installed_version"),
+                latest: version("latest_version"),
+                status: status(live.map_or(&entity.state, |s| &s.state), attributes).to_string(),
+                area: context.area.map(|a| a.name).unwrap_or_default(),
+            }
+        })
+        .collect();
+    rows.sort_by_key(|row| (row.status != "available", row.name.to_lowercase()));
+    rows
+}
+
+/// What an update entity's state and attributes mean for the user
+fn status(state: &str, attributes: &Value) -> &'static str {
+    if attributes["in_progress"].as_bool() == Some(true) || attributes["in_progress"].is_number() {
+        return "installing";
+    }
+    match state {
+        "on" => "available",
+        "off"
+            if attributes["skipped_version"].is_string()
+                && attributes["skipped_version"] == attributes["latest_version"] =>
+        {
+            "skipped"
+        }
+        "off" => "up to date",
+        _ => "unknown",
+    }
+}
+
+async fn install(ctx: &RuntimeContext, name: String, backup: bool, dry_run: bool) -> Result<()> {
+    let entity_id = resolve_in(ctx, "update", &["update"], Some(name)).await?;
+
+    let mut action = QuickAction::new("install");
+    if backup {
+        action = action.with_data("backup", true);
+    }
+    execute(ctx, "update", &entity_id, &[action], dry_run).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_status() {
+        assert_eq!(status("on", &json!({})), "available");
+        assert_eq!(
+            status("off", &json!({"latest_version": "1.2"})),
+            "up to date"
+        );
+        assert_eq!(
+            status(
+                "off",
+                &json!({"latest_version": "1.2", "skipped_version": "1.2"})
+            ),
+            "skipped"
+        );
+        assert_eq!(status("on", &json!({"in_progress": 40})), "installing");
+        assert_eq!(status("unavailable", &json!({})), "unknown");
+    }
+}
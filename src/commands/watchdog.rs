@@ -0,0 +1,501 @@
+//! Watchdog command implementation
+//!
+//! Polls the entities named in watchdog rules and runs local commands when a
+//! rule is violated, independent of Home Assistant automations. A violation
+//! must last for the rule's `for` period before it alerts (flap suppression);
+//! while it lasts, further commands run every `repeat` (escalation). When the
+//! violation clears, the `resolved` command runs and the rule re-arms.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Local;
+use serde::Serialize;
+use tabled::Tabled;
+
+use crate::api::HassClient;
+use crate::cli::{OutputFormat, WatchdogCommand};
+use crate::config::{RuntimeContext, WatchdogConfig, WatchdogRule};
+use crate::output::{print_output, print_table, warn};
+use crate::timespec;
+
+/// A rule with its durations parsed
+struct Rule {
+    config: WatchdogRule,
+    hold: Duration,
+    repeat: Option<Duration>,
+}
+
+impl Rule {
+    fn compile(config: WatchdogRule) -> Result<Self> {
+        let parse = |value: &str, field: &str| {
+            timespec::parse_duration(value)
+                .with_context(|| format!("rule '{}': invalid {field} '{value}'", config.name))
+        };
+        let hold = parse(&config.hold, "for")?;
+        let repeat = config
+            .repeat
+            .as_deref()
+            .map(|r| parse(r, "repeat"))
+            .transpose()?;
+
+        if config.above.is_none()
+            && config.below.is_none()
+            && config.state.is_none()
+            && config.not_state.is_none()
+            && !config.unavailable
+        {
+            bail!("rule '{}' has no condition", config.name);
+        }
+
+        Ok(Self {
+            config,
+            hold,
+            repeat,
+        })
+    }
+
+    /// Why the rule is violated by `state` (`None` for a missing entity), if it is
+    fn violation(&self, state: Option<&str>) -> Option<String> {
+        let rule = &self.config;
+        let state = match state {
+            None | Some("unavailable" | "unknown") => {
+                return rule
+                    .unavailable
+                    .then(|| state.unwrap_or("missing").to_string());
+            }
+            Some(state) => state,
+        };
+
+        if rule.state.as_deref() == Some(state) {
+            return Some(format!("state is {state}"));
+        }
+        if let Some(expected) = rule.not_state.as_deref().filter(|s| *s != state) {
+            return Some(format!("state is {state}, not {expected}"));
+        }
+
+        let value: f64 = state.parse().ok()?;
+        if let Some(above) = rule.above.filter(|above| value > *above) {
+            return Some(format!("{value} above {above}"));
+        }
+        if let Some(below) = rule.below.filter(|below| value < *below) {
+            return Some(format!("{value} below {below}"));
+        }
+        None
+    }
+
+    /// Human description of the conditions
+    fn describe(&self) -> String {
+        let rule = &self.config;
+        let mut parts = Vec::new();
+        if let Some(above) = rule.above {
+            parts.push(format!("above {above}"));
+        }
+        if let Some(below) = rule.below {
+            parts.push(format!("below {below}"));
+        }
+        if let Some(state) = &rule.state {
+            parts.push(format!("state {state}"));
+        }
+        if let Some(state) = &rule.not_state {
+            parts.push(format!("not {state}"));
+        }
+        if rule.unavailable {
+            parts.push("unavailable".to_string());
+        }
+        parts.join(", ")
+    }
+
+    /// Number of alert steps: one per command, at least one
+    fn steps(&self) -> usize {
+        self.config.commands.len().max(1)
+    }
+}
+
+/// What a rule's state change calls for
+#[derive(Debug, PartialEq)]
+enum Alert {
+    /// Run escalation step (0-based)
+    Fire(usize),
+    Resolve,
+}
+
+/// Violation progress of one rule
+#[derive(Debug, Default)]
+struct Tracker {
+    violated_since: Option<Instant>,
+    next_step: usize,
+    last_fired: Option<Instant>,
+}
+
+impl Tracker {
+    fn update(&mut self, rule: &Rule, violated: bool, now: Instant) -> Option<Alert> {
+        if !violated {
+            self.violated_since = None;
+            self.last_fired = None;
+            let alerted = self.next_step > 0;
+            self.next_step = 0;
+            return alerted.then_some(Alert::Resolve);
+        }
+
+        let since = *self.violated_since.get_or_insert(now);
+        if now.duration_since(since) < rule.hold {
+            return None;
+        }
+
+        let due = match (self.last_fired, rule.repeat) {
+            (None, _) => true,
+            (Some(last), Some(repeat)) => {
+                self.next_step < rule.steps() && now.duration_since(last) >= repeat
+            }
+            (Some(_), None) => false,
+        };
+        if !due {
+            return None;
+        }
+
+        let step = self.next_step;
+        self.next_step += 1;
+        self.last_fired = Some(now);
+        Some(Alert::Fire(step))
+    }
+}
+
+/// One alert or recovery, as printed
+#[derive(Debug, Serialize)]
+struct WatchdogEvent<'a> {
+    time: String,
+    event: &'static str,
+    rule: &'a str,
+    entity_id: &'a str,
+    state: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    step: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    command: Option<&'a str>,
+}
+
+#[derive(Debug, Tabled, Serialize)]
+struct RuleRow {
+    name: String,
+    entity_id: String,
+    condition: String,
+    #[tabled(rename = "for")]
+    #[serde(rename = "for")]
+    hold: String,
+    steps: usize,
+    repeat: String,
+}
+
+pub async fn run(ctx: &RuntimeContext, cmd: WatchdogCommand) -> Result<()> {
+    let config = match &cmd.file {
+        Some(path) => load_rules_file(path)?,
+        None => ctx.config.watchdog.clone(),
+    };
+    let interval = match cmd.interval {
+        Some(interval) => interval,
+        None => timespec::parse_interval(&config.interval)
+            .with_context(|| format!("invalid watchdog interval '{}'", config.interval))?,
+    };
+    let rules = config
+        .rules
+        .into_iter()
+        .map(Rule::compile)
+        .collect::<Result<Vec<_>>>()?;
+
+    if rules.is_empty() {
+        return Err(anyhow!(
+            "No watchdog rules; add [[watchdog.rules]] to {} or pass --file",
+            ctx.config_path().display()
+        ));
+    }
+
+    if cmd.check {
+        return list_rules(ctx, &rules);
+    }
+
+    watch(ctx, &rules, interval, cmd.dry_run).await
+}
+
+fn load_rules_file(path: &Path) -> Result<WatchdogConfig> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    toml::from_str(&content).with_context(|| format!("parsing {}", path.display()))
+}
+
+fn list_rules(ctx: &RuntimeContext, rules: &[Rule]) -> Result<()> {
+    let rows: Vec<RuleRow> = rules
+        .iter()
+        .map(|rule| RuleRow {
+            name: rule.config.name.clone(),
+            entity_id: rule.config.entity_id.clone(),
+            condition: rule.describe(),
+            hold: humantime::format_duration(rule.hold).to_string(),
+            steps: rule.steps(),
+            repeat: rule
+                .repeat
+                .map(|r| humantime::format_duration(r).to_string())
+                .unwrap_or_else(|| "-".to_string()),
+        })
+        .collect();
+    print_table(ctx, &rows)
+}
+
+async fn watch(
+    ctx: &RuntimeContext,
+    rules: &[Rule],
+    interval: Duration,
+    dry_run: bool,
+) -> Result<()> {
+    let client = HassClient::new(ctx)?;
+    let mut trackers: Vec<Tracker> = rules.iter().map(|_| Tracker::default()).collect();
+
+    if !ctx.global.quiet && !ctx.is_machine_output() {
+        eprintln!(
+            "Watching {} rules every {} (Ctrl+C to stop)",
+            rules.len(),
+            humantime::format_duration(interval)
+        );
+    }
+
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = tokio::signal::ctrl_c() => {
+                log::debug!("Received Ctrl+C, stopping watchdog");
+                return Ok(());
+            }
+        }
+
+        // A failed poll says nothing about the entities; skip it rather than alert
+        let states = match client.get_states().await {
+            Ok(states) => states,
+            Err(e) => {
+                report_error(ctx, "poll_failed", format!("Watchdog poll failed: {e:#}"));
+                continue;
+            }
+        };
+        let states: HashMap<&str, &str> = states
+            .iter()
+            .map(|s| (s.entity_id.as_str(), s.state.as_str()))
+            .collect();
+
+        let now = Instant::now();
+        for (rule, tracker) in rules.iter().zip(&mut trackers) {
+            let state = states.get(rule.config.entity_id.as_str()).copied();
+            let reason = rule.violation(state);
+            let Some(alert) = tracker.update(rule, reason.is_some(), now) else {
+                continue;
+            };
+
+            let (event, step, command) = match alert {
+                Alert::Fire(step) => ("alert", Some(step), rule.config.commands.get(step)),
+                Alert::Resolve => ("resolved", None, rule.config.resolved.as_ref()),
+            };
+            report(
+                ctx,
+                &WatchdogEvent {
+                    time: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+                    event,
+                    rule: &rule.config.name,
+                    entity_id: &rule.config.entity_id,
+                    state,
+                    reason,
+                    step: step.map(|s| s + 1),
+                    command: command.map(String::as_str),
+                },
+                rule.steps(),
+            )?;
+
+            if let (Some(command), false) = (command, dry_run) {
+                run_command(ctx, command, rule, state, step);
+            }
+        }
+    }
+}
+
+fn report(ctx: &RuntimeContext, event: &WatchdogEvent, steps: usize) -> Result<()> {
+    match ctx.output_format() {
+        OutputFormat::Json => print_output(ctx, event),
+        OutputFormat::Yaml => {
+            println!("---\n{}", serde_yaml::to_string(event)?);
+            Ok(())
+        }
+        _ => {
+            let state = event.state.unwrap_or("missing");
+            match (event.event, event.step) {
+                ("alert", Some(step)) => println!(
+                    "{} ALERT {}: {} is {state} ({}) [step {step}/{steps}]",
+                    event.time,
+                    event.rule,
+                    event.entity_id,
+                    event.reason.as_deref().unwrap_or("violated")
+                ),
+                _ => println!(
+                    "{} RESOLVED {}: {} is {state}",
+                    event.time, event.rule, event.entity_id
+                ),
+            }
+            Ok(())
+        }
+    }
+}
+
+/// A failed poll or alert command, as printed in machine mode
+#[derive(Debug, Serialize)]
+struct WatchdogError {
+    time: String,
+    event: &'static str,
+    error: String,
+}
+
+/// Report a failure while watching
+///
+/// Machine-mode warnings are held until exit, which a watchdog never reaches
+/// on its own, so there the failure goes out as an event in the stream.
+fn report_error(ctx: &RuntimeContext, event: &'static str, message: String) {
+    if !ctx.is_machine_output() {
+        warn(ctx, message);
+        return;
+    }
+    let error = WatchdogError {
+        time: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        event,
+        error: message,
+    };
+    let printed = match ctx.output_format() {
+        OutputFormat::Yaml => serde_yaml::to_string(&error)
+            .map(|yaml| println!("---\n{yaml}"))
+            .map_err(Into::into),
+        _ => print_output(ctx, &error),
+    };
+    if let Err(e) = printed {
+        log::debug!("Failed to print watchdog error: {e:#}");
+    }
+}
+
+/// Run an alert command through the shell without blocking the watch loop
+///
+/// The command sees HMR_WATCHDOG_RULE, HMR_WATCHDOG_ENTITY, HMR_WATCHDOG_STATE
+/// and HMR_WATCHDOG_STEP (1-based; empty for `resolved`).
+fn run_command(
+    ctx: &RuntimeContext,
+    command: &str,
+    rule: &Rule,
+    state: Option<&str>,
+    step: Option<usize>,
+) {
+    let mut process = std::process::Command::new("sh");
+    process
+        .arg("-c")
+        .arg(command)
+        .env("HMR_WATCHDOG_RULE", &rule.config.name)
+        .env("HMR_WATCHDOG_ENTITY", &rule.config.entity_id)
+        .env("HMR_WATCHDOG_STATE", state.unwrap_or(""))
+        .env(
+            "HMR_WATCHDOG_STEP",
+            step.map(|s| (s + 1).to_string()).unwrap_or_default(),
+        );
+
+    let ctx = ctx.clone();
+    let command = command.to_string();
+    tokio::task::spawn_blocking(move || match process.status() {
+        Ok(status) if status.success() => {}
+        Ok(status) => report_error(
+            &ctx,
+            "command_failed",
+            format!("Watchdog command '{command}' {status}"),
+        ),
+        Err(e) => report_error(
+            &ctx,
+            "command_failed",
+            format!("Watchdog command '{command}' failed: {e}"),
+        ),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(toml: &str) -> Rule {
+        let config: WatchdogRule = toml::from_str(toml).unwrap();
+        Rule::compile(config).unwrap()
+    }
+
+    #[test]
+    fn test_violation() {
+        let freezer = rule(
+            r#"
+            name = "freezer"
+            entity_id = "sensor.freezer"
+            above = -10.0
+            "#,
+        );
+        assert_eq!(freezer.violation(Some("-18")), None);
+        assert!(freezer.violation(Some("-5")).is_some());
+        assert!(freezer.violation(Some("unavailable")).is_some());
+        assert!(freezer.violation(None).is_some());
+
+        let pump = rule(
+            r#"
+            name = "sump"
+            entity_id = "switch.sump_pump"
+            not_state = "on"
+            unavailable = false
+            "#,
+        );
+        assert_eq!(pump.violation(Some("on")), None);
+        assert!(pump.violation(Some("off")).is_some());
+        assert_eq!(pump.violation(Some("unavailable")), None);
+    }
+
+    #[test]
+    fn test_rule_needs_condition() {
+        let config: WatchdogRule = toml::from_str(
+            r#"
+            name = "nothing"
+            entity_id = "sensor.x"
+            unavailable = false
+            "#,
+        )
+        .unwrap();
+        assert!(Rule::compile(config).is_err());
+    }
+
+    #[test]
+    fn test_tracker_hold_escalation_and_resolve() {
+        let rule = rule(
+            r#"
+            name = "ups"
+            entity_id = "sensor.ups_status"
+            state = "on_battery"
+            for = "1m"
+            repeat = "5m"
+            commands = ["notify", "call"]
+            "#,
+        );
+        let t0 = Instant::now();
+        let at = |secs: u64| t0 + Duration::from_secs(secs);
+        let mut tracker = Tracker::default();
+
+        // Flaps shorter than `for` never alert
+        assert_eq!(tracker.update(&rule, true, at(0)), None);
+        assert_eq!(tracker.update(&rule, false, at(30)), None);
+        assert_eq!(tracker.update(&rule, true, at(40)), None);
+        assert_eq!(tracker.update(&rule, true, at(100)), Some(Alert::Fire(0)));
+
+        // Escalates after `repeat`, then stops at the last step
+        assert_eq!(tracker.update(&rule, true, at(200)), None);
+        assert_eq!(tracker.update(&rule, true, at(400)), Some(Alert::Fire(1)));
+        assert_eq!(tracker.update(&rule, true, at(1000)), None);
+
+        assert_eq!(tracker.update(&rule, false, at(1030)), Some(Alert::Resolve));
+        assert_eq!(tracker.update(&rule, false, at(1060)), None);
+    }
+}
@@ -0,0 +1,199 @@
+//! Who command implementation
+//!
+//! `hmr who` lists the `person` and `device_tracker` entities with where they
+//! are and since when, people first. With `--watch` it keeps running and
+//! prints each arrival and departure as it happens.
+
+use anyhow::Result;
+use chrono::{DateTime, Local, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use tabled::Tabled;
+
+use crate::api::{EntityState, HassClient};
+use crate::cli::{OutputFormat, WhoCommand};
+use crate::config::RuntimeContext;
+use crate::output::{output_for_format, print_table};
+use crate::websocket;
+
+const DOMAINS: &[&str] = &["person", "device_tracker"];
+
+#[derive(Debug, Serialize, Tabled)]
+struct PresenceRow {
+    name: String,
+    #[tabled(rename = "where")]
+    location: String,
+    since: String,
+    entity_id: String,
+}
+
+impl PresenceRow {
+    fn new(state: &EntityState, now: DateTime<Utc>) -> Self {
+        Self {
+            name: state
+                .attributes
+                .get("friendly_name")
+                .and_then(|v| v.as_str())
+                .unwrap_or(&state.entity_id)
+                .to_string(),
+            location: location(&state.state).to_string(),
+            since: since(&state.last_changed, now),
+            entity_id: state.entity_id.clone(),
+        }
+    }
+}
+
+pub async fn run(ctx: &RuntimeContext, command: WhoCommand) -> Result<()> {
+    let client = HassClient::new(ctx)?;
+    let mut states: Vec<EntityState> = client
+        .get_states()
+        .await?
+        .into_iter()
+        .filter(|s| DOMAINS.contains(&domain(&s.entity_id)))
+        .collect();
+    // People first, then trackers, each by name
+    states.sort_by_key(|s| {
+        (
+            domain(&s.entity_id) != "person",
+            s.attributes
+                .get("friendly_name")
+                .and_then(|v| v.as_str())
+                .unwrap_or(&s.entity_id)
+                .to_lowercase(),
+        )
+    });
+
+    let now = Utc::now();
+    let rows: Vec<PresenceRow> = states.iter().map(|s| PresenceRow::new(s, now)).collect();
+    if !command.watch || !ctx.is_machine_output() {
+        output_for_format(ctx, &rows, || {
+            if rows.is_empty() {
+                println!("No people or device trackers found");
+                return Ok(());
+            }
+            print_table(ctx, &rows)
+        })?;
+    }
+    if !command.watch || states.is_empty() {
+        return Ok(());
+    }
+
+    if !ctx.is_machine_output() {
+        println!("\nWatching for changes (Ctrl+C to stop)...");
+    }
+    let entity_ids: Vec<String> = states.iter().map(|s| s.entity_id.clone()).collect();
+    let output_format = ctx.output_format();
+    websocket::watch_entities(ctx, &entity_ids, false, |data| {
+        match output_format {
+            OutputFormat::Json => println!("{}", serde_json::to_string(data)?),
+            OutputFormat::Yaml => println!("{}", serde_yaml::to_string(data)?),
+            OutputFormat::Table | OutputFormat::Auto => {
+                if let Some(line) = describe_change(data) {
+                    println!("{line}");
+                }
+            }
+        }
+        Ok(true)
+    })
+    .await
+}
+
+/// "Alice: home -> Work (14:32)", or None when only attributes changed
+fn describe_change(data: &Value) -> Option<String> {
+    let old = data["old_state"]["state"].as_str().unwrap_or("?");
+    let new = &data["new_state"];
+    let new_state = new["state"].as_str()?;
+    if old == new_state {
+        return None;
+    }
+    let name = new["attributes"]["friendly_name"]
+        .as_str()
+        .or(data["entity_id"].as_str())
+        .unwrap_or("?");
+    let at = new["last_changed"]
+        .as_str()
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Local).format("%H:%M").to_string())
+        .unwrap_or_default();
+    let replayed = if data["replayed"] == true {
+        " [replayed]"
+    } else {
+        ""
+    };
+    Some(format!(
+        "{name}: {} -> {} ({at}){replayed}",
+        location(old),
+        location(new_state)
+    ))
+}
+
+/// Home Assistant's zone state, with "not_home" spelled out
+fn location(state: &str) -> &str {
+    match state {
+        "not_home" => "away",
+        other => other,
+    }
+}
+
+/// Local time of the last change, with the day when it wasn't today, and how
+/// long ago it was ("14:32 (2h ago)")
+fn since(last_changed: &str, now: DateTime<Utc>) -> String {
+    let Ok(changed) = DateTime::parse_from_rfc3339(last_changed) else {
+        return last_changed.to_string();
+    };
+    let local = changed.with_timezone(&Local);
+    let time = if local.date_naive() == now.with_timezone(&Local).date_naive() {
+        local.format("%H:%M").to_string()
+    } else {
+        local.format("%Y-%m-%d %H:%M").to_string()
+    };
+    let minutes = (now - changed.with_timezone(&Utc)).num_minutes().max(0);
+    let ago = match minutes {
+        0 => "just now".to_string(),
+        m if m < 60 => format!("{m}m ago"),
+        m if m < 48 * 60 => format!("{}h ago", m / 60),
+        m => format!("{}d ago", m / (24 * 60)),
+    };
+    format!("{time} ({ago})")
+}
+
+fn domain(entity_id: &str) -> &str {
+    entity_id.split_once('.').map_or(entity_id, |(d, _)| d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_describe_change() {
+        let data = json!({
+            "entity_id": "person.alice",
+            "old_state": {"state": "home"},
+            "new_state": {
+                "state": "not_home",
+                "attributes": {"friendly_name": "Alice"},
+                "last_changed": "2024-05-01T12:00:00+00:00"
+            }
+        });
+        let line = describe_change(&data).unwrap();
+        assert!(line.starts_with("Alice: home -> away ("), "{line}");
+
+        let unchanged = json!({
+            "old_state": {"state": "home"},
+            "new_state": {"state": "home"}
+        });
+        assert!(describe_change(&unchanged).is_none());
+    }
+
+    #[test]
+    fn test_since_ago() {
+        let now = DateTime::parse_from_rfc3339("2024-05-01T12:00:00+00:00")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert!(since("2024-05-01T11:15:00+00:00", now).ends_with("(45m ago)"));
+        assert!(since("2024-04-28T12:00:00+00:00", now).ends_with("(3d ago)"));
+        assert_eq!(since("garbage", now), "garbage");
+    }
+}
@@ -5,6 +5,7 @@
 //! - Environment variable overrides
 //! - Command-line argument overrides
 
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
 use std::io::IsTerminal;
@@ -16,7 +17,7 @@ use env_logger::fmt::WriteStyle;
 use log::LevelFilter;
 use serde::{Deserialize, Serialize};
 
-use crate::cli::{GlobalOpts, OutputFormat};
+use crate::cli::{GlobalOpts, OutputFormat, PorcelainVersion};
 
 const APP_NAME: &str = env!("CARGO_PKG_NAME");
 
@@ -58,6 +59,7 @@ impl RuntimeContext {
 
         let force_color = env::var_os("FORCE_COLOR").is_some();
         let disable_color = self.global.no_color
+            || self.global.plain
             || env::var_os("NO_COLOR").is_some()
             || (!force_color && !std::io::stderr().is_terminal());
 
@@ -107,7 +109,34 @@ impl RuntimeContext {
     }
 
     /// Get the effective auth token
+    ///
+    /// A token profile chosen with `--as` wins over every other source.
     pub fn token(&self) -> Result<&str> {
+        if let Some(profile) = self.token_profile() {
+            return self
+                .config
+                .homeassistant
+                .tokens
+                .get(profile)
+                .map(String::as_str)
+                .ok_or_else(|| {
+                    let known: Vec<&str> = self
+                        .config
+                        .homeassistant
+                        .tokens
+                        .keys()
+                        .map(String::as_str)
+                        .collect();
+                    anyhow!(
+                        "No token profile '{profile}' in [homeassistant.tokens] (configured: {})",
+                        if known.is_empty() {
+                            "none".to_string()
+                        } else {
+                            known.join(", ")
+                        }
+                    )
+                });
+        }
         self.global
             .token
             .as_deref()
@@ -115,11 +144,16 @@ impl RuntimeContext {
             .ok_or_else(|| {
                 anyhow!(
                     "No authentication token configured.\n\
-                    Set via --token, HASS_TOKEN env var, or in config file."
+                    Set via --token, HASS_TOKEN env var, or in config file, or run `hmr auth login`."
                 )
             })
     }
 
+    /// The token profile chosen with `--as`
+    pub fn token_profile(&self) -> Option<&str> {
+        self.global.as_profile.as_deref()
+    }
+
     /// Get the effective timeout in seconds
     pub fn timeout(&self) -> u64 {
         self.global
@@ -149,6 +183,23 @@ impl RuntimeContext {
             })
     }
 
+    /// Get the requested porcelain version, if porcelain output is enabled
+    pub fn porcelain(&self) -> Option<PorcelainVersion> {
+        self.global.porcelain
+    }
+
+    /// Check if output is meant for programs rather than people
+    ///
+    /// True for JSON, YAML, and porcelain output, and for auto format when stdout is piped.
+    pub fn is_machine_output(&self) -> bool {
+        self.porcelain().is_some()
+            || match self.output_format() {
+                OutputFormat::Json | OutputFormat::Yaml => true,
+                OutputFormat::Auto => !std::io::stdout().is_terminal(),
+                OutputFormat::Table => false,
+            }
+    }
+
     /// Check if output should be in table format
     #[allow(dead_code)]
     pub fn is_table_output(&self) -> bool {
@@ -167,6 +218,12 @@ pub struct AppConfig {
     pub websocket: WebSocketConfig,
     pub output: OutputConfig,
     pub logging: LoggingConfig,
+    pub nl: NlConfig,
+    pub cache: CacheConfig,
+    pub watchdog: WatchdogConfig,
+    pub prompt: PromptConfig,
+    pub agent: AgentConfig,
+    pub telemetry: TelemetryConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -174,6 +231,8 @@ pub struct AppConfig {
 pub struct HomeAssistantConfig {
     pub server: Option<String>,
     pub token: Option<String>,
+    /// Named tokens selectable with `--as`, e.g. an admin and a restricted one
+    pub tokens: BTreeMap<String, String>,
     pub timeout: u64,
     pub insecure: bool,
 }
@@ -183,6 +242,7 @@ impl Default for HomeAssistantConfig {
         Self {
             server: None,
             token: None,
+            tokens: BTreeMap::new(),
             timeout: 30,
             insecure: false,
         }
@@ -213,6 +273,14 @@ pub struct OutputConfig {
     pub format: String,
     pub table_format: String,
     pub no_headers: bool,
+    /// Language for human-readable messages: auto (from the locale), en, de
+    pub language: String,
+    /// Table layout: auto (compact on narrow terminals), default, compact
+    pub profile: String,
+    /// Widest a table column gets before it is cut (0: no limit)
+    pub max_column_width: usize,
+    /// Wrap long cells onto more lines instead of cutting them with "…"
+    pub wrap: bool,
 }
 
 impl Default for OutputConfig {
@@ -221,6 +289,10 @@ impl Default for OutputConfig {
             format: "auto".to_string(),
             table_format: "simple".to_string(),
             no_headers: false,
+            language: "auto".to_string(),
+            profile: "auto".to_string(),
+            max_column_width: 40,
+            wrap: false,
         }
     }
 }
@@ -239,6 +311,152 @@ impl Default for LoggingConfig {
     }
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NlConfig {
+    /// Action for input that only names a target
+    pub default_action: DefaultAction,
+}
+
+/// What `hmr do` does with input that only names a target
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DefaultAction {
+    #[default]
+    TurnOn,
+    Toggle,
+    /// Print the current state instead of calling a service
+    Status,
+}
+
+impl DefaultAction {
+    /// The action as the natural language parser names it
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::TurnOn => "turn_on",
+            Self::Toggle => "toggle",
+            Self::Status => crate::nl::STATUS_ACTION,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// State attributes kept with each cached entity (device_class always is)
+    pub attributes: Vec<String>,
+    /// Attribute values longer than this as JSON, in bytes, aren't cached
+    pub max_attribute_bytes: usize,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            attributes: ["unit_of_measurement", "supported_features", "options"]
+                .map(String::from)
+                .to_vec(),
+            max_attribute_bytes: 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchdogConfig {
+    /// How often rule entities are checked (e.g., "30s")
+    pub interval: String,
+    pub rules: Vec<WatchdogRule>,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            interval: "30s".to_string(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+/// A condition on one entity that `hmr watchdog` alerts on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WatchdogRule {
+    pub name: String,
+    pub entity_id: String,
+    /// Violated when the numeric state is above this value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub above: Option<f64>,
+    /// Violated when the numeric state is below this value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub below: Option<f64>,
+    /// Violated when the state equals this value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    /// Violated when the state differs from this value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_state: Option<String>,
+    /// Treat unavailable/unknown (or a missing entity) as a violation
+    #[serde(default = "default_true")]
+    pub unavailable: bool,
+    /// How long a violation must last before alerting (flap suppression)
+    #[serde(rename = "for", default = "default_hold")]
+    pub hold: String,
+    /// Commands run in order: the first on violation, each next one after `repeat`
+    #[serde(default)]
+    pub commands: Vec<String>,
+    /// Interval between escalation steps while the violation lasts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeat: Option<String>,
+    /// Command run when an alerted violation clears
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PromptConfig {
+    /// Text printed by `hmr prompt`, with placeholders like {on:light}
+    pub template: String,
+    /// How long a rendered prompt is reused before asking the server again
+    pub ttl: String,
+    /// Longest wait for the server before falling back to the last rendered text
+    pub budget: String,
+}
+
+impl Default for PromptConfig {
+    fn default() -> Self {
+        Self {
+            template: DEFAULT_PROMPT_TEMPLATE.to_string(),
+            ttl: "30s".to_string(),
+            budget: "300ms".to_string(),
+        }
+    }
+}
+
+pub const DEFAULT_PROMPT_TEMPLATE: &str = "🏠 {on:light} lights";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentConfig {
+    /// Conversation agent used by `hmr agent` when --agent-id isn't given
+    pub agent_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelemetryConfig {
+    /// Keep local per-command usage metrics (see `hmr stats tool-usage`)
+    pub enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_hold() -> String {
+    "0s".to_string()
+}
+
 fn resolve_config_path(override_path: Option<&PathBuf>) -> Result<PathBuf> {
     if let Some(path) = override_path {
         let expanded = expand_path(path)?;
@@ -267,7 +485,13 @@ fn load_config(config_path: &Path, global: &GlobalOpts) -> Result<AppConfig> {
         .set_default("output.format", "auto")?
         .set_default("output.table_format", "simple")?
         .set_default("output.no_headers", false)?
+        .set_default("output.language", "auto")?
         .set_default("logging.level", "warn")?
+        .set_default("nl.default_action", "turn_on")?
+        .set_default("watchdog.interval", "30s")?
+        .set_default("prompt.template", DEFAULT_PROMPT_TEMPLATE)?
+        .set_default("prompt.ttl", "30s")?
+        .set_default("prompt.budget", "300ms")?
         // Load from file
         .add_source(
             File::from(config_path)
@@ -298,13 +522,17 @@ fn load_config(config_path: &Path, global: &GlobalOpts) -> Result<AppConfig> {
 }
 
 pub fn write_default_config(path: &Path) -> Result<()> {
+    write_config(path, &AppConfig::default())
+}
+
+/// Write a configuration file with the standard header and 0600 permissions
+pub fn write_config(path: &Path, config: &AppConfig) -> Result<()> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)
             .with_context(|| format!("creating config directory {}", parent.display()))?;
     }
 
-    let config = AppConfig::default();
-    let toml = toml::to_string_pretty(&config).context("serializing default config")?;
+    let toml = toml::to_string_pretty(config).context("serializing config")?;
 
     let content = format!(
         "# hmr configuration\n\
@@ -381,5 +609,32 @@ mod tests {
         assert!(toml.contains("[websocket]"));
         assert!(toml.contains("[output]"));
         assert!(toml.contains("[logging]"));
+        assert!(toml.contains("[nl]"));
+        assert!(toml.contains("[watchdog]"));
+        assert!(toml.contains("[prompt]"));
+    }
+
+    #[test]
+    fn test_token_profile() {
+        use clap::Parser;
+
+        let context = |args: &[&str]| {
+            let cli = crate::cli::Cli::try_parse_from(args).unwrap();
+            let mut config = AppConfig::default();
+            config.homeassistant.token = Some("default-token".to_string());
+            config.homeassistant.tokens =
+                BTreeMap::from([("kids".to_string(), "kids-token".to_string())]);
+            RuntimeContext {
+                global: cli.global,
+                config,
+                config_path: PathBuf::new(),
+            }
+        };
+
+        let kids = context(&["hmr", "--as", "kids", "--token", "cli-token", "info"]);
+        assert_eq!(kids.token_profile(), Some("kids"));
+        assert_eq!(kids.token().unwrap(), "kids-token");
+        assert!(context(&["hmr", "--as", "admin", "info"]).token().is_err());
+        assert_eq!(context(&["hmr", "info"]).token().unwrap(), "default-token");
     }
 }
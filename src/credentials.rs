@@ -0,0 +1,417 @@
+//! Stored login credentials
+//!
+//! `hmr auth login` keeps the tokens it obtains here, per server URL. On Linux
+//! with `secret-tool` (libsecret) and a session bus they go into the desktop
+//! keyring (unless built without the `keyring` feature); otherwise into
+//! `credentials.json` next to the config file, readable only by the owner.
+//! A token given by --token, HASS_TOKEN, or the config file always wins over
+//! stored credentials.
+//!
+//! Home Assistant identifies OAuth clients by a URL; a redirect URI on the same
+//! host is accepted without fetching anything, and hmr never follows it since
+//! it drives the login flow through the API instead of a browser.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Utc;
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::config::RuntimeContext;
+use crate::error::Error;
+
+pub const CLIENT_ID: &str = "https://github.com/byteowlz/hmr";
+const REDIRECT_URI: &str = "https://github.com/byteowlz/hmr/callback";
+
+/// Refresh this long before the access token expires
+const EXPIRY_MARGIN_SECS: i64 = 60;
+
+/// Tokens for one server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredToken {
+    pub access_token: String,
+    /// Absent for a long-lived token saved with `--flow token`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    /// Unix time the access token expires
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+}
+
+impl StoredToken {
+    /// Whether the access token is (nearly) expired and can be refreshed
+    pub fn needs_refresh(&self) -> bool {
+        self.refresh_token.is_some()
+            && self
+                .expires_at
+                .is_some_and(|at| Utc::now().timestamp() >= at - EXPIRY_MARGIN_SECS)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: i64,
+    #[serde(default)]
+    refresh_token: Option<String>,
+}
+
+/// One step of `/auth/login_flow`
+#[derive(Debug, Deserialize)]
+pub struct LoginStep {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub flow_id: Option<String>,
+    #[serde(default)]
+    pub step_id: Option<String>,
+    #[serde(default)]
+    pub errors: Option<Value>,
+    /// The authorization code, once the flow creates an entry
+    #[serde(default)]
+    pub result: Option<String>,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Start a login flow with the Home Assistant username/password provider
+pub async fn start_login(client: &Client, server: &str) -> Result<LoginStep> {
+    let body = json!({
+        "client_id": CLIENT_ID,
+        "handler": ["homeassistant", null],
+        "redirect_uri": REDIRECT_URI,
+    });
+    post_json(client, &format!("{server}/auth/login_flow"), &body).await
+}
+
+/// Submit one form of a login flow (credentials, or an MFA code)
+pub async fn submit_login(
+    client: &Client,
+    server: &str,
+    flow_id: &str,
+    mut fields: Value,
+) -> Result<LoginStep> {
+    fields["client_id"] = json!(CLIENT_ID);
+    post_json(
+        client,
+        &format!("{server}/auth/login_flow/{flow_id}"),
+        &fields,
+    )
+    .await
+}
+
+/// Exchange the authorization code from a finished login flow for tokens
+pub async fn exchange_code(client: &Client, server: &str, code: &str) -> Result<StoredToken> {
+    let response = request_token(
+        client,
+        server,
+        &[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("client_id", CLIENT_ID),
+        ],
+    )
+    .await?;
+    Ok(StoredToken {
+        expires_at: Some(Utc::now().timestamp() + response.expires_in),
+        access_token: response.access_token,
+        refresh_token: response.refresh_token,
+    })
+}
+
+/// Get a new access token with the refresh token
+pub async fn refresh(client: &Client, server: &str, token: &mut StoredToken) -> Result<()> {
+    let refresh_token = token
+        .refresh_token
+        .as_deref()
+        .ok_or_else(|| anyhow!("No refresh token stored"))?;
+    let response = request_token(
+        client,
+        server,
+        &[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+            ("client_id", CLIENT_ID),
+        ],
+    )
+    .await?;
+    log::debug!("Refreshed access token for {server}");
+    token.access_token = response.access_token;
+    token.expires_at = Some(Utc::now().timestamp() + response.expires_in);
+    Ok(())
+}
+
+/// Revoke a refresh token (and the access tokens issued with it)
+pub async fn revoke(client: &Client, server: &str, refresh_token: &str) -> Result<()> {
+    let url = format!("{server}/auth/revoke");
+    client
+        .post(&url)
+        .form(&[("token", refresh_token)])
+        .send()
+        .await
+        .map_err(|e| Error::network(format!("request to {url}"), e))?
+        .error_for_status()
+        .with_context(|| format!("revoking token at {url}"))?;
+    Ok(())
+}
+
+async fn request_token(
+    client: &Client,
+    server: &str,
+    form: &[(&str, &str)],
+) -> Result<TokenResponse> {
+    let url = format!("{server}/auth/token");
+    let response = client
+        .post(&url)
+        .form(form)
+        .send()
+        .await
+        .map_err(|e| Error::network(format!("request to {url}"), e))?;
+    let status = response.status();
+    if status == StatusCode::BAD_REQUEST || status == StatusCode::FORBIDDEN {
+        let body = response.text().await.unwrap_or_default();
+        return Err(Error::Auth(format!(
+            "Home Assistant refused the login ({body}). Run `hmr auth login` again"
+        ))
+        .into());
+    }
+    let response = response
+        .error_for_status()
+        .with_context(|| format!("requesting token from {url}"))?;
+    response
+        .json()
+        .await
+        .with_context(|| format!("parsing token from {url}"))
+}
+
+async fn post_json<T: serde::de::DeserializeOwned>(
+    client: &Client,
+    url: &str,
+    body: &Value,
+) -> Result<T> {
+    let response = client
+        .post(url)
+        .json(body)
+        .send()
+        .await
+        .map_err(|e| Error::network(format!("request to {url}"), e))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        bail!("HTTP {status} from {url}: {body}");
+    }
+    response
+        .json()
+        .await
+        .with_context(|| format!("parsing response from {url}"))
+}
+
+/// Where credentials are kept
+#[derive(Debug, Clone)]
+pub enum Store {
+    /// The desktop keyring, through `secret-tool`
+    #[cfg(feature = "keyring")]
+    Keyring,
+    /// A JSON file mapping server URLs to tokens
+    File(PathBuf),
+}
+
+impl Store {
+    pub fn new(ctx: &RuntimeContext) -> Self {
+        #[cfg(feature = "keyring")]
+        if keyring_available() {
+            return Self::Keyring;
+        }
+        Self::File(ctx.config_path().with_file_name("credentials.json"))
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            #[cfg(feature = "keyring")]
+            Self::Keyring => "the system keyring".to_string(),
+            Self::File(path) => path.display().to_string(),
+        }
+    }
+
+    pub fn load(&self, server: &str) -> Result<Option<StoredToken>> {
+        match self {
+            #[cfg(feature = "keyring")]
+            Self::Keyring => keyring::load(server),
+            Self::File(path) => Ok(read_file(path)?.remove(server)),
+        }
+    }
+
+    pub fn save(&self, server: &str, token: &StoredToken) -> Result<()> {
+        match self {
+            #[cfg(feature = "keyring")]
+            Self::Keyring => keyring::save(server, token),
+            Self::File(path) => {
+                let mut tokens = read_file(path)?;
+                tokens.insert(server.to_string(), token.clone());
+                write_file(path, &tokens)
+            }
+        }
+    }
+
+    /// Forget the credentials for `server`; false if there were none
+    pub fn remove(&self, server: &str) -> Result<bool> {
+        match self {
+            #[cfg(feature = "keyring")]
+            Self::Keyring => keyring::remove(server),
+            Self::File(path) => {
+                let mut tokens = read_file(path)?;
+                let existed = tokens.remove(server).is_some();
+                if existed {
+                    write_file(path, &tokens)?;
+                }
+                Ok(existed)
+            }
+        }
+    }
+}
+
+/// Credentials in the desktop keyring, stored as JSON through `secret-tool`
+#[cfg(feature = "keyring")]
+mod keyring {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    use anyhow::{anyhow, bail, Context, Result};
+
+    use super::StoredToken;
+
+    pub fn load(server: &str) -> Result<Option<StoredToken>> {
+        let output = Command::new("secret-tool")
+            .args(["lookup", "application", "hmr", "server", server])
+            .output()
+            .context("running secret-tool")?;
+        if !output.status.success() || output.stdout.is_empty() {
+            return Ok(None);
+        }
+        serde_json::from_slice(&output.stdout)
+            .map(Some)
+            .context("parsing credentials from the keyring")
+    }
+
+    pub fn save(server: &str, token: &StoredToken) -> Result<()> {
+        let mut child = Command::new("secret-tool")
+            .args(["store", "--label", &format!("hmr ({server})")])
+            .args(["application", "hmr", "server", server])
+            .stdin(Stdio::piped())
+            .spawn()
+            .context("running secret-tool")?;
+        child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow!("secret-tool has no stdin"))?
+            .write_all(serde_json::to_string(token)?.as_bytes())
+            .context("passing credentials to secret-tool")?;
+        if !child.wait()?.success() {
+            bail!("secret-tool could not store the credentials");
+        }
+        Ok(())
+    }
+
+    /// Forget the credentials for `server`; false if there were none
+    pub fn remove(server: &str) -> Result<bool> {
+        let existed = load(server)?.is_some();
+        let status = Command::new("secret-tool")
+            .args(["clear", "application", "hmr", "server", server])
+            .status()
+            .context("running secret-tool")?;
+        Ok(existed && status.success())
+    }
+}
+
+/// `secret-tool` is on the PATH and there is a session bus to reach the keyring
+pub fn keyring_available() -> bool {
+    cfg!(target_os = "linux")
+        && env::var_os("DBUS_SESSION_BUS_ADDRESS").is_some()
+        && env::var_os("PATH").is_some_and(|path| {
+            env::split_paths(&path).any(|dir| dir.join("secret-tool").is_file())
+        })
+}
+
+fn read_file(path: &PathBuf) -> Result<BTreeMap<String, StoredToken>> {
+    match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content)
+            .with_context(|| format!("parsing credentials in {}", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(e) => Err(e).with_context(|| format!("reading {}", path.display())),
+    }
+}
+
+fn write_file(path: &PathBuf, tokens: &BTreeMap<String, StoredToken>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("creating config directory {}", parent.display()))?;
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    // Owner-only from the start, so the tokens are never readable by others
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("writing credentials to {}", path.display()))?;
+
+    // The mode only applies to new files; tighten one left by an older hmr
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))
+            .with_context(|| format!("setting permissions on {}", path.display()))?;
+    }
+    file.write_all(serde_json::to_string_pretty(tokens)?.as_bytes())
+        .with_context(|| format!("writing credentials to {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(expires_at: Option<i64>) -> StoredToken {
+        StoredToken {
+            access_token: "access".to_string(),
+            refresh_token: Some("refresh".to_string()),
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn test_needs_refresh() {
+        let now = Utc::now().timestamp();
+        assert!(token(Some(now - 10)).needs_refresh());
+        assert!(token(Some(now + 30)).needs_refresh());
+        assert!(!token(Some(now + 1800)).needs_refresh());
+
+        let long_lived = StoredToken {
+            refresh_token: None,
+            ..token(None)
+        };
+        assert!(!long_lived.needs_refresh());
+    }
+
+    #[test]
+    fn test_file_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Store::File(dir.path().join("credentials.json"));
+        let server = "http://homeassistant.local:8123";
+
+        assert_eq!(store.load(server).unwrap(), None);
+        store.save(server, &token(Some(42))).unwrap();
+        assert_eq!(store.load(server).unwrap(), Some(token(Some(42))));
+        assert!(store.remove(server).unwrap());
+        assert!(!store.remove(server).unwrap());
+    }
+}
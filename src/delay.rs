@@ -0,0 +1,51 @@
+//! Delayed start for one-shot scheduling from cron or the shell
+//!
+//! `--wait-for 45m` runs the command after a delay and `--wait-until 07:30`
+//! at the next occurrence of that local time (today, or tomorrow once it has
+//! passed) or at a given date and time; a date and time already past is an
+//! error rather than a run right away. The wait happens before anything
+//! connects to the server.
+
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Local};
+
+use crate::config::RuntimeContext;
+use crate::progress;
+
+/// Sleep until the time `--wait-until`/`--wait-for` asks for, if any
+pub fn wait(ctx: &RuntimeContext) -> Result<()> {
+    let now = Local::now();
+    let Some(until) = start_time(ctx, now)? else {
+        return Ok(());
+    };
+    let delay = (until - now).to_std().unwrap_or_default();
+    log::info!(
+        "Waiting until {} ({})",
+        until.format("%Y-%m-%d %H:%M:%S"),
+        humantime::format_duration(Duration::from_secs(delay.as_secs()))
+    );
+    let spinner = progress::spinner(ctx, format!("Waiting until {}", until.format("%H:%M:%S")));
+    std::thread::sleep(delay);
+    spinner.finish_and_clear();
+    Ok(())
+}
+
+/// When the command should start, or None to start right away
+fn start_time(ctx: &RuntimeContext, now: DateTime<Local>) -> Result<Option<DateTime<Local>>> {
+    if let Some(delay) = ctx.global.wait_for {
+        return Ok(Some(now + delay));
+    }
+    let Some(until) = ctx.global.wait_until.map(|until| until.after(now)) else {
+        return Ok(None);
+    };
+    // Only a date and time can be past; times of day roll over to tomorrow
+    if until < now {
+        bail!(
+            "--wait-until {} is in the past",
+            until.format("%Y-%m-%d %H:%M:%S")
+        );
+    }
+    Ok(Some(until))
+}
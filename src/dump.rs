@@ -0,0 +1,178 @@
+//! Traffic dumps for `--dump-http`
+//!
+//! Every HTTP request and response and every WebSocket frame of one invocation
+//! is appended to the dump (a file, or stderr) with the token and known secret
+//! fields redacted, so the dump can be attached to a bug report as is.
+
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::sync::{LazyLock, Mutex};
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use regex::Regex;
+use serde_json::Value;
+
+use crate::config::RuntimeContext;
+
+const REDACTED: &str = "[REDACTED]";
+
+/// JSON fields whose values are replaced wherever they appear (case-insensitive)
+const SECRET_FIELDS: &[&str] = &[
+    "access_token",
+    "refresh_token",
+    "token",
+    "api_key",
+    "password",
+    "client_secret",
+    "secret",
+    "webhook_id",
+];
+
+/// `token=` query parameters, as in the `entity_picture` URLs of cameras and
+/// media players, which carry the entity's access token
+static QUERY_TOKEN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?i)([?&](?:access_)?token=)[^&#"'\\\s]+"#).unwrap());
+
+/// The open dump, if `--dump-http` was given
+static DUMP: Mutex<Option<Dump>> = Mutex::new(None);
+
+struct Dump {
+    out: Box<dyn Write + Send>,
+    token: Option<String>,
+}
+
+/// Open the dump target named by `--dump-http` ("-" for stderr)
+pub fn init(ctx: &RuntimeContext) -> Result<()> {
+    let Some(target) = &ctx.global.dump_http else {
+        return Ok(());
+    };
+
+    let out: Box<dyn Write + Send> = if target.as_os_str() == "-" {
+        Box::new(io::stderr())
+    } else {
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        // The dump is redacted, but it still shows the whole house; keep it private
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let file = options
+            .open(target)
+            .with_context(|| format!("creating HTTP dump file {}", target.display()))?;
+        Box::new(file)
+    };
+    let token = ctx.token().ok().map(String::from);
+
+    *DUMP.lock().unwrap_or_else(|e| e.into_inner()) = Some(Dump { out, token });
+    Ok(())
+}
+
+/// Whether traffic is being dumped (lets callers skip copying bodies otherwise)
+pub fn enabled() -> bool {
+    DUMP.lock().unwrap_or_else(|e| e.into_inner()).is_some()
+}
+
+pub fn http_request(method: &str, url: &str, body: Option<&Value>) {
+    let body = body.map(Value::to_string);
+    record(&format!("> {method} {url}"), body.as_deref());
+}
+
+pub fn http_response(status: u16, url: &str, body: &str) {
+    record(&format!("< {status} {url}"), Some(body));
+}
+
+pub fn ws_sent(text: &str) {
+    record("> WebSocket", Some(text));
+}
+
+pub fn ws_received(text: &str) {
+    record("< WebSocket", Some(text));
+}
+
+fn record(heading: &str, body: Option<&str>) {
+    let mut guard = DUMP.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(dump) = guard.as_mut() else {
+        return;
+    };
+
+    let time = Local::now().format("%H:%M:%S%.3f");
+    let heading = QUERY_TOKEN.replace_all(heading, format!("${{1}}{REDACTED}"));
+    let mut entry = format!("[{time}] {heading}\n");
+    if let Some(body) = body.filter(|b| !b.is_empty()) {
+        entry.push_str(&redact(body, dump.token.as_deref()));
+        entry.push('\n');
+    }
+    entry.push('\n');
+
+    // A dump that can't be written must not break the command being debugged
+    if let Err(e) = dump.out.write_all(entry.as_bytes()) {
+        log::debug!("Failed to write HTTP dump: {e}");
+    }
+}
+
+/// Pretty-print JSON bodies with secret fields replaced, and remove the token
+/// and `token=` query parameters anywhere
+fn redact(body: &str, token: Option<&str>) -> String {
+    let text = match serde_json::from_str::<Value>(body) {
+        Ok(mut value) => {
+            redact_value(&mut value);
+            serde_json::to_string_pretty(&value).unwrap_or_else(|_| body.to_string())
+        }
+        Err(_) => body.to_string(),
+    };
+
+    let text = QUERY_TOKEN
+        .replace_all(&text, format!("${{1}}{REDACTED}"))
+        .into_owned();
+    match token.filter(|t| !t.is_empty()) {
+        Some(token) => text.replace(token, REDACTED),
+        None => text,
+    }
+}
+
+fn redact_value(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let key = key.to_lowercase();
+                if SECRET_FIELDS.contains(&key.as_str()) && !value.is_null() {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact_value(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact() {
+        let body = r#"{"type":"auth","access_token":"abc.def","nested":[{"Password":"hunter2","name":"x"}]}"#;
+        let redacted = redact(body, Some("abc.def"));
+        assert!(!redacted.contains("abc.def"));
+        assert!(!redacted.contains("hunter2"));
+        assert!(redacted.contains(r#""name": "x""#));
+
+        // Non-JSON bodies still lose the token
+        assert_eq!(
+            redact("error for token abc.def", Some("abc.def")),
+            "error for token [REDACTED]"
+        );
+
+        // Entity access tokens ride along in picture URLs
+        let state =
+            r#"{"entity_picture":"/api/camera_proxy/camera.door?token=f00dcafe&width=640"}"#;
+        let redacted = redact(state, None);
+        assert!(!redacted.contains("f00dcafe"));
+        assert!(redacted.contains("camera.door?token=[REDACTED]&width=640"));
+    }
+}
@@ -0,0 +1,178 @@
+//! Error type with programmatic codes
+//!
+//! Most of the crate reports failures through `anyhow` with added context.
+//! Failures a script may want to react to (bad token, unreachable server,
+//! ambiguous name, ...) are raised as an [`Error`] variant instead. The variant
+//! stays in the `anyhow` chain, so `main` can pick the exit code from it and,
+//! with `--json`/`-o yaml`, print a structured error on stderr.
+
+use serde::Serialize;
+
+/// Exit code for failures that aren't an [`Error`] variant
+pub const EXIT_FAILURE: u8 = 1;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The token was rejected or lacks the permissions for the request
+    #[error("{0}")]
+    Auth(String),
+
+    /// The server could not be reached or the connection failed mid-request
+    #[error("{context}")]
+    Network {
+        context: String,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    /// The entity, device, area, or API resource does not exist
+    #[error("{0}")]
+    NotFound(String),
+
+    /// The input matches more than one object
+    #[error(
+        "Multiple matches for {kind} '{input}': {}. Please be more specific.",
+        candidates.join(", ")
+    )]
+    Ambiguous {
+        kind: String,
+        input: String,
+        candidates: Vec<String>,
+    },
+
+    /// Nothing matched in the local cache, which may be out of date
+    #[error("{0}")]
+    CacheStale(String),
+
+    /// The WebSocket API sent something other than what the protocol expects
+    #[error("{0}")]
+    WsProtocol(String),
+
+    /// Any other non-success HTTP status
+    #[error("{message}")]
+    Http { status: u16, message: String },
+
+    /// The command needs a cargo feature this build was made without
+    #[error(
+        "hmr was built without the `{0}` feature; rebuild with `cargo install hmr --features {0}`"
+    )]
+    FeatureDisabled(String),
+}
+
+impl Error {
+    pub fn network(
+        context: impl Into<String>,
+        source: impl std::error::Error + Send + Sync + 'static,
+    ) -> Self {
+        Self::Network {
+            context: context.into(),
+            source: Box::new(source),
+        }
+    }
+
+    /// Stable identifier for machine-readable output
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Auth(_) => "auth",
+            Self::Network { .. } => "network",
+            Self::NotFound(_) => "not_found",
+            Self::Ambiguous { .. } => "ambiguous",
+            Self::CacheStale(_) => "cache_stale",
+            Self::WsProtocol(_) => "ws_protocol",
+            Self::Http { .. } => "http",
+            Self::FeatureDisabled(_) => "feature_disabled",
+        }
+    }
+
+    /// Process exit code; 1 is left for other failures and 2 for usage errors
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::Auth(_) => 3,
+            Self::Network { .. } => 4,
+            Self::NotFound(_) => 5,
+            Self::Ambiguous { .. } => 6,
+            Self::CacheStale(_) => 7,
+            Self::WsProtocol(_) => 8,
+            Self::Http { .. } => 9,
+            Self::FeatureDisabled(_) => 10,
+        }
+    }
+}
+
+/// The first [`Error`] in an error chain, if any
+pub fn find(err: &anyhow::Error) -> Option<&Error> {
+    err.chain().find_map(|e| e.downcast_ref::<Error>())
+}
+
+/// Process exit code for an error chain
+pub fn exit_code(err: &anyhow::Error) -> u8 {
+    find(err).map_or(EXIT_FAILURE, Error::exit_code)
+}
+
+/// `{"error": {...}}` as printed in machine-readable output
+#[derive(Debug, Serialize)]
+pub struct ErrorReport {
+    error: ErrorDetails,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorDetails {
+    code: &'static str,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    candidates: Vec<String>,
+}
+
+impl ErrorReport {
+    pub fn new(err: &anyhow::Error) -> Self {
+        let found = find(err);
+        let (status, candidates) = match found {
+            Some(Error::Http { status, .. }) => (Some(*status), Vec::new()),
+            Some(Error::Ambiguous { candidates, .. }) => (None, candidates.clone()),
+            _ => (None, Vec::new()),
+        };
+        Self {
+            error: ErrorDetails {
+                code: found.map_or("error", Error::code),
+                message: format!("{err:#}"),
+                status,
+                candidates,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_code_survives_context() {
+        let err = Err::<(), _>(Error::NotFound("No entity matches 'lamp'".to_string()))
+            .context("resolving target")
+            .unwrap_err();
+        assert_eq!(exit_code(&err), 5);
+        assert_eq!(find(&err).map(Error::code), Some("not_found"));
+
+        assert_eq!(exit_code(&anyhow::anyhow!("plain failure")), EXIT_FAILURE);
+    }
+
+    #[test]
+    fn test_error_report() {
+        let err = anyhow::Error::new(Error::Ambiguous {
+            kind: "entity".to_string(),
+            input: "kitchen".to_string(),
+            candidates: vec!["light.kitchen".to_string(), "sensor.kitchen".to_string()],
+        });
+        let report = serde_json::to_value(ErrorReport::new(&err)).unwrap();
+        assert_eq!(report["error"]["code"], "ambiguous");
+        assert_eq!(report["error"]["candidates"][1], "sensor.kitchen");
+        assert_eq!(
+            report["error"]["message"],
+            "Multiple matches for entity 'kitchen': light.kitchen, sensor.kitchen. Please be more specific."
+        );
+    }
+}
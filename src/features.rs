@@ -0,0 +1,47 @@
+//! Optional subsystems chosen at build time
+//!
+//! `cargo build --no-default-features` leaves out everything listed in
+//! [`FEATURES`] for small static builds on routers and in containers. The
+//! commands and options of a left-out subsystem stay in the CLI, so scripts
+//! and `--help` are the same for every build; using one fails with a
+//! [`Error::FeatureDisabled`] naming the feature to rebuild with.
+
+use anyhow::Result;
+
+use crate::error::Error;
+
+/// Every optional feature and whether this build has it
+pub const FEATURES: &[(&str, bool)] = &[
+    ("completions", cfg!(feature = "completions")),
+    ("keyring", cfg!(feature = "keyring")),
+];
+
+/// Whether this build has the feature
+pub fn enabled(feature: &str) -> bool {
+    FEATURES
+        .iter()
+        .any(|(name, enabled)| *name == feature && *enabled)
+}
+
+/// Fail unless this build has the feature
+pub fn require(feature: &str) -> Result<()> {
+    if enabled(feature) {
+        return Ok(());
+    }
+    Err(Error::FeatureDisabled(feature.to_string()).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_require() {
+        assert_eq!(require("keyring").is_ok(), cfg!(feature = "keyring"));
+        let err = require("teleport").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "hmr was built without the `teleport` feature; rebuild with `cargo install hmr --features teleport`"
+        );
+    }
+}
@@ -3,6 +3,9 @@
 //! Provides typo-tolerant matching for:
 //! - Entity IDs and friendly names
 //! - Area names
+//! - Device names
+//! - Label names
+//! - Integration config entries
 //! - Service/domain names
 //! - Commands
 //!
@@ -13,11 +16,19 @@
 //! - Fuzzy scoring (skim algorithm)
 
 use std::cmp::Ordering;
+use std::collections::HashSet;
 
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher as FuzzyMatcherTrait;
 
-use crate::cache::{Cache, CachedArea, CachedEntity, CachedService};
+use crate::api::ConfigEntry;
+use crate::cache::{Cache, CachedArea, CachedDevice, CachedEntity, CachedLabel, CachedService};
+
+/// Matcher version recorded with each history entry.
+///
+/// Matching behavior only changes with hmr releases, so the package version
+/// identifies it.
+pub const MATCHER_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 /// Maximum Levenshtein distance for auto-correction
 const MAX_EDIT_DISTANCE: usize = 2;
@@ -181,6 +192,8 @@ impl<T> MatchResult<T> {
 /// Fuzzy matcher for Home Assistant entities and metadata
 pub struct FuzzyMatcher {
     matcher: SkimMatcherV2,
+    /// Pinned entity IDs, preferred when several entities match equally
+    pinned: HashSet<String>,
 }
 
 impl Default for FuzzyMatcher {
@@ -193,9 +206,31 @@ impl FuzzyMatcher {
     pub fn new() -> Self {
         Self {
             matcher: SkimMatcherV2::default(),
+            pinned: HashSet::new(),
         }
     }
 
+    /// Prefer these (pinned) entities when a name matches several
+    pub fn with_pinned(mut self, pinned: HashSet<String>) -> Self {
+        self.pinned = pinned;
+        self
+    }
+
+    /// Settle ambiguous entity matches on the one pinned entity, or list
+    /// pinned entities first
+    fn prefer_pinned<'a>(
+        &self,
+        mut matches: Vec<Match<&'a CachedEntity>>,
+    ) -> MatchResult<&'a CachedEntity> {
+        let pinned = |m: &Match<&CachedEntity>| self.pinned.contains(&m.item.entity_id);
+        if matches.iter().filter(|m| pinned(m)).count() == 1 {
+            let idx = matches.iter().position(pinned).unwrap_or_default();
+            return MatchResult::Single(matches.remove(idx));
+        }
+        matches.sort_by_key(|m| !pinned(m));
+        MatchResult::Multiple(matches)
+    }
+
     /// Find matching entities from cache
     pub fn find_entity<'a>(&self, input: &str, cache: &'a Cache) -> MatchResult<&'a CachedEntity> {
         let input_lower = input.to_lowercase();
@@ -233,7 +268,7 @@ impl FuzzyMatcher {
         if prefix_matches.len() == 1 {
             return MatchResult::Single(prefix_matches.remove(0));
         } else if !prefix_matches.is_empty() {
-            return MatchResult::Multiple(prefix_matches);
+            return self.prefer_pinned(prefix_matches);
         }
 
         // Third pass: typo correction with Levenshtein distance
@@ -257,7 +292,7 @@ impl FuzzyMatcher {
                     .partial_cmp(&a.confidence)
                     .unwrap_or(Ordering::Equal)
             });
-            return MatchResult::Multiple(typo_matches);
+            return self.prefer_pinned(typo_matches);
         }
 
         // Fourth pass: fuzzy matching
@@ -306,7 +341,7 @@ impl FuzzyMatcher {
         if fuzzy_matches.len() == 1 {
             MatchResult::Single(fuzzy_matches.remove(0))
         } else {
-            MatchResult::Multiple(fuzzy_matches)
+            self.prefer_pinned(fuzzy_matches)
         }
     }
 
@@ -404,6 +439,228 @@ impl FuzzyMatcher {
         }
     }
 
+    /// Find matching devices from cache
+    pub fn find_device<'a>(&self, input: &str, cache: &'a Cache) -> MatchResult<&'a CachedDevice> {
+        let input_lower = input.to_lowercase();
+        let devices = cache.devices();
+
+        // Exact matches on ID or either name
+        for device in devices {
+            if device.id == input {
+                return MatchResult::Single(Match::exact(device, input, &device.id));
+            }
+            for name in [&device.name_by_user, &device.name].into_iter().flatten() {
+                if name.to_lowercase() == input_lower {
+                    return MatchResult::Single(Match::exact(device, input, name));
+                }
+            }
+        }
+
+        // Prefix matches
+        let mut prefix_matches = Vec::new();
+        for device in devices {
+            for search_name in &device.search_names {
+                if search_name.to_lowercase().starts_with(&input_lower) {
+                    prefix_matches.push(Match::prefix(device, input, search_name));
+                    break;
+                }
+            }
+        }
+        if prefix_matches.len() == 1 {
+            return MatchResult::Single(prefix_matches.remove(0));
+        } else if !prefix_matches.is_empty() {
+            return MatchResult::Multiple(prefix_matches);
+        }
+
+        // Typo correction
+        let mut typo_matches = Vec::new();
+        for device in devices {
+            for search_name in &device.search_names {
+                let distance = levenshtein(&input_lower, &search_name.to_lowercase());
+                if distance <= MAX_EDIT_DISTANCE && distance > 0 {
+                    typo_matches.push(Match::typo(device, input, search_name, distance));
+                    break;
+                }
+            }
+        }
+        if !typo_matches.is_empty() {
+            typo_matches.sort_by(|a, b| {
+                b.confidence
+                    .partial_cmp(&a.confidence)
+                    .unwrap_or(Ordering::Equal)
+            });
+            if typo_matches.len() == 1 {
+                return MatchResult::Single(typo_matches.remove(0));
+            }
+            return MatchResult::Multiple(typo_matches);
+        }
+
+        // Fuzzy matching
+        let mut fuzzy_matches = Vec::new();
+        let max_score = (input.len() as i64) * 16;
+
+        for device in devices {
+            let best = device
+                .search_names
+                .iter()
+                .filter_map(|name| {
+                    self.matcher
+                        .fuzzy_match(name, &input_lower)
+                        .map(|score| (score, name))
+                })
+                .max_by_key(|(score, _)| *score);
+
+            if let Some((score, name)) = best.filter(|(score, _)| *score >= MIN_FUZZY_SCORE) {
+                fuzzy_matches.push(Match::fuzzy(device, input, name, score, max_score));
+            }
+        }
+
+        if fuzzy_matches.is_empty() {
+            return MatchResult::None;
+        }
+
+        fuzzy_matches.sort_by(|a, b| {
+            b.confidence
+                .partial_cmp(&a.confidence)
+                .unwrap_or(Ordering::Equal)
+        });
+
+        if fuzzy_matches.len() == 1 {
+            MatchResult::Single(fuzzy_matches.remove(0))
+        } else {
+            MatchResult::Multiple(fuzzy_matches)
+        }
+    }
+
+    /// Find matching integration config entries by entry ID, title, or domain
+    ///
+    /// A domain is exact but may have several entries (two Hue bridges);
+    /// otherwise titles are matched by prefix, typo, and fuzzy score.
+    pub fn find_config_entry<'a>(
+        &self,
+        input: &str,
+        entries: &'a [ConfigEntry],
+    ) -> MatchResult<&'a ConfigEntry> {
+        let input_lower = input.to_lowercase();
+        let several = |mut matches: Vec<Match<&'a ConfigEntry>>| match matches.len() {
+            0 => MatchResult::None,
+            1 => MatchResult::Single(matches.remove(0)),
+            _ => MatchResult::Multiple(matches),
+        };
+
+        if let Some(entry) = entries.iter().find(|e| e.entry_id == input) {
+            return MatchResult::Single(Match::exact(entry, input, &entry.entry_id));
+        }
+        let fields: [fn(&ConfigEntry) -> &str; 2] = [|e| &e.title, |e| &e.domain];
+        for field in fields {
+            let exact: Vec<_> = entries
+                .iter()
+                .filter(|e| field(e).to_lowercase() == input_lower)
+                .map(|e| Match::exact(e, input, field(e)))
+                .collect();
+            if !exact.is_empty() {
+                return several(exact);
+            }
+        }
+
+        let prefix: Vec<_> = entries
+            .iter()
+            .filter(|e| e.title.to_lowercase().starts_with(&input_lower))
+            .map(|e| Match::prefix(e, input, &e.title))
+            .collect();
+        if !prefix.is_empty() {
+            return several(prefix);
+        }
+
+        let mut typo: Vec<_> = entries
+            .iter()
+            .filter_map(|e| {
+                let distance = levenshtein(&input_lower, &e.title.to_lowercase());
+                (distance <= MAX_EDIT_DISTANCE).then(|| Match::typo(e, input, &e.title, distance))
+            })
+            .collect();
+        if !typo.is_empty() {
+            typo.sort_by(|a, b| {
+                b.confidence
+                    .partial_cmp(&a.confidence)
+                    .unwrap_or(Ordering::Equal)
+            });
+            return several(typo);
+        }
+
+        let max_score = (input.len() as i64) * 16;
+        let mut fuzzy: Vec<_> = entries
+            .iter()
+            .filter_map(|e| {
+                self.matcher
+                    .fuzzy_match(&e.title, &input_lower)
+                    .filter(|score| *score >= MIN_FUZZY_SCORE)
+                    .map(|score| Match::fuzzy(e, input, &e.title, score, max_score))
+            })
+            .collect();
+        fuzzy.sort_by(|a, b| {
+            b.confidence
+                .partial_cmp(&a.confidence)
+                .unwrap_or(Ordering::Equal)
+        });
+        several(fuzzy)
+    }
+
+    /// Find matching labels from cache
+    ///
+    /// Label names are short words ("holiday", "energy"), where skim scoring
+    /// matches almost anything, so only exact, prefix, and typo matches count.
+    pub fn find_label<'a>(&self, input: &str, cache: &'a Cache) -> MatchResult<&'a CachedLabel> {
+        let input_lower = input.to_lowercase();
+        let labels = cache.labels();
+
+        // Exact matches on ID or name
+        for label in labels {
+            if label.label_id == input_lower || label.name.to_lowercase() == input_lower {
+                return MatchResult::Single(Match::exact(label, input, &label.name));
+            }
+        }
+
+        // Prefix matches
+        let mut prefix_matches: Vec<_> = labels
+            .iter()
+            .filter_map(|label| {
+                label
+                    .search_names
+                    .iter()
+                    .find(|name| name.to_lowercase().starts_with(&input_lower))
+                    .map(|name| Match::prefix(label, input, name))
+            })
+            .collect();
+        if prefix_matches.len() == 1 {
+            return MatchResult::Single(prefix_matches.remove(0));
+        } else if !prefix_matches.is_empty() {
+            return MatchResult::Multiple(prefix_matches);
+        }
+
+        // Typo correction
+        let mut typo_matches: Vec<_> = labels
+            .iter()
+            .filter_map(|label| {
+                label.search_names.iter().find_map(|name| {
+                    let distance = levenshtein(&input_lower, &name.to_lowercase());
+                    (distance <= MAX_EDIT_DISTANCE && distance > 0)
+                        .then(|| Match::typo(label, input, name, distance))
+                })
+            })
+            .collect();
+        typo_matches.sort_by(|a, b| {
+            b.confidence
+                .partial_cmp(&a.confidence)
+                .unwrap_or(Ordering::Equal)
+        });
+        match typo_matches.len() {
+            0 => MatchResult::None,
+            1 => MatchResult::Single(typo_matches.remove(0)),
+            _ => MatchResult::Multiple(typo_matches),
+        }
+    }
+
     /// Find matching service from cache
     pub fn find_service<'a>(
         &self,
@@ -598,7 +855,10 @@ impl FuzzyMatcher {
 }
 
 /// Calculate Levenshtein distance between two strings
-fn levenshtein(a: &str, b: &str) -> usize {
+///
+/// Distances above the typo threshold aren't exact: strings whose lengths
+/// differ by more than it report the threshold plus one.
+pub fn levenshtein(a: &str, b: &str) -> usize {
     let a_chars: Vec<char> = a.chars().collect();
     let b_chars: Vec<char> = b.chars().collect();
 
@@ -669,10 +929,6 @@ mod tests {
         // Add test entities
         let entities = vec![
             CachedEntity {
-                entity_id: "light.kitchen".to_string(),
-                domain: "light".to_string(),
-                object_id: "kitchen".to_string(),
-                state: "on".to_string(),
                 friendly_name: Some("Kitchen Light".to_string()),
                 area_id: Some("kitchen".to_string()),
                 search_names: vec![
@@ -682,12 +938,9 @@ mod tests {
                     "kitchen light".to_string(),
                     "kitchen_light".to_string(),
                 ],
+                ..CachedEntity::for_test("light.kitchen", "on")
             },
             CachedEntity {
-                entity_id: "light.living_room".to_string(),
-                domain: "light".to_string(),
-                object_id: "living_room".to_string(),
-                state: "off".to_string(),
                 friendly_name: Some("Living Room Light".to_string()),
                 area_id: Some("living_room".to_string()),
                 search_names: vec![
@@ -697,12 +950,9 @@ mod tests {
                     "living room light".to_string(),
                     "living_room_light".to_string(),
                 ],
+                ..CachedEntity::for_test("light.living_room", "off")
             },
             CachedEntity {
-                entity_id: "switch.bedroom_fan".to_string(),
-                domain: "switch".to_string(),
-                object_id: "bedroom_fan".to_string(),
-                state: "off".to_string(),
                 friendly_name: Some("Bedroom Fan".to_string()),
                 area_id: Some("bedroom".to_string()),
                 search_names: vec![
@@ -711,6 +961,7 @@ mod tests {
                     "Bedroom Fan".to_string(),
                     "bedroom fan".to_string(),
                 ],
+                ..CachedEntity::for_test("switch.bedroom_fan", "off")
             },
         ];
 
@@ -725,6 +976,8 @@ mod tests {
                 name: "Kitchen".to_string(),
                 aliases: vec![],
                 search_names: vec!["kitchen".to_string(), "Kitchen".to_string()],
+                floor_id: None,
+                floor_name: None,
             },
             CachedArea {
                 area_id: "living_room".to_string(),
@@ -737,6 +990,8 @@ mod tests {
                     "Lounge".to_string(),
                     "lounge".to_string(),
                 ],
+                floor_id: None,
+                floor_name: None,
             },
         ];
 
@@ -769,6 +1024,45 @@ mod tests {
             crate::cache::CacheFile::new(services, 3600, "http://localhost:8123".to_string());
         cache.set_services(file);
 
+        // Add test devices
+        let devices = vec![
+            CachedDevice {
+                id: "a1b2c3".to_string(),
+                name: Some("Hue Bridge".to_string()),
+                name_by_user: None,
+                manufacturer: Some("Signify".to_string()),
+                model: None,
+                area_id: None,
+                sw_version: None,
+                hw_version: None,
+                search_names: vec![
+                    "a1b2c3".to_string(),
+                    "Hue Bridge".to_string(),
+                    "hue bridge".to_string(),
+                ],
+            },
+            CachedDevice {
+                id: "d4e5f6".to_string(),
+                name: Some("Motion Sensor".to_string()),
+                name_by_user: Some("Hallway Motion".to_string()),
+                manufacturer: None,
+                model: None,
+                area_id: None,
+                sw_version: None,
+                hw_version: None,
+                search_names: vec![
+                    "d4e5f6".to_string(),
+                    "Motion Sensor".to_string(),
+                    "motion sensor".to_string(),
+                    "Hallway Motion".to_string(),
+                    "hallway motion".to_string(),
+                ],
+            },
+        ];
+
+        let file = crate::cache::CacheFile::new(devices, 3600, "http://localhost:8123".to_string());
+        cache.set_devices(file);
+
         cache
     }
 
@@ -847,6 +1141,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_find_entity_prefers_pinned() {
+        let cache = create_test_cache();
+        assert!(matches!(
+            FuzzyMatcher::new().find_entity("li", &cache),
+            MatchResult::Multiple(_)
+        ));
+
+        let pinned = HashSet::from(["light.living_room".to_string()]);
+        match FuzzyMatcher::new()
+            .with_pinned(pinned)
+            .find_entity("li", &cache)
+        {
+            MatchResult::Single(m) => assert_eq!(m.item.entity_id, "light.living_room"),
+            _ => panic!("Expected the pinned entity"),
+        }
+    }
+
     #[test]
     fn test_find_entity_friendly_name_match() {
         let cache = create_test_cache();
@@ -886,6 +1198,39 @@ mod tests {
         assert!(matches!(result, MatchResult::None));
     }
 
+    #[test]
+    fn test_find_device_by_name_and_id() {
+        let cache = create_test_cache();
+        let matcher = FuzzyMatcher::new();
+
+        match matcher.find_device("hallway motion", &cache) {
+            MatchResult::Single(m) => {
+                assert_eq!(m.item.id, "d4e5f6");
+                assert!(matches!(m.match_type, MatchType::Exact));
+            }
+            _ => panic!("Expected single match"),
+        }
+        assert!(matcher.find_device("a1b2c3", &cache).is_exact());
+    }
+
+    #[test]
+    fn test_find_device_typo_and_prefix() {
+        let cache = create_test_cache();
+        let matcher = FuzzyMatcher::new();
+
+        match matcher.find_device("hue brige", &cache) {
+            MatchResult::Single(m) => {
+                assert_eq!(m.item.id, "a1b2c3");
+                assert!(matches!(m.match_type, MatchType::Typo { .. }));
+            }
+            _ => panic!("Expected single typo match"),
+        }
+        match matcher.find_device("motion", &cache) {
+            MatchResult::Single(m) => assert_eq!(m.item.id, "d4e5f6"),
+            _ => panic!("Expected single prefix match"),
+        }
+    }
+
     #[test]
     fn test_find_area_exact_match() {
         let cache = create_test_cache();
@@ -1000,4 +1345,38 @@ mod tests {
         assert_eq!(format_correction("light", "light"), "light");
         assert_eq!(format_correction("ligth", "light"), "ligth -> light");
     }
+
+    #[test]
+    fn test_find_config_entry() {
+        let entry = |id: &str, domain: &str, title: &str| ConfigEntry {
+            entry_id: id.to_string(),
+            domain: domain.to_string(),
+            title: title.to_string(),
+            source: None,
+            state: "loaded".to_string(),
+            disabled_by: None,
+            reason: None,
+        };
+        let entries = vec![
+            entry("01HUE1", "hue", "Living Room Hue"),
+            entry("01HUE2", "hue", "Upstairs Bridge"),
+            entry("01ZHA", "zha", "Zigbee Coordinator"),
+        ];
+        let matcher = FuzzyMatcher::new();
+        let found = |input| match matcher.find_config_entry(input, &entries) {
+            MatchResult::Single(m) => Some(m.item.entry_id.as_str()),
+            _ => None,
+        };
+
+        assert_eq!(found("01HUE2"), Some("01HUE2"));
+        assert_eq!(found("upstairs bridge"), Some("01HUE2"));
+        assert_eq!(found("zha"), Some("01ZHA"));
+        assert_eq!(found("zigbee"), Some("01ZHA"));
+        assert_eq!(found("Upstars Bridge"), Some("01HUE2"));
+        assert!(matches!(
+            matcher.find_config_entry("hue", &entries),
+            MatchResult::Multiple(ref m) if m.len() == 2
+        ));
+        assert_eq!(found("mqtt"), None);
+    }
 }
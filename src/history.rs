@@ -5,7 +5,7 @@
 //! - Current context for follow-up commands (e.g., "brighter" after "turn on kitchen light")
 //! - Accuracy statistics for fuzzy matching improvement
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs::{self, File, OpenOptions};
 use std::io::{BufRead, BufReader, Write};
@@ -42,6 +42,15 @@ pub struct HistoryEntry {
     pub error: Option<String>,
     /// Match type used (exact, fuzzy, typo)
     pub match_type: Option<String>,
+    /// Matcher (hmr) version that interpreted the command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matcher_version: Option<String>,
+    /// Fingerprint of the entity cache the command was matched against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_fingerprint: Option<String>,
+    /// Token profile (`--as`) the command ran with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
 }
 
 impl HistoryEntry {
@@ -60,6 +69,9 @@ impl HistoryEntry {
             success: false,
             error: None,
             match_type: None,
+            matcher_version: None,
+            cache_fingerprint: None,
+            profile: None,
         }
     }
 
@@ -87,6 +99,89 @@ impl HistoryEntry {
         self.match_type = Some(match_type.to_string());
         self
     }
+
+    pub fn with_matcher(mut self, version: &str, cache_fingerprint: Option<String>) -> Self {
+        self.matcher_version = Some(version.to_string());
+        self.cache_fingerprint = cache_fingerprint;
+        self
+    }
+
+    pub fn with_profile(mut self, profile: Option<&str>) -> Self {
+        self.profile = profile.map(str::to_string);
+        self
+    }
+}
+
+/// Accuracy over a run of consecutive commands that used the same matcher
+/// version and cache fingerprint
+#[derive(Debug, Clone, Serialize)]
+pub struct SegmentStats {
+    pub matcher_version: Option<String>,
+    pub cache_fingerprint: Option<String>,
+    /// Timestamp of the first command in the segment
+    pub since: u64,
+    pub commands: u64,
+    pub successes: u64,
+    pub exact: u64,
+    pub typo: u64,
+    pub fuzzy: u64,
+}
+
+impl SegmentStats {
+    fn new(entry: &HistoryEntry) -> Self {
+        Self {
+            matcher_version: entry.matcher_version.clone(),
+            cache_fingerprint: entry.cache_fingerprint.clone(),
+            since: entry.timestamp,
+            commands: 0,
+            successes: 0,
+            exact: 0,
+            typo: 0,
+            fuzzy: 0,
+        }
+    }
+
+    fn record(&mut self, entry: &HistoryEntry) {
+        self.commands += 1;
+        if entry.success {
+            self.successes += 1;
+        }
+        match entry.match_type.as_deref() {
+            Some("Exact") => self.exact += 1,
+            Some(t) if t.starts_with("Typo") => self.typo += 1,
+            Some(t) if t.contains("Fuzzy") => self.fuzzy += 1,
+            _ => {}
+        }
+    }
+
+    /// Success rate as a percentage
+    pub fn success_rate(&self) -> f64 {
+        if self.commands == 0 {
+            return 0.0;
+        }
+        (self.successes as f64 / self.commands as f64) * 100.0
+    }
+}
+
+/// Split history (oldest first) into segments whenever the matcher version or
+/// cache fingerprint changes, so accuracy can be compared across them
+pub fn compare_segments(entries: &[HistoryEntry]) -> Vec<SegmentStats> {
+    let mut segments: Vec<SegmentStats> = Vec::new();
+
+    for entry in entries {
+        let same_segment = segments.last().is_some_and(|seg| {
+            seg.matcher_version == entry.matcher_version
+                && seg.cache_fingerprint == entry.cache_fingerprint
+        });
+        if !same_segment {
+            segments.push(SegmentStats::new(entry));
+        }
+        if let Some(seg) = segments.last_mut() {
+            seg.record(entry);
+        }
+    }
+
+    segments
 }
 
 /// Current command context for follow-up commands
@@ -372,12 +467,43 @@ impl History {
                     || e.targets
                         .iter()
                         .any(|t| t.to_lowercase().contains(&pattern_lower))
+                    || e.profile
+                        .as_deref()
+                        .is_some_and(|p| p.to_lowercase() == pattern_lower)
             })
             .collect();
 
         Ok(entries)
     }
 
+    /// Count history entries
+    pub fn count(&self) -> Result<usize> {
+        if !self.history_path.exists() {
+            return Ok(0);
+        }
+        let content = fs::read_to_string(&self.history_path)
+            .with_context(|| format!("reading {}", self.history_path.display()))?;
+        Ok(content.lines().filter(|l| !l.trim().is_empty()).count())
+    }
+
+    /// Merge entries into the history file, skipping ones already present
+    ///
+    /// The file is rewritten in timestamp order. Returns the number of entries added.
+    pub fn merge(&self, entries: &[HistoryEntry]) -> Result<usize> {
+        let (merged, added) = merge_entries(self.recent(usize::MAX)?, entries);
+        if added == 0 {
+            return Ok(0);
+        }
+
+        let mut file = File::create(&self.history_path)
+            .with_context(|| format!("writing history file {}", self.history_path.display()))?;
+        for entry in &merged {
+            writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        }
+
+        Ok(added)
+    }
+
     /// Clear all history
     pub fn clear(&self) -> Result<()> {
         if self.history_path.exists() {
@@ -416,8 +542,31 @@ impl History {
     }
 }
 
+/// Combine two entry lists, dropping duplicates by timestamp and input
+fn merge_entries(
+    existing: Vec<HistoryEntry>,
+    incoming: &[HistoryEntry],
+) -> (Vec<HistoryEntry>, usize) {
+    let seen: HashSet<(u64, String)> = existing
+        .iter()
+        .map(|e| (e.timestamp, e.input.clone()))
+        .collect();
+
+    let new: Vec<HistoryEntry> = incoming
+        .iter()
+        .filter(|e| !seen.contains(&(e.timestamp, e.input.clone())))
+        .cloned()
+        .collect();
+    let added = new.len();
+
+    let mut merged = existing;
+    merged.extend(new);
+    merged.sort_by_key(|e| e.timestamp);
+    (merged, added)
+}
+
 /// Get the state directory path (for context and stats)
-fn state_dir() -> Result<PathBuf> {
+pub fn state_dir() -> Result<PathBuf> {
     // Check XDG_STATE_HOME first
     if let Some(dir) = env::var_os("XDG_STATE_HOME").filter(|v| !v.is_empty()) {
         return Ok(PathBuf::from(dir).join(APP_NAME));
@@ -457,6 +606,19 @@ mod tests {
         assert!(entry.timestamp > 0);
     }
 
+    #[test]
+    fn test_merge_entries_skips_duplicates() {
+        let mut a = HistoryEntry::new("kitchen on", "turn_on kitchen");
+        a.timestamp = 100;
+        let mut b = HistoryEntry::new("kitchen off", "turn_off kitchen");
+        b.timestamp = 50;
+
+        let (merged, added) = merge_entries(vec![a.clone()], &[a, b]);
+        assert_eq!(added, 1);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].input, "kitchen off");
+    }
+
     #[test]
     fn test_history_entry_with_error() {
         let entry = HistoryEntry::new("bad command", "").with_error("No matching entities");
@@ -618,4 +780,43 @@ mod tests {
         assert!(path.to_string_lossy().contains("hmr"));
         assert!(path.to_string_lossy().ends_with("history.jsonl"));
     }
+
+    #[test]
+    fn test_compare_segments() {
+        let entry = |fp: Option<&str>, success: bool, match_type: &str| {
+            let mut e = HistoryEntry::new("x", "x").with_match_type(match_type);
+            if success {
+                e = e.with_success();
+            }
+            if let Some(fp) = fp {
+                e = e.with_matcher("0.4.1", Some(fp.to_string()));
+            }
+            e
+        };
+
+        let entries = vec![
+            entry(None, true, "Exact"),
+            entry(Some("aaaa"), true, "Exact"),
+            entry(Some("aaaa"), false, "Typo(1)"),
+            entry(Some("bbbb"), true, "Fuzzy"),
+        ];
+
+        let segments = compare_segments(&entries);
+        assert_eq!(segments.len(), 3);
+        assert_eq!(segments[0].matcher_version, None);
+        assert_eq!(segments[1].commands, 2);
+        assert_eq!(segments[1].successes, 1);
+        assert_eq!(segments[1].typo, 1);
+        assert_eq!(segments[1].success_rate(), 50.0);
+        assert_eq!(segments[2].cache_fingerprint.as_deref(), Some("bbbb"));
+        assert_eq!(segments[2].fuzzy, 1);
+    }
+
+    #[test]
+    fn test_history_entry_matcher_fields_optional() {
+        let json = r#"{"timestamp":1,"input":"a","interpretation":"b","service":null,"targets":[],"success":true,"error":null,"match_type":null}"#;
+        let entry: HistoryEntry = serde_json::from_str(json).unwrap();
+        assert!(entry.matcher_version.is_none());
+        assert!(entry.cache_fingerprint.is_none());
+    }
 }
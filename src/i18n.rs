@@ -0,0 +1,191 @@
+//! Message catalogs for human-readable output
+//!
+//! Interpretation lines, prompts and run summaries are looked up here in the
+//! language chosen by `output.language`, or by the locale (`LC_ALL`,
+//! `LC_MESSAGES`, `LANG`) when that is "auto". Messages without a translation
+//! fall back to English. Machine output (JSON, YAML, porcelain) and error
+//! messages are never translated.
+
+use std::sync::OnceLock;
+
+/// Languages with a message catalog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    German,
+}
+
+impl Language {
+    /// Parse a language code or locale such as "de", "de_DE.UTF-8" or "en-US"
+    pub fn from_code(code: &str) -> Option<Self> {
+        let base = code.split(['_', '-', '.', '@']).next()?.to_lowercase();
+        match base.as_str() {
+            "en" | "c" | "posix" => Some(Self::English),
+            "de" => Some(Self::German),
+            _ => None,
+        }
+    }
+}
+
+/// Messages that have translations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Msg {
+    /// {0}: the parser's interpretation
+    InterpretedAs,
+    /// {0}: number of targets
+    Targets,
+    Parameters,
+    DryRun,
+    Simulated,
+    /// {0}: service, {1}: number of entities
+    Calling,
+    Done,
+    /// {0}: duration, {1}: the service called afterwards
+    RunningFor,
+    /// {0}: the service that ended the run
+    RunFinished,
+    /// {0}: count (with a noun for the first group), {1}: state
+    NowState,
+    /// {0}: count, {1}: state, {2}: entity IDs
+    NowStateIds,
+    /// {0}: count, {1}: state, {2}: entity IDs
+    StillState,
+    /// {0}: count, {1}: entity IDs
+    Unavailable,
+    ConfirmContinue,
+    /// {0}: number of options
+    SelectPrompt,
+    /// {0}: what was typed
+    InvalidSelection,
+}
+
+static LANGUAGE: OnceLock<Language> = OnceLock::new();
+
+/// Choose the output language from the configured value ("auto" for the locale)
+pub fn init(configured: &str) {
+    let language = resolve_language(configured, |name| std::env::var(name).ok());
+    let _ = LANGUAGE.set(language);
+}
+
+/// The output language (English until [`init`] runs)
+pub fn language() -> Language {
+    LANGUAGE.get().copied().unwrap_or(Language::English)
+}
+
+/// A message in the output language with `{0}`, `{1}`, ... replaced by `args`
+pub fn tr(msg: Msg, args: &[&str]) -> String {
+    fill(catalog(language(), msg), args)
+}
+
+/// Answers accepted as "yes" at a confirmation prompt
+pub fn yes_words() -> &'static [&'static str] {
+    match language() {
+        Language::English => &["y", "yes"],
+        Language::German => &["y", "yes", "j", "ja"],
+    }
+}
+
+fn resolve_language(configured: &str, env: impl Fn(&str) -> Option<String>) -> Language {
+    if configured != "auto" {
+        return Language::from_code(configured).unwrap_or(Language::English);
+    }
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| env(name).filter(|v| !v.is_empty()))
+        .next()
+        .and_then(|locale| Language::from_code(&locale))
+        .unwrap_or(Language::English)
+}
+
+fn fill(template: &str, args: &[&str]) -> String {
+    args.iter()
+        .enumerate()
+        .fold(template.to_string(), |text, (i, arg)| {
+            text.replace(&format!("{{{i}}}"), arg)
+        })
+}
+
+fn catalog(language: Language, msg: Msg) -> &'static str {
+    match language {
+        Language::English => english(msg),
+        Language::German => german(msg),
+    }
+}
+
+fn english(msg: Msg) -> &'static str {
+    match msg {
+        Msg::InterpretedAs => "Interpreted as: {0}",
+        Msg::Targets => "Targets ({0}):",
+        Msg::Parameters => "Parameters:",
+        Msg::DryRun => "(dry run - no action taken)",
+        Msg::Simulated => "(simulated on cached states - nothing sent to Home Assistant)",
+        Msg::Calling => "Calling {0} on {1} entities...",
+        Msg::Done => "Done.",
+        Msg::RunningFor => "Running for {0}; {1} follows (Ctrl+C to stop now)",
+        Msg::RunFinished => "Called {0} - run finished.",
+        Msg::NowState => "{0} now {1}",
+        Msg::NowStateIds => "{0} now {1}: {2}",
+        Msg::StillState => "{0} still {1}: {2}",
+        Msg::Unavailable => "{0} unavailable: {1}",
+        Msg::ConfirmContinue => "Continue? [y/N]: ",
+        Msg::SelectPrompt => "Select [1-{0}] (empty to cancel): ",
+        Msg::InvalidSelection => "Invalid selection: {0}",
+    }
+}
+
+fn german(msg: Msg) -> &'static str {
+    match msg {
+        Msg::InterpretedAs => "Verstanden als: {0}",
+        Msg::Targets => "Ziele ({0}):",
+        Msg::Parameters => "Parameter:",
+        Msg::DryRun => "(Probelauf - nichts ausgeführt)",
+        Msg::Simulated => "(simuliert auf zwischengespeicherten Zuständen - nichts gesendet)",
+        Msg::Calling => "Rufe {0} für {1} Entitäten auf...",
+        Msg::Done => "Fertig.",
+        Msg::RunningFor => "Läuft für {0}; danach {1} (Strg+C beendet sofort)",
+        Msg::RunFinished => "{0} aufgerufen - Lauf beendet.",
+        Msg::NowState => "{0} jetzt {1}",
+        Msg::NowStateIds => "{0} jetzt {1}: {2}",
+        Msg::StillState => "{0} weiterhin {1}: {2}",
+        Msg::Unavailable => "{0} nicht verfügbar: {1}",
+        Msg::ConfirmContinue => "Fortfahren? [j/N]: ",
+        Msg::SelectPrompt => "Auswahl [1-{0}] (leer zum Abbrechen): ",
+        Msg::InvalidSelection => "Ungültige Auswahl: {0}",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_language() {
+        let env = |lang: &'static str| move |name: &str| (name == "LANG").then(|| lang.to_string());
+
+        assert_eq!(
+            resolve_language("auto", env("de_DE.UTF-8")),
+            Language::German
+        );
+        assert_eq!(
+            resolve_language("auto", env("fr_FR.UTF-8")),
+            Language::English
+        );
+        assert_eq!(
+            resolve_language("en", env("de_DE.UTF-8")),
+            Language::English
+        );
+        assert_eq!(resolve_language("de", |_| None), Language::German);
+    }
+
+    #[test]
+    fn test_fill() {
+        assert_eq!(
+            fill(
+                catalog(Language::German, Msg::Calling),
+                &["light.turn_on", "2"]
+            ),
+            "Rufe light.turn_on für 2 Entitäten auf..."
+        );
+        assert_eq!(fill("{1} after {0}", &["a", "b"]), "b after a");
+    }
+}
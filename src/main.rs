@@ -6,23 +6,45 @@
 mod api;
 mod cache;
 mod cli;
+mod colors;
 mod commands;
 mod config;
+mod credentials;
+mod delay;
+mod dump;
+mod error;
+mod features;
 mod fuzzy;
 mod history;
+mod i18n;
+mod names;
 mod natural_args;
 mod nl;
 mod output;
+mod paging;
+mod pins;
+mod progress;
+mod prompt;
+mod resolve;
+mod simulate;
+mod timespec;
+mod units;
+mod usage;
+mod validate;
+mod watch_stats;
 mod websocket;
 
 use std::io::{self, Write};
 use std::process::ExitCode;
+use std::time::Instant;
 
-use anyhow::Result;
-use clap::{CommandFactory, Parser};
+use anyhow::{anyhow, Result};
+use clap::{CommandFactory, FromArgMatches};
+use futures_util::FutureExt;
 
-use crate::cli::{Cli, Command};
+use crate::cli::{CacheCommand, Cli, Command, HistoryCommand, OutputFormat, SystemCommand};
 use crate::config::RuntimeContext;
+use crate::error::ErrorReport;
 
 fn main() -> ExitCode {
     // Reset SIGPIPE to default behavior to avoid panics on broken pipes
@@ -33,20 +55,35 @@ fn main() -> ExitCode {
         libc::signal(libc::SIGPIPE, libc::SIG_DFL);
     }
 
-    match try_main() {
+    // Normalize natural command variations before parsing
+    let normalized_args = natural_args::normalize_args();
+    let matches = Cli::command().get_matches_from(normalized_args);
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let command_name = usage::command_name(&matches);
+    let error_format = match cli.global.output_format {
+        _ if cli.global.json => OutputFormat::Json,
+        Some(format) => format,
+        None => OutputFormat::Auto,
+    };
+
+    match try_main(cli, &command_name) {
         Ok(()) => ExitCode::SUCCESS,
         Err(err) => {
-            let _ = writeln!(io::stderr(), "Error: {err:#}");
-            ExitCode::from(1)
+            // Structured errors go to stderr so stdout stays a valid document
+            let report = ErrorReport::new(&err);
+            let structured = match error_format {
+                OutputFormat::Json => serde_json::to_string(&report).ok(),
+                OutputFormat::Yaml => serde_yaml::to_string(&report).ok(),
+                _ => None,
+            };
+            let text = structured.unwrap_or_else(|| format!("Error: {err:#}"));
+            let _ = writeln!(io::stderr(), "{}", text.trim_end());
+            ExitCode::from(error::exit_code(&err))
         }
     }
 }
 
-fn try_main() -> Result<()> {
-    // Normalize natural command variations before parsing
-    let normalized_args = natural_args::normalize_args();
-    let cli = Cli::parse_from(normalized_args);
-
+fn try_main(cli: Cli, command_name: &str) -> Result<()> {
     // If no command is provided, print help and exit
     let Some(command) = cli.command else {
         let _ = Cli::command().print_help();
@@ -55,44 +92,170 @@ fn try_main() -> Result<()> {
 
     let ctx = RuntimeContext::new(&cli.global)?;
     ctx.init_logging()?;
+    i18n::init(&ctx.config.output.language);
 
     log::debug!("Config loaded from: {:?}", ctx.config_path());
+    delay::wait(&ctx)?;
+
+    // Local commands never talk to the server, so they skip the HTTP dump and
+    // the async runtime; shell prompts and scripts call them constantly
+    let started = Instant::now();
+    let result = if is_local(&command) {
+        run_local(&ctx, command)
+    } else {
+        run_remote(&ctx, command)
+    };
+    output::flush_warnings();
+    if ctx.config.telemetry.enabled {
+        usage::record(command_name, started.elapsed(), &result);
+    }
+    result
+}
+
+/// Whether a command only reads and writes local files
+fn is_local(command: &Command) -> bool {
+    matches!(
+        command,
+        Command::Config { .. }
+            | Command::Cache {
+                command: CacheCommand::Path
+            }
+            | Command::History {
+                command: HistoryCommand::List { .. } | HistoryCommand::Path
+            }
+            | Command::Stats { .. }
+            | Command::Completions { .. }
+    )
+}
+
+/// Run a local command without an async runtime
+///
+/// Local commands never await, so their future finishes on the first poll.
+fn run_local(ctx: &RuntimeContext, command: Command) -> Result<()> {
+    run_command(ctx, command)
+        .now_or_never()
+        .unwrap_or_else(|| Err(anyhow!("Local command waited on I/O without a runtime")))
+}
 
+/// Run a command that may need the server, on a fresh async runtime
+fn run_remote(ctx: &RuntimeContext, command: Command) -> Result<()> {
+    dump::init(ctx)?;
     let runtime = tokio::runtime::Builder::new_current_thread()
         .enable_all()
         .build()?;
-
-    runtime.block_on(run_command(&ctx, command))
+    runtime.block_on(run_command(ctx, command))
 }
 
 async fn run_command(ctx: &RuntimeContext, command: Command) -> Result<()> {
     match command {
         Command::Info => commands::info::run(ctx).await,
+        Command::Status => commands::status::run(ctx).await,
         Command::Entity { command } => commands::entity::run(ctx, command).await,
+        Command::Suggest(cmd) => commands::suggest::run(ctx, cmd).await,
+        Command::Who(cmd) => commands::who::run(ctx, cmd).await,
+        Command::On(cmd) => commands::on::run(ctx, cmd).await,
+        Command::Pin { command } => commands::pin::run(ctx, command).await,
+        Command::Sensor { command } => commands::sensor::run(ctx, command).await,
         Command::Service { command } => commands::service::run(ctx, command).await,
         Command::Event { command } => commands::event::run(ctx, command).await,
+        Command::Logbook(cmd) => commands::logbook::run(ctx, cmd).await,
+        Command::Energy(cmd) => commands::energy::run(ctx, cmd).await,
+        Command::Say(cmd) => commands::say::run(ctx, cmd).await,
+        Command::Camera { command } => commands::camera::run(ctx, command).await,
+        Command::Calendar { command } => commands::calendar::run(ctx, command).await,
+        Command::Todo { command } => commands::todo::run(ctx, command).await,
+        Command::Notification { command } => commands::notification::run(ctx, command).await,
+        Command::Shopping { command } => commands::shopping::run(ctx, command).await,
         Command::Template(cmd) => commands::template::run(ctx, cmd).await,
+        Command::Dashboard { command } => commands::dashboard::run(ctx, command).await,
+        Command::Blueprint { command } => commands::blueprint::run(ctx, command).await,
         Command::Area { command } => commands::area::run(ctx, command).await,
+        Command::Label { command } => commands::label::run(ctx, command).await,
         Command::Device { command } => commands::device::run(ctx, command).await,
         Command::Config { command } => commands::config::run(ctx, command),
         Command::Cache { command } => commands::cache::execute(ctx, command).await,
         Command::Do(cmd) => commands::do_cmd::execute(ctx, cmd).await,
         Command::History { command } => commands::history::execute(ctx, command).await,
+        Command::Migrate { command } => commands::migrate::run(ctx, command),
+        Command::Stats { command } => commands::stats::run(ctx, command),
+        Command::Scene { command } => commands::scene::run(ctx, command).await,
+        Command::Automation { command } => commands::automation::run(ctx, command).await,
+        Command::Script { command } => commands::script::run(ctx, command).await,
+        Command::Update { command } => commands::update::run(ctx, command).await,
+        Command::Addon { command } => commands::addon::run(ctx, command).await,
+        Command::Watchdog(cmd) => commands::watchdog::run(ctx, cmd).await,
+        Command::Health(cmd) => commands::health::run(ctx, cmd).await,
+        Command::Reload(cmd) => commands::reload::run(ctx, cmd).await,
+        Command::System {
+            command: SystemCommand::Reload(cmd),
+        } => commands::reload::run(ctx, cmd).await,
+        Command::Core { command } => commands::core::run(ctx, command).await,
+        Command::Doctor { command } => commands::doctor::run(ctx, command).await,
+        Command::Bulk { command } => commands::bulk::run(ctx, command).await,
+        Command::Export { command } => commands::export::run(ctx, command).await,
+        Command::Auth { command } => commands::auth::run(ctx, command).await,
+        Command::Prompt(cmd) => commands::prompt::run(ctx, cmd).await,
+        Command::Light(cmd) => commands::quick::light(ctx, cmd).await,
+        Command::Switch(cmd) => commands::quick::run(ctx, &commands::quick::SWITCH, cmd).await,
+        Command::Cover(cmd) => commands::quick::cover(ctx, cmd).await,
+        Command::Climate(cmd) => commands::quick::climate(ctx, cmd).await,
+        Command::Select(cmd) => commands::quick::select(ctx, cmd).await,
+        Command::Number(cmd) => commands::quick::number(ctx, cmd).await,
+        Command::Text(cmd) => commands::quick::text(ctx, cmd).await,
+        Command::Press(cmd) => commands::quick::press(ctx, cmd).await,
+        Command::Remote(cmd) => commands::quick::remote(ctx, cmd).await,
+        Command::Siren(cmd) => commands::quick::siren(ctx, cmd).await,
+        Command::Fan(cmd) => commands::quick::run(ctx, &commands::quick::FAN, cmd).await,
+        Command::Valve(cmd) => commands::quick::run(ctx, &commands::quick::VALVE, cmd).await,
         Command::Completions { shell } => commands::completions::run(shell),
         Command::Agent(cmd) => {
             let client = api::HassClient::new(ctx)?;
             commands::agent::handle(&client, &cmd, ctx).await
         }
+        Command::Intent(cmd) => commands::intent::run(ctx, cmd).await,
+        Command::Integration { command } => commands::integration::run(ctx, command).await,
+        Command::Assist { command } => commands::assist::run(ctx, command).await,
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use clap::CommandFactory;
+    use clap::Parser;
 
     #[test]
     fn verify_cli() {
         Cli::command().debug_assert();
     }
+
+    #[test]
+    fn quick_command_flags_follow_words() {
+        let cli = Cli::try_parse_from([
+            "hmr",
+            "climate",
+            "bedroom",
+            "21",
+            "--mode",
+            "heat",
+            "--dry-run",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Command::Climate(cmd)) => {
+                assert_eq!(cmd.words, vec!["bedroom", "21"]);
+                assert_eq!(cmd.mode.as_deref(), Some("heat"));
+                assert!(cmd.dry_run);
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn number_command_accepts_negative_values() {
+        let cli = Cli::try_parse_from(["hmr", "number", "offset", "-1"]).unwrap();
+        match cli.command {
+            Some(Command::Number(cmd)) => assert_eq!(cmd.words, vec!["offset", "-1"]),
+            other => panic!("unexpected command: {other:?}"),
+        }
+    }
 }
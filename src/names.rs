@@ -0,0 +1,77 @@
+//! How entities are named in human output
+//!
+//! `--names friendly|id|both` picks whether tables and watch lines show an
+//! entity as "Kitchen Light", `light.kitchen`, or "Kitchen Light
+//! (light.kitchen)". Names come from the entity cache, so data fetched live is
+//! named the same way; an expired cache still serves, since names rarely
+//! change. Entities without a cached name keep their ID, and JSON and YAML
+//! output always carry IDs.
+
+use std::collections::HashMap;
+
+use crate::cache::cached_friendly_names;
+use crate::cli::NameStyle;
+use crate::config::RuntimeContext;
+
+#[derive(Debug, Clone)]
+pub struct EntityNames {
+    style: NameStyle,
+    friendly: HashMap<String, String>,
+}
+
+impl EntityNames {
+    /// Names in the style chosen with `--names` (IDs by default)
+    pub fn load(ctx: &RuntimeContext) -> Self {
+        let style = ctx.global.names.unwrap_or(NameStyle::Id);
+        let friendly = match style {
+            NameStyle::Id => HashMap::new(),
+            _ => cached_friendly_names(ctx.server_url().unwrap_or("")),
+        };
+        Self { style, friendly }
+    }
+
+    /// Whether entities are shown by their plain ID
+    pub fn is_id(&self) -> bool {
+        self.style == NameStyle::Id
+    }
+
+    /// How to show `entity_id`
+    pub fn display(&self, entity_id: &str) -> String {
+        match (self.style, self.friendly.get(entity_id)) {
+            (NameStyle::Friendly, Some(name)) => name.clone(),
+            (NameStyle::Both, Some(name)) => format!("{name} ({entity_id})"),
+            _ => entity_id.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let friendly = HashMap::from([("light.kitchen".to_string(), "Kitchen".to_string())]);
+        let names = |style| EntityNames {
+            style,
+            friendly: friendly.clone(),
+        };
+
+        assert_eq!(
+            names(NameStyle::Id).display("light.kitchen"),
+            "light.kitchen"
+        );
+        assert_eq!(
+            names(NameStyle::Friendly).display("light.kitchen"),
+            "Kitchen"
+        );
+        assert_eq!(
+            names(NameStyle::Both).display("light.kitchen"),
+            "Kitchen (light.kitchen)"
+        );
+        assert_eq!(
+            names(NameStyle::Friendly).display("light.hall"),
+            "light.hall"
+        );
+    }
+}
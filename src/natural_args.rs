@@ -37,24 +37,22 @@ const RESOURCES: &[&str] = &[
 /// - Reordering (list entities -> entity list)
 /// - Common aliases (show -> info, get -> list)
 pub fn normalize_args() -> Vec<String> {
-    let args: Vec<String> = env::args().collect();
-
-    // Need at least: program_name + command
-    if args.len() < 2 {
-        return args;
+    let mut args: Vec<String> = env::args().collect();
+
+    // Skip the program name; flags, quick commands and the like pass through
+    // untouched so the common case costs nothing
+    if args.len() >= 2 && needs_normalizing(&args[1]) {
+        let normalized = normalize_command(&args[1..]);
+        args.truncate(1);
+        args.extend(normalized);
     }
+    args
+}
 
-    // Skip the program name
-    let program_name = args[0].clone();
-    let rest = &args[1..];
-
-    // Normalize the command portion
-    let normalized = normalize_command(rest);
-
-    // Rebuild args
-    let mut result = vec![program_name];
-    result.extend(normalized);
-    result
+/// Only commands starting with an action verb or a resource name are rewritten
+fn needs_normalizing(first: &str) -> bool {
+    let lower = first.to_ascii_lowercase();
+    RESOURCES.contains(&lower.as_str()) || ACTIONS.contains(&normalize_action(&lower).as_str())
 }
 
 fn normalize_command(args: &[String]) -> Vec<String> {
@@ -151,6 +149,16 @@ mod tests {
         normalize_command(&args)
     }
 
+    #[test]
+    fn test_needs_normalizing() {
+        assert!(needs_normalizing("List"));
+        assert!(needs_normalizing("display"));
+        assert!(needs_normalizing("entities"));
+        assert!(!needs_normalizing("completions"));
+        assert!(!needs_normalizing("light"));
+        assert!(!needs_normalizing("--json"));
+    }
+
     #[test]
     fn test_list_entities_to_entity_list() {
         let result = normalize_test_args(&["list", "entities"]);
@@ -4,18 +4,20 @@
 //! - "turn on kitchen light" -> light.turn_on for light.kitchen
 //! - "set bedroom temperature to 72" -> climate.set_temperature for climate.bedroom
 //! - "dim living room lights to 50%" -> light.turn_on with brightness for light.living_room
+//! - "turn off everything labeled holiday" -> turn_off for every entity with that label
 //!
 //! Supports flexible argument order:
 //! - "turn on kitchen light"
 //! - "kitchen light on"
 //! - "on kitchen light"
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use anyhow::{anyhow, Result};
+use chrono::NaiveTime;
 use serde::{Deserialize, Serialize};
 
-use crate::cache::{Cache, CachedEntity};
+use crate::cache::{Cache, CachedArea, CachedEntity};
 use crate::fuzzy::{FuzzyMatcher, Match, MatchResult, MatchType};
 
 /// Action verbs and their mappings to Home Assistant services
@@ -38,6 +40,34 @@ impl ActionMapping {
             .copied()
             .unwrap_or(self.default_service)
     }
+
+    /// Domain assumed for actions that don't infer one from context
+    ///
+    /// Actions defined for a single domain (volume -> media_player) use that
+    /// domain; the rest (dim, brighten) are light actions.
+    pub fn implied_domain(&self) -> &'static str {
+        let mut domains = self.domain_overrides.keys();
+        match (domains.next(), domains.next()) {
+            (Some(domain), None) => domain,
+            _ => "light",
+        }
+    }
+}
+
+/// Pseudo-action that reports the targets' states instead of calling a service
+pub const STATUS_ACTION: &str = "status";
+
+/// Service data implied by the trigger word itself
+///
+/// "reverse" and "forward" share the `set_direction` mapping; the word picks the
+/// direction. "oscillate" turns oscillation on.
+fn implied_parameters(trigger: &str) -> Option<(&'static str, serde_json::Value)> {
+    match trigger.to_lowercase().as_str() {
+        "reverse" => Some(("direction", "reverse".into())),
+        "forward" => Some(("direction", "forward".into())),
+        "oscillate" | "swing" => Some(("oscillating", true.into())),
+        _ => None,
+    }
 }
 
 /// Get all known action mappings
@@ -115,6 +145,36 @@ pub fn action_mappings() -> Vec<ActionMapping> {
         infers_domain: true,
     });
 
+    // Irrigation ("water the garden"): switches turn on, valves open
+    let mut water_overrides = HashMap::new();
+    water_overrides.insert("valve", "open_valve");
+    mappings.push(ActionMapping {
+        trigger_words: vec!["water", "irrigate", "sprinkle"],
+        default_service: "turn_on",
+        domain_overrides: water_overrides,
+        infers_domain: true, // Targets are narrowed by is_irrigation_entity instead
+    });
+
+    // Fan direction ("reverse", "forward" become the direction parameter)
+    let mut direction_overrides = HashMap::new();
+    direction_overrides.insert("fan", "set_direction");
+    mappings.push(ActionMapping {
+        trigger_words: vec!["reverse", "forward"],
+        default_service: "set_direction",
+        domain_overrides: direction_overrides,
+        infers_domain: false, // Only fans have a direction
+    });
+
+    // Fan oscillation
+    let mut oscillate_overrides = HashMap::new();
+    oscillate_overrides.insert("fan", "oscillate");
+    mappings.push(ActionMapping {
+        trigger_words: vec!["oscillate", "swing"],
+        default_service: "oscillate",
+        domain_overrides: oscillate_overrides,
+        infers_domain: false,
+    });
+
     // Volume up
     let mut volume_up_overrides = HashMap::new();
     volume_up_overrides.insert("media_player", "volume_up");
@@ -224,6 +284,7 @@ impl From<Match<&CachedEntity>> for ParsedTarget {
 pub struct NLParser {
     matcher: FuzzyMatcher,
     actions: Vec<ActionMapping>,
+    default_action: Option<String>,
 }
 
 impl Default for NLParser {
@@ -237,9 +298,25 @@ impl NLParser {
         Self {
             matcher: FuzzyMatcher::new(),
             actions: action_mappings(),
+            default_action: None,
         }
     }
 
+    /// Prefer these (pinned) entities when a name matches several
+    pub fn with_pinned(mut self, pinned: HashSet<String>) -> Self {
+        self.matcher = self.matcher.with_pinned(pinned);
+        self
+    }
+
+    /// Set the action used when the input names only targets
+    ///
+    /// Without a default, such input is left without an action and
+    /// `to_service_call` falls back to `turn_on`.
+    pub fn with_default_action(mut self, action: impl Into<String>) -> Self {
+        self.default_action = Some(action.into());
+        self
+    }
+
     /// Parse a natural language command
     pub fn parse(&self, input: &str, cache: &Cache) -> Result<ParsedCommand> {
         let input = input.trim();
@@ -258,6 +335,16 @@ impl NLParser {
             return self.parse_service_based(input, &tokens, cache);
         }
 
+        // "for 10 minutes" is a run time, not an entity name or brightness
+        let (tokens, duration) = extract_duration(&tokens);
+        // "everything labeled holiday" targets by label, not by name
+        let (tokens, label) = extract_label(&tokens);
+        // "are any doors open" asks about states instead of changing them
+        let (tokens, question) = match extract_question(&tokens) {
+            Some((rest, state)) => (rest, Some(state)),
+            None => (tokens, None),
+        };
+
         let mut result = ParsedCommand {
             original: input.to_string(),
             action: None,
@@ -269,9 +356,23 @@ impl NLParser {
             matched_area: None,
         };
 
+        if let Some(secs) = duration {
+            result
+                .parameters
+                .insert("duration".to_string(), secs.into());
+        }
+        if let Some(state) = &question {
+            result.action = Some(STATUS_ACTION.to_string());
+            if let Some(state) = state {
+                result
+                    .parameters
+                    .insert("state".to_string(), state.clone().into());
+            }
+        }
+
         // First, extract action from tokens
         // Handle multi-word actions like "turn on", "turn off" by consuming "turn" with the action
-        let mut action_found = false;
+        let mut action_found = question.is_some();
         let mut action_mapping: Option<&ActionMapping> = None;
         let mut non_action_tokens: Vec<&str> = Vec::new();
 
@@ -298,13 +399,19 @@ impl NLParser {
                     let next_lower = next_token.to_lowercase();
                     if next_lower == "up" && !action_found {
                         result.action = Some("volume_up".to_string());
-                        action_mapping = self.actions.iter().find(|m| m.trigger_words.contains(&"volume_up"));
+                        action_mapping = self
+                            .actions
+                            .iter()
+                            .find(|m| m.trigger_words.contains(&"volume_up"));
                         action_found = true;
                         skip_next = true;
                         continue;
                     } else if next_lower == "down" && !action_found {
                         result.action = Some("volume_down".to_string());
-                        action_mapping = self.actions.iter().find(|m| m.trigger_words.contains(&"volume_down"));
+                        action_mapping = self
+                            .actions
+                            .iter()
+                            .find(|m| m.trigger_words.contains(&"volume_down"));
                         action_found = true;
                         skip_next = true;
                         continue;
@@ -314,6 +421,9 @@ impl NLParser {
 
             if let Some((action, mapping)) = self.find_action_with_mapping(token) {
                 if !action_found {
+                    if let Some((key, value)) = implied_parameters(token) {
+                        result.parameters.insert(key.to_string(), value);
+                    }
                     result.action = Some(action);
                     action_mapping = Some(mapping);
                     action_found = true;
@@ -329,6 +439,28 @@ impl NLParser {
             .as_ref()
             .is_some_and(|a| a.contains("volume") || a == "volume_set");
 
+        if let Some(label) = label {
+            let domain_hint = self.target_label(
+                &mut result,
+                &label,
+                &non_action_tokens,
+                action_mapping,
+                cache,
+            );
+            result.confidence = self.calculate_confidence(&result);
+            result.interpretation = self.build_interpretation(&result, &domain_hint, cache);
+            return Ok(result);
+        }
+
+        // "all the windows", "everything that's a heater": a device class, not a name
+        if let (rest, Some(group)) = extract_device_group(&non_action_tokens) {
+            if self.target_group(&mut result, group, &rest, cache) {
+                result.confidence = self.calculate_confidence(&result);
+                result.interpretation = self.build_interpretation(&result, &None, cache);
+                return Ok(result);
+            }
+        }
+
         // PRIORITY 1: Try to match all non-action tokens as a combined entity name first
         // This handles cases like "spots wohnzimmer" -> "spots.wohnzimmer" or "light.spots_wohnzimmer"
         // Filter out numeric/percentage tokens that are likely parameters, not entity names
@@ -361,7 +493,8 @@ impl NLParser {
                         }
                     }
                     result.confidence = self.calculate_confidence(&result);
-                    result.interpretation = self.build_interpretation(&result, &None);
+                    self.apply_default_action(&mut result);
+                    result.interpretation = self.build_interpretation(&result, &None, cache);
                     return Ok(result);
                 }
             }
@@ -411,8 +544,7 @@ impl NLParser {
 
         // PRIORITY 3: If we have remaining tokens AND an area, try combining them
         // e.g., "spots" + area "wohnzimmer" -> try "spots wohnzimmer", "spots_wohnzimmer"
-        if !remaining_tokens.is_empty() && area_hint.is_some() {
-            let area = area_hint.as_ref().unwrap();
+        if let Some(area) = area_hint.as_ref().filter(|_| !remaining_tokens.is_empty()) {
             let remaining_str = remaining_tokens.join(" ");
 
             // Try various combinations
@@ -503,12 +635,14 @@ impl NLParser {
         if domain_hint.is_none() {
             if let Some(mapping) = action_mapping {
                 if !mapping.infers_domain {
-                    // Actions like dim/brighten are light-specific
-                    domain_hint = Some("light".to_string());
+                    domain_hint = Some(mapping.implied_domain().to_string());
                 }
             }
         }
 
+        // "water the garden" should only reach valves and irrigation switches
+        let irrigation = action_mapping.is_some_and(|m| m.trigger_words.contains(&"water"));
+
         // If we have area + domain hints but no entities, find all matching
         if result.targets.is_empty() {
             if let Some(ref area) = area_hint {
@@ -518,6 +652,11 @@ impl NLParser {
                         .into_iter()
                         .filter(|e| &e.domain == domain)
                         .collect()
+                } else if irrigation {
+                    entities
+                        .into_iter()
+                        .filter(|e| is_irrigation_entity(e))
+                        .collect()
                 } else {
                     entities
                 };
@@ -533,10 +672,28 @@ impl NLParser {
             }
         }
 
+        // Irrigation without an area or name: all irrigation entities, if few
+        if irrigation && domain_hint.is_none() && result.targets.is_empty() {
+            let entities: Vec<_> = cache
+                .entities()
+                .iter()
+                .filter(|e| is_irrigation_entity(e))
+                .collect();
+            if entities.len() <= 15 {
+                for entity in entities.into_iter().take(10) {
+                    result.targets.push(ParsedTarget {
+                        entity_id: entity.entity_id.clone(),
+                        friendly_name: entity.friendly_name.clone(),
+                        match_type: "domain_match".to_string(),
+                        matched_input: "irrigation".to_string(),
+                    });
+                }
+            }
+        }
+
         // If we have a domain hint but no targets, get all in domain
         // This is a fallback when no specific entity was matched
-        if result.targets.is_empty() && domain_hint.is_some() {
-            let domain = domain_hint.as_ref().unwrap();
+        if let Some(domain) = domain_hint.as_ref().filter(|_| result.targets.is_empty()) {
             let entities = self.matcher.find_entities_in_domain(domain, cache);
             let entity_count = entities.len();
 
@@ -568,12 +725,130 @@ impl NLParser {
         // Calculate confidence
         result.confidence = self.calculate_confidence(&result);
 
+        // Bare target names get the configured default action. Confidence is
+        // computed first so an implied action doesn't count as a detected one.
+        self.apply_default_action(&mut result);
+
         // Build interpretation string
-        result.interpretation = self.build_interpretation(&result, &domain_hint);
+        result.interpretation = self.build_interpretation(&result, &domain_hint, cache);
 
         Ok(result)
     }
 
+    /// Target every entity carrying `label`, narrowed by a domain named in
+    /// `tokens` ("lights labeled holiday") or implied by the action ("dim")
+    ///
+    /// Returns the domain hint for the interpretation.
+    fn target_label(
+        &self,
+        result: &mut ParsedCommand,
+        label: &str,
+        tokens: &[&str],
+        action_mapping: Option<&ActionMapping>,
+        cache: &Cache,
+    ) -> Option<String> {
+        let domain_hint = tokens
+            .iter()
+            .find_map(|token| self.find_domain(token, cache))
+            .or_else(|| {
+                action_mapping
+                    .filter(|m| !m.infers_domain)
+                    .map(|m| m.implied_domain().to_string())
+            });
+        if let Some(pct) = tokens.iter().find_map(|token| parse_percentage(token)) {
+            let is_volume = result.action.as_ref().is_some_and(|a| a.contains("volume"));
+            let param_name = if is_volume {
+                "volume_pct"
+            } else {
+                "brightness_pct"
+            };
+            result.parameters.insert(param_name.to_string(), pct.into());
+        }
+
+        let Some(label_match) = self.matcher.find_label(label, cache).best() else {
+            result.notes.push(format!("No label matches '{label}'"));
+            return domain_hint;
+        };
+        let label = label_match.item;
+        for entity in cache.entities_with_label(&label.label_id) {
+            if domain_hint.as_ref().is_some_and(|d| *d != entity.domain) {
+                continue;
+            }
+            result.targets.push(ParsedTarget {
+                entity_id: entity.entity_id.clone(),
+                friendly_name: entity.friendly_name.clone(),
+                match_type: "label_match".to_string(),
+                matched_input: label.name.clone(),
+            });
+        }
+        if result.targets.is_empty() {
+            result
+                .notes
+                .push(format!("No entities are labeled '{}'", label.name));
+        }
+        domain_hint
+    }
+
+    /// Target every entity of a device group, in the area named by `tokens`
+    /// if any ("bedroom windows")
+    ///
+    /// Returns false, leaving `result` alone, when `tokens` has words that
+    /// aren't an area; the input is then matched by name as usual.
+    fn target_group(
+        &self,
+        result: &mut ParsedCommand,
+        group: &DeviceGroup,
+        tokens: &[&str],
+        cache: &Cache,
+    ) -> bool {
+        let area = if tokens.is_empty() {
+            None
+        } else {
+            match self.matcher.find_area(&tokens.join(" "), cache) {
+                MatchResult::Single(m) => Some(m.item),
+                _ => return false,
+            }
+        };
+        // Sensors can't be opened or switched; they only answer questions
+        let changes_state = result.action.as_deref() != Some(STATUS_ACTION);
+        let plural = format!("{}s", group.noun);
+        for entity in cache.entities() {
+            if !group.members.iter().any(|m| m.matches(entity))
+                || (changes_state && matches!(entity.domain.as_str(), "binary_sensor" | "sensor"))
+                || area.is_some_and(|a| entity.area_id.as_deref() != Some(&a.area_id))
+            {
+                continue;
+            }
+            result.targets.push(ParsedTarget {
+                entity_id: entity.entity_id.clone(),
+                friendly_name: entity.friendly_name.clone(),
+                match_type: "device_class_match".to_string(),
+                matched_input: plural.clone(),
+            });
+        }
+        if let Some(area) = area {
+            result.matched_area = Some(area.area_id.clone());
+        }
+        if result.targets.is_empty() {
+            let place = area.map(|a| format!(" in {}", a.name)).unwrap_or_default();
+            result.notes.push(format!("No {plural} found{place}"));
+            if cache.entities().iter().all(|e| e.device_class.is_none()) {
+                result.notes.push(
+                    "The cache has no device classes; refresh it with: hmr cache refresh"
+                        .to_string(),
+                );
+            }
+        }
+        true
+    }
+
+    fn apply_default_action(&self, result: &mut ParsedCommand) {
+        // Input with parameters ("kitchen 50%") keeps the implicit turn_on
+        if result.action.is_none() && result.parameters.is_empty() && !result.targets.is_empty() {
+            result.action = self.default_action.clone();
+        }
+    }
+
     fn find_action(&self, token: &str) -> Option<String> {
         self.find_action_with_mapping(token)
             .map(|(action, _)| action)
@@ -624,26 +899,65 @@ impl NLParser {
         score
     }
 
-    fn build_interpretation(&self, result: &ParsedCommand, domain_hint: &Option<String>) -> String {
+    /// What will be done, e.g. "turn_off 2 lights in Kitchen: Ceiling, Spots"
+    ///
+    /// The area is the matched one, or the one all targets share, so history
+    /// and prompts record where an action happened.
+    fn build_interpretation(
+        &self,
+        result: &ParsedCommand,
+        domain_hint: &Option<String>,
+        cache: &Cache,
+    ) -> String {
         let mut parts = Vec::new();
 
         if let Some(ref action) = result.action {
             parts.push(action.clone());
         }
 
-        if !result.targets.is_empty() {
-            let targets: Vec<String> = result
-                .targets
-                .iter()
-                .map(|t| {
-                    t.friendly_name
-                        .clone()
-                        .unwrap_or_else(|| t.entity_id.clone())
-                })
-                .collect();
-            parts.push(targets.join(", "));
-        } else if let Some(ref domain) = domain_hint {
-            parts.push(format!("all {domain}s"));
+        let names: Vec<String> = result
+            .targets
+            .iter()
+            .map(|t| {
+                t.friendly_name
+                    .clone()
+                    .unwrap_or_else(|| t.entity_id.clone())
+            })
+            .collect();
+        let area = target_area(result, cache).map(|area| match &area.floor_name {
+            Some(floor) => format!("in {} ({floor})", area.name),
+            None => format!("in {}", area.name),
+        });
+
+        match names.as_slice() {
+            [] => {
+                if let Some(ref domain) = domain_hint {
+                    parts.push(format!("all {}", plural(domain)));
+                }
+                parts.extend(area);
+            }
+            [name] => {
+                parts.push(name.clone());
+                parts.extend(area);
+            }
+            _ => {
+                let mut domains: Vec<&str> = result
+                    .targets
+                    .iter()
+                    .filter_map(|t| t.entity_id.split_once('.').map(|(d, _)| d))
+                    .collect();
+                domains.sort_unstable();
+                domains.dedup();
+                let noun = match domains.as_slice() {
+                    [domain] => plural(domain),
+                    _ => "entities".to_string(),
+                };
+                let summary = match area {
+                    Some(area) => format!("{} {noun} {area}", names.len()),
+                    None => format!("{} {noun}", names.len()),
+                };
+                parts.push(format!("{summary}: {}", names.join(", ")));
+            }
         }
 
         for (key, value) in &result.parameters {
@@ -807,6 +1121,350 @@ impl NLParser {
     }
 }
 
+/// Words that mark a switch as part of an irrigation system
+const IRRIGATION_WORDS: &[&str] = &["irrigat", "sprinkl", "water", "drip"];
+
+/// Valves, and switches whose names suggest irrigation
+fn is_irrigation_entity(entity: &CachedEntity) -> bool {
+    match entity.domain.as_str() {
+        "valve" => true,
+        "switch" => entity.search_names.iter().any(|name| {
+            let name = name.to_lowercase();
+            IRRIGATION_WORDS.iter().any(|word| name.contains(word))
+        }),
+        _ => false,
+    }
+}
+
+/// The matched area, or the area every target is in
+fn target_area<'a>(result: &ParsedCommand, cache: &'a Cache) -> Option<&'a CachedArea> {
+    if let Some(area) = result
+        .matched_area
+        .as_deref()
+        .and_then(|id| cache.get_area(id))
+    {
+        return Some(area);
+    }
+    let mut area_ids = result.targets.iter().map(|t| {
+        cache
+            .get_entity(&t.entity_id)
+            .and_then(|e| cache.entity_area_id(e))
+    });
+    let first = area_ids.next()??;
+    if area_ids.all(|id| id == Some(first)) {
+        cache.get_area(first)
+    } else {
+        None
+    }
+}
+
+/// "light" -> "lights", "switch" -> "switches"
+fn plural(domain: &str) -> String {
+    if domain.ends_with(['s', 'x']) || domain.ends_with("sh") || domain.ends_with("ch") {
+        format!("{domain}es")
+    } else {
+        format!("{domain}s")
+    }
+}
+
+/// Remove a run time ("10 minutes", "90s", "1h") from the tokens
+///
+/// Returns the remaining tokens and the duration in seconds.
+fn extract_duration<'a>(tokens: &[&'a str]) -> (Vec<&'a str>, Option<u64>) {
+    let unit_secs = |unit: &str| -> Option<u64> {
+        match unit.to_lowercase().as_str() {
+            "s" | "sec" | "secs" | "second" | "seconds" => Some(1),
+            "m" | "min" | "mins" | "minute" | "minutes" => Some(60),
+            "h" | "hr" | "hrs" | "hour" | "hours" => Some(3600),
+            "d" | "day" | "days" => Some(86400),
+            _ => None,
+        }
+    };
+
+    for (i, token) in tokens.iter().enumerate() {
+        // "10 minutes"
+        if let (Some(n), Some(unit)) = (parse_number(token), tokens.get(i + 1)) {
+            if let Some(secs) = unit_secs(unit).filter(|_| n > 0) {
+                let mut rest = tokens[..i].to_vec();
+                rest.extend_from_slice(&tokens[i + 2..]);
+                return (rest, Some(n as u64 * secs));
+            }
+        }
+        // "10min"
+        let split = token.find(|c: char| !c.is_ascii_digit()).unwrap_or(0);
+        if split > 0 {
+            let (number, unit) = token.split_at(split);
+            if let (Ok(n), Some(secs)) = (number.parse::<u64>(), unit_secs(unit)) {
+                if n > 0 {
+                    let mut rest = tokens[..i].to_vec();
+                    rest.extend_from_slice(&tokens[i + 1..]);
+                    return (rest, Some(n * secs));
+                }
+            }
+        }
+    }
+
+    (tokens.to_vec(), None)
+}
+
+/// Parse a whole phrase as a duration ("45m", "1h 30m", "2 hours and 10 minutes")
+/// into seconds; None if any part isn't a duration
+pub fn parse_duration_phrase(input: &str) -> Option<u64> {
+    let mut tokens = tokenize(input);
+    let mut total = 0;
+    while !tokens.is_empty() {
+        let (rest, secs) = extract_duration(&tokens);
+        if rest.len() == tokens.len() {
+            return None;
+        }
+        total += secs?;
+        tokens = rest;
+    }
+    (total > 0).then_some(total)
+}
+
+/// Parse a time of day: "07:30", "7:30pm", "7 am", "19:05:30", "noon", "midnight"
+pub fn parse_time_of_day(input: &str) -> Option<NaiveTime> {
+    let input: String = input
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    match input.as_str() {
+        "noon" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return NaiveTime::from_hms_opt(0, 0, 0),
+        _ => {}
+    }
+    let (clock, pm) = match input.strip_suffix("pm") {
+        Some(clock) => (clock, Some(true)),
+        None => match input.strip_suffix("am") {
+            Some(clock) => (clock, Some(false)),
+            None => (input.as_str(), None),
+        },
+    };
+    let mut parts = clock.split(':').map(|p| p.parse::<u32>().ok());
+    let hour = parts.next()??;
+    let minute = parts.next().unwrap_or(Some(0))?;
+    let second = parts.next().unwrap_or(Some(0))?;
+    if parts.next().is_some() {
+        return None;
+    }
+    let hour = match pm {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(true) => hour % 12 + 12,
+        Some(false) => hour % 12,
+        // A bare "7" is ambiguous enough to need a minute or am/pm
+        None if !clock.contains(':') => return None,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, second)
+}
+
+/// One kind of entity a [`DeviceGroup`] takes in
+#[derive(Debug, Clone, Copy)]
+enum GroupMember {
+    /// Entities of a domain with this device class
+    Class(&'static str, &'static str),
+    /// Every entity of a domain
+    Domain(&'static str),
+    /// Entities of a domain with this word in their name
+    Named(&'static str, &'static str),
+}
+
+impl GroupMember {
+    fn matches(&self, entity: &CachedEntity) -> bool {
+        match *self {
+            Self::Class(domain, class) => {
+                entity.domain == domain && entity.device_class.as_deref() == Some(class)
+            }
+            Self::Domain(domain) => entity.domain == domain,
+            Self::Named(domain, word) => {
+                entity.domain == domain
+                    && entity
+                        .search_names
+                        .iter()
+                        .any(|name| name.to_lowercase().contains(word))
+            }
+        }
+    }
+}
+
+/// The entities a common noun stands for ("windows", "every heater")
+#[derive(Debug)]
+struct DeviceGroup {
+    noun: &'static str,
+    members: &'static [GroupMember],
+}
+
+/// Device groups by noun; doors include garage doors, as people say
+const DEVICE_GROUPS: &[DeviceGroup] = &[
+    DeviceGroup {
+        noun: "window",
+        members: &[
+            GroupMember::Class("cover", "window"),
+            GroupMember::Class("binary_sensor", "window"),
+        ],
+    },
+    DeviceGroup {
+        noun: "door",
+        members: &[
+            GroupMember::Class("cover", "door"),
+            GroupMember::Class("cover", "garage"),
+            GroupMember::Class("binary_sensor", "door"),
+            GroupMember::Class("binary_sensor", "garage_door"),
+        ],
+    },
+    DeviceGroup {
+        noun: "garage",
+        members: &[
+            GroupMember::Class("cover", "garage"),
+            GroupMember::Class("binary_sensor", "garage_door"),
+        ],
+    },
+    DeviceGroup {
+        noun: "gate",
+        members: &[GroupMember::Class("cover", "gate")],
+    },
+    DeviceGroup {
+        noun: "blind",
+        members: &[GroupMember::Class("cover", "blind")],
+    },
+    DeviceGroup {
+        noun: "shade",
+        members: &[GroupMember::Class("cover", "shade")],
+    },
+    DeviceGroup {
+        noun: "shutter",
+        members: &[GroupMember::Class("cover", "shutter")],
+    },
+    DeviceGroup {
+        noun: "curtain",
+        members: &[GroupMember::Class("cover", "curtain")],
+    },
+    DeviceGroup {
+        noun: "awning",
+        members: &[GroupMember::Class("cover", "awning")],
+    },
+    DeviceGroup {
+        noun: "heater",
+        members: &[
+            GroupMember::Domain("climate"),
+            GroupMember::Named("switch", "heater"),
+        ],
+    },
+    DeviceGroup {
+        noun: "outlet",
+        members: &[GroupMember::Class("switch", "outlet")],
+    },
+    DeviceGroup {
+        noun: "plug",
+        members: &[GroupMember::Class("switch", "outlet")],
+    },
+    DeviceGroup {
+        noun: "tv",
+        members: &[GroupMember::Class("media_player", "tv")],
+    },
+    DeviceGroup {
+        noun: "speaker",
+        members: &[GroupMember::Class("media_player", "speaker")],
+    },
+];
+
+/// Words that make a singular noun mean the whole group ("every heater")
+const QUANTIFIERS: &[&str] = &["all", "any", "every", "everything", "each"];
+
+/// Words around a group noun that say nothing about the targets
+const GROUP_FILLERS: &[&str] = &["that's", "thats", "that", "which", "is", "are", "of"];
+
+/// Split off a device group noun when it names the group rather than one
+/// entity: in the plural ("windows") or with a quantifier ("every heater",
+/// "everything that's a heater"). Returns the tokens left for an area.
+fn extract_device_group<'a>(tokens: &[&'a str]) -> (Vec<&'a str>, Option<&'static DeviceGroup>) {
+    let lower: Vec<String> = tokens.iter().map(|t| t.to_lowercase()).collect();
+    let quantified = lower.iter().any(|t| QUANTIFIERS.contains(&t.as_str()));
+    let found = lower.iter().enumerate().find_map(|(i, word)| {
+        DEVICE_GROUPS
+            .iter()
+            .find(|group| {
+                word.strip_suffix('s') == Some(group.noun) || (quantified && word == group.noun)
+            })
+            .map(|group| (i, group))
+    });
+    let Some((index, group)) = found else {
+        return (tokens.to_vec(), None);
+    };
+    let rest = tokens
+        .iter()
+        .zip(&lower)
+        .enumerate()
+        .filter(|(i, (_, word))| {
+            *i != index
+                && !QUANTIFIERS.contains(&word.as_str())
+                && !GROUP_FILLERS.contains(&word.as_str())
+        })
+        .map(|(_, (token, _))| *token)
+        .collect();
+    (rest, Some(group))
+}
+
+/// States a question can ask about ("is the garage door open")
+const QUESTION_STATES: &[&str] = &[
+    "open", "closed", "on", "off", "locked", "unlocked", "home", "away",
+];
+
+/// Recognize a yes/no question ("is ...", "are ..."): returns the tokens
+/// after the question word, less the state asked about, and that state
+fn extract_question<'a>(tokens: &[&'a str]) -> Option<(Vec<&'a str>, Option<String>)> {
+    let first = tokens.first()?.to_lowercase();
+    if !matches!(first.as_str(), "is" | "are") {
+        return None;
+    }
+    let mut state = None;
+    let rest = tokens[1..]
+        .iter()
+        .filter(|token| {
+            let lower = token.to_lowercase();
+            if state.is_none() && QUESTION_STATES.contains(&lower.as_str()) {
+                state = Some(lower);
+                return false;
+            }
+            true
+        })
+        .copied()
+        .collect();
+    Some((rest, state))
+}
+
+/// Whether `input` targets by label ("... labeled holiday"), so the label
+/// registry is only fetched when it is needed
+pub fn mentions_label(input: &str) -> bool {
+    extract_label(&tokenize(input)).1.is_some()
+}
+
+/// Split off a label phrase: "labeled holiday", "tagged holiday", or
+/// "with label holiday". Everything after the keyword is the label name.
+///
+/// A bare "label" is part of a name ("label printer"), not a keyword.
+fn extract_label<'a>(tokens: &[&'a str]) -> (Vec<&'a str>, Option<String>) {
+    let keyword =
+        tokens
+            .iter()
+            .enumerate()
+            .position(|(i, token)| match token.to_lowercase().as_str() {
+                "labeled" | "labelled" | "tagged" => true,
+                "label" => i > 0 && tokens[i - 1].eq_ignore_ascii_case("with"),
+                _ => false,
+            });
+    let Some(i) = keyword.filter(|&i| i + 1 < tokens.len()) else {
+        return (tokens.to_vec(), None);
+    };
+    let mut rest = tokens[..i].to_vec();
+    if rest.last().is_some_and(|t| t.eq_ignore_ascii_case("with")) {
+        rest.pop();
+    }
+    (rest, Some(tokens[i + 1..].join(" ")))
+}
+
 /// Tokenize input into words, handling punctuation
 fn tokenize(input: &str) -> Vec<&str> {
     input
@@ -848,6 +1506,35 @@ pub struct ServiceCall {
     pub service: String,
     pub target: ServiceTarget,
     pub data: serde_json::Map<String, serde_json::Value>,
+    /// Run time in seconds; hmr reverses the call when it elapses
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<u64>,
+}
+
+impl ServiceCall {
+    /// The call that ends a timed run by undoing this one (close the valve
+    /// that was opened, turn back on the light that was turned off); None
+    /// when the service has no opposite
+    pub fn stop_call(&self) -> Option<ServiceCall> {
+        let service = match self.service.as_str() {
+            "turn_on" | "set_percentage" => "turn_off",
+            "turn_off" => "turn_on",
+            "open_valve" | "set_valve_position" => "close_valve",
+            "close_valve" => "open_valve",
+            "open_cover" | "set_cover_position" => "close_cover",
+            "close_cover" => "open_cover",
+            "lock" => "unlock",
+            "unlock" => "lock",
+            _ => return None,
+        };
+        Some(ServiceCall {
+            domain: self.domain.clone(),
+            service: service.to_string(),
+            target: self.target.clone(),
+            data: serde_json::Map::new(),
+            duration_secs: None,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -876,6 +1563,7 @@ const STANDARD_DOMAINS: &[&str] = &[
     "siren",
     "switch",
     "vacuum",
+    "valve",
     "water_heater",
 ];
 
@@ -921,6 +1609,26 @@ impl ParsedCommand {
         // Convert parameters
         for (key, value) in &self.parameters {
             match key.as_str() {
+                "brightness_pct" | "value" if domain == "fan" => {
+                    // Fan speed is a percentage
+                    data.insert("percentage".to_string(), value.clone());
+                }
+                "brightness_pct" | "value" if domain == "valve" => {
+                    // Valve opening is a percentage
+                    data.insert("position".to_string(), value.clone());
+                }
+                "brightness_pct" | "value" if domain == "cover" => {
+                    // So is a cover position ("blinds 30%")
+                    match value.as_u64() {
+                        Some(pct) if pct <= 100 => {
+                            data.insert("position".to_string(), pct.into());
+                        }
+                        _ => return Err(anyhow!("Cover position must be 0-100%, got {value}")),
+                    }
+                }
+                "duration" => {
+                    // Handled by hmr, not part of the service data
+                }
                 "brightness_pct" => {
                     // Convert percentage to 0-255 range
                     if let Some(pct) = value.as_i64() {
@@ -968,7 +1676,35 @@ impl ParsedCommand {
             }
         }
 
-        Ok(ServiceCall {
+        // A speed without another action means "set the speed"
+        let service_name =
+            if domain == "fan" && data.contains_key("percentage") && service_name == "turn_on" {
+                "set_percentage"
+            } else {
+                service_name
+            };
+
+        // Likewise a valve position ("garden valve 40%")
+        let service_name = if domain == "valve"
+            && data.contains_key("position")
+            && matches!(service_name, "turn_on" | "open_valve")
+        {
+            "set_valve_position"
+        } else {
+            service_name
+        };
+
+        // And a cover position ("open the blinds 30%")
+        let service_name = if domain == "cover"
+            && data.contains_key("position")
+            && matches!(service_name, "turn_on" | "open_cover" | "close_cover")
+        {
+            "set_cover_position"
+        } else {
+            service_name
+        };
+
+        let call = ServiceCall {
             domain,
             service: service_name.to_string(),
             target: ServiceTarget {
@@ -976,14 +1712,23 @@ impl ParsedCommand {
                 area_id: None,
             },
             data,
-        })
+            duration_secs: self.parameters.get("duration").and_then(|v| v.as_u64()),
+        };
+        if call.duration_secs.is_some() && call.stop_call().is_none() {
+            return Err(anyhow!(
+                "{}.{} can't run for a duration: there is no service that undoes it",
+                call.domain,
+                call.service
+            ));
+        }
+        Ok(call)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::cache::{Cache, CacheFile, CachedArea, CachedEntity};
+    use crate::cache::{Cache, CacheFile, CachedArea, CachedEntity, CachedLabel};
 
     fn create_test_cache() -> Cache {
         let mut cache = Cache::new();
@@ -991,10 +1736,6 @@ mod tests {
         // Add test entities
         let entities = vec![
             CachedEntity {
-                entity_id: "light.kitchen".to_string(),
-                domain: "light".to_string(),
-                object_id: "kitchen".to_string(),
-                state: "on".to_string(),
                 friendly_name: Some("Kitchen Light".to_string()),
                 area_id: Some("kitchen".to_string()),
                 search_names: vec![
@@ -1004,12 +1745,9 @@ mod tests {
                     "kitchen light".to_string(),
                     "kitchen_light".to_string(),
                 ],
+                ..CachedEntity::for_test("light.kitchen", "on")
             },
             CachedEntity {
-                entity_id: "light.living_room".to_string(),
-                domain: "light".to_string(),
-                object_id: "living_room".to_string(),
-                state: "off".to_string(),
                 friendly_name: Some("Living Room Light".to_string()),
                 area_id: Some("living_room".to_string()),
                 search_names: vec![
@@ -1018,12 +1756,9 @@ mod tests {
                     "Living Room Light".to_string(),
                     "living room light".to_string(),
                 ],
+                ..CachedEntity::for_test("light.living_room", "off")
             },
             CachedEntity {
-                entity_id: "switch.bedroom_fan".to_string(),
-                domain: "switch".to_string(),
-                object_id: "bedroom_fan".to_string(),
-                state: "off".to_string(),
                 friendly_name: Some("Bedroom Fan".to_string()),
                 area_id: Some("bedroom".to_string()),
                 search_names: vec![
@@ -1032,6 +1767,7 @@ mod tests {
                     "Bedroom Fan".to_string(),
                     "bedroom fan".to_string(),
                 ],
+                ..CachedEntity::for_test("switch.bedroom_fan", "off")
             },
         ];
 
@@ -1045,6 +1781,8 @@ mod tests {
                 name: "Kitchen".to_string(),
                 aliases: vec![],
                 search_names: vec!["kitchen".to_string(), "Kitchen".to_string()],
+                floor_id: Some("ground_floor".to_string()),
+                floor_name: Some("Ground Floor".to_string()),
             },
             CachedArea {
                 area_id: "living_room".to_string(),
@@ -1055,6 +1793,8 @@ mod tests {
                     "Living Room".to_string(),
                     "living room".to_string(),
                 ],
+                floor_id: None,
+                floor_name: None,
             },
         ];
 
@@ -1064,6 +1804,107 @@ mod tests {
         cache
     }
 
+    #[test]
+    fn test_parse_device_groups() {
+        let mut cache = create_test_cache();
+        for (entity_id, device_class, area) in [
+            ("cover.kitchen_window", Some("window"), "kitchen"),
+            ("cover.living_room_window", Some("window"), "living_room"),
+            (
+                "binary_sensor.living_room_window",
+                Some("window"),
+                "living_room",
+            ),
+            ("binary_sensor.front_door", Some("door"), "living_room"),
+            ("climate.living_room", None, "living_room"),
+        ] {
+            let (_, object_id) = entity_id.split_once('.').unwrap();
+            cache.upsert_entity(CachedEntity {
+                area_id: Some(area.to_string()),
+                device_class: device_class.map(str::to_string),
+                search_names: vec![entity_id.to_string(), object_id.to_string()],
+                ..CachedEntity::for_test(entity_id, "closed")
+            });
+        }
+        let parser = NLParser::new();
+        let ids = |result: &ParsedCommand| -> Vec<String> {
+            result.targets.iter().map(|t| t.entity_id.clone()).collect()
+        };
+
+        // Sensors can't be closed, so only the covers are targeted
+        let result = parser.parse("close all the windows", &cache).unwrap();
+        assert_eq!(result.action.as_deref(), Some("close_cover"));
+        assert_eq!(
+            ids(&result),
+            vec!["cover.kitchen_window", "cover.living_room_window"]
+        );
+
+        let result = parser
+            .parse("close the living room windows", &cache)
+            .unwrap();
+        assert_eq!(ids(&result), vec!["cover.living_room_window"]);
+
+        let result = parser
+            .parse("turn off everything that's a heater", &cache)
+            .unwrap();
+        assert_eq!(result.action.as_deref(), Some("turn_off"));
+        assert_eq!(ids(&result), vec!["climate.living_room"]);
+
+        // Questions report states, sensors included
+        let result = parser.parse("are any doors open", &cache).unwrap();
+        assert_eq!(result.action.as_deref(), Some(STATUS_ACTION));
+        assert_eq!(result.parameters["state"], "open");
+        assert_eq!(ids(&result), vec!["binary_sensor.front_door"]);
+    }
+
+    #[test]
+    fn test_parse_label_targets() {
+        let mut cache = create_test_cache();
+        for entity_id in ["light.kitchen", "switch.bedroom_fan"] {
+            let mut entity = cache.get_entity(entity_id).unwrap().clone();
+            entity.labels = vec!["holiday".to_string()];
+            cache.upsert_entity(entity);
+        }
+        let label = crate::websocket::Label {
+            label_id: "holiday".to_string(),
+            name: "Holiday".to_string(),
+            icon: None,
+            color: None,
+            description: None,
+        };
+        cache.set_labels(CacheFile::new(
+            vec![CachedLabel::from(&label)],
+            3600,
+            "http://localhost:8123".to_string(),
+        ));
+
+        let parser = NLParser::new();
+        let result = parser
+            .parse("turn off everything labeled holiday", &cache)
+            .unwrap();
+        assert_eq!(result.action.as_deref(), Some("turn_off"));
+        let ids: Vec<&str> = result
+            .targets
+            .iter()
+            .map(|t| t.entity_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["light.kitchen", "switch.bedroom_fan"]);
+
+        let result = parser
+            .parse("turn on lights with label Holliday", &cache)
+            .unwrap();
+        let ids: Vec<&str> = result
+            .targets
+            .iter()
+            .map(|t| t.entity_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["light.kitchen"]);
+
+        // Without "with", "label" is part of a name
+        assert!(!mentions_label("turn on label printer"));
+        assert!(mentions_label("turn on lights with label holiday"));
+    }
+
     #[test]
     fn test_tokenize() {
         assert_eq!(
@@ -1172,6 +2013,33 @@ mod tests {
         assert!(!parser.actions.is_empty());
     }
 
+    #[test]
+    fn test_interpretation_names_area_and_count() {
+        let cache = create_test_cache();
+        let parser = NLParser::new();
+
+        let result = parser.parse("turn off kitchen light", &cache).unwrap();
+        assert_eq!(
+            result.interpretation,
+            "turn_off Kitchen Light in Kitchen (Ground Floor)"
+        );
+
+        let mut result = result;
+        result.targets.push(ParsedTarget {
+            entity_id: "light.living_room".to_string(),
+            friendly_name: Some("Living Room Light".to_string()),
+            match_type: "Exact".to_string(),
+            matched_input: "lights".to_string(),
+        });
+        assert_eq!(
+            parser.build_interpretation(&result, &None, &cache),
+            "turn_off 2 lights: Kitchen Light, Living Room Light"
+        );
+
+        assert_eq!(plural("switch"), "switches");
+        assert_eq!(plural("cover"), "covers");
+    }
+
     #[test]
     fn test_parse_simple_command() {
         let cache = create_test_cache();
@@ -1201,6 +2069,185 @@ mod tests {
         assert_eq!(result.parameters["brightness_pct"], 50);
     }
 
+    #[test]
+    fn test_parse_bare_name_uses_default_action() {
+        let cache = create_test_cache();
+
+        let result = NLParser::new().parse("kitchen light", &cache).unwrap();
+        assert_eq!(result.action, None);
+
+        let parser = NLParser::new().with_default_action("toggle");
+        let result = parser.parse("kitchen light", &cache).unwrap();
+        assert_eq!(result.action, Some("toggle".to_string()));
+        assert_eq!(result.to_service_call().unwrap().service, "toggle");
+
+        // Explicit actions and parameters are not overridden
+        let result = parser.parse("turn off kitchen", &cache).unwrap();
+        assert_eq!(result.action, Some("turn_off".to_string()));
+        let result = parser.parse("kitchen 50%", &cache).unwrap();
+        assert_eq!(result.action, None);
+    }
+
+    #[test]
+    fn test_fan_percentage_uses_set_percentage() {
+        let mut params = HashMap::new();
+        params.insert("brightness_pct".to_string(), serde_json::json!(40));
+        let cmd = ParsedCommand {
+            original: "bedroom fan 40%".to_string(),
+            action: None,
+            targets: vec![ParsedTarget {
+                entity_id: "fan.bedroom".to_string(),
+                friendly_name: None,
+                match_type: "Exact".to_string(),
+                matched_input: "bedroom fan".to_string(),
+            }],
+            parameters: params,
+            confidence: 1.0,
+            interpretation: String::new(),
+            notes: Vec::new(),
+            matched_area: None,
+        };
+
+        let call = cmd.to_service_call().unwrap();
+        assert_eq!(call.service, "set_percentage");
+        assert_eq!(call.data["percentage"], 40);
+        assert!(!call.data.contains_key("brightness"));
+    }
+
+    #[test]
+    fn test_extract_duration() {
+        let (rest, secs) = extract_duration(&["start", "irrigation", "10", "minutes"]);
+        assert_eq!(rest, vec!["start", "irrigation"]);
+        assert_eq!(secs, Some(600));
+
+        let (rest, secs) = extract_duration(&["water", "garden", "90s"]);
+        assert_eq!(rest, vec!["water", "garden"]);
+        assert_eq!(secs, Some(90));
+
+        let (rest, secs) = extract_duration(&["kitchen", "50"]);
+        assert_eq!(rest, vec!["kitchen", "50"]);
+        assert_eq!(secs, None);
+    }
+
+    #[test]
+    fn test_parse_time_phrases() {
+        assert_eq!(parse_duration_phrase("45m"), Some(2700));
+        assert_eq!(parse_duration_phrase("1h 30m"), Some(5400));
+        assert_eq!(parse_duration_phrase("2 hours and 10 minutes"), Some(7800));
+        assert_eq!(parse_duration_phrase("soon"), None);
+        assert_eq!(parse_duration_phrase("10m later"), None);
+
+        let time = |h, m| NaiveTime::from_hms_opt(h, m, 0);
+        assert_eq!(parse_time_of_day("07:30"), time(7, 30));
+        assert_eq!(parse_time_of_day("7:30 PM"), time(19, 30));
+        assert_eq!(parse_time_of_day("12am"), time(0, 0));
+        assert_eq!(parse_time_of_day("noon"), time(12, 0));
+        assert_eq!(parse_time_of_day("7"), None);
+        assert_eq!(parse_time_of_day("25:00"), None);
+        assert_eq!(parse_time_of_day("13pm"), None);
+    }
+
+    #[test]
+    fn test_cover_position() {
+        let cmd = |value: i64| {
+            let mut params = HashMap::new();
+            params.insert("brightness_pct".to_string(), serde_json::json!(value));
+            ParsedCommand {
+                original: "open blinds".to_string(),
+                action: Some("open_cover".to_string()),
+                targets: vec![ParsedTarget {
+                    entity_id: "cover.blinds".to_string(),
+                    friendly_name: None,
+                    match_type: "Exact".to_string(),
+                    matched_input: "blinds".to_string(),
+                }],
+                parameters: params,
+                confidence: 1.0,
+                interpretation: String::new(),
+                notes: Vec::new(),
+                matched_area: None,
+            }
+        };
+
+        let call = cmd(30).to_service_call().unwrap();
+        assert_eq!(call.service, "set_cover_position");
+        assert_eq!(call.data["position"], 30);
+        assert!(cmd(130).to_service_call().is_err());
+    }
+
+    #[test]
+    fn test_valve_position_and_duration() {
+        let mut params = HashMap::new();
+        params.insert("brightness_pct".to_string(), serde_json::json!(40));
+        params.insert("duration".to_string(), serde_json::json!(600));
+        let cmd = ParsedCommand {
+            original: "open garden valve 40% 10 minutes".to_string(),
+            action: Some("open_cover".to_string()),
+            targets: vec![ParsedTarget {
+                entity_id: "valve.garden".to_string(),
+                friendly_name: None,
+                match_type: "Exact".to_string(),
+                matched_input: "garden valve".to_string(),
+            }],
+            parameters: params,
+            confidence: 1.0,
+            interpretation: String::new(),
+            notes: Vec::new(),
+            matched_area: None,
+        };
+
+        let call = cmd.to_service_call().unwrap();
+        assert_eq!(call.service, "set_valve_position");
+        assert_eq!(call.data["position"], 40);
+        assert!(!call.data.contains_key("duration"));
+        assert_eq!(call.duration_secs, Some(600));
+        assert_eq!(call.stop_call().unwrap().service, "close_valve");
+
+        // A timed "off" ends by turning back on, not off again
+        let off = ParsedCommand {
+            original: "turn off the porch light for 10 minutes".to_string(),
+            action: Some("turn_off".to_string()),
+            targets: vec![ParsedTarget {
+                entity_id: "light.porch".to_string(),
+                friendly_name: None,
+                match_type: "Exact".to_string(),
+                matched_input: "porch light".to_string(),
+            }],
+            ..cmd.clone()
+        };
+        let call = off.to_service_call().unwrap();
+        assert_eq!(call.service, "turn_off");
+        assert_eq!(call.stop_call().unwrap().service, "turn_on");
+
+        let toggle = ParsedCommand {
+            action: Some("toggle".to_string()),
+            ..off
+        };
+        assert!(toggle.to_service_call().is_err());
+    }
+
+    #[test]
+    fn test_implied_domain() {
+        let mappings = action_mappings();
+        let find = |word: &str| mappings.iter().find(|m| m.trigger_words.contains(&word));
+        assert_eq!(find("dim").unwrap().implied_domain(), "light");
+        assert_eq!(find("louder").unwrap().implied_domain(), "media_player");
+        assert_eq!(find("oscillate").unwrap().implied_domain(), "fan");
+    }
+
+    #[test]
+    fn test_implied_parameters() {
+        assert_eq!(
+            implied_parameters("Reverse"),
+            Some(("direction", serde_json::json!("reverse")))
+        );
+        assert_eq!(
+            implied_parameters("oscillate"),
+            Some(("oscillating", serde_json::json!(true)))
+        );
+        assert_eq!(implied_parameters("on"), None);
+    }
+
     #[test]
     fn test_parse_empty_command() {
         let cache = create_test_cache();
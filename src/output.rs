@@ -1,8 +1,25 @@
 //! Output formatting utilities
 //!
-//! Handles JSON, YAML, and table output formats, as well as stdin piping support.
+//! Handles JSON, YAML, table, and porcelain output formats, as well as stdin piping support.
+//!
+//! Results go to stdout; warnings and notes go through [`warn`] so they never
+//! contaminate machine-readable output.
+//!
+//! The `compact` output profile (`[output] profile`, picked automatically on
+//! terminals narrower than 80 columns) fits tables to small screens such as
+//! phone SSH clients: borders go, an entity ID column drops the domain all its
+//! rows share, columns are dropped until the table fits (secondary ones from
+//! the right first, entity ID and state last), and what still doesn't fit
+//! wraps.
+//!
+//! Other tables are fitted too, so one long friendly name can't blow out the
+//! layout: columns are capped at `[output] max_column_width`, then the widest
+//! columns shrink until the table fits the terminal. Cells that don't fit are
+//! cut with "…", or wrapped with `[output] wrap`. `--full` turns all of this off.
 
+use std::borrow::Cow;
 use std::io::{IsTerminal, Read};
+use std::sync::Mutex;
 
 use anyhow::{Context, Result};
 use serde::Serialize;
@@ -10,6 +27,62 @@ use tabled::{settings::Style, Table, Tabled};
 
 use crate::cli::OutputFormat;
 use crate::config::RuntimeContext;
+use crate::names::EntityNames;
+
+/// Terminal width below which the "auto" profile turns compact
+const COMPACT_BELOW: usize = 80;
+
+/// Width compact tables fit to when the terminal size is unknown
+const COMPACT_WIDTH: usize = 60;
+
+/// Columns a compact table keeps however narrow the terminal is
+const MIN_COLUMNS: usize = 2;
+
+/// Columns a compact table drops last, the most important first
+const KEY_COLUMNS: &[&str] = &["entity_id", "state"];
+
+/// Marks a cell that was cut to fit
+const ELLIPSIS: &str = "…";
+
+/// Warnings collected in machine output mode, emitted by [`flush_warnings`]
+static WARNINGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Report a non-fatal warning or note (typo corrections, ambiguous matches, ...).
+///
+/// Warnings never go to stdout. For human output they are printed to stderr
+/// immediately; in machine mode they are collected and emitted as a single
+/// `{"warnings": [...]}` JSON object on stderr when the command finishes.
+/// Suppressed entirely with `--quiet`.
+pub fn warn(ctx: &RuntimeContext, message: impl Into<String>) {
+    if ctx.global.quiet {
+        return;
+    }
+
+    let message = message.into();
+    if ctx.is_machine_output() {
+        WARNINGS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(message);
+    } else {
+        eprintln!("{message}");
+    }
+}
+
+/// Emit warnings collected in machine mode. Called once when the command finishes.
+pub fn flush_warnings() {
+    let warnings = std::mem::take(&mut *WARNINGS.lock().unwrap_or_else(|e| e.into_inner()));
+    if let Some(line) = format_warnings(&warnings) {
+        eprintln!("{line}");
+    }
+}
+
+fn format_warnings(warnings: &[String]) -> Option<String> {
+    if warnings.is_empty() {
+        return None;
+    }
+    Some(serde_json::json!({ "warnings": warnings }).to_string())
+}
 
 /// Format and print data according to the configured output format
 pub fn print_output<T: Serialize>(ctx: &RuntimeContext, data: &T) -> Result<()> {
@@ -51,6 +124,17 @@ pub fn format_output<T: Serialize>(ctx: &RuntimeContext, data: &T) -> Result<Str
 /// - YAML: outputs YAML
 /// - Table/Auto: outputs a formatted table (or JSON when piped with Auto)
 pub fn print_table<T: Tabled + Serialize>(ctx: &RuntimeContext, items: &[T]) -> Result<()> {
+    print_table_without(ctx, items, &[])
+}
+
+/// Print a table like [`print_table`], leaving out the columns named in `hidden`.
+///
+/// For rows whose optional columns are chosen at runtime (e.g., `--columns`).
+pub fn print_table_without<T: Tabled + Serialize>(
+    ctx: &RuntimeContext,
+    items: &[T],
+    hidden: &[&str],
+) -> Result<()> {
     let format = ctx.output_format();
     let is_tty = std::io::stdout().is_terminal();
 
@@ -71,8 +155,11 @@ pub fn print_table<T: Tabled + Serialize>(ctx: &RuntimeContext, items: &[T]) ->
             // When piped with auto, use compact JSON
             println!("{}", serde_json::to_string(items)?);
         }
+        OutputFormat::Table | OutputFormat::Auto if ctx.global.plain => {
+            println!("{}", plain_records(items, hidden));
+        }
         OutputFormat::Table | OutputFormat::Auto => {
-            let table = build_table(ctx, items);
+            let table = build_table(ctx, items, hidden);
             println!("{table}");
         }
     }
@@ -80,9 +167,134 @@ pub fn print_table<T: Tabled + Serialize>(ctx: &RuntimeContext, items: &[T]) ->
     Ok(())
 }
 
-fn build_table<T: Tabled + Serialize>(ctx: &RuntimeContext, items: &[T]) -> Table {
+/// Columns to leave out of a table given the `--columns` selection.
+///
+/// Without a selection every column not in `defaults` is hidden. Unknown column
+/// names are an error listing the available ones.
+pub fn hidden_columns(
+    selection: Option<&str>,
+    all: &[&'static str],
+    defaults: &[&str],
+) -> Result<Vec<&'static str>> {
+    let Some(selection) = selection else {
+        return Ok(all
+            .iter()
+            .copied()
+            .filter(|c| !defaults.contains(c))
+            .collect());
+    };
+
+    let selected: Vec<&str> = selection
+        .split(',')
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .collect();
+    if let Some(unknown) = selected.iter().find(|c| !all.contains(c)) {
+        anyhow::bail!("Unknown column '{unknown}'; available: {}", all.join(", "));
+    }
+    Ok(all
+        .iter()
+        .copied()
+        .filter(|c| !selected.contains(c))
+        .collect())
+}
+
+/// Render rows as a table, or as labeled records with `--plain`.
+///
+/// For human output that builds its own text around a table.
+pub fn render_rows<T: Tabled>(ctx: &RuntimeContext, rows: &[T]) -> String {
+    if ctx.global.plain {
+        plain_records(rows, &[])
+    } else {
+        Table::new(rows).to_string()
+    }
+}
+
+/// Rows as `label: value` lines with a blank line between rows (`--plain`).
+///
+/// Screen readers read these naturally, unlike box-drawing tables: labels have
+/// underscores replaced with spaces and empty values are left out.
+fn plain_records<T: Tabled>(items: &[T], hidden: &[&str]) -> String {
+    let headers = T::headers();
+    let records: Vec<String> = items
+        .iter()
+        .map(|item| {
+            headers
+                .iter()
+                .zip(item.fields())
+                .filter(|(header, value)| {
+                    !hidden.contains(&header.as_ref()) && !value.trim().is_empty()
+                })
+                .map(|(header, value)| {
+                    format!("{}: {}", header.replace('_', " "), plain_text(&value))
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect();
+    records.join("\n\n")
+}
+
+/// Drop emoji and drawing symbols from text meant for `--plain` output
+pub fn plain_text(text: &str) -> String {
+    let is_symbol = |c: char| {
+        matches!(
+            c as u32,
+            0x2190..=0x21FF     // arrows
+            | 0x2500..=0x259F   // box drawing and block elements
+            | 0x25A0..=0x27BF   // geometric shapes, dingbats, check marks
+            | 0x2B00..=0x2BFF   // more arrows and stars
+            | 0x1F000..=0x1FAFF // emoji and pictographs
+            | 0xFE0F | 0x200D   // emoji presentation and joiners
+        )
+    };
+    let stripped: String = text.chars().filter(|c| !is_symbol(*c)).collect();
+    stripped.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Width of the terminal stdout is attached to (`COLUMNS` wins when set)
+pub fn terminal_width() -> Option<usize> {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|c| c.parse().ok())
+        .filter(|&c| c > 0)
+        .or_else(|| {
+            console::Term::stdout()
+                .size_checked()
+                .map(|(_, cols)| usize::from(cols))
+        })
+}
+
+/// Whether tables use the compact profile
+pub fn is_compact(ctx: &RuntimeContext) -> bool {
+    match ctx.config.output.profile.as_str() {
+        "compact" => true,
+        "auto" => terminal_width().is_some_and(|w| w < COMPACT_BELOW),
+        _ => false,
+    }
+}
+
+fn build_table<T: Tabled + Serialize>(ctx: &RuntimeContext, items: &[T], hidden: &[&str]) -> Table {
     let mut table = Table::new(items);
-    table.with(Style::sharp());
+    let compact = is_compact(ctx);
+    let names = EntityNames::load(ctx);
+    if compact {
+        table.with(Style::blank());
+    } else {
+        table.with(Style::sharp());
+    }
+    if !names.is_id() {
+        name_entities::<T>(&mut table, names);
+    } else if compact {
+        shorten_entity_ids(&mut table, items);
+    }
+
+    // Columns are located by header, so this must happen before headers are removed
+    for name in hidden {
+        table.with(tabled::settings::Remove::column(
+            tabled::settings::location::ByColumnName::new(*name),
+        ));
+    }
 
     if ctx.global.no_headers || ctx.config.output.no_headers {
         table.with(tabled::settings::Remove::row(
@@ -90,9 +302,163 @@ fn build_table<T: Tabled + Serialize>(ctx: &RuntimeContext, items: &[T]) -> Tabl
         ));
     }
 
+    if ctx.global.full {
+        return table;
+    }
+    let output = &ctx.config.output;
+    limit_columns(&mut table, output.max_column_width, output.wrap);
+    let width = terminal_width();
+    if compact {
+        let columns = T::headers()
+            .iter()
+            .filter(|h| !hidden.contains(&h.as_ref()))
+            .map(|h| h.to_string())
+            .collect();
+        fit_width(&mut table, columns, width.unwrap_or(COMPACT_WIDTH));
+    } else if let Some(width) = width {
+        shrink_to(&mut table, width, output.wrap);
+    }
     table
 }
 
+/// Cap every column at `max` characters (0: no cap)
+fn limit_columns(table: &mut Table, max: usize, wrap: bool) {
+    if max == 0 {
+        return;
+    }
+    let cells = tabled::settings::Modify::new(tabled::settings::object::Segment::all());
+    if wrap {
+        table.with(cells.with(tabled::settings::Width::wrap(max).keep_words(true)));
+    } else {
+        table.with(cells.with(tabled::settings::Width::truncate(max).suffix(ELLIPSIS)));
+    }
+}
+
+/// Narrow the widest columns until the table fits `width`
+fn shrink_to(table: &mut Table, width: usize, wrap: bool) {
+    if table.total_width() <= width {
+        return;
+    }
+    let widest_first = tabled::settings::peaker::Priority::max(true);
+    if wrap {
+        table.with(
+            tabled::settings::Width::wrap(width)
+                .keep_words(true)
+                .priority(widest_first),
+        );
+    } else {
+        table.with(
+            tabled::settings::Width::truncate(width)
+                .suffix(ELLIPSIS)
+                .priority(widest_first),
+        );
+    }
+}
+
+/// Show the `entity_id` column in the `--names` style
+fn name_entities<T: Tabled>(table: &mut Table, names: EntityNames) {
+    let Some(column) = T::headers().iter().position(|h| h == "entity_id") else {
+        return;
+    };
+    table.with(
+        // The header isn't an entity ID, so it keeps its text
+        tabled::settings::Modify::new(tabled::settings::object::Columns::single(column)).with(
+            tabled::settings::Format::content(move |cell| names.display(cell)),
+        ),
+    );
+}
+
+/// Drop the domain from an `entity_id` column when every row shares it
+fn shorten_entity_ids<T: Tabled>(table: &mut Table, items: &[T]) {
+    let Some(column) = T::headers().iter().position(|h| h == "entity_id") else {
+        return;
+    };
+    let ids: Vec<String> = items
+        .iter()
+        .filter_map(|item| item.fields().into_iter().nth(column).map(Cow::into_owned))
+        .collect();
+    let Some(prefix) = shared_domain(&ids) else {
+        return;
+    };
+    table.with(
+        tabled::settings::Modify::new(tabled::settings::object::Columns::single(column)).with(
+            tabled::settings::Format::content(move |cell| {
+                cell.strip_prefix(&prefix).unwrap_or(cell).to_string()
+            }),
+        ),
+    );
+}
+
+/// "light." when every ID is a light
+fn shared_domain(ids: &[String]) -> Option<String> {
+    let (domain, _) = ids.first()?.split_once('.')?;
+    let prefix = format!("{domain}.");
+    ids.iter()
+        .all(|id| id.starts_with(&prefix))
+        .then_some(prefix)
+}
+
+/// Drop columns until the table fits `width`, then wrap cells of what remains
+///
+/// `columns` names the table's columns. Secondary columns go first, from the
+/// right (the least important by convention); the [`KEY_COLUMNS`] go last.
+fn fit_width(table: &mut Table, mut columns: Vec<String>, width: usize) {
+    let priority = |name: &str| KEY_COLUMNS.iter().position(|key| *key == name);
+    while table.total_width() > width && columns.len() > MIN_COLUMNS {
+        // On a tie max_by_key takes the last, i.e. the rightmost column
+        let Some(drop) =
+            (0..columns.len()).max_by_key(|&i| priority(&columns[i]).unwrap_or(usize::MAX))
+        else {
+            break;
+        };
+        table.with(tabled::settings::Remove::column(
+            tabled::settings::object::Columns::single(drop),
+        ));
+        columns.remove(drop);
+    }
+    shrink_to(table, width, true);
+}
+
+/// Print a single porcelain record.
+///
+/// Porcelain records are one line each: the record kind followed by space-separated
+/// fields. Every field except the last is guaranteed to contain no whitespace (it is
+/// percent-encoded if needed, and empty values are written as `-`). The last field
+/// extends to the end of the line, with backslashes and newlines escaped.
+pub fn print_porcelain(kind: &str, fields: &[&str]) {
+    println!("{}", format_porcelain(kind, fields));
+}
+
+/// Format a porcelain record without printing it
+pub fn format_porcelain(kind: &str, fields: &[&str]) -> String {
+    let mut line = kind.to_string();
+
+    if let Some((last, head)) = fields.split_last() {
+        for field in head {
+            line.push(' ');
+            line.push_str(&porcelain_field(field));
+        }
+        line.push(' ');
+        if last.is_empty() {
+            line.push('-');
+        } else {
+            line.push_str(&last.replace('\\', "\\\\").replace('\n', "\\n"));
+        }
+    }
+
+    line
+}
+
+fn porcelain_field(field: &str) -> Cow<'_, str> {
+    if field.is_empty() {
+        Cow::Borrowed("-")
+    } else if field.contains(|c: char| c.is_whitespace() || c == '%') {
+        urlencoding::encode(field)
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
 /// Read JSON from stdin. Returns None if stdin is a terminal (no piped data).
 pub fn read_stdin_json() -> Result<Option<serde_json::Value>> {
     if std::io::stdin().is_terminal() {
@@ -232,6 +598,105 @@ where
 mod tests {
     use super::*;
 
+    #[derive(Tabled)]
+    struct PlainRow {
+        entity_id: String,
+        friendly_name: String,
+        state: String,
+    }
+
+    #[test]
+    fn test_compact_table() {
+        let rows = [
+            PlainRow {
+                entity_id: "light.kitchen".to_string(),
+                friendly_name: "Kitchen ceiling light over the island".to_string(),
+                state: "on".to_string(),
+            },
+            PlainRow {
+                entity_id: "light.hall".to_string(),
+                friendly_name: "Hall".to_string(),
+                state: "off".to_string(),
+            },
+        ];
+        let mut table = Table::new(&rows);
+        table.with(Style::blank());
+        shorten_entity_ids(&mut table, &rows);
+        let columns = PlainRow::headers().iter().map(|h| h.to_string()).collect();
+        fit_width(&mut table, columns, 30);
+
+        assert!(table.total_width() <= 30);
+        assert_eq!(table.count_columns(), 2);
+        let text = table.to_string();
+        assert!(text.contains("kitchen") && !text.contains("light.kitchen"));
+        assert!(text.contains("off") && !text.contains("ceiling"));
+
+        assert_eq!(shared_domain(&["light.a".into(), "switch.b".into()]), None);
+    }
+
+    #[test]
+    fn test_fitted_table() {
+        let rows = [PlainRow {
+            entity_id: "sensor.outdoor".to_string(),
+            friendly_name: "Outdoor temperature sensor on the north-facing garage wall".to_string(),
+            state: "12.5".to_string(),
+        }];
+        let mut table = Table::new(&rows);
+        limit_columns(&mut table, 30, false);
+        let text = table.to_string();
+        assert!(text.contains("Outdoor temperature sensor on…"));
+        assert!(!text.contains("garage"));
+
+        shrink_to(&mut table, 40, false);
+        assert!(table.total_width() <= 40);
+        assert_eq!(table.count_columns(), 3);
+        assert!(table.to_string().contains("12.5"));
+
+        let mut wrapped = Table::new(&rows);
+        limit_columns(&mut wrapped, 30, true);
+        assert!(wrapped.to_string().contains("garage"));
+    }
+
+    #[test]
+    fn test_plain_records() {
+        let rows = [
+            PlainRow {
+                entity_id: "light.kitchen".to_string(),
+                friendly_name: "Kitchen".to_string(),
+                state: "on".to_string(),
+            },
+            PlainRow {
+                entity_id: "light.hall".to_string(),
+                friendly_name: String::new(),
+                state: "off".to_string(),
+            },
+        ];
+        assert_eq!(
+            plain_records(&rows, &["state"]),
+            "entity id: light.kitchen\nfriendly name: Kitchen\n\nentity id: light.hall"
+        );
+    }
+
+    #[test]
+    fn test_plain_text() {
+        assert_eq!(plain_text("🏠 3 lights · 21.4 °C"), "3 lights · 21.4 °C");
+        assert_eq!(plain_text("✓ ok"), "ok");
+    }
+
+    #[test]
+    fn test_hidden_columns() {
+        let all = ["id", "state", "unit", "icon"];
+        assert_eq!(
+            hidden_columns(None, &all, &["id", "state"]).unwrap(),
+            vec!["unit", "icon"]
+        );
+        assert_eq!(
+            hidden_columns(Some("id, unit"), &all, &["id", "state"]).unwrap(),
+            vec!["state", "icon"]
+        );
+        assert!(hidden_columns(Some("id,colour"), &all, &["id"]).is_err());
+    }
+
     #[test]
     fn test_parse_key_value_args() {
         let args = vec![
@@ -261,6 +726,32 @@ mod tests {
         assert_eq!(result["state"], "on");
     }
 
+    #[test]
+    fn test_format_warnings() {
+        assert_eq!(format_warnings(&[]), None);
+        assert_eq!(
+            format_warnings(&["Matched: ligth -> light".to_string()]).unwrap(),
+            r#"{"warnings":["Matched: ligth -> light"]}"#
+        );
+    }
+
+    #[test]
+    fn test_format_porcelain() {
+        assert_eq!(
+            format_porcelain("state", &["light.kitchen", "on"]),
+            "state light.kitchen on"
+        );
+        assert_eq!(
+            format_porcelain("target", &["light.kitchen", "", "Kitchen Light"]),
+            "target light.kitchen - Kitchen Light"
+        );
+        assert_eq!(
+            format_porcelain("attr", &["sensor.x", "my key", "a\nb"]),
+            "attr sensor.x my%20key a\\nb"
+        );
+        assert_eq!(format_porcelain("done", &[]), "done");
+    }
+
     #[test]
     fn test_truncate() {
         assert_eq!(truncate("hello", 10), "hello");
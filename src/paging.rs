@@ -0,0 +1,117 @@
+//! Pages of long lists (`--page-size`, `--page`)
+//!
+//! Home Assistant returns states and registries whole, with no offset or
+//! limit to ask for, so lists are paged here: the full list is fetched and
+//! filtered as before and only the requested page is printed. Machine output
+//! gets the page's items as the usual array; tables get a footer with the
+//! totals and the option that shows the next page.
+
+use anyhow::{bail, Result};
+
+use crate::cli::PageArgs;
+use crate::config::RuntimeContext;
+
+/// The items of one page, and where the page sits in the whole list
+#[derive(Debug)]
+pub struct Page<'a, T> {
+    pub items: &'a [T],
+    number: usize,
+    pages: usize,
+    start: usize,
+    total: usize,
+    paged: bool,
+}
+
+impl<'a, T> Page<'a, T> {
+    /// The page `args` asks for; all of `items` without `--page-size`
+    pub fn of(items: &'a [T], args: &PageArgs) -> Result<Self> {
+        let total = items.len();
+        let Some(size) = args.page_size.map(|s| s as usize) else {
+            return Ok(Self {
+                items,
+                number: 1,
+                pages: 1,
+                start: 0,
+                total,
+                paged: false,
+            });
+        };
+        let pages = total.div_ceil(size).max(1);
+        let number = args.page as usize;
+        if number > pages {
+            bail!("There is no page {number}; the last page is {pages}");
+        }
+        let start = (number - 1) * size;
+        let end = (start + size).min(total);
+        Ok(Self {
+            items: &items[start..end],
+            number,
+            pages,
+            start,
+            total,
+            paged: true,
+        })
+    }
+
+    /// "Page 2 of 14 · 51-100 of 683 entities · next: --page 3"; None when not paging
+    pub fn footer(&self, noun: &str) -> Option<String> {
+        if !self.paged || self.items.is_empty() {
+            return None;
+        }
+        let mut footer = format!(
+            "Page {} of {} · {}-{} of {} {noun}",
+            self.number,
+            self.pages,
+            self.start + 1,
+            self.start + self.items.len(),
+            self.total
+        );
+        if self.number < self.pages {
+            footer.push_str(&format!(" · next: --page {}", self.number + 1));
+        }
+        Some(footer)
+    }
+
+    /// Print the footer below a table; machine output has none
+    ///
+    /// It goes to stderr, so piping the table (e.g., into `wc -l`) sees rows only.
+    pub fn print_footer(&self, ctx: &RuntimeContext, noun: &str) {
+        if ctx.is_machine_output() || ctx.global.quiet {
+            return;
+        }
+        if let Some(footer) = self.footer(noun) {
+            eprintln!("{footer}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page() {
+        let items: Vec<u32> = (1..=23).collect();
+        let args = |page_size, page| PageArgs { page_size, page };
+
+        let all = Page::of(&items, &args(None, 1)).unwrap();
+        assert_eq!(all.items.len(), 23);
+        assert_eq!(all.footer("entities"), None);
+
+        let second = Page::of(&items, &args(Some(10), 2)).unwrap();
+        assert_eq!(second.items, &items[10..20]);
+        assert_eq!(
+            second.footer("entities").unwrap(),
+            "Page 2 of 3 · 11-20 of 23 entities · next: --page 3"
+        );
+
+        let last = Page::of(&items, &args(Some(10), 3)).unwrap();
+        assert_eq!(last.items, &[21, 22, 23]);
+        assert_eq!(
+            last.footer("entities").unwrap(),
+            "Page 3 of 3 · 21-23 of 23 entities"
+        );
+
+        assert!(Page::of(&items, &args(Some(10), 4)).is_err());
+    }
+}
@@ -0,0 +1,97 @@
+//! Pinned (favorite) entities
+//!
+//! `hmr pin add` keeps entity IDs in `pins.json` in the state directory, in
+//! the order they were pinned. Pinned entities win ties in the fuzzy matcher,
+//! can be listed with `entity list --pinned`, and lead `hmr status`.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::history::state_dir;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Pins {
+    #[serde(default)]
+    pub entities: Vec<String>,
+}
+
+impl Pins {
+    /// Load the pins file; a missing file means nothing is pinned
+    pub fn load() -> Result<Self> {
+        let path = pins_path()?;
+        match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)
+                .with_context(|| format!("parsing pins from {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("reading pins from {}", path.display())),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = pins_path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("creating state directory {}", parent.display()))?;
+        }
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(&path, content).with_context(|| format!("writing pins to {}", path.display()))
+    }
+
+    /// Pin an entity; false if it already was
+    pub fn add(&mut self, entity_id: &str) -> bool {
+        if self.contains(entity_id) {
+            return false;
+        }
+        self.entities.push(entity_id.to_string());
+        true
+    }
+
+    /// Unpin an entity; false if it wasn't pinned
+    pub fn remove(&mut self, entity_id: &str) -> bool {
+        let before = self.entities.len();
+        self.entities.retain(|e| e != entity_id);
+        self.entities.len() != before
+    }
+
+    pub fn contains(&self, entity_id: &str) -> bool {
+        self.entities.iter().any(|e| e == entity_id)
+    }
+}
+
+/// The pinned entity IDs, for ranking; an unreadable file pins nothing
+pub fn pinned() -> HashSet<String> {
+    match Pins::load() {
+        Ok(pins) => pins.entities.into_iter().collect(),
+        Err(e) => {
+            log::debug!("Ignoring pins: {e:#}");
+            HashSet::new()
+        }
+    }
+}
+
+pub fn pins_path() -> Result<PathBuf> {
+    Ok(state_dir()?.join("pins.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_remove() {
+        let mut pins = Pins::default();
+        assert!(pins.add("light.kitchen"));
+        assert!(pins.add("sensor.outside"));
+        assert!(!pins.add("light.kitchen"));
+        assert_eq!(pins.entities, vec!["light.kitchen", "sensor.outside"]);
+
+        assert!(pins.remove("light.kitchen"));
+        assert!(!pins.remove("light.kitchen"));
+        assert!(!pins.contains("light.kitchen"));
+        assert!(pins.contains("sensor.outside"));
+    }
+}
@@ -0,0 +1,57 @@
+//! Progress indicators for operations that take a while
+//!
+//! Spinners and bars draw on stderr, and only for a person watching: with
+//! `--quiet`, `--plain`, machine output (JSON, YAML, porcelain, or a piped
+//! stdout), or stderr not being a terminal they are hidden. Bars clear
+//! themselves when dropped, so an early return or error leaves no half-drawn
+//! line behind.
+
+use std::borrow::Cow;
+use std::io::IsTerminal;
+use std::time::Duration;
+
+use indicatif::{ProgressBar, ProgressFinish, ProgressStyle};
+
+use crate::config::RuntimeContext;
+
+const TICK: Duration = Duration::from_millis(100);
+
+/// A spinner for a step of unknown length ("Refreshing entities")
+pub fn spinner(ctx: &RuntimeContext, message: impl Into<Cow<'static, str>>) -> ProgressBar {
+    if !enabled(ctx) {
+        return ProgressBar::hidden();
+    }
+    let spinner = ProgressBar::new_spinner()
+        .with_style(
+            ProgressStyle::with_template("{spinner} {msg} {elapsed:.dim}")
+                .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+        )
+        .with_message(message)
+        .with_finish(ProgressFinish::AndClear);
+    spinner.enable_steady_tick(TICK);
+    spinner
+}
+
+/// A bar counting `len` items (calls, batches)
+pub fn bar(ctx: &RuntimeContext, len: u64, message: impl Into<Cow<'static, str>>) -> ProgressBar {
+    if !enabled(ctx) {
+        return ProgressBar::hidden();
+    }
+    let bar = ProgressBar::new(len)
+        .with_style(
+            ProgressStyle::with_template("{msg} [{bar:30}] {pos}/{len} {eta:.dim}")
+                .unwrap_or_else(|_| ProgressStyle::default_bar())
+                .progress_chars("=> "),
+        )
+        .with_message(message)
+        .with_finish(ProgressFinish::AndClear);
+    bar.enable_steady_tick(TICK);
+    bar
+}
+
+fn enabled(ctx: &RuntimeContext) -> bool {
+    !ctx.global.quiet
+        && !ctx.global.plain
+        && !ctx.is_machine_output()
+        && std::io::stderr().is_terminal()
+}
@@ -0,0 +1,294 @@
+//! Interactive prompts
+//!
+//! Prompts write to stderr and read from stdin so they never mix with command
+//! output. When either side is not a terminal, prompting fails instead of blocking.
+
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use anyhow::{bail, Context, Result};
+
+use crate::i18n::{tr, yes_words, Msg};
+
+/// Check whether we can prompt the user
+pub fn is_interactive() -> bool {
+    io::stdin().is_terminal() && io::stderr().is_terminal()
+}
+
+/// Ask for confirmation before a destructive operation.
+///
+/// `affected` lists exactly what will change. Passing `yes` (from `--yes`) skips the
+/// prompt. Declining, or running without a terminal and without `--yes`, is an error.
+pub fn confirm(action: &str, affected: &[String], yes: bool) -> Result<()> {
+    if yes {
+        return Ok(());
+    }
+    if !is_interactive() {
+        bail!("Confirmation required: {action} (pass --yes to proceed non-interactively)");
+    }
+
+    eprintln!("{action}");
+    for item in affected {
+        eprintln!("  - {item}");
+    }
+    eprint!("{}", tr(Msg::ConfirmContinue, &[]));
+    io::stderr().flush().ok();
+
+    let mut line = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .context("reading confirmation")?;
+
+    if is_yes(&line) {
+        Ok(())
+    } else {
+        bail!("Aborted")
+    }
+}
+
+/// Domains whose entities can alarm the household or disarm security
+pub const PROTECTED_DOMAINS: &[&str] = &["siren", "alarm_control_panel"];
+
+/// Ask for confirmation before a service call that touches protected entities.
+///
+/// Turning things off never needs confirmation, so a sounding siren can always be
+/// silenced. A call in a protected domain without listed targets is also confirmed.
+pub fn confirm_protected(service: &str, entity_ids: &[String], yes: bool) -> Result<()> {
+    confirm_protected_call(None, service, entity_ids, yes)
+}
+
+/// Like [`confirm_protected`], showing what was asked for (such as a natural
+/// language interpretation) above the question
+pub fn confirm_protected_request(
+    request: &str,
+    service: &str,
+    entity_ids: &[String],
+    yes: bool,
+) -> Result<()> {
+    confirm_protected_call(Some(request), service, entity_ids, yes)
+}
+
+fn confirm_protected_call(
+    request: Option<&str>,
+    service: &str,
+    entity_ids: &[String],
+    yes: bool,
+) -> Result<()> {
+    let protected = protected_targets(service, entity_ids);
+    if protected.is_empty() {
+        return Ok(());
+    }
+    let question = format!("Call {service} on protected entities?");
+    match request {
+        Some(request) => confirm(&format!("{request}\n{question}"), &protected, yes),
+        None => confirm(&question, &protected, yes),
+    }
+}
+
+/// Targets of `service` that need confirmation (empty when none do)
+fn protected_targets(service: &str, entity_ids: &[String]) -> Vec<String> {
+    let (domain, name) = service.split_once('.').unwrap_or((service, ""));
+    if name == "turn_off" {
+        return Vec::new();
+    }
+
+    let is_protected = |id: &str| {
+        id.split_once('.')
+            .is_some_and(|(domain, _)| PROTECTED_DOMAINS.contains(&domain))
+    };
+    let protected: Vec<String> = entity_ids
+        .iter()
+        .filter(|id| is_protected(id))
+        .cloned()
+        .collect();
+
+    if protected.is_empty() && entity_ids.is_empty() && PROTECTED_DOMAINS.contains(&domain) {
+        return vec![format!("every {domain} entity")];
+    }
+    protected
+}
+
+/// Ask a yes/no question; declining (or no terminal) is `false`, not an error
+pub fn ask_yes(question: &str) -> Result<bool> {
+    if !is_interactive() {
+        return Ok(false);
+    }
+    eprint!("{question} {}", tr(Msg::ConfirmContinue, &[]));
+    io::stderr().flush().ok();
+
+    let mut line = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .context("reading answer")?;
+    Ok(is_yes(&line))
+}
+
+fn is_yes(input: &str) -> bool {
+    yes_words().contains(&input.trim().to_lowercase().as_str())
+}
+
+/// Ask the user to pick one of `options`, returning the chosen index
+pub fn pick(prompt: &str, options: &[String]) -> Result<usize> {
+    if !is_interactive() {
+        bail!("{prompt} (cannot prompt: not running in a terminal)");
+    }
+
+    eprintln!("{prompt}");
+    for (idx, option) in options.iter().enumerate() {
+        eprintln!("  {}. {option}", idx + 1);
+    }
+
+    let stdin = io::stdin();
+    loop {
+        eprint!("{}", tr(Msg::SelectPrompt, &[&options.len().to_string()]));
+        io::stderr().flush().ok();
+
+        let mut line = String::new();
+        let read = stdin
+            .lock()
+            .read_line(&mut line)
+            .context("reading selection")?;
+        let line = line.trim();
+        if read == 0 || line.is_empty() {
+            bail!("Selection cancelled");
+        }
+
+        match parse_selection(line, options.len()) {
+            Some(idx) => return Ok(idx),
+            None => eprintln!("{}", tr(Msg::InvalidSelection, &[line])),
+        }
+    }
+}
+
+/// Ask for one line of text, such as a username
+pub fn ask(label: &str) -> Result<String> {
+    if !is_interactive() {
+        bail!("{label} (cannot prompt: not running in a terminal)");
+    }
+    eprint!("{label}: ");
+    io::stderr().flush().ok();
+    read_line(label)
+}
+
+/// Ask for a secret without echoing it
+///
+/// Without a terminal the secret is read from stdin, so it can be piped in.
+pub fn ask_secret(label: &str) -> Result<String> {
+    if !io::stdin().is_terminal() {
+        return read_line(label);
+    }
+    eprint!("{label}: ");
+    io::stderr().flush().ok();
+
+    let _echo = EchoOff::new();
+    let line = read_line(label);
+    eprintln!();
+    line
+}
+
+fn read_line(label: &str) -> Result<String> {
+    let mut line = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .with_context(|| format!("reading {}", label.to_lowercase()))?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Turns terminal echo off until dropped
+struct EchoOff {
+    #[cfg(unix)]
+    saved: Option<libc::termios>,
+}
+
+impl EchoOff {
+    #[cfg(unix)]
+    fn new() -> Self {
+        // SAFETY: tcgetattr/tcsetattr only read and write the termios struct
+        // for stdin, which stays open for the life of the process
+        unsafe {
+            let mut term: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut term) != 0 {
+                return Self { saved: None };
+            }
+            let saved = term;
+            term.c_lflag &= !libc::ECHO;
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &term);
+            Self { saved: Some(saved) }
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Drop for EchoOff {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(saved) = &self.saved {
+            // SAFETY: restores the settings read in `new`
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, saved);
+            }
+        }
+    }
+}
+
+/// Parse a 1-based selection into a 0-based index
+fn parse_selection(input: &str, len: usize) -> Option<usize> {
+    input
+        .parse::<usize>()
+        .ok()
+        .filter(|n| (1..=len).contains(n))
+        .map(|n| n - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_yes() {
+        assert!(is_yes("y\n"));
+        assert!(is_yes("YES"));
+        assert!(!is_yes(""));
+        assert!(!is_yes("n"));
+        assert!(!is_yes("yep"));
+    }
+
+    #[test]
+    fn test_confirm_with_yes_skips_prompt() {
+        assert!(confirm("Delete everything", &[], true).is_ok());
+    }
+
+    #[test]
+    fn test_protected_targets() {
+        let ids = vec!["siren.hallway".to_string(), "light.kitchen".to_string()];
+        assert_eq!(
+            protected_targets("siren.turn_on", &ids),
+            vec!["siren.hallway"]
+        );
+        assert_eq!(
+            protected_targets("homeassistant.turn_on", &ids),
+            vec!["siren.hallway"]
+        );
+        assert!(protected_targets("siren.turn_off", &ids).is_empty());
+        assert!(protected_targets("light.turn_on", &ids[1..]).is_empty());
+        assert_eq!(
+            protected_targets("alarm_control_panel.alarm_disarm", &[]),
+            vec!["every alarm_control_panel entity"]
+        );
+    }
+
+    #[test]
+    fn test_parse_selection() {
+        assert_eq!(parse_selection("1", 3), Some(0));
+        assert_eq!(parse_selection("3", 3), Some(2));
+        assert_eq!(parse_selection("0", 3), None);
+        assert_eq!(parse_selection("4", 3), None);
+        assert_eq!(parse_selection("two", 3), None);
+    }
+}
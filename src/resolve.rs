@@ -0,0 +1,301 @@
+//! Resolve user input to registry objects
+//!
+//! Commands that take a device or area accept IDs, names, or approximate names.
+//! Input goes through the fuzzy matcher; corrections are reported, and ambiguous
+//! input is resolved with an interactive picker when a terminal is available.
+
+use std::collections::HashSet;
+
+use anyhow::Result;
+
+use crate::api::ConfigEntry;
+use crate::cache::{Cache, CacheManager, CachedArea, CachedDevice, CachedEntity, CachedLabel};
+use crate::config::RuntimeContext;
+use crate::error::Error;
+use crate::fuzzy::{format_correction, FuzzyMatcher, Match, MatchResult, MatchType};
+use crate::output;
+use crate::pins;
+use crate::prompt;
+
+/// Maximum number of candidates offered in the picker
+const MAX_CHOICES: usize = 10;
+
+/// Resolve an area ID, name, alias, or approximate name
+pub fn resolve_area(ctx: &RuntimeContext, cache: &Cache, input: &str) -> Result<CachedArea> {
+    let result = FuzzyMatcher::new().find_area(input, cache);
+    choose(ctx, "area", input, result, |area| {
+        format!("{} ({})", area.name, area.area_id)
+    })
+}
+
+/// Resolve the `--room` area and narrow the cache to its entities
+///
+/// Returns `None` without `--room`; callers then use the full cache. Loads the
+/// areas and devices needed to place entities in rooms.
+pub async fn room_scope(
+    ctx: &RuntimeContext,
+    cache_manager: &mut CacheManager<'_>,
+) -> Result<Option<(CachedArea, Cache)>> {
+    let Some(room) = ctx.global.room.as_deref() else {
+        return Ok(None);
+    };
+    cache_manager.ensure_entities().await?;
+    cache_manager.ensure_areas().await?;
+    cache_manager.ensure_devices().await?;
+
+    let cache = cache_manager.cache();
+    let area = resolve_area(ctx, cache, room)?;
+    let scoped = cache.restricted_to_area(&area.area_id);
+    Ok(Some((area, scoped)))
+}
+
+/// Entity IDs in the `--room` area, for filtering listings (`None` without `--room`)
+pub async fn room_entity_ids(ctx: &RuntimeContext) -> Result<Option<HashSet<String>>> {
+    if ctx.global.room.is_none() {
+        return Ok(None);
+    }
+    let mut cache_manager = CacheManager::new(ctx)?;
+    let scope = room_scope(ctx, &mut cache_manager).await?;
+    Ok(scope.map(|(_, cache)| {
+        cache
+            .entities()
+            .iter()
+            .map(|e| e.entity_id.clone())
+            .collect()
+    }))
+}
+
+/// IDs of the entities carrying a label (ID or approximate name)
+///
+/// Entity labels come from the entity registry, so this needs the registry
+/// join in the entity cache.
+pub async fn label_entity_ids(ctx: &RuntimeContext, input: &str) -> Result<HashSet<String>> {
+    let mut cache_manager = CacheManager::new(ctx)?;
+    cache_manager.ensure_entities().await?;
+    cache_manager.ensure_labels().await?;
+
+    let cache = cache_manager.cache();
+    let label = resolve_label(ctx, cache, input)?;
+    Ok(cache
+        .entities_with_label(&label.label_id)
+        .into_iter()
+        .map(|e| e.entity_id.clone())
+        .collect())
+}
+
+/// Resolve a device ID or (approximate) device name
+///
+/// A device added since the cache was filled isn't in it, so when nothing
+/// matches, or a device ID matches only approximately, the devices are
+/// refreshed once before giving up.
+pub async fn resolve_device(
+    ctx: &RuntimeContext,
+    cache_manager: &mut CacheManager<'_>,
+    input: &str,
+) -> Result<CachedDevice> {
+    cache_manager.ensure_devices().await?;
+    let result = FuzzyMatcher::new().find_device(input, cache_manager.cache());
+    let stale = match &result {
+        MatchResult::None => true,
+        _ => is_device_id(input) && !result.is_exact(),
+    };
+    if stale {
+        cache_manager.refresh_devices().await?;
+    }
+    let result = FuzzyMatcher::new().find_device(input, cache_manager.cache());
+    choose(ctx, "device", input, result, describe_device)
+}
+
+/// Device registry IDs are 32 lowercase hex digits
+fn is_device_id(input: &str) -> bool {
+    input.len() == 32 && input.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f'))
+}
+
+/// Resolve a label ID, name, or approximate name
+pub fn resolve_label(ctx: &RuntimeContext, cache: &Cache, input: &str) -> Result<CachedLabel> {
+    let result = FuzzyMatcher::new().find_label(input, cache);
+    choose(ctx, "label", input, result, |label| {
+        format!("{} ({})", label.name, label.label_id)
+    })
+}
+
+/// Resolve a config entry ID, integration title, or domain
+pub fn resolve_config_entry(
+    ctx: &RuntimeContext,
+    entries: &[ConfigEntry],
+    input: &str,
+) -> Result<ConfigEntry> {
+    let result = FuzzyMatcher::new().find_config_entry(input, entries);
+    choose(ctx, "integration", input, result, |entry| {
+        format!("{} ({}, {})", entry.title, entry.domain, entry.entry_id)
+    })
+}
+
+/// Resolve a config entry by its exact ID, title, or domain
+///
+/// For changes that are hard to undo, where an approximate title could hit
+/// the wrong integration; a near miss fails and names the closest entry.
+pub fn resolve_config_entry_exact(
+    ctx: &RuntimeContext,
+    entries: &[ConfigEntry],
+    input: &str,
+) -> Result<ConfigEntry> {
+    let result = FuzzyMatcher::new().find_config_entry(input, entries);
+    let approximate = match &result {
+        MatchResult::Single(m) => m.match_type != MatchType::Exact,
+        MatchResult::Multiple(matches) => matches.iter().any(|m| m.match_type != MatchType::Exact),
+        MatchResult::None => false,
+    };
+    if approximate {
+        let hint = result
+            .best()
+            .map(|m| format!(" (did you mean '{}'?)", m.item.title))
+            .unwrap_or_default();
+        return Err(Error::NotFound(format!(
+            "No integration is exactly '{input}'{hint}; give its entry ID, title or domain"
+        ))
+        .into());
+    }
+    choose(ctx, "integration", input, result, |entry| {
+        format!("{} ({}, {})", entry.title, entry.domain, entry.entry_id)
+    })
+}
+
+/// Resolve an entity ID or (approximate) friendly name
+///
+/// When several entities match, candidates in `domain` are preferred so that
+/// `light.turn_on kitchen` picks the kitchen light over the kitchen sensor.
+pub fn resolve_entity(
+    ctx: &RuntimeContext,
+    cache: &Cache,
+    input: &str,
+    domain: Option<&str>,
+) -> Result<CachedEntity> {
+    let matcher = FuzzyMatcher::new().with_pinned(pins::pinned());
+    let result = match matcher.find_entity(input, cache) {
+        MatchResult::Multiple(matches) => prefer_domain(matches, domain),
+        other => other,
+    };
+    choose(ctx, "entity", input, result, describe_entity)
+}
+
+/// Resolve an entity ID or name, considering only entities in `domains`
+pub fn resolve_domain_entity(
+    ctx: &RuntimeContext,
+    cache: &Cache,
+    input: &str,
+    domains: &[&str],
+) -> Result<CachedEntity> {
+    let restricted = cache.restricted_to_domains(domains);
+    let result = FuzzyMatcher::new()
+        .with_pinned(pins::pinned())
+        .find_entity(input, &restricted);
+    let kind = match domains {
+        [domain] => *domain,
+        _ => "entity",
+    };
+    choose(ctx, kind, input, result, describe_entity)
+}
+
+/// Match `input` within `domains` without warnings or prompts
+///
+/// The entity if exactly one matches, and whether that match was exact.
+pub fn match_domain_entity(
+    cache: &Cache,
+    input: &str,
+    domains: &[&str],
+) -> Option<(CachedEntity, bool)> {
+    let restricted = cache.restricted_to_domains(domains);
+    let result = FuzzyMatcher::new()
+        .with_pinned(pins::pinned())
+        .find_entity(input, &restricted);
+    let exact = result.is_exact();
+    match result {
+        MatchResult::Single(m) => Some((m.item.clone(), exact)),
+        _ => None,
+    }
+}
+
+fn describe_entity(entity: &CachedEntity) -> String {
+    match entity.friendly_name.as_deref() {
+        Some(name) => format!("{name} ({})", entity.entity_id),
+        None => entity.entity_id.clone(),
+    }
+}
+
+/// Narrow ambiguous entity matches to one domain, if any match it
+fn prefer_domain<'a>(
+    matches: Vec<Match<&'a CachedEntity>>,
+    domain: Option<&str>,
+) -> MatchResult<&'a CachedEntity> {
+    let Some(domain) = domain else {
+        return MatchResult::Multiple(matches);
+    };
+    let (mut in_domain, others): (Vec<_>, Vec<_>) =
+        matches.into_iter().partition(|m| m.item.domain == domain);
+    match in_domain.len() {
+        0 => MatchResult::Multiple(others),
+        1 => MatchResult::Single(in_domain.remove(0)),
+        _ => MatchResult::Multiple(in_domain),
+    }
+}
+
+fn describe_device(device: &CachedDevice) -> String {
+    let name = device
+        .name_by_user
+        .as_deref()
+        .or(device.name.as_deref())
+        .unwrap_or("(unnamed)");
+    match device.manufacturer.as_deref() {
+        Some(manufacturer) => format!("{name} [{manufacturer}] ({})", device.id),
+        None => format!("{name} ({})", device.id),
+    }
+}
+
+/// Turn a match result into a single item, prompting on ambiguity
+fn choose<T: Clone>(
+    ctx: &RuntimeContext,
+    kind: &str,
+    input: &str,
+    result: MatchResult<&T>,
+    describe: impl Fn(&T) -> String,
+) -> Result<T> {
+    let is_exact = result.is_exact();
+    match result {
+        MatchResult::Single(m) => {
+            if !is_exact {
+                output::warn(
+                    ctx,
+                    format!(
+                        "Matched {kind}: {}",
+                        format_correction(input, &m.matched_on)
+                    ),
+                );
+            }
+            Ok(m.item.clone())
+        }
+        MatchResult::Multiple(matches) => {
+            let candidates: Vec<String> = matches
+                .iter()
+                .take(MAX_CHOICES)
+                .map(|m| describe(m.item))
+                .collect();
+
+            if !prompt::is_interactive() {
+                return Err(Error::Ambiguous {
+                    kind: kind.to_string(),
+                    input: input.to_string(),
+                    candidates,
+                }
+                .into());
+            }
+
+            let idx = prompt::pick(
+                &format!("Multiple matches for {kind} '{input}':"),
+                &candidates,
+            )?;
+            Ok(matches[idx].item.clone())
+        }
+        MatchResult::None => Err(Error::NotFound(format!("No {kind} matches '{input}'")).into()),
+    }
+}
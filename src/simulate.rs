@@ -0,0 +1,198 @@
+//! Offline simulation of service calls for `hmr do --simulate`
+//!
+//! The predicted effect of a call is applied to an in-memory copy of the
+//! cached states; nothing is sent to Home Assistant. Only states are
+//! predicted: a toggle flips on/off or open/closed, calls with a certain
+//! outcome (turn_off, lock, open_cover, ...) set it, and option, value and
+//! HVAC mode calls take it from the call data. Anything else, such as a
+//! brightness change or a script run, keeps the cached state and is marked
+//! as unpredicted.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::cache::Cache;
+use crate::nl::ServiceCall;
+
+/// What a simulated call did to one target
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SimulatedChange {
+    /// The call, e.g. "light.turn_off"
+    pub step: String,
+    pub entity_id: String,
+    pub before: String,
+    pub after: String,
+    /// False when the outcome can't be known offline and `after` is a guess
+    pub predicted: bool,
+}
+
+/// Cached states with simulated calls applied
+#[derive(Debug, Clone)]
+pub struct Simulator {
+    states: HashMap<String, String>,
+}
+
+impl Simulator {
+    /// Start from a copy of the cached entity states
+    pub fn new(cache: &Cache) -> Self {
+        let states = cache
+            .entities()
+            .iter()
+            .map(|e| (e.entity_id.clone(), e.state.clone()))
+            .collect();
+        Self { states }
+    }
+
+    /// Apply `call` to its targets and report how each changed
+    pub fn apply(&mut self, call: &ServiceCall) -> Vec<SimulatedChange> {
+        let step = format!("{}.{}", call.domain, call.service);
+        call.target
+            .entity_id
+            .iter()
+            .map(|entity_id| {
+                let before = self
+                    .states
+                    .get(entity_id)
+                    .cloned()
+                    .unwrap_or_else(|| "unknown".to_string());
+                let predicted = predict_state(entity_id, &call.service, &call.data, &before);
+                let after = predicted.clone().unwrap_or_else(|| before.clone());
+                self.states.insert(entity_id.clone(), after.clone());
+                SimulatedChange {
+                    step: step.clone(),
+                    entity_id: entity_id.clone(),
+                    before,
+                    after,
+                    predicted: predicted.is_some(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// The state `service` leaves an entity of `domain` in, if it's certain
+pub fn expected_state(domain: &str, service: &str) -> Option<&'static str> {
+    match (domain, service) {
+        ("cover" | "valve", "turn_on" | "open_cover" | "open_valve") => Some("open"),
+        ("cover" | "valve", "turn_off" | "close_cover" | "close_valve") => Some("closed"),
+        ("lock", "lock") => Some("locked"),
+        ("lock", "unlock") => Some("unlocked"),
+        (
+            "light" | "switch" | "fan" | "input_boolean" | "siren" | "humidifier" | "automation",
+            "turn_on",
+        ) => Some("on"),
+        (
+            "light" | "switch" | "fan" | "input_boolean" | "siren" | "humidifier" | "automation",
+            "turn_off",
+        ) => Some("off"),
+        _ => None,
+    }
+}
+
+/// The state `service` moves `entity_id` to from `current`, when predictable
+fn predict_state(
+    entity_id: &str,
+    service: &str,
+    data: &serde_json::Map<String, serde_json::Value>,
+    current: &str,
+) -> Option<String> {
+    // Unreachable devices ignore calls
+    if current == "unavailable" {
+        return Some(current.to_string());
+    }
+    let domain = entity_id.split('.').next().unwrap_or_default();
+    if let Some(state) = expected_state(domain, service) {
+        return Some(state.to_string());
+    }
+    let from_data = |key: &str| {
+        data.get(key).map(|v| match v {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })
+    };
+    match service {
+        "toggle" => match current {
+            "on" => Some("off"),
+            "off" => Some("on"),
+            "open" => Some("closed"),
+            "closed" => Some("open"),
+            _ => None,
+        }
+        .map(str::to_string),
+        "select_option" => from_data("option"),
+        "set_value" => from_data("value"),
+        "set_hvac_mode" => from_data("hvac_mode"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nl::ServiceTarget;
+    use serde_json::json;
+
+    fn call(
+        domain: &str,
+        service: &str,
+        entity_ids: &[&str],
+        data: serde_json::Value,
+    ) -> ServiceCall {
+        ServiceCall {
+            domain: domain.to_string(),
+            service: service.to_string(),
+            target: ServiceTarget {
+                entity_id: entity_ids.iter().map(|id| id.to_string()).collect(),
+                area_id: None,
+            },
+            data: data.as_object().cloned().unwrap_or_default(),
+            duration_secs: None,
+        }
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut simulator = Simulator {
+            states: HashMap::from([
+                ("light.kitchen".to_string(), "on".to_string()),
+                ("light.hall".to_string(), "off".to_string()),
+                ("light.porch".to_string(), "unavailable".to_string()),
+                ("input_select.mode".to_string(), "Home".to_string()),
+            ]),
+        };
+
+        let changes = simulator.apply(&call(
+            "light",
+            "toggle",
+            &["light.kitchen", "light.hall", "light.porch"],
+            json!({}),
+        ));
+        let afters: Vec<&str> = changes.iter().map(|c| c.after.as_str()).collect();
+        assert_eq!(afters, vec!["off", "on", "unavailable"]);
+        assert_eq!(simulator.states["light.kitchen"], "off");
+
+        let changes = simulator.apply(&call(
+            "light",
+            "turn_on",
+            &["light.kitchen"],
+            json!({"brightness_pct": 50}),
+        ));
+        assert_eq!(
+            (changes[0].before.as_str(), changes[0].after.as_str()),
+            ("off", "on")
+        );
+
+        let changes = simulator.apply(&call(
+            "input_select",
+            "select_option",
+            &["input_select.mode"],
+            json!({"option": "Away"}),
+        ));
+        assert_eq!(changes[0].after, "Away");
+
+        let changes = simulator.apply(&call("script", "turn_on", &["script.goodnight"], json!({})));
+        assert!(!changes[0].predicted);
+        assert_eq!(changes[0].after, "unknown");
+    }
+}
@@ -0,0 +1,211 @@
+//! Durations and points in time given on the command line
+//!
+//! Every option that takes a duration (`--delay`, `--interval`, `--wait-for`,
+//! `--timeout`, ...) goes through [`parse_duration`], so "90s", "1h30m",
+//! "1h 30m" and "2 hours and 10 minutes" work everywhere. Options that take a
+//! point in time (`--since`, `--until`, `--wait-until`) go through
+//! [`TimeSpec`], which also takes absolute times. Whether a duration counts
+//! back or forward from now is up to the option: `--since 2h` is two hours
+//! ago, `--until 2h` two hours ahead.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Days, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+
+use crate::nl::{parse_duration_phrase, parse_time_of_day};
+
+const DATETIME_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+];
+
+/// Parse a duration: "90s", "1h30m", "1h 30m", "2 hours and 10 minutes"
+pub fn parse_duration(input: &str) -> Result<Duration> {
+    let input = input.trim();
+    if let Ok(duration) = humantime::parse_duration(input) {
+        return Ok(duration);
+    }
+    parse_duration_phrase(input)
+        .map(Duration::from_secs)
+        .ok_or_else(|| anyhow!("invalid duration '{input}': expected e.g. 45m, 1h 30m or 2 hours"))
+}
+
+/// Parse a polling interval: a duration that must be longer than zero
+pub fn parse_interval(input: &str) -> Result<Duration> {
+    let interval = parse_duration(input)?;
+    if interval.is_zero() {
+        bail!(
+            "invalid interval '{}': must be longer than zero",
+            input.trim()
+        );
+    }
+    Ok(interval)
+}
+
+/// Parse a duration in whole seconds; a bare number is taken as seconds
+pub fn parse_seconds(input: &str) -> Result<u64> {
+    match input.trim().parse::<u64>() {
+        Ok(secs) => Ok(secs),
+        Err(_) => Ok(parse_duration(input)?.as_secs()),
+    }
+}
+
+/// A point in time: a duration from now, a time of day, or a date and time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeSpec {
+    /// "2h", "3 days ago", "now"
+    Offset(Duration),
+    /// "07:30", "7pm", "noon": the nearest such time in the right direction
+    Clock(NaiveTime),
+    /// "2025-01-15", "2025-01-15 07:30", RFC 3339, "today", "yesterday"
+    At(DateTime<Local>),
+}
+
+impl TimeSpec {
+    /// The moment meant when looking back from `now` (`--since`)
+    pub fn before(&self, now: DateTime<Local>) -> DateTime<Local> {
+        match self {
+            Self::Offset(offset) => now - *offset,
+            Self::Clock(time) => {
+                let date = if *time <= now.time() {
+                    now.date_naive()
+                } else {
+                    now.date_naive() - Days::new(1)
+                };
+                local_time(date.and_time(*time)).unwrap_or(now)
+            }
+            Self::At(at) => *at,
+        }
+    }
+
+    /// The moment meant when looking ahead from `now` (`--until`, `--wait-until`)
+    pub fn after(&self, now: DateTime<Local>) -> DateTime<Local> {
+        match self {
+            Self::Offset(offset) => now + *offset,
+            Self::Clock(time) => {
+                let date = if *time <= now.time() {
+                    now.date_naive() + Days::new(1)
+                } else {
+                    now.date_naive()
+                };
+                local_time(date.and_time(*time)).unwrap_or(now)
+            }
+            Self::At(at) => *at,
+        }
+    }
+
+    fn parse_at(input: &str, now: DateTime<Local>) -> Result<Self> {
+        let input = input.trim();
+        let lower = input.to_lowercase();
+        let midnight = |date: NaiveDate| {
+            local_time(date.and_time(NaiveTime::MIN))
+                .map(Self::At)
+                .ok_or_else(|| anyhow!("{date} has no midnight in the local time zone"))
+        };
+        match lower.as_str() {
+            "now" => return Ok(Self::Offset(Duration::ZERO)),
+            "today" => return midnight(now.date_naive()),
+            "yesterday" => return midnight(now.date_naive() - Days::new(1)),
+            _ => {}
+        }
+        let offset = lower.strip_suffix("ago").unwrap_or(&lower);
+        if let Ok(duration) = parse_duration(offset) {
+            return Ok(Self::Offset(duration));
+        }
+        if let Some(time) = parse_time_of_day(input) {
+            return Ok(Self::Clock(time));
+        }
+        if let Ok(at) = DateTime::parse_from_rfc3339(input) {
+            return Ok(Self::At(at.with_timezone(&Local)));
+        }
+        for format in DATETIME_FORMATS {
+            if let Ok(at) = NaiveDateTime::parse_from_str(input, format) {
+                return local_time(at)
+                    .map(Self::At)
+                    .ok_or_else(|| anyhow!("{at} does not exist in the local time zone"));
+            }
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+            return midnight(date);
+        }
+        bail!(
+            "invalid time '{input}': expected a duration (2h, 3 days ago), a time (07:30, 7pm) or a date (2025-01-15 08:00)"
+        )
+    }
+}
+
+impl FromStr for TimeSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        Self::parse_at(input, Local::now())
+    }
+}
+
+impl fmt::Display for TimeSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Offset(offset) => write!(f, "{}", humantime::format_duration(*offset)),
+            Self::Clock(time) => write!(f, "{}", time.format("%H:%M")),
+            Self::At(at) => write!(f, "{}", at.format("%Y-%m-%d %H:%M")),
+        }
+    }
+}
+
+/// A local date and time; one skipped by a DST change happens an hour later
+fn local_time(at: NaiveDateTime) -> Option<DateTime<Local>> {
+    Local.from_local_datetime(&at).earliest().or_else(|| {
+        Local
+            .from_local_datetime(&(at + chrono::Duration::hours(1)))
+            .earliest()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("1h").unwrap(), Duration::from_secs(3600));
+        assert_eq!(parse_duration("2h30m").unwrap(), Duration::from_secs(9000));
+        assert_eq!(parse_duration("1d").unwrap(), Duration::from_secs(86400));
+        assert_eq!(
+            parse_duration("2 hours and 10 minutes").unwrap(),
+            Duration::from_secs(7800)
+        );
+        assert!(parse_duration("invalid").is_err());
+        assert_eq!(parse_seconds("45").unwrap(), 45);
+        assert_eq!(parse_seconds("2m").unwrap(), 120);
+        assert_eq!(parse_interval("10s").unwrap(), Duration::from_secs(10));
+        assert!(parse_interval("0s").is_err());
+    }
+
+    #[test]
+    fn test_time_spec() {
+        let now = Local.with_ymd_and_hms(2025, 1, 15, 9, 0, 0).unwrap();
+        let at = |d, h, m| Local.with_ymd_and_hms(2025, 1, d, h, m, 0).unwrap();
+        let parse = |input| TimeSpec::parse_at(input, now).unwrap();
+
+        assert_eq!(parse("2h").before(now), at(15, 7, 0));
+        assert_eq!(parse("3 days ago").before(now), at(12, 9, 0));
+        assert_eq!(parse("2h").after(now), at(15, 11, 0));
+        assert_eq!(parse("now").before(now), now);
+        assert_eq!(parse("yesterday").before(now), at(14, 0, 0));
+        assert_eq!(parse("2025-01-10").before(now), at(10, 0, 0));
+        assert_eq!(parse("2025-01-10 18:45").after(now), at(10, 18, 45));
+
+        // A time of day is the nearest one in the direction the option looks
+        assert_eq!(parse("17:30").before(now), at(14, 17, 30));
+        assert_eq!(parse("17:30").after(now), at(15, 17, 30));
+        assert_eq!(parse("7:30am").before(now), at(15, 7, 30));
+        assert_eq!(parse("7:30am").after(now), at(16, 7, 30));
+
+        assert!(TimeSpec::parse_at("last tuesday", now).is_err());
+    }
+}
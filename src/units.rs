@@ -0,0 +1,250 @@
+//! Unit conversion for display
+//!
+//! `--units metric|imperial` converts temperature, length and speed states to
+//! the chosen system regardless of the server's unit system. Only human output
+//! changes: JSON, YAML and porcelain keep Home Assistant's values.
+
+use anyhow::{anyhow, Result};
+
+use crate::api::EntityState;
+use crate::cli::UnitSystem;
+
+/// Device classes with length or speed values; temperatures are recognized by unit
+const CONVERTIBLE_CLASSES: &[&str] = &[
+    "temperature",
+    "distance",
+    "precipitation",
+    "speed",
+    "wind_speed",
+];
+
+/// An entity's state and unit as displayed, converted to `units` when possible
+pub fn display_value(state: &EntityState, units: Option<UnitSystem>) -> (String, String) {
+    let attr = |key: &str| {
+        state
+            .attributes
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+    };
+    let unit = attr("unit_of_measurement");
+
+    match units.and_then(|system| convert(&state.state, unit, attr("device_class"), system)) {
+        Some((value, unit)) => (value, unit.to_string()),
+        None => (state.state.clone(), unit.to_string()),
+    }
+}
+
+/// An entity's state with its (converted) unit, e.g. "21.5 °C"
+pub fn display_state(state: &EntityState, units: Option<UnitSystem>) -> String {
+    let (value, unit) = display_value(state, units);
+    with_unit(&value, &unit)
+}
+
+/// Append a unit to a state; states without a value stay bare
+pub fn with_unit(state: &str, unit: &str) -> String {
+    if unit.is_empty() || matches!(state, "unavailable" | "unknown" | "") {
+        state.to_string()
+    } else {
+        format!("{state} {unit}")
+    }
+}
+
+/// A number with an optional unit, as given on the command line ("200°C", "30")
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quantity {
+    pub value: f64,
+    pub unit: Option<String>,
+}
+
+impl std::fmt::Display for Quantity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.unit {
+            Some(unit) => write!(f, "{} {unit}", self.value),
+            None => write!(f, "{}", self.value),
+        }
+    }
+}
+
+/// Parse "200", "200°C", "392 F" or "30%"; bare C and F mean degrees
+pub fn parse_quantity(input: &str) -> Result<Quantity> {
+    let input = input.trim();
+    let split = input
+        .char_indices()
+        .find(|&(i, c)| !(c.is_ascii_digit() || c == '.' || (i == 0 && matches!(c, '-' | '+'))))
+        .map_or(input.len(), |(i, _)| i);
+    let (number, unit) = input.split_at(split);
+    let value: f64 = number
+        .parse()
+        .map_err(|_| anyhow!("'{input}' is not a number (e.g., 30 or 200°C)"))?;
+
+    let unit = match unit.trim() {
+        "" => None,
+        "C" | "c" | "°c" => Some("°C".to_string()),
+        "F" | "f" | "°f" => Some("°F".to_string()),
+        other => Some(other.to_string()),
+    };
+    Ok(Quantity { value, unit })
+}
+
+/// An entity's numeric state in `unit`, converting between unit systems when
+/// needed. Without a unit the displayed value (after `--units`) is used.
+///
+/// Returns None for non-numeric states such as "unavailable".
+pub fn numeric_value(
+    state: &EntityState,
+    unit: Option<&str>,
+    units: Option<UnitSystem>,
+) -> Result<Option<f64>> {
+    let attr = |key: &str| {
+        state
+            .attributes
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+    };
+    let state_unit = attr("unit_of_measurement");
+
+    let value = match unit {
+        None => display_value(state, units).0,
+        Some(unit) if unit == state_unit => state.state.clone(),
+        Some(unit) => {
+            if state.state.parse::<f64>().is_err() {
+                return Ok(None);
+            }
+            [UnitSystem::Metric, UnitSystem::Imperial]
+                .into_iter()
+                .filter_map(|system| {
+                    convert(&state.state, state_unit, attr("device_class"), system)
+                })
+                .find(|(_, target)| *target == unit)
+                .map(|(value, _)| value)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "{} is measured in '{state_unit}', which can't be compared with '{unit}'",
+                        state.entity_id
+                    )
+                })?
+        }
+    };
+    Ok(value.parse().ok())
+}
+
+/// Convert a numeric value to `system`, returning the new value and unit
+///
+/// Keeps the input's precision (at least one decimal), or more when the target
+/// unit is much larger (mm of rain in inches).
+fn convert(
+    value: &str,
+    unit: &str,
+    device_class: &str,
+    system: UnitSystem,
+) -> Option<(String, &'static str)> {
+    let is_temperature = matches!(unit, "°C" | "°F");
+    if !is_temperature && !CONVERTIBLE_CLASSES.contains(&device_class) {
+        return None;
+    }
+    let n: f64 = value.parse().ok()?;
+
+    let (converted, target, min_decimals) = match (system, unit) {
+        (UnitSystem::Imperial, "°C") => (n * 9.0 / 5.0 + 32.0, "°F", 1),
+        (UnitSystem::Imperial, "km") => (n / 1.609_344, "mi", 1),
+        (UnitSystem::Imperial, "m") => (n / 0.3048, "ft", 1),
+        (UnitSystem::Imperial, "cm") => (n / 2.54, "in", 1),
+        (UnitSystem::Imperial, "mm") => (n / 25.4, "in", 2),
+        (UnitSystem::Imperial, "km/h") => (n / 1.609_344, "mph", 1),
+        (UnitSystem::Imperial, "m/s") => (n * 3.6 / 1.609_344, "mph", 1),
+        (UnitSystem::Metric, "°F") => ((n - 32.0) * 5.0 / 9.0, "°C", 1),
+        (UnitSystem::Metric, "mi") => (n * 1.609_344, "km", 1),
+        (UnitSystem::Metric, "yd") => (n * 0.9144, "m", 1),
+        (UnitSystem::Metric, "ft") => (n * 0.3048, "m", 1),
+        (UnitSystem::Metric, "in") if device_class == "precipitation" => (n * 25.4, "mm", 1),
+        (UnitSystem::Metric, "in") => (n * 2.54, "cm", 1),
+        (UnitSystem::Metric, "mph") => (n * 1.609_344, "km/h", 1),
+        _ => return None,
+    };
+
+    let decimals = value
+        .split_once('.')
+        .map_or(0, |(_, fraction)| fraction.len())
+        .max(min_decimals);
+    Some((format!("{converted:.decimals$}"), target))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert() {
+        let imperial = UnitSystem::Imperial;
+        let metric = UnitSystem::Metric;
+
+        assert_eq!(
+            convert("21.5", "°C", "temperature", imperial),
+            Some(("70.7".to_string(), "°F"))
+        );
+        assert_eq!(
+            convert("70", "°F", "", metric),
+            Some(("21.1".to_string(), "°C"))
+        );
+        assert_eq!(
+            convert("2", "mm", "precipitation", imperial),
+            Some(("0.08".to_string(), "in"))
+        );
+        assert_eq!(
+            convert("10", "km/h", "wind_speed", imperial),
+            Some(("6.2".to_string(), "mph"))
+        );
+
+        // Already in the target system, not a number, or not a length/speed
+        assert_eq!(convert("21.5", "°C", "temperature", metric), None);
+        assert_eq!(convert("unavailable", "°C", "temperature", imperial), None);
+        assert_eq!(convert("3", "m", "", imperial), None);
+    }
+
+    #[test]
+    fn test_parse_quantity() {
+        let quantity = |value: f64, unit: Option<&str>| Quantity {
+            value,
+            unit: unit.map(String::from),
+        };
+        assert_eq!(parse_quantity("30").unwrap(), quantity(30.0, None));
+        assert_eq!(parse_quantity("-5.5").unwrap(), quantity(-5.5, None));
+        assert_eq!(
+            parse_quantity("200°C").unwrap(),
+            quantity(200.0, Some("°C"))
+        );
+        assert_eq!(
+            parse_quantity("392 F").unwrap(),
+            quantity(392.0, Some("°F"))
+        );
+        assert_eq!(parse_quantity("40%").unwrap(), quantity(40.0, Some("%")));
+        assert!(parse_quantity("hot").is_err());
+    }
+
+    #[test]
+    fn test_numeric_value() {
+        let state = EntityState {
+            entity_id: "sensor.oven".to_string(),
+            state: "392".to_string(),
+            attributes: serde_json::json!({"unit_of_measurement": "°F"}),
+            last_changed: String::new(),
+            last_updated: String::new(),
+            context: serde_json::Value::Null,
+        };
+        assert_eq!(numeric_value(&state, None, None).unwrap(), Some(392.0));
+        assert_eq!(
+            numeric_value(&state, Some("°C"), None).unwrap(),
+            Some(200.0)
+        );
+        assert!(numeric_value(&state, Some("%"), None).is_err());
+    }
+
+    #[test]
+    fn test_with_unit() {
+        assert_eq!(with_unit("21.5", "°C"), "21.5 °C");
+        assert_eq!(with_unit("on", ""), "on");
+        assert_eq!(with_unit("unavailable", "°C"), "unavailable");
+    }
+}
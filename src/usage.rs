@@ -0,0 +1,135 @@
+//! Opt-in local usage metrics
+//!
+//! With `telemetry.enabled = true`, every command adds its run time and error
+//! category to `usage.json` in the state directory. Only subcommand names are
+//! stored, never arguments, so entity names and tokens stay out of it. Nothing
+//! is sent anywhere; `hmr stats tool-usage --export` writes the file out for
+//! attaching to bug reports.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use clap::ArgMatches;
+use serde::{Deserialize, Serialize};
+
+use crate::error;
+use crate::history::state_dir;
+
+/// Aggregated runs, keyed by command path (e.g. "entity watch")
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UsageStats {
+    /// When recording started (RFC 3339)
+    #[serde(default)]
+    pub since: Option<String>,
+    #[serde(default)]
+    pub commands: BTreeMap<String, CommandUsage>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct CommandUsage {
+    pub count: u64,
+    pub total_ms: u64,
+    pub max_ms: u64,
+    /// Failed runs per error code ("network", "auth", ..., "error")
+    #[serde(default)]
+    pub errors: BTreeMap<String, u64>,
+}
+
+impl UsageStats {
+    /// Load the metrics file; a missing or unreadable file starts empty
+    pub fn load() -> Result<Self> {
+        let path = usage_path()?;
+        Ok(fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = usage_path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("creating state directory {}", parent.display()))?;
+        }
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(&path, content).with_context(|| format!("writing usage to {}", path.display()))
+    }
+
+    /// Add one run of `command`
+    pub fn record(&mut self, command: &str, elapsed: Duration, error_code: Option<&str>) {
+        self.since.get_or_insert_with(|| Utc::now().to_rfc3339());
+        let usage = self.commands.entry(command.to_string()).or_default();
+        let ms = u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX);
+        usage.count += 1;
+        usage.total_ms = usage.total_ms.saturating_add(ms);
+        usage.max_ms = usage.max_ms.max(ms);
+        if let Some(code) = error_code {
+            *usage.errors.entry(code.to_string()).or_default() += 1;
+        }
+    }
+}
+
+/// Record a finished command; failures to write are only logged
+pub fn record(command: &str, elapsed: Duration, result: &Result<()>) {
+    let error_code = result
+        .as_ref()
+        .err()
+        .map(|err| error::find(err).map_or("error", error::Error::code));
+    let update = || -> Result<()> {
+        let mut stats = UsageStats::load()?;
+        stats.record(command, elapsed, error_code);
+        stats.save()
+    };
+    if let Err(e) = update() {
+        log::debug!("Not recording usage: {e:#}");
+    }
+}
+
+/// The subcommand path of a parsed command line, without any arguments
+pub fn command_name(matches: &ArgMatches) -> String {
+    let mut names = Vec::new();
+    let mut current = matches;
+    while let Some((name, sub)) = current.subcommand() {
+        names.push(name);
+        current = sub;
+    }
+    names.join(" ")
+}
+
+pub fn usage_path() -> Result<PathBuf> {
+    Ok(state_dir()?.join("usage.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    use crate::cli::Cli;
+
+    #[test]
+    fn test_command_name_has_no_arguments() {
+        let matches = Cli::command()
+            .try_get_matches_from(["hmr", "--json", "entity", "get", "light.bedroom"])
+            .unwrap();
+        assert_eq!(command_name(&matches), "entity get");
+    }
+
+    #[test]
+    fn test_record() {
+        let mut stats = UsageStats::default();
+        stats.record("entity list", Duration::from_millis(120), None);
+        stats.record("entity list", Duration::from_millis(80), Some("network"));
+
+        let usage = &stats.commands["entity list"];
+        assert_eq!(usage.count, 2);
+        assert_eq!(usage.total_ms, 200);
+        assert_eq!(usage.max_ms, 120);
+        assert_eq!(usage.errors["network"], 1);
+        assert!(stats.since.is_some());
+    }
+}
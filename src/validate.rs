@@ -0,0 +1,161 @@
+//! Validation of entity IDs and service names typed on the command line
+//!
+//! Commands that pass an ID straight to the API check it first: the syntax
+//! offline, then against the cache, refreshing it once before giving up so
+//! new entities aren't rejected. Unknown IDs fail with "did you mean"
+//! suggestions from the fuzzy matcher instead of the API's bare 404. When
+//! the cache can't be loaded or refreshed only the syntax is checked.
+
+use std::sync::LazyLock;
+
+use anyhow::Result;
+use regex::Regex;
+
+use crate::cache::{Cache, CacheManager};
+use crate::config::RuntimeContext;
+use crate::error::Error;
+use crate::fuzzy::{FuzzyMatcher, MatchResult};
+
+/// `domain.object_id`, also the shape of `domain.service`
+static DOTTED_ID: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[a-z0-9_]+\.[a-z0-9_]+$").unwrap());
+
+/// Most suggestions listed in an error
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Check that every ID is a known entity
+pub async fn entity_ids(ctx: &RuntimeContext, ids: &[String]) -> Result<()> {
+    for id in ids {
+        check_syntax("entity ID", id, "light.kitchen")?;
+    }
+    let mut cache_manager = CacheManager::new(ctx)?;
+    if let Err(e) = cache_manager.ensure_entities().await {
+        log::debug!("Entity cache unavailable, checking syntax only: {e:#}");
+        return Ok(());
+    }
+    if ids
+        .iter()
+        .any(|id| unknown_entity(cache_manager.cache(), id).is_some())
+    {
+        if let Err(e) = cache_manager.refresh_entities().await {
+            log::debug!("Could not refresh entities, accepting the IDs: {e:#}");
+            return Ok(());
+        }
+    }
+    match ids
+        .iter()
+        .find_map(|id| unknown_entity(cache_manager.cache(), id))
+    {
+        Some(error) => Err(error.into()),
+        None => Ok(()),
+    }
+}
+
+/// Check that `service` is a known `domain.service`; returns its two parts
+pub async fn service<'a>(ctx: &RuntimeContext, service: &'a str) -> Result<(&'a str, &'a str)> {
+    check_syntax("service", service, "light.turn_on")?;
+    let parts = service.split_once('.').unwrap_or_default();
+    let mut cache_manager = CacheManager::new(ctx)?;
+    if let Err(e) = cache_manager.ensure_services().await {
+        log::debug!("Service cache unavailable, checking syntax only: {e:#}");
+        return Ok(parts);
+    }
+    if unknown_service(cache_manager.cache(), service).is_some() {
+        if let Err(e) = cache_manager.refresh_services().await {
+            log::debug!("Could not refresh services, accepting '{service}': {e:#}");
+            return Ok(parts);
+        }
+    }
+    match unknown_service(cache_manager.cache(), service) {
+        Some(error) => Err(error.into()),
+        None => Ok(parts),
+    }
+}
+
+fn check_syntax(kind: &str, input: &str, example: &str) -> Result<()> {
+    if DOTTED_ID.is_match(input) {
+        return Ok(());
+    }
+    Err(anyhow::anyhow!(
+        "Invalid {kind} '{input}': expected lowercase letters, digits and underscores \
+         around one dot (e.g., {example})"
+    ))
+}
+
+/// The error for an entity ID the cache doesn't know
+fn unknown_entity(cache: &Cache, id: &str) -> Option<Error> {
+    if cache.get_entity(id).is_some() {
+        return None;
+    }
+    let suggestions = match FuzzyMatcher::new().find_entity(id, cache) {
+        MatchResult::Single(m) => vec![m.item.entity_id.clone()],
+        MatchResult::Multiple(matches) => matches
+            .iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|m| m.item.entity_id.clone())
+            .collect(),
+        MatchResult::None => Vec::new(),
+    };
+    Some(not_found("entity", id, &suggestions))
+}
+
+/// The error for a service the cache doesn't know
+fn unknown_service(cache: &Cache, service: &str) -> Option<Error> {
+    if cache.services().iter().any(|s| s.full_name == service) {
+        return None;
+    }
+    let suggestions = match FuzzyMatcher::new().find_service(service, cache) {
+        MatchResult::Single(m) => vec![m.item.full_name.clone()],
+        MatchResult::Multiple(matches) => matches
+            .iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|m| m.item.full_name.clone())
+            .collect(),
+        MatchResult::None => Vec::new(),
+    };
+    Some(not_found("service", service, &suggestions))
+}
+
+fn not_found(kind: &str, input: &str, suggestions: &[String]) -> Error {
+    match suggestions {
+        [] => Error::NotFound(format!("Unknown {kind} '{input}'")),
+        _ => Error::NotFound(format!(
+            "Unknown {kind} '{input}'. Did you mean: {}?",
+            suggestions.join(", ")
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::{CacheFile, CachedEntity};
+
+    #[test]
+    fn test_unknown_entity() {
+        assert!(check_syntax("entity ID", "light.kitchen", "").is_ok());
+        assert!(check_syntax("entity ID", "Kitchen Light", "").is_err());
+        assert!(check_syntax("entity ID", "light.kitchen.main", "").is_err());
+
+        let mut cache = Cache::new();
+        cache.set_entities(CacheFile::new(
+            vec![CachedEntity {
+                friendly_name: Some("Kitchen Light".to_string()),
+                search_names: vec!["light.kitchen".to_string(), "kitchen".to_string()],
+                ..CachedEntity::for_test("light.kitchen", "on")
+            }],
+            3600,
+            String::new(),
+        ));
+
+        assert!(unknown_entity(&cache, "light.kitchen").is_none());
+        assert_eq!(
+            unknown_entity(&cache, "light.kitchn").map(|e| e.to_string()),
+            Some("Unknown entity 'light.kitchn'. Did you mean: light.kitchen?".to_string())
+        );
+        assert_eq!(
+            unknown_entity(&cache, "lock.front_door").map(|e| e.to_string()),
+            Some("Unknown entity 'lock.front_door'".to_string())
+        );
+    }
+}
@@ -0,0 +1,193 @@
+//! Stream health for long-running watches (`--stats`)
+//!
+//! Counts the events a watch receives, the ones it had to skip because they
+//! couldn't be parsed, and how often the connection was re-established. With
+//! stderr on a terminal the figures are kept on an inline status line below
+//! the watch output; otherwise (piped, JSON/YAML output, or `--plain`) a
+//! notice is written to stderr every half minute and whenever the connection
+//! state changes. Without `--stats` nothing is drawn.
+
+use std::io::IsTerminal;
+use std::time::{Duration, Instant};
+
+use indicatif::{ProgressBar, ProgressFinish, ProgressStyle};
+
+use crate::config::RuntimeContext;
+
+/// How often the status line is redrawn
+pub const REFRESH: Duration = Duration::from_secs(1);
+
+/// Span the events/sec rate is measured over
+const RATE_WINDOW: Duration = Duration::from_secs(5);
+
+/// Time between stderr notices when there is no status line
+const NOTICE_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamState {
+    Connected,
+    Reconnecting,
+}
+
+enum Display {
+    Off,
+    Line(ProgressBar),
+    Notices { last: Instant },
+}
+
+pub struct WatchStats {
+    state: StreamState,
+    events: u64,
+    skipped: u64,
+    reconnects: u32,
+    window_start: Instant,
+    window_events: u64,
+    rate: f64,
+    display: Display,
+}
+
+impl WatchStats {
+    /// Stats for a watch; `enabled` is the `--stats` flag
+    pub fn new(ctx: &RuntimeContext, enabled: bool) -> Self {
+        let display = if !enabled {
+            Display::Off
+        } else if !ctx.is_machine_output() && !ctx.global.plain && std::io::stderr().is_terminal() {
+            let line = ProgressBar::new_spinner()
+                .with_style(
+                    ProgressStyle::with_template("{msg:.dim}")
+                        .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+                )
+                .with_finish(ProgressFinish::AndClear);
+            Display::Line(line)
+        } else {
+            Display::Notices {
+                last: Instant::now(),
+            }
+        };
+        let stats = Self::counting(display, Instant::now());
+        stats.redraw();
+        stats
+    }
+
+    fn counting(display: Display, now: Instant) -> Self {
+        Self {
+            state: StreamState::Connected,
+            events: 0,
+            skipped: 0,
+            reconnects: 0,
+            window_start: now,
+            window_events: 0,
+            rate: 0.0,
+            display,
+        }
+    }
+
+    /// An event arrived
+    pub fn event(&mut self) {
+        self.events += 1;
+        self.window_events += 1;
+    }
+
+    /// An event arrived but couldn't be parsed, so it was skipped
+    pub fn skipped(&mut self) {
+        self.skipped += 1;
+    }
+
+    pub fn set_state(&mut self, state: StreamState) {
+        if state == self.state {
+            return;
+        }
+        if state == StreamState::Connected {
+            self.reconnects += 1;
+        }
+        self.state = state;
+        if let Display::Notices { last } = &mut self.display {
+            *last = Instant::now();
+            eprintln!("Stream: {}", self.summary());
+        }
+        self.redraw();
+    }
+
+    /// Update the rate and the display; call every [`REFRESH`]
+    pub fn tick(&mut self) {
+        let now = Instant::now();
+        self.roll(now);
+        if let Display::Notices { last } = &mut self.display {
+            if now.duration_since(*last) >= NOTICE_INTERVAL {
+                *last = now;
+                eprintln!("Stream: {}", self.summary());
+            }
+        }
+        self.redraw();
+    }
+
+    /// Run `f`, which writes watch output, with the status line hidden
+    pub fn suspend<R>(&self, f: impl FnOnce() -> R) -> R {
+        match &self.display {
+            Display::Line(line) => line.suspend(f),
+            _ => f(),
+        }
+    }
+
+    /// Close the rate window once it has run its span
+    fn roll(&mut self, now: Instant) {
+        let elapsed = now.duration_since(self.window_start);
+        if elapsed < RATE_WINDOW {
+            return;
+        }
+        self.rate = self.window_events as f64 / elapsed.as_secs_f64();
+        self.window_start = now;
+        self.window_events = 0;
+    }
+
+    fn redraw(&self) {
+        if let Display::Line(line) = &self.display {
+            line.set_message(self.summary());
+        }
+    }
+
+    /// "connected · 2.4 events/s · 130 events · 0 skipped · 1 reconnect"
+    fn summary(&self) -> String {
+        let state = match self.state {
+            StreamState::Connected => "connected",
+            StreamState::Reconnecting => "reconnecting",
+        };
+        let reconnects = match self.reconnects {
+            1 => "1 reconnect".to_string(),
+            n => format!("{n} reconnects"),
+        };
+        format!(
+            "{state} · {:.1} events/s · {} events · {} skipped · {reconnects}",
+            self.rate, self.events, self.skipped
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let start = Instant::now();
+        let mut stats = WatchStats::counting(Display::Off, start);
+        for _ in 0..12 {
+            stats.event();
+        }
+        stats.skipped();
+
+        stats.roll(start + Duration::from_secs(2));
+        assert_eq!(
+            stats.summary(),
+            "connected · 0.0 events/s · 12 events · 1 skipped · 0 reconnects"
+        );
+
+        stats.roll(start + RATE_WINDOW);
+        stats.set_state(StreamState::Reconnecting);
+        stats.set_state(StreamState::Connected);
+        assert_eq!(
+            stats.summary(),
+            "connected · 2.4 events/s · 12 events · 1 skipped · 1 reconnect"
+        );
+    }
+}
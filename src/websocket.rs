@@ -1,18 +1,32 @@
 //! Home Assistant WebSocket API client
 //!
 //! Handles real-time event streaming and entity watching.
+//!
+//! A single connection is shared by one-shot requests and any number of
+//! subscriptions: a background router task delivers each `result` to the request
+//! that is waiting for its id, and each `event` to the subscription with that id.
 
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 use tokio::task::JoinHandle;
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
+use crate::api::{EntityState, HassClient};
 use crate::config::RuntimeContext;
+use crate::dump;
+use crate::error::{self, Error};
+use crate::output;
+use crate::watch_stats::{self, StreamState, WatchStats};
 
 /// WebSocket message types from Home Assistant
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,9 +51,11 @@ pub enum WsMessage {
         #[serde(default)]
         error: Option<WsError>,
     },
+    /// Event for a subscription. The payload shape depends on the subscription type
+    /// (`subscribe_events` payloads parse as [`WsEvent`]).
     Event {
         id: u64,
-        event: WsEvent,
+        event: Value,
     },
     Pong {
         id: u64,
@@ -63,22 +79,129 @@ pub struct WsEvent {
     pub context: Value,
 }
 
+/// Longest wait between attempts to reconnect a watch, unless
+/// `websocket.reconnect_delay` starts out longer
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Number of undelivered events buffered per subscription.
+///
+/// The bounded channel provides backpressure: if a subscriber falls behind, the
+/// router waits for it, which in turn stalls reading from the socket.
+const SUBSCRIPTION_BUFFER: usize = 32;
+
+/// Routing tables shared between the client and its receive task
+#[derive(Default)]
+struct Router {
+    /// Requests waiting for their `result`, keyed by request id
+    pending: Mutex<HashMap<u64, oneshot::Sender<WsMessage>>>,
+    /// Active subscriptions, keyed by the id of the subscribe request
+    subscriptions: Mutex<HashMap<u64, mpsc::Sender<Value>>>,
+}
+
+impl Router {
+    fn register_request(&self, id: u64) -> oneshot::Receiver<WsMessage> {
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, tx);
+        rx
+    }
+
+    fn cancel_request(&self, id: u64) {
+        self.pending.lock().unwrap().remove(&id);
+    }
+
+    fn register_subscription(&self, id: u64) -> mpsc::Receiver<Value> {
+        let (tx, rx) = mpsc::channel(SUBSCRIPTION_BUFFER);
+        self.subscriptions.lock().unwrap().insert(id, tx);
+        rx
+    }
+
+    fn remove_subscription(&self, id: u64) {
+        self.subscriptions.lock().unwrap().remove(&id);
+    }
+
+    /// Deliver a message to whoever is waiting for its id
+    async fn dispatch(&self, msg: WsMessage) {
+        match msg {
+            WsMessage::Result { id, .. } | WsMessage::Pong { id } => {
+                let waiter = self.pending.lock().unwrap().remove(&id);
+                match waiter {
+                    Some(tx) => {
+                        let _ = tx.send(msg);
+                    }
+                    None => log::debug!("Dropping reply to unknown or abandoned request {id}"),
+                }
+            }
+            WsMessage::Event { id, event } => {
+                // Clone the sender so the lock is not held across the await
+                let sub = self.subscriptions.lock().unwrap().get(&id).cloned();
+                match sub {
+                    Some(tx) => {
+                        if tx.send(event).await.is_err() {
+                            log::debug!("Subscription {id} dropped, no longer routing its events");
+                            self.remove_subscription(id);
+                        }
+                    }
+                    None => log::debug!("Dropping event for unknown subscription {id}"),
+                }
+            }
+            other => log::debug!("Ignoring unexpected WebSocket message: {other:?}"),
+        }
+    }
+
+    /// Fail all waiters once the connection is gone
+    fn close(&self) {
+        self.pending.lock().unwrap().clear();
+        self.subscriptions.lock().unwrap().clear();
+    }
+}
+
+/// A live subscription on a [`WsClient`] connection
+pub struct Subscription {
+    id: u64,
+    events: mpsc::Receiver<Value>,
+}
+
+impl Subscription {
+    /// The id of the subscribe request (used by Home Assistant to tag events)
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Receive the next raw event payload. Returns None once the connection closes.
+    pub async fn next_raw(&mut self) -> Option<Value> {
+        self.events.recv().await
+    }
+
+    /// Receive the next bus event. Returns None once the connection closes.
+    pub async fn next_event(&mut self) -> Result<Option<WsEvent>> {
+        match self.events.recv().await {
+            Some(event) => serde_json::from_value(event)
+                .map(Some)
+                .context("parsing subscription event"),
+            None => Ok(None),
+        }
+    }
+}
+
 /// Home Assistant WebSocket client
 pub struct WsClient {
-    sender: mpsc::Sender<String>,
-    receiver: mpsc::Receiver<WsMessage>,
-    msg_id: u64,
+    sender: mpsc::Sender<Message>,
+    router: Arc<Router>,
+    msg_id: AtomicU64,
     /// Handle to the sender task for error detection
     send_task: JoinHandle<()>,
     /// Handle to the receiver task for error detection
     recv_task: JoinHandle<()>,
+    /// How long to wait for the reply to a single request
+    request_timeout: Duration,
 }
 
 impl WsClient {
     /// Connect to Home Assistant WebSocket API
     pub async fn connect(ctx: &RuntimeContext) -> Result<Self> {
         let server_url = ctx.server_url()?;
-        let token = ctx.token()?.to_string();
+        let token = HassClient::new(ctx)?.access_token().await?;
+        let request_timeout = Duration::from_secs(ctx.timeout());
 
         // Convert HTTP URL to WebSocket URL
         let ws_url = http_to_ws_url(server_url);
@@ -89,42 +212,74 @@ impl WsClient {
         // Use string directly - tokio-tungstenite accepts &str
         let (ws_stream, _) = connect_async(&ws_url)
             .await
-            .context("connecting to WebSocket")?;
+            .map_err(|e| Error::network(format!("connecting to {ws_url}"), e))?;
 
         let (mut write, mut read) = ws_stream.split();
 
-        // Create channels for communication.
-        // The bounded channels provide natural backpressure - if events arrive faster
-        // than they can be processed, the sender will block until space is available.
-        // This prevents unbounded memory growth at the cost of potentially dropping
-        // the WebSocket connection if the receiver is too slow.
-        let (tx_send, mut rx_send) = mpsc::channel::<String>(32);
-        let (tx_recv, rx_recv) = mpsc::channel::<WsMessage>(32);
+        // Authenticate before handing the stream over to the router
+        match read_handshake_message(&mut read, request_timeout).await? {
+            WsMessage::AuthRequired { ha_version } => {
+                log::debug!("Connected to Home Assistant {ha_version}");
+            }
+            _ => {
+                return Err(Error::WsProtocol(
+                    "unexpected message, expected auth_required".to_string(),
+                )
+                .into())
+            }
+        }
+
+        // Send auth message
+        let auth_msg = json!({
+            "type": "auth",
+            "access_token": token
+        });
+        let auth_text = auth_msg.to_string();
+        dump::ws_sent(&auth_text);
+        write
+            .send(Message::Text(auth_text))
+            .await
+            .context("sending WebSocket auth message")?;
+
+        // Wait for auth response
+        match read_handshake_message(&mut read, request_timeout).await? {
+            WsMessage::AuthOk { ha_version } => {
+                log::info!("Authenticated with Home Assistant {ha_version}");
+            }
+            WsMessage::AuthInvalid { message } => {
+                return Err(Error::Auth(format!("Authentication failed: {message}")).into());
+            }
+            _ => return Err(Error::WsProtocol("unexpected auth response".to_string()).into()),
+        }
+
+        // The bounded outgoing channel provides backpressure for senders.
+        let (tx_send, mut rx_send) = mpsc::channel::<Message>(32);
 
         // Spawn task to handle sending messages
         // Store the JoinHandle so we can detect task panics
-        let tx_send_clone = tx_send.clone();
         let send_task = tokio::spawn(async move {
             while let Some(msg) = rx_send.recv().await {
-                if write.send(Message::Text(msg)).await.is_err() {
+                match &msg {
+                    Message::Text(text) => dump::ws_sent(text),
+                    other => log::trace!("Sending {} byte binary frame", other.len()),
+                }
+                if write.send(msg).await.is_err() {
                     log::debug!("WebSocket send task: connection closed");
                     break;
                 }
             }
         });
 
-        // Spawn task to handle receiving messages
+        // Spawn the router task that delivers incoming messages by id
         // Store the JoinHandle so we can detect task panics
+        let router = Arc::new(Router::default());
+        let task_router = Arc::clone(&router);
         let recv_task = tokio::spawn(async move {
             while let Some(Ok(msg)) = read.next().await {
                 if let Message::Text(text) = msg {
+                    dump::ws_received(&text);
                     match serde_json::from_str::<WsMessage>(&text) {
-                        Ok(ws_msg) => {
-                            if tx_recv.send(ws_msg).await.is_err() {
-                                log::debug!("WebSocket recv task: receiver dropped");
-                                break;
-                            }
-                        }
+                        Ok(ws_msg) => task_router.dispatch(ws_msg).await,
                         Err(e) => {
                             log::debug!("Failed to parse WebSocket message: {e}");
                             log::trace!("Malformed message content: {text}");
@@ -133,89 +288,116 @@ impl WsClient {
                 }
             }
             log::debug!("WebSocket recv task: stream ended");
+            task_router.close();
         });
 
-        let mut client = Self {
-            sender: tx_send_clone,
-            receiver: rx_recv,
-            msg_id: 0,
+        Ok(Self::from_parts(
+            tx_send,
+            router,
             send_task,
             recv_task,
-        };
-
-        // Wait for auth_required
-        let auth_required = client.receive().await?;
-        match auth_required {
-            WsMessage::AuthRequired { ha_version } => {
-                log::debug!("Connected to Home Assistant {ha_version}");
-            }
-            _ => return Err(anyhow!("unexpected message, expected auth_required")),
-        }
-
-        // Send auth message
-        let auth_msg = json!({
-            "type": "auth",
-            "access_token": token
-        });
-        client.send_raw(&auth_msg.to_string()).await?;
+            request_timeout,
+        ))
+    }
 
-        // Wait for auth response
-        let auth_response = client.receive().await?;
-        match auth_response {
-            WsMessage::AuthOk { ha_version } => {
-                log::info!("Authenticated with Home Assistant {ha_version}");
-            }
-            WsMessage::AuthInvalid { message } => {
-                return Err(anyhow!("Authentication failed: {message}"));
-            }
-            _ => return Err(anyhow!("unexpected auth response")),
+    fn from_parts(
+        sender: mpsc::Sender<Message>,
+        router: Arc<Router>,
+        send_task: JoinHandle<()>,
+        recv_task: JoinHandle<()>,
+        request_timeout: Duration,
+    ) -> Self {
+        Self {
+            sender,
+            router,
+            msg_id: AtomicU64::new(0),
+            send_task,
+            recv_task,
+            request_timeout,
         }
-
-        Ok(client)
     }
 
     /// Send a raw string message, accepting owned or borrowed strings efficiently.
     async fn send_raw<'a>(&self, msg: impl Into<Cow<'a, str>>) -> Result<()> {
+        self.send_message(Message::Text(msg.into().into_owned()))
+            .await
+    }
+
+    async fn send_message(&self, msg: Message) -> Result<()> {
         // Check if the background tasks are still alive
         if self.send_task.is_finished() {
             return Err(anyhow!("WebSocket send task has terminated unexpectedly"));
         }
 
         self.sender
-            .send(msg.into().into_owned())
+            .send(msg)
             .await
             .context("sending WebSocket message")
     }
 
-    fn next_id(&mut self) -> u64 {
-        self.msg_id += 1;
-        self.msg_id
-    }
-
-    async fn send(&mut self, msg: &Value) -> Result<u64> {
-        let id = self.next_id();
-        let mut msg = msg.clone();
-        msg["id"] = json!(id);
-        self.send_raw(msg.to_string()).await?;
-        Ok(id)
+    fn next_id(&self) -> u64 {
+        self.msg_id.fetch_add(1, Ordering::Relaxed) + 1
     }
 
-    async fn receive(&mut self) -> Result<WsMessage> {
-        // Check if the receive task has panicked or terminated
+    /// Send a request with the given id and wait for its reply.
+    ///
+    /// If no reply arrives within the request timeout the request is abandoned;
+    /// a late reply is then dropped by the router instead of being mis-routed.
+    async fn request_with_id(&self, id: u64, msg: &Value) -> Result<WsMessage> {
         if self.recv_task.is_finished() {
             return Err(anyhow!(
                 "WebSocket receive task has terminated unexpectedly"
             ));
         }
 
-        self.receiver
-            .recv()
-            .await
-            .ok_or_else(|| anyhow!("WebSocket connection closed"))
+        let reply = self.router.register_request(id);
+
+        let mut msg = msg.clone();
+        msg["id"] = json!(id);
+        if let Err(e) = self.send_raw(msg.to_string()).await {
+            self.router.cancel_request(id);
+            return Err(e);
+        }
+
+        match tokio::time::timeout(self.request_timeout, reply).await {
+            Ok(Ok(reply)) => Ok(reply),
+            Ok(Err(_)) => Err(anyhow!("WebSocket connection closed")),
+            Err(_) => {
+                self.router.cancel_request(id);
+                Err(anyhow!(
+                    "Timed out after {}s waiting for reply to WebSocket request {id}",
+                    self.request_timeout.as_secs()
+                ))
+            }
+        }
+    }
+
+    /// Open a subscription and wait for Home Assistant to confirm it
+    async fn subscribe(&self, msg: &Value) -> Result<Subscription> {
+        let id = self.next_id();
+        // Register before sending so no event can arrive ahead of its channel
+        let events = self.router.register_subscription(id);
+
+        let confirmed = match self.request_with_id(id, msg).await {
+            Ok(WsMessage::Result { success: true, .. }) => Ok(()),
+            Ok(WsMessage::Result {
+                error: Some(err), ..
+            }) => Err(anyhow!("Subscription failed: {}", err.message)),
+            Ok(_) => Err(anyhow!("Subscription failed")),
+            Err(e) => Err(e),
+        };
+
+        if let Err(e) = confirmed {
+            self.router.remove_subscription(id);
+            return Err(e);
+        }
+
+        log::debug!("Subscription {id} confirmed");
+        Ok(Subscription { id, events })
     }
 
-    /// Subscribe to all events
-    pub async fn subscribe_events(&mut self, event_type: Option<&str>) -> Result<u64> {
+    /// Subscribe to all events, or only those of `event_type`
+    pub async fn subscribe_events(&self, event_type: Option<&str>) -> Result<Subscription> {
         let mut msg = json!({
             "type": "subscribe_events"
         });
@@ -224,77 +406,290 @@ impl WsClient {
             msg["event_type"] = json!(et);
         }
 
-        self.send(&msg).await
+        self.subscribe(&msg).await
     }
 
-    /// Subscribe to state changes for specific entities
+    /// Subscribe to compressed state updates for specific entities
+    ///
+    /// Events on this subscription are raw `subscribe_entities` payloads; read
+    /// them with [`Subscription::next_raw`].
     #[allow(dead_code)]
-    pub async fn subscribe_entities(&mut self, entity_ids: &[String]) -> Result<u64> {
+    pub async fn subscribe_entities(&self, entity_ids: &[String]) -> Result<Subscription> {
         let msg = json!({
             "type": "subscribe_entities",
             "entity_ids": entity_ids
         });
 
-        self.send(&msg).await
+        self.subscribe(&msg).await
     }
 
-    /// Receive the next event
-    pub async fn next_event(&mut self) -> Result<WsMessage> {
-        self.receive().await
+    /// Render a template and re-render whenever the entities it uses change
+    ///
+    /// Events are raw payloads with either a `result` or an `error` field; read
+    /// them with [`Subscription::next_raw`].
+    pub async fn subscribe_template(&self, template: &str) -> Result<Subscription> {
+        let msg = json!({
+            "type": "render_template",
+            "template": template,
+            "report_errors": true
+        });
+
+        self.subscribe(&msg).await
     }
 
-    /// Wait for a subscription confirmation message
-    pub async fn wait_for_subscription_confirmation(&mut self, sub_id: u64) -> Result<()> {
-        loop {
-            match self.next_event().await? {
-                WsMessage::Result {
-                    id, success, error, ..
-                } if id == sub_id => {
-                    if !success {
-                        if let Some(err) = error {
-                            return Err(anyhow!("Subscription failed: {}", err.message));
-                        }
-                        return Err(anyhow!("Subscription failed"));
-                    }
-                    return Ok(());
-                }
-                _ => continue,
-            }
-        }
+    /// Cancel a subscription. Events still in flight for it are discarded.
+    pub async fn unsubscribe(&self, subscription: Subscription) -> Result<()> {
+        self.router.remove_subscription(subscription.id);
+        self.call_rpc(&json!({
+            "type": "unsubscribe_events",
+            "subscription": subscription.id
+        }))
+        .await?;
+        Ok(())
     }
 
     /// Call an RPC method and wait for the result
     ///
     /// This sends a message to Home Assistant and waits for a response with matching ID.
     /// Use this for registry operations like listing/creating/deleting areas and devices.
-    pub async fn call_rpc(&mut self, msg: &Value) -> Result<Value> {
-        let id = self.send(msg).await?;
+    pub async fn call_rpc(&self, msg: &Value) -> Result<Value> {
+        let id = self.next_id();
 
-        // Wait for the response with matching ID
-        loop {
-            match self.receive().await? {
-                WsMessage::Result {
-                    id: result_id,
-                    success,
-                    result,
-                    error,
-                } if result_id == id => {
-                    if success {
-                        return Ok(result);
-                    }
-                    if let Some(err) = error {
-                        return Err(anyhow!("RPC call failed: {} ({})", err.message, err.code));
-                    }
-                    return Err(anyhow!("RPC call failed without error details"));
-                }
-                // Ignore other messages while waiting for our response
-                _ => continue,
+        match self.request_with_id(id, msg).await? {
+            WsMessage::Result {
+                success: true,
+                result,
+                ..
+            } => Ok(result),
+            WsMessage::Result {
+                error: Some(err), ..
+            } => {
+                let message = match rpc_error_hint(&err.code) {
+                    Some(hint) => format!(
+                        "RPC call failed: {} ({})\nHint: {hint}",
+                        err.message, err.code
+                    ),
+                    None => format!("RPC call failed: {} ({})", err.message, err.code),
+                };
+                Err(match err.code.as_str() {
+                    "unauthorized" => Error::Auth(message).into(),
+                    "not_found" => Error::NotFound(message).into(),
+                    _ => anyhow!(message),
+                })
             }
+            _ => Err(Error::WsProtocol("RPC call failed without error details".to_string()).into()),
+        }
+    }
+
+    /// The user the token belongs to
+    pub async fn current_user(&self) -> Result<CurrentUser> {
+        let msg = json!({
+            "type": "auth/current_user"
+        });
+
+        let result = self.call_rpc(&msg).await?;
+        serde_json::from_value(result).context("parsing current user response")
+    }
+
+    /// List the Home Assistant users (admin only)
+    pub async fn list_users(&self) -> Result<Vec<User>> {
+        let msg = json!({
+            "type": "config/auth/list"
+        });
+
+        let result = self.call_rpc(&msg).await?;
+        serde_json::from_value(result).context("parsing user list response")
+    }
+
+    /// The energy dashboard configuration: grid, solar, battery and gas sources
+    pub async fn get_energy_prefs(&self) -> Result<EnergyPrefs> {
+        let result = self
+            .call_rpc(&json!({ "type": "energy/get_prefs" }))
+            .await?;
+        serde_json::from_value(result).context("parsing energy preferences")
+    }
+
+    /// Cost statistics Home Assistant keeps for energy sources with a price,
+    /// keyed by the energy statistic they belong to
+    pub async fn get_energy_cost_sensors(&self) -> Result<HashMap<String, String>> {
+        let result = self.call_rpc(&json!({ "type": "energy/info" })).await?;
+        serde_json::from_value(result["cost_sensors"].clone()).context("parsing energy info")
+    }
+
+    /// Long-term statistics between `start` and `end` (RFC 3339) in buckets of
+    /// `period` ("hour", "day", "month"), with energy in kWh
+    pub async fn statistics_during_period(
+        &self,
+        statistic_ids: &[String],
+        start: &str,
+        end: &str,
+        period: &str,
+    ) -> Result<HashMap<String, Vec<StatisticPoint>>> {
+        let result = self
+            .call_rpc(&json!({
+                "type": "recorder/statistics_during_period",
+                "statistic_ids": statistic_ids,
+                "start_time": start,
+                "end_time": end,
+                "period": period,
+                "types": ["change"],
+                "units": {"energy": "kWh"}
+            }))
+            .await?;
+        serde_json::from_value(result).context("parsing statistics")
+    }
+
+    /// List the conversation agents, optionally only those supporting `language`
+    pub async fn list_conversation_agents(
+        &self,
+        language: Option<&str>,
+    ) -> Result<Vec<ConversationAgent>> {
+        let mut msg = json!({
+            "type": "conversation/agent/list"
+        });
+        if let Some(language) = language {
+            msg["language"] = json!(language);
+        }
+
+        let result = self.call_rpc(&msg).await?;
+        let agents = result.get("agents").cloned().unwrap_or_else(|| json!([]));
+        serde_json::from_value(agents).context("parsing conversation agent list")
+    }
+
+    /// List the Assist pipelines and which one is preferred
+    pub async fn list_assist_pipelines(&self) -> Result<AssistPipelineList> {
+        let msg = json!({
+            "type": "assist_pipeline/pipeline/list"
+        });
+
+        let result = self.call_rpc(&msg).await?;
+        serde_json::from_value(result).context("parsing assist pipeline list")
+    }
+
+    /// List the Lovelace dashboards besides the default one
+    pub async fn list_dashboards(&self) -> Result<Vec<Dashboard>> {
+        let msg = json!({
+            "type": "lovelace/dashboards/list"
+        });
+
+        let result = self.call_rpc(&msg).await?;
+        serde_json::from_value(result).context("parsing dashboard list")
+    }
+
+    /// Lovelace config of a dashboard; `None` is the default dashboard
+    pub async fn get_lovelace_config(&self, url_path: Option<&str>) -> Result<Value> {
+        let msg = json!({
+            "type": "lovelace/config",
+            "url_path": url_path,
+        });
+
+        self.call_rpc(&msg).await
+    }
+
+    /// List the resources (custom cards, themes, ...) Lovelace loads
+    pub async fn list_lovelace_resources(&self) -> Result<Vec<LovelaceResource>> {
+        let msg = json!({
+            "type": "lovelace/resources"
+        });
+
+        let result = self.call_rpc(&msg).await?;
+        serde_json::from_value(result).context("parsing Lovelace resource list")
+    }
+
+    /// List the installed blueprints of a domain ("automation" or "script")
+    pub async fn list_blueprints(&self, domain: &str) -> Result<Vec<Blueprint>> {
+        let msg = json!({
+            "type": "blueprint/list",
+            "domain": domain,
+        });
+
+        let result = self.call_rpc(&msg).await?;
+        let listed: HashMap<String, Value> =
+            serde_json::from_value(result).context("parsing blueprint list")?;
+        Ok(listed
+            .into_iter()
+            .map(|(path, entry)| Blueprint::from_listing(domain, path, &entry))
+            .collect())
+    }
+
+    /// Fetch and validate a blueprint from a URL without installing it
+    pub async fn import_blueprint(&self, url: &str) -> Result<ImportedBlueprint> {
+        let msg = json!({
+            "type": "blueprint/import",
+            "url": url,
+        });
+
+        let result = self.call_rpc(&msg).await?;
+        serde_json::from_value(result).context("parsing imported blueprint")
+    }
+
+    /// Install an imported blueprint
+    pub async fn save_blueprint(
+        &self,
+        blueprint: &ImportedBlueprint,
+        source_url: &str,
+        allow_override: bool,
+    ) -> Result<()> {
+        let msg = json!({
+            "type": "blueprint/save",
+            "domain": blueprint.blueprint.metadata.domain,
+            "path": blueprint.suggested_filename,
+            "yaml": blueprint.raw_data,
+            "source_url": source_url,
+            "allow_override": allow_override,
+        });
+
+        self.call_rpc(&msg).await?;
+        Ok(())
+    }
+
+    /// Run an Assist pipeline from `start_stage` to `end_stage`
+    ///
+    /// The returned subscription yields one payload per pipeline event
+    /// (`run-start`, `stt-end`, `intent-end`, ..., `run-end` or `error`).
+    pub async fn run_assist_pipeline(
+        &self,
+        pipeline: Option<&str>,
+        start_stage: &str,
+        end_stage: &str,
+        input: Value,
+    ) -> Result<Subscription> {
+        let mut msg = json!({
+            "type": "assist_pipeline/run",
+            "start_stage": start_stage,
+            "end_stage": end_stage,
+            "input": input,
+        });
+        if let Some(pipeline) = pipeline {
+            msg["pipeline"] = json!(pipeline);
         }
+
+        self.subscribe(&msg).await
+    }
+
+    /// Start collecting system health info
+    ///
+    /// The subscription yields an `initial` payload with every integration's
+    /// checks, where slow ones are `{"type": "pending"}`, then one `update`
+    /// per pending check as it completes, then `finish`.
+    pub async fn system_health_info(&self) -> Result<Subscription> {
+        self.subscribe(&json!({ "type": "system_health/info" }))
+            .await
+    }
+
+    /// Stream audio to a running pipeline's speech-to-text stage
+    ///
+    /// `handler_id` comes from the `run-start` event; an empty chunk marks
+    /// the end of the audio.
+    pub async fn send_stt_audio(&self, handler_id: u8, chunk: &[u8]) -> Result<()> {
+        let mut frame = Vec::with_capacity(chunk.len() + 1);
+        frame.push(handler_id);
+        frame.extend_from_slice(chunk);
+        self.send_message(Message::Binary(frame)).await
     }
 
     /// List all areas from the area registry
-    pub async fn list_areas(&mut self) -> Result<Vec<Area>> {
+    pub async fn list_areas(&self) -> Result<Vec<Area>> {
         let msg = json!({
             "type": "config/area_registry/list"
         });
@@ -303,8 +698,28 @@ impl WsClient {
         serde_json::from_value(result).context("parsing area list response")
     }
 
+    /// List all floors from the floor registry
+    pub async fn list_floors(&self) -> Result<Vec<Floor>> {
+        let msg = json!({
+            "type": "config/floor_registry/list"
+        });
+
+        let result = self.call_rpc(&msg).await?;
+        serde_json::from_value(result).context("parsing floor list response")
+    }
+
+    /// List all labels from the label registry
+    pub async fn list_labels(&self) -> Result<Vec<Label>> {
+        let msg = json!({
+            "type": "config/label_registry/list"
+        });
+
+        let result = self.call_rpc(&msg).await?;
+        serde_json::from_value(result).context("parsing label list response")
+    }
+
     /// Create a new area
-    pub async fn create_area(&mut self, request: &CreateAreaRequest) -> Result<Area> {
+    pub async fn create_area(&self, request: &CreateAreaRequest) -> Result<Area> {
         let msg = json!({
             "type": "config/area_registry/create",
             "name": request.name,
@@ -319,7 +734,7 @@ impl WsClient {
     }
 
     /// Delete an area by ID
-    pub async fn delete_area(&mut self, area_id: &str) -> Result<()> {
+    pub async fn delete_area(&self, area_id: &str) -> Result<()> {
         let msg = json!({
             "type": "config/area_registry/delete",
             "area_id": area_id
@@ -330,7 +745,7 @@ impl WsClient {
     }
 
     /// List all devices from the device registry
-    pub async fn list_devices(&mut self) -> Result<Vec<Device>> {
+    pub async fn list_devices(&self) -> Result<Vec<Device>> {
         let msg = json!({
             "type": "config/device_registry/list"
         });
@@ -339,8 +754,73 @@ impl WsClient {
         serde_json::from_value(result).context("parsing device list response")
     }
 
+    /// List all entries from the entity registry
+    pub async fn list_entity_registry(&self) -> Result<Vec<EntityRegistryEntry>> {
+        let msg = json!({
+            "type": "config/entity_registry/list"
+        });
+
+        let result = self.call_rpc(&msg).await?;
+        serde_json::from_value(result).context("parsing entity registry response")
+    }
+
+    /// Get one entry from the entity registry
+    pub async fn get_entity_registry_entry(&self, entity_id: &str) -> Result<EntityRegistryEntry> {
+        let msg = json!({
+            "type": "config/entity_registry/get",
+            "entity_id": entity_id,
+        });
+
+        let result = self.call_rpc(&msg).await?;
+        serde_json::from_value(result).context("parsing entity registry entry")
+    }
+
+    /// Disable (as the user) or enable a config entry; returns whether Home
+    /// Assistant must restart for the change to take effect
+    pub async fn set_config_entry_disabled(&self, entry_id: &str, disabled: bool) -> Result<bool> {
+        let result = self
+            .call_rpc(&json!({
+                "type": "config_entries/disable",
+                "entry_id": entry_id,
+                "disabled_by": disabled.then_some("user"),
+            }))
+            .await?;
+        Ok(result["require_restart"].as_bool().unwrap_or(false))
+    }
+
+    /// Update an entity's registry entry (name, area, aliases, disabled, hidden)
+    pub async fn update_entity_registry(
+        &self,
+        request: &UpdateEntityRequest,
+    ) -> Result<EntityRegistryEntry> {
+        let mut msg = json!({
+            "type": "config/entity_registry/update",
+            "entity_id": request.entity_id,
+        });
+
+        if let Some(name) = &request.name {
+            msg["name"] = json!(name);
+        }
+        if let Some(area_id) = &request.area_id {
+            msg["area_id"] = json!(area_id);
+        }
+        if let Some(aliases) = &request.aliases {
+            msg["aliases"] = json!(aliases);
+        }
+        if let Some(disabled_by) = &request.disabled_by {
+            msg["disabled_by"] = json!(disabled_by);
+        }
+        if let Some(hidden_by) = &request.hidden_by {
+            msg["hidden_by"] = json!(hidden_by);
+        }
+
+        let result = self.call_rpc(&msg).await?;
+        serde_json::from_value(result["entity_entry"].clone())
+            .context("parsing updated entity registry entry")
+    }
+
     /// Update a device's metadata
-    pub async fn update_device(&mut self, request: &UpdateDeviceRequest) -> Result<Device> {
+    pub async fn update_device(&self, request: &UpdateDeviceRequest) -> Result<Device> {
         let mut msg = json!({
             "type": "config/device_registry/update",
             "device_id": request.device_id,
@@ -359,81 +839,443 @@ impl WsClient {
         let result = self.call_rpc(&msg).await?;
         serde_json::from_value(result).context("parsing updated device response")
     }
-}
 
-/// Run an event watch loop
-pub async fn watch_events(
-    ctx: &RuntimeContext,
-    event_type: Option<&str>,
-    mut handler: impl FnMut(&WsEvent) -> Result<bool>,
-) -> Result<()> {
-    let mut client = WsClient::connect(ctx).await?;
-    let sub_id = client.subscribe_events(event_type).await?;
+    /// Call a service on one entity over the WebSocket connection
+    pub async fn call_service(
+        &self,
+        domain: &str,
+        service: &str,
+        entity_id: &str,
+        data: &Value,
+    ) -> Result<Value> {
+        let msg = json!({
+            "type": "call_service",
+            "domain": domain,
+            "service": service,
+            "target": { "entity_id": entity_id },
+            "service_data": data,
+        });
+
+        self.call_rpc(&msg).await
+    }
+
+    /// List the items of a todo list entity
+    pub async fn list_todo_items(&self, entity_id: &str) -> Result<Vec<TodoItem>> {
+        let msg = json!({
+            "type": "todo/item/list",
+            "entity_id": entity_id,
+        });
+
+        let result = self.call_rpc(&msg).await?;
+        let items = result.get("items").cloned().unwrap_or_else(|| json!([]));
+        serde_json::from_value(items).context("parsing todo item list response")
+    }
+
+    /// List the items on the shopping list integration's list
+    pub async fn list_shopping_items(&self) -> Result<Vec<ShoppingItem>> {
+        let msg = json!({
+            "type": "shopping_list/items"
+        });
 
-    log::debug!("Subscribed to events with id {sub_id}");
+        let result = self.call_rpc(&msg).await?;
+        serde_json::from_value(result).context("parsing shopping list response")
+    }
 
-    client.wait_for_subscription_confirmation(sub_id).await?;
+    /// Add an item to the shopping list
+    pub async fn add_shopping_item(&self, name: &str) -> Result<ShoppingItem> {
+        let msg = json!({
+            "type": "shopping_list/items/add",
+            "name": name,
+        });
+
+        let result = self.call_rpc(&msg).await?;
+        serde_json::from_value(result).context("parsing added shopping item")
+    }
+
+    /// List the persistent notifications, oldest first
+    ///
+    /// There is no plain list command: subscribing yields a `current` event
+    /// with every notification, after which the subscription is dropped.
+    pub async fn list_notifications(&self) -> Result<Vec<Notification>> {
+        let mut subscription = self
+            .subscribe(&json!({ "type": "persistent_notification/subscribe" }))
+            .await?;
+        let event = tokio::time::timeout(self.request_timeout, subscription.next_raw())
+            .await
+            .map_err(|_| anyhow!("Timed out waiting for the notification list"))?
+            .ok_or_else(|| anyhow!("Connection closed before the notification list arrived"))?;
+        self.unsubscribe(subscription).await?;
+
+        let notifications: HashMap<String, Notification> =
+            serde_json::from_value(event.get("notifications").cloned().unwrap_or_default())
+                .context("parsing notification list")?;
+        let mut notifications: Vec<Notification> = notifications.into_values().collect();
+        notifications.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        Ok(notifications)
+    }
+
+    /// Dismiss one persistent notification
+    pub async fn dismiss_notification(&self, notification_id: &str) -> Result<()> {
+        self.call_rpc(&json!({
+            "type": "call_service",
+            "domain": "persistent_notification",
+            "service": "dismiss",
+            "service_data": { "notification_id": notification_id },
+        }))
+        .await?;
+        Ok(())
+    }
+
+    /// Dismiss every persistent notification
+    pub async fn dismiss_all_notifications(&self) -> Result<()> {
+        self.call_rpc(&json!({
+            "type": "call_service",
+            "domain": "persistent_notification",
+            "service": "dismiss_all",
+        }))
+        .await?;
+        Ok(())
+    }
+
+    /// List the stored runs of an automation or script (oldest first)
+    pub async fn list_traces(&self, domain: &str, item_id: &str) -> Result<Vec<TraceSummary>> {
+        let msg = json!({
+            "type": "trace/list",
+            "domain": domain,
+            "item_id": item_id,
+        });
+
+        let result = self.call_rpc(&msg).await?;
+        serde_json::from_value(result).context("parsing trace list response")
+    }
+
+    /// Get one stored run of an automation or script, including every step
+    pub async fn get_trace(&self, domain: &str, item_id: &str, run_id: &str) -> Result<Value> {
+        let msg = json!({
+            "type": "trace/get",
+            "domain": domain,
+            "item_id": item_id,
+            "run_id": run_id,
+        });
+
+        self.call_rpc(&msg).await
+    }
+}
+
+/// Explain the WebSocket error codes that most often confuse non-admin users
+fn rpc_error_hint(code: &str) -> Option<&'static str> {
+    match code {
+        "unauthorized" => Some(
+            "This command needs a Home Assistant admin user; \
+             run `hmr auth scopes` to see what your token can do",
+        ),
+        "unknown_command" => Some(
+            "Home Assistant doesn't know this command; it may need a newer version \
+             or an integration that isn't loaded",
+        ),
+        _ => None,
+    }
+}
+
+/// Read the next text message during the auth handshake, before the router starts
+async fn read_handshake_message<S>(read: &mut S, timeout: Duration) -> Result<WsMessage>
+where
+    S: futures_util::Stream<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin,
+{
+    let next = async {
+        while let Some(msg) = read.next().await {
+            if let Message::Text(text) = msg.context("reading WebSocket message")? {
+                dump::ws_received(&text);
+                return serde_json::from_str::<WsMessage>(&text)
+                    .context("parsing WebSocket message");
+            }
+        }
+        Err(anyhow!("WebSocket connection closed during authentication"))
+    };
+
+    tokio::time::timeout(timeout, next).await.map_err(|_| {
+        anyhow!(
+            "Timed out after {}s waiting for WebSocket authentication",
+            timeout.as_secs()
+        )
+    })?
+}
+
+/// Run an event watch loop
+///
+/// A dropped connection is re-established; events fired while it was down
+/// are lost. `stats` shows stream health while watching (`--stats`).
+pub async fn watch_events(
+    ctx: &RuntimeContext,
+    event_type: Option<&str>,
+    stats: bool,
+    mut handler: impl FnMut(&WsEvent) -> Result<bool>,
+) -> Result<()> {
+    // The client must outlive its subscription, so it is held until the
+    // connection drops
+    let mut client = WsClient::connect(ctx).await?;
+    let mut subscription = client.subscribe_events(event_type).await?;
+
+    log::debug!("Subscribed to events with id {}", subscription.id());
+    let mut stats = WatchStats::new(ctx, stats);
+    let mut refresh = tokio::time::interval(watch_stats::REFRESH);
 
-    // Process events
     loop {
-        tokio::select! {
-            msg = client.next_event() => {
-                if let WsMessage::Event { event, .. } = msg? {
-                    if !handler(&event)? {
-                        break;
+        // Process events until the connection drops
+        loop {
+            tokio::select! {
+                event = subscription.next_event() => {
+                    let event = match event {
+                        Ok(Some(event)) => event,
+                        Ok(None) => break,
+                        Err(e) => {
+                            log::debug!("Skipping event: {e:#}");
+                            stats.skipped();
+                            continue;
+                        }
+                    };
+                    stats.event();
+                    if !stats.suspend(|| handler(&event))? {
+                        return Ok(());
                     }
                 }
+                _ = refresh.tick() => stats.tick(),
+                _ = tokio::signal::ctrl_c() => {
+                    log::debug!("Received Ctrl+C, stopping watch");
+                    return Ok(());
+                }
             }
-            _ = tokio::signal::ctrl_c() => {
-                log::debug!("Received Ctrl+C, stopping watch");
-                break;
+        }
+
+        if !ctx.config.websocket.reconnect {
+            return Err(anyhow!("WebSocket connection closed"));
+        }
+        // Release the dead connection's tasks before opening a new one
+        drop(client);
+        stats.suspend(|| output::warn(ctx, "Connection lost, reconnecting"));
+        stats.set_state(StreamState::Reconnecting);
+        tokio::select! {
+            reconnected = resubscribe(ctx, event_type) => {
+                (client, subscription) = reconnected?;
             }
+            _ = tokio::signal::ctrl_c() => return Ok(()),
         }
+        stats.set_state(StreamState::Connected);
+        stats.suspend(|| {
+            output::warn(
+                ctx,
+                "Reconnected; events fired while disconnected were missed",
+            )
+        });
     }
-
-    Ok(())
 }
 
 /// Run an entity watch loop
+///
+/// A dropped connection is re-established. Changes made while it was down
+/// are then fetched from the history API and passed to the handler, marked
+/// `"replayed": true`, before any live change; live events arriving meanwhile
+/// wait in the new subscription's buffer. `stats` shows stream health
+/// while watching (`--stats`).
 pub async fn watch_entities(
     ctx: &RuntimeContext,
     entity_ids: &[String],
+    stats: bool,
     mut handler: impl FnMut(&Value) -> Result<bool>,
 ) -> Result<()> {
+    // The client must outlive its subscription, so it is held until the
+    // connection drops
     let mut client = WsClient::connect(ctx).await?;
 
     // Subscribe to state_changed events
-    let sub_id = client.subscribe_events(Some("state_changed")).await?;
-
-    log::debug!("Subscribed to state_changed events with id {sub_id}");
+    let mut subscription = client.subscribe_events(Some("state_changed")).await?;
 
-    client.wait_for_subscription_confirmation(sub_id).await?;
+    log::debug!(
+        "Subscribed to state_changed events with id {}",
+        subscription.id()
+    );
 
     // Filter and process events
     let entity_set: std::collections::HashSet<&str> =
         entity_ids.iter().map(|s| s.as_str()).collect();
+    // The newest state handed to the handler per entity, so replayed changes
+    // get an old_state and changes seen both ways are only delivered once
+    let mut last_states: HashMap<String, Value> = HashMap::new();
+    let mut stats = WatchStats::new(ctx, stats);
+    let mut refresh = tokio::time::interval(watch_stats::REFRESH);
 
     loop {
-        tokio::select! {
-            msg = client.next_event() => {
-                if let WsMessage::Event { event, .. } = msg? {
-                    if event.event_type == "state_changed" {
-                        if let Some(entity_id) = event.data.get("entity_id").and_then(|v| v.as_str()) {
-                            if entity_set.contains(entity_id) && !handler(&event.data)? {
-                                break;
-                            }
+        let mut last_contact = Utc::now();
+        loop {
+            tokio::select! {
+                event = subscription.next_event() => {
+                    let event = match event {
+                        Ok(Some(event)) => event,
+                        Ok(None) => break,
+                        Err(e) => {
+                            log::debug!("Skipping event: {e:#}");
+                            stats.skipped();
+                            continue;
                         }
+                    };
+                    last_contact = Utc::now();
+                    let Some(entity_id) = event.data.get("entity_id").and_then(|v| v.as_str()) else {
+                        continue;
+                    };
+                    if !entity_set.contains(entity_id) {
+                        continue;
+                    }
+                    let new_state = &event.data["new_state"];
+                    if last_states.get(entity_id).is_some_and(|s| same_update(s, new_state)) {
+                        continue;
+                    }
+                    last_states.insert(entity_id.to_string(), new_state.clone());
+                    stats.event();
+                    if !stats.suspend(|| handler(&event.data))? {
+                        return Ok(());
                     }
                 }
+                _ = refresh.tick() => stats.tick(),
+                _ = tokio::signal::ctrl_c() => {
+                    log::debug!("Received Ctrl+C, stopping watch");
+                    return Ok(());
+                }
             }
-            _ = tokio::signal::ctrl_c() => {
-                log::debug!("Received Ctrl+C, stopping watch");
-                break;
+        }
+
+        if !ctx.config.websocket.reconnect {
+            return Err(anyhow!("WebSocket connection closed"));
+        }
+        // Release the dead connection's tasks before opening a new one
+        drop(client);
+        stats.suspend(|| output::warn(ctx, "Connection lost, reconnecting"));
+        stats.set_state(StreamState::Reconnecting);
+        tokio::select! {
+            reconnected = resubscribe(ctx, Some("state_changed")) => {
+                (client, subscription) = reconnected?;
+            }
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+        stats.set_state(StreamState::Connected);
+
+        let history = HassClient::new(ctx)?
+            .get_history_window(
+                entity_ids,
+                &last_contact.to_rfc3339(),
+                &Utc::now().to_rfc3339(),
+            )
+            .await;
+        let missed = match history {
+            Ok(history) => missed_changes(history, last_contact, &mut last_states),
+            Err(e) => {
+                stats.suspend(|| {
+                    output::warn(
+                        ctx,
+                        format!("Reconnected, but could not fetch missed changes: {e:#}"),
+                    )
+                });
+                continue;
+            }
+        };
+        stats.suspend(|| {
+            output::warn(
+                ctx,
+                format!("Reconnected; replaying {} missed changes", missed.len()),
+            )
+        });
+        for data in &missed {
+            stats.event();
+            if !stats.suspend(|| handler(data))? {
+                return Ok(());
             }
         }
     }
+}
+
+/// Connect and subscribe again after a dropped connection, backing off
+/// between attempts from `websocket.reconnect_delay` and giving up after
+/// `websocket.max_reconnect_attempts` (0 = never)
+///
+/// A rejected token is returned at once since retrying cannot fix it.
+async fn resubscribe(
+    ctx: &RuntimeContext,
+    event_type: Option<&str>,
+) -> Result<(WsClient, Subscription)> {
+    let config = &ctx.config.websocket;
+    let mut delay = Duration::from_secs(config.reconnect_delay);
+    let max_delay = MAX_RECONNECT_DELAY.max(delay);
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let attempt = async {
+            let client = WsClient::connect(ctx).await?;
+            let subscription = client.subscribe_events(event_type).await?;
+            Ok::<_, anyhow::Error>((client, subscription))
+        };
+        match attempt.await {
+            Ok(connected) => return Ok(connected),
+            Err(e) if matches!(error::find(&e), Some(Error::Auth(_))) => return Err(e),
+            Err(e)
+                if config.max_reconnect_attempts > 0
+                    && attempts >= config.max_reconnect_attempts =>
+            {
+                return Err(e.context(format!("Gave up reconnecting after {attempts} attempts")));
+            }
+            Err(e) => {
+                log::debug!("Reconnect failed, retrying in {}s: {e:#}", delay.as_secs());
+                tokio::time::sleep(delay).await;
+                // A zero delay retries once at once, then backs off like any other
+                delay = (delay * 2).clamp(Duration::from_secs(1), max_delay);
+            }
+        }
+    }
+}
+
+/// `state_changed` event data for the history states recorded after `since`,
+/// oldest first, skipping states the handler has already seen
+fn missed_changes(
+    history: Vec<Vec<EntityState>>,
+    since: DateTime<Utc>,
+    last_states: &mut HashMap<String, Value>,
+) -> Vec<Value> {
+    let mut states: Vec<(DateTime<Utc>, EntityState)> = history
+        .into_iter()
+        .flatten()
+        .filter_map(|state| {
+            let updated = DateTime::parse_from_rfc3339(&state.last_updated).ok()?;
+            Some((updated.with_timezone(&Utc), state))
+        })
+        .filter(|(updated, _)| *updated > since)
+        .collect();
+    states.sort_by_key(|(updated, _)| *updated);
+
+    let mut changes = Vec::new();
+    for (_, state) in states {
+        let Ok(new_state) = serde_json::to_value(&state) else {
+            continue;
+        };
+        let old_state = last_states.get(&state.entity_id);
+        if old_state.is_some_and(|old| {
+            same_update(old, &new_state)
+                || (old["state"] == new_state["state"]
+                    && old["attributes"] == new_state["attributes"])
+        }) {
+            continue;
+        }
+        changes.push(json!({
+            "entity_id": state.entity_id,
+            "old_state": old_state.cloned().unwrap_or(Value::Null),
+            "new_state": new_state,
+            "replayed": true,
+        }));
+        last_states.insert(state.entity_id, new_state);
+    }
+    changes
+}
 
-    Ok(())
+/// Whether two state objects are the same recorded update
+fn same_update(a: &Value, b: &Value) -> bool {
+    a["last_updated"].is_string() && a["last_updated"] == b["last_updated"]
 }
 
 /// Convert an HTTP/HTTPS URL to a WebSocket URL.
@@ -484,6 +1326,114 @@ mod tests {
         assert_eq!(event.data["entity_id"], "light.kitchen");
     }
 
+    fn test_client(timeout: Duration) -> (WsClient, Arc<Router>, mpsc::Receiver<Message>) {
+        let (tx_send, rx_send) = mpsc::channel::<Message>(32);
+        let router = Arc::new(Router::default());
+        let idle = || tokio::spawn(std::future::pending::<()>());
+        let client = WsClient::from_parts(tx_send, Arc::clone(&router), idle(), idle(), timeout);
+        (client, router, rx_send)
+    }
+
+    fn result(id: u64) -> WsMessage {
+        WsMessage::Result {
+            id,
+            success: true,
+            result: json!(id),
+            error: None,
+        }
+    }
+
+    fn event(id: u64, event_type: &str) -> WsMessage {
+        WsMessage::Event {
+            id,
+            event: json!({
+                "event_type": event_type,
+                "data": {},
+                "origin": "LOCAL",
+                "time_fired": "2025-01-15T10:30:00Z"
+            }),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_router_delivers_results_by_id() {
+        let router = Router::default();
+        let mut first = router.register_request(1);
+        let mut second = router.register_request(2);
+
+        router.dispatch(result(2)).await;
+        assert!(first.try_recv().is_err());
+        assert!(matches!(
+            second.try_recv(),
+            Ok(WsMessage::Result { id: 2, .. })
+        ));
+
+        router.dispatch(result(1)).await;
+        assert!(matches!(
+            first.try_recv(),
+            Ok(WsMessage::Result { id: 1, .. })
+        ));
+
+        // Replies nobody is waiting for are dropped
+        router.dispatch(result(3)).await;
+        assert!(router.pending.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_router_delivers_events_by_subscription() {
+        let router = Router::default();
+        let mut states = router.register_subscription(1);
+        let mut calls = router.register_subscription(2);
+
+        router.dispatch(event(2, "call_service")).await;
+        router.dispatch(event(1, "state_changed")).await;
+        router.dispatch(event(9, "unknown")).await;
+
+        let mut states = Subscription {
+            id: 1,
+            events: {
+                states.close();
+                states
+            },
+        };
+        let got = states.next_event().await.unwrap().unwrap();
+        assert_eq!(got.event_type, "state_changed");
+        assert!(states.next_event().await.unwrap().is_none());
+
+        assert_eq!(calls.try_recv().unwrap()["event_type"], "call_service");
+        assert!(calls.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_router_drops_closed_subscription() {
+        let router = Router::default();
+        drop(router.register_subscription(1));
+        router.dispatch(event(1, "state_changed")).await;
+        assert!(router.subscriptions.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_request_times_out_and_is_abandoned() {
+        let (client, router, mut sent) = test_client(Duration::from_millis(20));
+        let err = client.call_rpc(&json!({"type": "ping"})).await.unwrap_err();
+        assert!(err.to_string().contains("Timed out"));
+        let sent = sent.try_recv().unwrap();
+        assert!(sent.to_text().unwrap().contains(r#""id":1"#));
+        assert!(router.pending.lock().unwrap().is_empty());
+
+        // A late reply is dropped rather than delivered to a later request
+        router.dispatch(result(1)).await;
+        assert!(router.pending.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_close_fails_pending_requests() {
+        let router = Router::default();
+        let reply = router.register_request(1);
+        router.close();
+        assert!(reply.await.is_err());
+    }
+
     #[test]
     fn test_http_to_ws_url() {
         assert_eq!(
@@ -505,6 +1455,46 @@ mod tests {
             "wss://home.example.com"
         );
     }
+
+    #[test]
+    fn test_missed_changes() {
+        let state = |value: &str, updated: &str| EntityState {
+            entity_id: "light.desk".to_string(),
+            state: value.to_string(),
+            attributes: json!({}),
+            last_changed: updated.to_string(),
+            last_updated: updated.to_string(),
+            context: Value::Null,
+        };
+        let since: DateTime<Utc> = "2024-05-01T10:00:00+00:00".parse().unwrap();
+        let mut last_states = HashMap::from([(
+            "light.desk".to_string(),
+            serde_json::to_value(state("on", "2024-05-01T09:00:00+00:00")).unwrap(),
+        )]);
+        let history = vec![vec![
+            // The state at the start of the window, as history reports it
+            state("on", "2024-05-01T10:00:00+00:00"),
+            state("off", "2024-05-01T10:02:00+00:00"),
+            state("on", "2024-05-01T10:05:00+00:00"),
+        ]];
+
+        let changes = missed_changes(history, since, &mut last_states);
+        let summary: Vec<(&str, &str)> = changes
+            .iter()
+            .map(|c| {
+                (
+                    c["old_state"]["state"].as_str().unwrap(),
+                    c["new_state"]["state"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(summary, vec![("on", "off"), ("off", "on")]);
+        assert_eq!(changes[0]["replayed"], true);
+        assert_eq!(
+            last_states["light.desk"]["last_updated"],
+            "2024-05-01T10:05:00+00:00"
+        );
+    }
 }
 
 // --- Area Registry Types ---
@@ -526,6 +1516,17 @@ pub struct Area {
     pub labels: Vec<String>,
 }
 
+/// Floor from the Home Assistant floor registry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Floor {
+    pub floor_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub level: Option<i64>,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
 /// Request to create a new area
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateAreaRequest {
@@ -552,6 +1553,21 @@ impl CreateAreaRequest {
     }
 }
 
+// --- Label Registry Types ---
+
+/// Label from the Home Assistant label registry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Label {
+    pub label_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
 // --- Device Registry Types ---
 
 /// Device information from Home Assistant device registry
@@ -590,6 +1606,93 @@ pub struct Device {
     pub labels: Vec<String>,
 }
 
+// --- Entity Registry Types ---
+
+/// Entity registry entry from Home Assistant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityRegistryEntry {
+    pub entity_id: String,
+    #[serde(default)]
+    pub device_id: Option<String>,
+    #[serde(default)]
+    pub area_id: Option<String>,
+    #[serde(default)]
+    pub platform: Option<String>,
+    #[serde(default)]
+    pub config_entry_id: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub original_name: Option<String>,
+    #[serde(default)]
+    pub disabled_by: Option<String>,
+    #[serde(default)]
+    pub hidden_by: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Extra names voice assistants know the entity by
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+/// Request to update an entity registry entry; unset fields are left alone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateEntityRequest {
+    pub entity_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub area_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aliases: Option<Vec<String>>,
+    /// `Some(None)` enables the entity
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disabled_by: Option<Option<String>>,
+    /// `Some(None)` unhides the entity
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hidden_by: Option<Option<String>>,
+}
+
+impl UpdateEntityRequest {
+    pub fn new(entity_id: String) -> Self {
+        Self {
+            entity_id,
+            name: None,
+            area_id: None,
+            aliases: None,
+            disabled_by: None,
+            hidden_by: None,
+        }
+    }
+
+    pub fn with_name(mut self, name: String) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    pub fn with_area_id(mut self, area_id: String) -> Self {
+        self.area_id = Some(area_id);
+        self
+    }
+
+    pub fn with_aliases(mut self, aliases: Vec<String>) -> Self {
+        self.aliases = Some(aliases);
+        self
+    }
+
+    /// Disable (as the user) or enable the entity
+    pub fn with_disabled(mut self, disabled: bool) -> Self {
+        self.disabled_by = Some(disabled.then(|| "user".to_string()));
+        self
+    }
+
+    /// Hide (as the user) or unhide the entity
+    pub fn with_hidden(mut self, hidden: bool) -> Self {
+        self.hidden_by = Some(hidden.then(|| "user".to_string()));
+        self
+    }
+}
+
 /// Request to update device metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateDeviceRequest {
@@ -629,3 +1732,293 @@ impl UpdateDeviceRequest {
         self
     }
 }
+
+// --- Trace Types ---
+
+/// Summary of one stored automation or script run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceSummary {
+    pub run_id: String,
+    /// Run state: running, stopped or debugged
+    pub state: String,
+    /// How the run ended (finished, failed_conditions, error, ...)
+    #[serde(default)]
+    pub script_execution: Option<String>,
+    pub timestamp: TraceTimestamp,
+    /// Description of what triggered the run
+    #[serde(default)]
+    pub trigger: Option<String>,
+    #[serde(default)]
+    pub last_step: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceTimestamp {
+    pub start: String,
+    #[serde(default)]
+    pub finish: Option<String>,
+}
+
+// --- Auth Types ---
+
+/// The user behind the current token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurrentUser {
+    pub id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub is_owner: bool,
+    #[serde(default)]
+    pub is_admin: bool,
+}
+
+/// A user from `config/auth/list`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct User {
+    pub id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub system_generated: bool,
+}
+
+/// A conversation agent from `conversation/agent/list`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationAgent {
+    pub id: String,
+    pub name: String,
+    /// Language codes, or "*" for any language
+    #[serde(default)]
+    pub supported_languages: Value,
+}
+
+// --- Energy Types ---
+
+/// Response of `energy/get_prefs`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EnergyPrefs {
+    #[serde(default)]
+    pub energy_sources: Vec<EnergySource>,
+}
+
+/// An energy dashboard source; which fields are set depends on `kind`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnergySource {
+    /// grid, solar, battery, gas or water
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub stat_energy_from: Option<String>,
+    #[serde(default)]
+    pub stat_energy_to: Option<String>,
+    #[serde(default)]
+    pub stat_cost: Option<String>,
+    /// Grid import meters
+    #[serde(default)]
+    pub flow_from: Vec<GridFlow>,
+    /// Grid export meters
+    #[serde(default)]
+    pub flow_to: Vec<GridFlow>,
+}
+
+/// One grid import or export meter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GridFlow {
+    #[serde(default)]
+    pub stat_energy_from: Option<String>,
+    #[serde(default)]
+    pub stat_energy_to: Option<String>,
+    #[serde(default)]
+    pub stat_cost: Option<String>,
+    #[serde(default)]
+    pub stat_compensation: Option<String>,
+}
+
+/// One bucket of a long-term statistic
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatisticPoint {
+    /// Milliseconds since the epoch, or RFC 3339 on older versions
+    pub start: Value,
+    /// How much the statistic grew during the bucket
+    #[serde(default)]
+    pub change: Option<f64>,
+}
+
+impl StatisticPoint {
+    pub fn start_time(&self) -> Option<DateTime<Utc>> {
+        match &self.start {
+            Value::Number(ms) => DateTime::from_timestamp_millis(ms.as_f64()? as i64),
+            Value::String(s) => DateTime::parse_from_rfc3339(s)
+                .ok()
+                .map(|t| t.with_timezone(&Utc)),
+            _ => None,
+        }
+    }
+}
+
+// --- Assist Pipeline Types ---
+
+/// Response of `assist_pipeline/pipeline/list`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssistPipelineList {
+    pub pipelines: Vec<AssistPipeline>,
+    #[serde(default)]
+    pub preferred_pipeline: Option<String>,
+}
+
+/// A configured Assist pipeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssistPipeline {
+    pub id: String,
+    pub name: String,
+    pub language: String,
+    #[serde(default)]
+    pub conversation_engine: Option<String>,
+    /// Language the conversation agent is addressed in, "*" for any
+    #[serde(default)]
+    pub conversation_language: Option<String>,
+    #[serde(default)]
+    pub stt_engine: Option<String>,
+    #[serde(default)]
+    pub tts_engine: Option<String>,
+}
+
+// --- Lovelace Types ---
+
+/// A Lovelace dashboard from `lovelace/dashboards/list`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dashboard {
+    pub id: String,
+    pub url_path: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// "storage" (edited in the UI) or "yaml"
+    #[serde(default)]
+    pub mode: Option<String>,
+    #[serde(default)]
+    pub require_admin: bool,
+    #[serde(default)]
+    pub show_in_sidebar: bool,
+}
+
+/// A resource Lovelace loads, e.g. a custom card's JavaScript module
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LovelaceResource {
+    pub id: String,
+    pub url: String,
+    /// "module", "js", or "css"
+    #[serde(rename = "type")]
+    pub resource_type: String,
+}
+
+// --- Blueprint Types ---
+
+/// An installed blueprint from `blueprint/list`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Blueprint {
+    pub domain: String,
+    /// Path below the domain's blueprint folder, e.g. "homeassistant/motion_light.yaml"
+    pub path: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+    /// Why the blueprint failed to load, when it did
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Blueprint {
+    /// Build from one `path: {metadata: {...}}` or `path: {error: ...}` listing
+    fn from_listing(domain: &str, path: String, entry: &Value) -> Self {
+        let metadata = &entry["metadata"];
+        let text = |value: &Value| value.as_str().map(str::to_string);
+        Self {
+            domain: domain.to_string(),
+            name: text(&metadata["name"]).unwrap_or_else(|| path.clone()),
+            path,
+            description: text(&metadata["description"]),
+            source_url: text(&metadata["source_url"]),
+            error: text(&entry["error"]),
+        }
+    }
+}
+
+/// A blueprint fetched by `blueprint/import`, ready to be saved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedBlueprint {
+    pub suggested_filename: String,
+    /// The blueprint's YAML as published
+    pub raw_data: String,
+    pub blueprint: ImportedBlueprintBody,
+    #[serde(default)]
+    pub validation_errors: Option<Vec<String>>,
+    /// Whether a blueprint with the suggested file name is installed
+    #[serde(default)]
+    pub exists: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedBlueprintBody {
+    pub metadata: BlueprintMetadata,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlueprintMetadata {
+    pub name: String,
+    pub domain: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub author: Option<String>,
+    /// Input names and their definitions
+    #[serde(default)]
+    pub input: serde_json::Map<String, Value>,
+}
+
+// --- Todo Types ---
+
+/// An item on a todo list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodoItem {
+    pub uid: String,
+    pub summary: String,
+    /// needs_action or completed
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+// --- Notification Types ---
+
+/// A persistent notification, as shown in the sidebar
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    pub notification_id: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    pub message: String,
+    /// RFC 3339
+    pub created_at: String,
+}
+
+// --- Shopping List Types ---
+
+/// An item on the shopping list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShoppingItem {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub complete: bool,
+}